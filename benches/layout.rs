@@ -20,6 +20,7 @@ fn layout(c: &mut Criterion) {
         for (shape_name, shape) in &[
             ("Simple", ct::Shaping::Basic),
             ("Advanced", ct::Shaping::Advanced),
+            ("Auto", ct::Shaping::Auto),
         ] {
             let mut group = c.benchmark_group(format!("Wrap({wrap_name}, {shape_name})"));
             buffer.set_wrap(&mut fs, *wrap);
@@ -62,19 +62,19 @@ fn main() {
     .unwrap();
 
     let font_sizes = [
-        Metrics::new(10.0, 14.0).scale(display_scale), // Caption
-        Metrics::new(14.0, 20.0).scale(display_scale), // Body
-        Metrics::new(20.0, 28.0).scale(display_scale), // Title 4
-        Metrics::new(24.0, 32.0).scale(display_scale), // Title 3
-        Metrics::new(28.0, 36.0).scale(display_scale), // Title 2
-        Metrics::new(32.0, 44.0).scale(display_scale), // Title 1
+        Metrics::new(10.0, 14.0), // Caption
+        Metrics::new(14.0, 20.0), // Body
+        Metrics::new(20.0, 28.0), // Title 4
+        Metrics::new(24.0, 32.0), // Title 3
+        Metrics::new(28.0, 36.0), // Title 2
+        Metrics::new(32.0, 44.0), // Title 1
     ];
     let font_size_default = 1; // Body
 
     let mut buffer = Buffer::new(&mut font_system, font_sizes[font_size_default]);
-    buffer
-        .borrow_with(&mut font_system)
-        .set_size(Some(window.width() as f32), Some(window.height() as f32));
+    let mut buffer_borrowed = buffer.borrow_with(&mut font_system);
+    buffer_borrowed.set_scale(display_scale);
+    buffer_borrowed.set_size(Some(window.width() as f32), Some(window.height() as f32));
 
     let mut editor = Editor::new(buffer);
 
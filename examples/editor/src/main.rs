@@ -42,15 +42,13 @@ fn main() {
     let mut font_size_i = font_size_default;
 
     let mut editor = SyntaxEditor::new(
-        Buffer::new(
-            &mut font_system,
-            font_sizes[font_size_i].scale(display_scale),
-        ),
+        Buffer::new(&mut font_system, font_sizes[font_size_i]),
         &syntax_system,
         "base16-eighties.dark",
     )
     .unwrap();
     let mut editor = editor.borrow_with(&mut font_system);
+    editor.with_buffer_mut(|buffer| buffer.set_scale(display_scale));
 
     let attrs = Attrs::new().family(Family::Monospace);
 
@@ -62,6 +60,7 @@ fn main() {
     }
 
     let mut ctrl_pressed = false;
+    let mut shift_pressed = false;
     let mut mouse_x = 0.0;
     let mut mouse_y = 0.0;
     let mut mouse_left = ElementState::Released;
@@ -78,9 +77,7 @@ fn main() {
                             log::info!("Updated scale factor for {window_id:?}");
 
                             display_scale = scale_factor as f32;
-                            editor.with_buffer_mut(|buffer| {
-                                buffer.set_metrics(font_sizes[font_size_i].scale(display_scale))
-                            });
+                            editor.with_buffer_mut(|buffer| buffer.set_scale(display_scale));
 
                             window.request_redraw();
                         }
@@ -130,38 +127,31 @@ fn main() {
                                     None,
                                 );
                             });
-                            if let Some((x, y)) = editor.cursor_position() {
+                            if let Some((x, y, w, h)) = editor.ime_cursor_area() {
                                 window.set_ime_cursor_area(
                                     PhysicalPosition::new(x, y),
-                                    PhysicalSize::new(20, 20),
+                                    PhysicalSize::new(w, h),
                                 );
                             }
 
                             // Draw scrollbar
                             {
-                                let mut start_line_opt = None;
-                                let mut end_line = 0;
-                                editor.with_buffer(|buffer| {
-                                    for run in buffer.layout_runs() {
-                                        end_line = run.line_i;
-                                        if start_line_opt.is_none() {
-                                            start_line_opt = Some(end_line);
-                                        }
-                                    }
-                                });
+                                let (content_height, scroll_fraction) =
+                                    editor.with_buffer(|buffer| {
+                                        (buffer.content_size().1, buffer.scroll_fraction())
+                                    });
 
-                                let start_line = start_line_opt.unwrap_or(end_line);
-                                let lines = editor.with_buffer(|buffer| buffer.lines.len());
-                                let start_y = (start_line * height as usize) / lines;
-                                let end_y = (end_line * height as usize) / lines;
-                                paint.set_color_rgba8(0xFF, 0xFF, 0xFF, 0x40);
-                                if end_y > start_y {
+                                let view_height = height as f32;
+                                if content_height > view_height {
+                                    let thumb_height = view_height * view_height / content_height;
+                                    let thumb_y = scroll_fraction * (view_height - thumb_height);
+                                    paint.set_color_rgba8(0xFF, 0xFF, 0xFF, 0x40);
                                     pixmap.fill_rect(
                                         Rect::from_xywh(
                                             width as f32 - scrollbar_width * display_scale,
-                                            start_y as f32,
+                                            thumb_y,
                                             scrollbar_width * display_scale,
-                                            (end_y - start_y) as f32,
+                                            thumb_height,
                                         )
                                         .unwrap(),
                                         &paint,
@@ -174,45 +164,64 @@ fn main() {
                             surface_buffer.present().unwrap();
                         }
                         WindowEvent::ModifiersChanged(modifiers) => {
-                            ctrl_pressed = modifiers.state().control_key()
+                            ctrl_pressed = modifiers.state().control_key();
+                            shift_pressed = modifiers.state().shift_key();
                         }
                         WindowEvent::KeyboardInput { event, .. } => {
                             let KeyEvent {
                                 logical_key, state, ..
                             } = event;
 
+                            // Shift extends the selection
+                            let motion = |motion| {
+                                if shift_pressed {
+                                    Action::Select(motion)
+                                } else {
+                                    Action::Motion(motion)
+                                }
+                            };
+
                             if state.is_pressed() {
                                 match logical_key {
                                     Key::Named(NamedKey::ArrowLeft) => {
-                                        editor.action(Action::Motion(Motion::Left))
+                                        editor.action(motion(Motion::Left));
                                     }
                                     Key::Named(NamedKey::ArrowRight) => {
-                                        editor.action(Action::Motion(Motion::Right))
+                                        editor.action(motion(Motion::Right));
                                     }
                                     Key::Named(NamedKey::ArrowUp) => {
-                                        editor.action(Action::Motion(Motion::Up))
+                                        editor.action(motion(Motion::Up));
                                     }
                                     Key::Named(NamedKey::ArrowDown) => {
-                                        editor.action(Action::Motion(Motion::Down))
+                                        editor.action(motion(Motion::Down));
                                     }
                                     Key::Named(NamedKey::Home) => {
-                                        editor.action(Action::Motion(Motion::Home))
+                                        editor.action(motion(Motion::SmartHome));
                                     }
                                     Key::Named(NamedKey::End) => {
-                                        editor.action(Action::Motion(Motion::End))
+                                        editor.action(motion(Motion::End));
                                     }
                                     Key::Named(NamedKey::PageUp) => {
-                                        editor.action(Action::Motion(Motion::PageUp))
+                                        editor.action(motion(Motion::PageUp));
                                     }
                                     Key::Named(NamedKey::PageDown) => {
-                                        editor.action(Action::Motion(Motion::PageDown))
+                                        editor.action(motion(Motion::PageDown));
+                                    }
+                                    Key::Named(NamedKey::Escape) => {
+                                        editor.action(Action::Escape);
+                                    }
+                                    Key::Named(NamedKey::Enter) => {
+                                        editor.action(Action::Enter);
                                     }
-                                    Key::Named(NamedKey::Escape) => editor.action(Action::Escape),
-                                    Key::Named(NamedKey::Enter) => editor.action(Action::Enter),
                                     Key::Named(NamedKey::Backspace) => {
-                                        editor.action(Action::Backspace)
+                                        editor.action(Action::Backspace);
+                                    }
+                                    Key::Named(NamedKey::Delete) => {
+                                        editor.action(Action::Delete);
+                                    }
+                                    Key::Named(NamedKey::Insert) => {
+                                        editor.action(Action::ToggleOvertype);
                                     }
-                                    Key::Named(NamedKey::Delete) => editor.action(Action::Delete),
                                     Key::Named(key) => {
                                         if let Some(text) = key.to_text() {
                                             for c in text.chars() {
@@ -226,10 +235,7 @@ fn main() {
                                                 "0" => {
                                                     font_size_i = font_size_default;
                                                     editor.with_buffer_mut(|buffer| {
-                                                        buffer.set_metrics(
-                                                            font_sizes[font_size_i]
-                                                                .scale(display_scale),
-                                                        )
+                                                        buffer.set_metrics(font_sizes[font_size_i])
                                                     });
                                                 }
                                                 "-" => {
@@ -237,8 +243,7 @@ fn main() {
                                                         font_size_i -= 1;
                                                         editor.with_buffer_mut(|buffer| {
                                                             buffer.set_metrics(
-                                                                font_sizes[font_size_i]
-                                                                    .scale(display_scale),
+                                                                font_sizes[font_size_i],
                                                             )
                                                         });
                                                     }
@@ -248,8 +253,7 @@ fn main() {
                                                         font_size_i += 1;
                                                         editor.with_buffer_mut(|buffer| {
                                                             buffer.set_metrics(
-                                                                font_sizes[font_size_i]
-                                                                    .scale(display_scale),
+                                                                font_sizes[font_size_i],
                                                             )
                                                         });
                                                     }
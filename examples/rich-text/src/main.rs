@@ -118,9 +118,10 @@ fn main() {
 
     let mut display_scale = window.scale_factor() as f32;
     let metrics = Metrics::new(32.0, 44.0);
-    let mut editor = Editor::new(Buffer::new_empty(metrics.scale(display_scale)));
+    let mut editor = Editor::new(Buffer::new_empty(metrics));
     let mut editor = editor.borrow_with(&mut font_system);
     editor.with_buffer_mut(|buffer| {
+        buffer.set_scale(display_scale);
         buffer.set_size(
             Some(window.inner_size().width as f32),
             Some(window.inner_size().height as f32),
@@ -151,9 +152,7 @@ fn main() {
                             log::info!("Updated scale factor for {window_id:?}");
 
                             display_scale = scale_factor as f32;
-                            editor.with_buffer_mut(|buffer| {
-                                buffer.set_metrics(metrics.scale(display_scale))
-                            });
+                            editor.with_buffer_mut(|buffer| buffer.set_scale(display_scale));
 
                             window.request_redraw();
                         }
@@ -227,35 +226,44 @@ fn main() {
                             if state.is_pressed() {
                                 match logical_key {
                                     Key::Named(NamedKey::ArrowLeft) => {
-                                        editor.action(Action::Motion(Motion::Left))
+                                        editor.action(Action::Motion(Motion::Left));
                                     }
                                     Key::Named(NamedKey::ArrowRight) => {
-                                        editor.action(Action::Motion(Motion::Right))
+                                        editor.action(Action::Motion(Motion::Right));
                                     }
                                     Key::Named(NamedKey::ArrowUp) => {
-                                        editor.action(Action::Motion(Motion::Up))
+                                        editor.action(Action::Motion(Motion::Up));
                                     }
                                     Key::Named(NamedKey::ArrowDown) => {
-                                        editor.action(Action::Motion(Motion::Down))
+                                        editor.action(Action::Motion(Motion::Down));
                                     }
                                     Key::Named(NamedKey::Home) => {
-                                        editor.action(Action::Motion(Motion::Home))
+                                        editor.action(Action::Motion(Motion::Home));
                                     }
                                     Key::Named(NamedKey::End) => {
-                                        editor.action(Action::Motion(Motion::End))
+                                        editor.action(Action::Motion(Motion::End));
                                     }
                                     Key::Named(NamedKey::PageUp) => {
-                                        editor.action(Action::Motion(Motion::PageUp))
+                                        editor.action(Action::Motion(Motion::PageUp));
                                     }
                                     Key::Named(NamedKey::PageDown) => {
-                                        editor.action(Action::Motion(Motion::PageDown))
+                                        editor.action(Action::Motion(Motion::PageDown));
+                                    }
+                                    Key::Named(NamedKey::Escape) => {
+                                        editor.action(Action::Escape);
+                                    }
+                                    Key::Named(NamedKey::Enter) => {
+                                        editor.action(Action::Enter);
                                     }
-                                    Key::Named(NamedKey::Escape) => editor.action(Action::Escape),
-                                    Key::Named(NamedKey::Enter) => editor.action(Action::Enter),
                                     Key::Named(NamedKey::Backspace) => {
-                                        editor.action(Action::Backspace)
+                                        editor.action(Action::Backspace);
+                                    }
+                                    Key::Named(NamedKey::Delete) => {
+                                        editor.action(Action::Delete);
+                                    }
+                                    Key::Named(NamedKey::Insert) => {
+                                        editor.action(Action::ToggleOvertype);
                                     }
-                                    Key::Named(NamedKey::Delete) => editor.action(Action::Delete),
                                     Key::Named(key) => {
                                         if let Some(text) = key.to_text() {
                                             for c in text.chars() {
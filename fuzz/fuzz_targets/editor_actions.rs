@@ -0,0 +1,101 @@
+#![no_main]
+
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion, Selection,
+    SelectionHandle, Shaping, Wrap,
+};
+use libfuzzer_sys::fuzz_target;
+use std::sync::{Mutex, OnceLock};
+
+static FONT_SYSTEM: OnceLock<Mutex<FontSystem>> = OnceLock::new();
+
+const MOTIONS: [Motion; 20] = [
+    Motion::Previous,
+    Motion::Next,
+    Motion::Left,
+    Motion::Right,
+    Motion::Up,
+    Motion::Down,
+    Motion::UpParagraph,
+    Motion::DownParagraph,
+    Motion::Home,
+    Motion::SoftHome,
+    Motion::End,
+    Motion::ParagraphStart,
+    Motion::ParagraphEnd,
+    Motion::PageUp,
+    Motion::PageDown,
+    Motion::PreviousWord,
+    Motion::NextWord,
+    Motion::LeftWord,
+    Motion::RightWord,
+    Motion::BufferEnd,
+];
+
+fn action(op: u8, arg: u8) -> Action {
+    let x = i32::from(arg) * 4 - 64;
+    let y = i32::from(arg) * 2 - 32;
+    match op % 18 {
+        0 => Action::Motion(MOTIONS[usize::from(arg) % MOTIONS.len()]),
+        1 => Action::Insert(char::from(arg)),
+        2 => Action::Insert(['\u{301}', '\u{200D}', '\u{2029}', 'ש', '😀'][usize::from(arg) % 5]),
+        3 => Action::Enter,
+        4 => Action::Backspace,
+        5 => Action::Delete,
+        6 => Action::Indent,
+        7 => Action::Unindent,
+        8 => Action::Escape,
+        9 => Action::Click { x, y },
+        10 => Action::DoubleClick { x, y },
+        11 => Action::TripleClick { x, y },
+        12 => Action::Drag { x, y },
+        13 => Action::LongPress { x, y },
+        14 => Action::Scroll {
+            lines: i32::from(arg as i8),
+        },
+        15 => Action::ToggleOvertype,
+        16 => Action::DragHandle {
+            which: if arg % 2 == 0 {
+                SelectionHandle::Start
+            } else {
+                SelectionHandle::End
+            },
+            x,
+            y,
+        },
+        _ => Action::Motion(Motion::GotoLine(usize::from(arg))),
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (text, ops) = match data.iter().position(|&b| b == 0) {
+        Some(i) => (String::from_utf8_lossy(&data[..i]), &data[i + 1..]),
+        None => (String::from_utf8_lossy(data), &[][..]),
+    };
+
+    let mut font_system = FONT_SYSTEM
+        .get_or_init(|| Mutex::new(FontSystem::new()))
+        .lock()
+        .unwrap();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(120.0), Some(80.0));
+    buffer.set_wrap(&mut font_system, Wrap::WordOrGlyph);
+    buffer.set_text(&mut font_system, &text, Attrs::new(), Shaping::Advanced);
+
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    for chunk in ops.chunks_exact(2) {
+        match chunk[0] {
+            // Set cursors and selections that may be outside of the text
+            0xFE => editor.set_cursor(Cursor::new(usize::from(chunk[1] >> 4), usize::from(chunk[1] & 0xF))),
+            0xFF => editor.set_selection(Selection::Normal(Cursor::new(
+                usize::from(chunk[1] >> 4),
+                usize::from(chunk[1] & 0xF),
+            ))),
+            op => editor.action(action(op, chunk[1])),
+        }
+    }
+    editor.shape_as_needed(false);
+    let _ = editor.copy_selection();
+    editor.delete_selection();
+});
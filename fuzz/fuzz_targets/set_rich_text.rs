@@ -0,0 +1,74 @@
+#![no_main]
+
+use cosmic_text::{
+    Align, Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Style, Weight, Wrap,
+};
+use libfuzzer_sys::fuzz_target;
+use std::sync::{Mutex, OnceLock};
+
+static FONT_SYSTEM: OnceLock<Mutex<FontSystem>> = OnceLock::new();
+
+fn attrs(b: u8) -> Attrs<'static> {
+    let mut attrs = Attrs::new();
+    if b & 1 != 0 {
+        attrs = attrs.weight(Weight::BOLD);
+    }
+    if b & 2 != 0 {
+        attrs = attrs.style(Style::Italic);
+    }
+    if b & 4 != 0 {
+        attrs = attrs.family(Family::Monospace);
+    }
+    if b & 8 != 0 {
+        attrs = attrs.metrics(Metrics::new(f32::from(b >> 4) + 1.0, f32::from(b >> 4) * 2.0));
+    }
+    attrs
+}
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+    let width = f32::from(data[0]) - 16.0;
+    let wrap = [Wrap::None, Wrap::Glyph, Wrap::Word, Wrap::WordOrGlyph][usize::from(data[1] % 4)];
+    let align = [None, Some(Align::Center), Some(Align::Right), Some(Align::Justified)]
+        [usize::from(data[2] % 4)];
+    let shaping = if data[3] % 2 == 0 {
+        Shaping::Advanced
+    } else {
+        Shaping::Auto
+    };
+
+    // Spans are separated by zero bytes and start with a byte selecting their attributes
+    let spans: Vec<(String, Attrs)> = data[4..]
+        .split(|&b| b == 0)
+        .map(|span| match span.split_first() {
+            Some((&b, text)) => (String::from_utf8_lossy(text).into_owned(), attrs(b)),
+            None => (String::new(), Attrs::new()),
+        })
+        .collect();
+
+    let mut font_system = FONT_SYSTEM
+        .get_or_init(|| Mutex::new(FontSystem::new()))
+        .lock()
+        .unwrap();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(width), Some(200.0));
+    buffer.set_wrap(wrap);
+    buffer.set_rich_text(
+        spans.iter().map(|(text, attrs)| (text.as_str(), *attrs)),
+        Attrs::new(),
+        shaping,
+    );
+    for line in buffer.lines.iter_mut() {
+        line.set_align(align);
+    }
+    buffer.shape_until_scroll(false);
+    for run in buffer.layout_runs() {
+        let _ = run.highlight(
+            cosmic_text::Cursor::new(run.line_i, 0),
+            cosmic_text::Cursor::new(run.line_i, run.text.len()),
+        );
+    }
+});
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Cursor;
+
+/// Identifier of an anchor registered with [`Buffer::add_anchor`]
+///
+/// [`Buffer::add_anchor`]: crate::Buffer::add_anchor
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AnchorId(pub(crate) usize);
+
+/// Which side of text inserted exactly at an anchor the anchor ends up on
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum AnchorBias {
+    /// Stay before the inserted text, useful for the start of a range
+    #[default]
+    Before,
+    /// Move after the inserted text, useful for the end of a range or a caret
+    After,
+}
+
+/// A position in a [`Buffer`] that follows the text around it as the text is edited
+///
+/// [`Buffer`]: crate::Buffer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Anchor {
+    /// Current position of the anchor
+    pub cursor: Cursor,
+    /// Behavior when text is inserted at the position of the anchor
+    pub bias: AnchorBias,
+}
+
+impl Anchor {
+    /// Create a new anchor
+    pub const fn new(cursor: Cursor, bias: AnchorBias) -> Self {
+        Self { cursor, bias }
+    }
+
+    /// Shift the anchor after text was inserted from `start` to `end`
+    pub fn shift_insert(&mut self, start: Cursor, end: Cursor) {
+        let cursor = &mut self.cursor;
+        if cursor.line > start.line {
+            cursor.line += end.line - start.line;
+        } else if cursor.line == start.line
+            && (cursor.index > start.index
+                || (cursor.index == start.index && self.bias == AnchorBias::After))
+        {
+            cursor.line = end.line;
+            cursor.index = end.index + (cursor.index - start.index);
+        }
+    }
+
+    /// Shift the anchor after text was deleted from `start` to `end`
+    ///
+    /// Anchors inside of the deleted text are moved to `start`.
+    pub fn shift_delete(&mut self, start: Cursor, end: Cursor) {
+        let cursor = &mut self.cursor;
+        if cursor.line > end.line {
+            cursor.line -= end.line - start.line;
+        } else if cursor.line == end.line && cursor.index >= end.index {
+            cursor.line = start.line;
+            cursor.index = start.index + (cursor.index - end.index);
+        } else if (cursor.line, cursor.index) > (start.line, start.index) {
+            cursor.line = start.line;
+            cursor.index = start.index;
+        }
+    }
+}
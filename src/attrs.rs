@@ -8,9 +8,10 @@ use alloc::{
 use core::ops::Range;
 use rangemap::RangeMap;
 
-use crate::{CacheKeyFlags, Metrics};
+use crate::{math, CacheKeyFlags, Metrics, StyleId};
 
 pub use fontdb::{Family, Stretch, Style, Weight};
+pub use unicode_script::Script;
 
 /// Text color
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, Eq, Hash, PartialEq)]
@@ -64,6 +65,71 @@ impl Color {
     pub fn a(&self) -> u8 {
         ((self.0 & 0xFF_00_00_00) >> 24) as u8
     }
+
+    /// Get the color with its alpha component multiplied by `alpha`, for example the coverage
+    /// of a glyph mask
+    #[inline]
+    pub fn mul_alpha(self, alpha: u8) -> Self {
+        let a = (u32::from(self.a()) * u32::from(alpha) + 127) / 255;
+        Self((a << 24) | (self.0 & 0xFF_FF_FF))
+    }
+
+    /// Get the color with its red, green, and blue components multiplied by its alpha component,
+    /// for blending with premultiplied alpha in sRGB space
+    #[inline]
+    pub fn premultiply(self) -> Self {
+        let a = u32::from(self.a());
+        let mul = |c: u8| ((u32::from(c) * a + 127) / 255) as u8;
+        Self::rgba(mul(self.r()), mul(self.g()), mul(self.b()), self.a())
+    }
+
+    /// Convert the sRGB components to linear components, in `[r, g, b, a]` order from 0.0 to 1.0
+    ///
+    /// Alpha is not affected by the conversion.
+    pub fn to_linear(self) -> [f32; 4] {
+        let linear = |c: u8| {
+            let c = f32::from(c) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                math::powf((c + 0.055) / 1.055, 2.4)
+            }
+        };
+        [
+            linear(self.r()),
+            linear(self.g()),
+            linear(self.b()),
+            f32::from(self.a()) / 255.0,
+        ]
+    }
+
+    /// Convert the sRGB components to linear components multiplied by alpha, in `[r, g, b, a]`
+    /// order from 0.0 to 1.0, for blending with premultiplied alpha in linear space
+    pub fn to_linear_premultiplied(self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_linear();
+        [r * a, g * a, b * a, a]
+    }
+
+    /// Create a color from linear components, in `[r, g, b, a]` order from 0.0 to 1.0
+    ///
+    /// Components are clamped, and alpha is not affected by the conversion.
+    pub fn from_linear(rgba: [f32; 4]) -> Self {
+        let srgb = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            let c = if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * math::powf(c, 1.0 / 2.4) - 0.055
+            };
+            math::roundf(c * 255.0) as u8
+        };
+        Self::rgba(
+            srgb(rgba[0]),
+            srgb(rgba[1]),
+            srgb(rgba[2]),
+            math::roundf(rgba[3].clamp(0.0, 1.0) * 255.0) as u8,
+        )
+    }
 }
 
 /// An owned version of [`Family`]
@@ -101,6 +167,148 @@ impl FamilyOwned {
     }
 }
 
+/// Additional CSS generic font families, which are used as [`Family::Name`] with their CSS
+/// keyword
+///
+/// A [`FontSystem`](crate::FontSystem) resolves these through its family substitutes, which
+/// start out with platform defaults and can be replaced with
+/// [`FontSystem::set_family_substitutes`](crate::FontSystem::set_family_substitutes).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GenericFamily {
+    /// `system-ui`, the default user interface font of the platform
+    SystemUi,
+    /// `ui-serif`, the serif user interface font of the platform
+    UiSerif,
+    /// `ui-sans-serif`, the sans-serif user interface font of the platform
+    UiSansSerif,
+    /// `ui-monospace`, the monospace user interface font of the platform
+    UiMonospace,
+    /// `ui-rounded`, the rounded user interface font of the platform
+    UiRounded,
+    /// `emoji`, a font for emoji
+    Emoji,
+    /// `math`, a font for mathematical expressions
+    Math,
+    /// `fangsong`, a Chinese font in the Fang Song style
+    Fangsong,
+}
+
+impl GenericFamily {
+    /// All generic families
+    pub const ALL: [Self; 8] = [
+        Self::SystemUi,
+        Self::UiSerif,
+        Self::UiSansSerif,
+        Self::UiMonospace,
+        Self::UiRounded,
+        Self::Emoji,
+        Self::Math,
+        Self::Fangsong,
+    ];
+
+    /// Get the CSS keyword of this generic family
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SystemUi => "system-ui",
+            Self::UiSerif => "ui-serif",
+            Self::UiSansSerif => "ui-sans-serif",
+            Self::UiMonospace => "ui-monospace",
+            Self::UiRounded => "ui-rounded",
+            Self::Emoji => "emoji",
+            Self::Math => "math",
+            Self::Fangsong => "fangsong",
+        }
+    }
+
+    /// Get the generic family with a CSS keyword
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|generic| generic.name() == name)
+    }
+
+    /// Get the family to use in [`Attrs`]
+    pub fn family(self) -> Family<'static> {
+        Family::Name(self.name())
+    }
+}
+
+/// Substitution of decimal digits during shaping
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum NumberSubstitution {
+    /// Shape digits as written
+    #[default]
+    None,
+    /// Shape ASCII digits as the native digits of the language, see [`Attrs::lang`]
+    Native,
+    /// Shape native digits as ASCII digits
+    Western,
+}
+
+impl NumberSubstitution {
+    /// Zero digits of decimal digit sets, used by [`NumberSubstitution::Western`]
+    const ZEROS: &'static [char] = &[
+        '\u{0660}', '\u{06F0}', '\u{07C0}', '\u{0966}', '\u{09E6}', '\u{0A66}', '\u{0AE6}',
+        '\u{0B66}', '\u{0BE6}', '\u{0C66}', '\u{0CE6}', '\u{0D66}', '\u{0DE6}', '\u{0E50}',
+        '\u{0ED0}', '\u{0F20}', '\u{1040}', '\u{1090}', '\u{17E0}', '\u{1810}', '\u{FF10}',
+    ];
+
+    /// Get the native zero digit for a BCP 47 language tag
+    pub fn native_zero(language: &str) -> Option<char> {
+        let primary = language.split(['-', '_']).next().unwrap_or(language);
+        match primary.to_ascii_lowercase().as_str() {
+            "ar" => Some('\u{0660}'),
+            "fa" | "ps" | "ur" => Some('\u{06F0}'),
+            "nqo" => Some('\u{07C0}'),
+            "hi" | "mr" | "ne" | "sa" => Some('\u{0966}'),
+            "as" | "bn" => Some('\u{09E6}'),
+            "pa" => Some('\u{0A66}'),
+            "gu" => Some('\u{0AE6}'),
+            "or" => Some('\u{0B66}'),
+            "ta" => Some('\u{0BE6}'),
+            "te" => Some('\u{0C66}'),
+            "kn" => Some('\u{0CE6}'),
+            "ml" => Some('\u{0D66}'),
+            "th" => Some('\u{0E50}'),
+            "lo" => Some('\u{0ED0}'),
+            "bo" | "dz" => Some('\u{0F20}'),
+            "my" => Some('\u{1040}'),
+            "km" => Some('\u{17E0}'),
+            "mn" => Some('\u{1810}'),
+            _ => None,
+        }
+    }
+
+    /// Substitute a character, using the language to find native digits
+    pub fn substitute(self, c: char, language_opt: Option<&str>) -> char {
+        match self {
+            Self::None => c,
+            Self::Native => match (c.to_digit(10), language_opt.and_then(Self::native_zero)) {
+                (Some(digit), Some(zero)) if c.is_ascii_digit() => {
+                    char::from_u32(zero as u32 + digit).unwrap_or(c)
+                }
+                _ => c,
+            },
+            Self::Western => Self::ZEROS
+                .iter()
+                .find_map(|zero| char::from_digit((c as u32).checked_sub(*zero as u32)?, 10))
+                .unwrap_or(c),
+        }
+    }
+}
+
+/// Ligatures formed during shaping, see [`Attrs::ligatures`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Ligatures {
+    /// Ligatures the font enables by default, which are standard (`liga`) and contextual
+    /// (`clig`) ligatures
+    #[default]
+    Normal,
+    /// No standard, contextual, or discretionary ligatures, such as for code editors that do
+    /// not show programming ligatures
+    None,
+    /// Standard, contextual, and discretionary (`dlig`) ligatures, such as for display fonts
+    All,
+}
+
 /// Metrics, but implementing Eq and Hash using u32 representation of f32
 //TODO: what are the edge cases of this?
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -127,6 +335,65 @@ impl From<CacheMetrics> for Metrics {
     }
 }
 
+/// Horizontal padding and minimum width of a span, in units of its font size, for chips around
+/// mentions and tags, see [`Attrs::padding`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SpanPadding {
+    horizontal_bits: u32,
+    min_width_bits: u32,
+}
+
+impl SpanPadding {
+    /// Create padding of `horizontal` on each side, growing both sides evenly if needed so the
+    /// span is at least `min_width` wide, both in units of the font size
+    pub fn new(horizontal: f32, min_width: f32) -> Self {
+        Self {
+            horizontal_bits: horizontal.to_bits(),
+            min_width_bits: min_width.to_bits(),
+        }
+    }
+
+    /// Padding on each side, in units of the font size
+    pub fn horizontal(&self) -> f32 {
+        f32::from_bits(self.horizontal_bits)
+    }
+
+    /// Minimum width including padding, in units of the font size
+    pub fn min_width(&self) -> f32 {
+        f32::from_bits(self.min_width_bits)
+    }
+}
+
+/// Animated emphasis of text, such as the blink attribute of terminals (SGR 5)
+///
+/// Layout carries the kind to [`LayoutGlyph::animation_opt`], and renderers choose the phase and
+/// timing of the animation. Renderers should draw text statically if the user prefers reduced
+/// motion.
+///
+/// [`LayoutGlyph::animation_opt`]: crate::LayoutGlyph::animation_opt
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextAnimation {
+    /// Periodically hide and show the text
+    Blink,
+    /// Periodically fade the text between its color and a dimmer version of it
+    Pulse,
+    /// Scroll the text horizontally, disabled by default, see [`Self::enabled_by_default`]
+    Marquee,
+}
+
+impl TextAnimation {
+    /// True if renderers should animate this kind unless the user opts out, false if it should
+    /// only be animated when the user opts in
+    ///
+    /// [`Self::Marquee`] is disabled by default, as moving text is hard to read.
+    pub fn enabled_by_default(self) -> bool {
+        match self {
+            Self::Blink | Self::Pulse => true,
+            Self::Marquee => false,
+        }
+    }
+}
+
 /// Text attributes
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Attrs<'a> {
@@ -139,6 +406,33 @@ pub struct Attrs<'a> {
     pub metadata: usize,
     pub cache_key_flags: CacheKeyFlags,
     pub metrics_opt: Option<CacheMetrics>,
+    /// BCP 47 language tag, such as `"ja"` or `"sr-Latn"`
+    pub language_opt: Option<&'a str>,
+    /// Script override, used instead of the script detected from the text
+    pub script_opt: Option<Script>,
+    pub number_substitution: NumberSubstitution,
+    pub ligatures: Ligatures,
+    /// Apply pair kerning from the font, true by default
+    pub kerning: bool,
+    /// Style of a [`StyleSheet`](crate::StyleSheet) these attributes were resolved from
+    pub style_id_opt: Option<StyleId>,
+    /// Index of a color in the palette given when drawing, used instead of `color_opt` if the
+    /// palette contains it
+    pub palette_index_opt: Option<u16>,
+    /// Resize monospace glyphs to match [`Buffer::monospace_width`], true by default
+    ///
+    /// [`Buffer::monospace_width`]: crate::Buffer::monospace_width
+    pub match_mono_width: bool,
+    /// Animated emphasis, drawn by the renderer
+    pub animation_opt: Option<TextAnimation>,
+    /// Identifier of an inline decoration drawn by the host, see [`Attrs::decoration`]
+    pub decoration_id_opt: Option<u32>,
+    /// Horizontal padding of the span, see [`Attrs::padding`]
+    pub padding_opt: Option<SpanPadding>,
+    /// Cursor color hint for the span, see [`Attrs::cursor_color`]
+    pub cursor_color_opt: Option<Color>,
+    /// Selection background color hint for the span, see [`Attrs::selection_color`]
+    pub selection_color_opt: Option<Color>,
 }
 
 impl<'a> Attrs<'a> {
@@ -155,6 +449,19 @@ impl<'a> Attrs<'a> {
             metadata: 0,
             cache_key_flags: CacheKeyFlags::empty(),
             metrics_opt: None,
+            language_opt: None,
+            script_opt: None,
+            number_substitution: NumberSubstitution::None,
+            ligatures: Ligatures::Normal,
+            kerning: true,
+            style_id_opt: None,
+            palette_index_opt: None,
+            match_mono_width: true,
+            animation_opt: None,
+            decoration_id_opt: None,
+            padding_opt: None,
+            cursor_color_opt: None,
+            selection_color_opt: None,
         }
     }
 
@@ -164,6 +471,17 @@ impl<'a> Attrs<'a> {
         self
     }
 
+    /// Set a palette color index, resolved when drawing so that themes can be switched without
+    /// reshaping, see [`LayoutGlyph::color`]
+    ///
+    /// The color set with [`Attrs::color`] is used if the palette does not contain the index.
+    ///
+    /// [`LayoutGlyph::color`]: crate::LayoutGlyph::color
+    pub fn palette_color(mut self, index: u16) -> Self {
+        self.palette_index_opt = Some(index);
+        self
+    }
+
     /// Set [Family]
     pub fn family(mut self, family: Family<'a>) -> Self {
         self.family = family;
@@ -206,6 +524,141 @@ impl<'a> Attrs<'a> {
         self
     }
 
+    /// Set language, as a BCP 47 tag like `"ja"` or `"zh-TW"`
+    ///
+    /// The language is used by shaping to select language specific glyph forms and by font
+    /// fallback to pick between regional variants, such as the Han unification variants used in
+    /// Chinese, Japanese, and Korean text.
+    pub fn lang(mut self, language: &'a str) -> Self {
+        self.language_opt = Some(language);
+        self
+    }
+
+    /// Set [Script], overriding the script detected from the text
+    pub fn script(mut self, script: Script) -> Self {
+        self.script_opt = Some(script);
+        self
+    }
+
+    /// Set [`NumberSubstitution`]
+    pub fn number_substitution(mut self, number_substitution: NumberSubstitution) -> Self {
+        self.number_substitution = number_substitution;
+        self
+    }
+
+    /// Set [`Ligatures`]
+    pub fn ligatures(mut self, ligatures: Ligatures) -> Self {
+        self.ligatures = ligatures;
+        self
+    }
+
+    /// Set if pair kerning from the font is applied
+    ///
+    /// Disabling kerning keeps the advance of every glyph independent of its neighbors, whichever
+    /// font it falls back to, for terminals and tabular text that must stay aligned to columns.
+    pub fn kerning(mut self, kerning: bool) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Set if monospace glyphs are resized to match [`Buffer::monospace_width`]
+    ///
+    /// This can be disabled for spans that should keep the natural width of their font, such as
+    /// prose next to code blocks in a buffer with a monospace width.
+    ///
+    /// [`Buffer::monospace_width`]: crate::Buffer::monospace_width
+    pub fn match_mono_width(mut self, match_mono_width: bool) -> Self {
+        self.match_mono_width = match_mono_width;
+        self
+    }
+
+    /// Set [`TextAnimation`]
+    pub fn animation(mut self, animation: TextAnimation) -> Self {
+        self.animation_opt = Some(animation);
+        self
+    }
+
+    /// Set the identifier of an inline decoration, such as a pill background around a mention
+    ///
+    /// Drawing with [`Editor::draw_with_decorations`] calls back with the rectangles of each
+    /// span with a decoration, so the host can draw it before the text.
+    ///
+    /// [`Editor::draw_with_decorations`]: crate::Editor::draw_with_decorations
+    pub fn decoration(mut self, id: u32) -> Self {
+        self.decoration_id_opt = Some(id);
+        self
+    }
+
+    /// Set [`SpanPadding`], in units of the font size
+    ///
+    /// The padding is added to the advances of the first and last glyphs of the span, so it
+    /// affects layout and clicks on it hit the span. This applies to spans of an [`AttrsList`],
+    /// and adjacent spans with equal attributes are padded as one.
+    pub fn padding(mut self, horizontal: f32, min_width: f32) -> Self {
+        self.padding_opt = Some(SpanPadding::new(horizontal, min_width));
+        self
+    }
+
+    /// Set a cursor color hint, used by [`Editor::draw`] instead of its cursor color when the
+    /// cursor is inside this span
+    ///
+    /// This keeps the cursor visible over spans with a custom background, such as code blocks.
+    ///
+    /// [`Editor::draw`]: crate::Editor::draw
+    pub fn cursor_color(mut self, color: Color) -> Self {
+        self.cursor_color_opt = Some(color);
+        self
+    }
+
+    /// Set a selection color hint, used by [`Editor::draw`] instead of its selection color for
+    /// selected text in this span
+    ///
+    /// [`Editor::draw`]: crate::Editor::draw
+    pub fn selection_color(mut self, color: Color) -> Self {
+        self.selection_color_opt = Some(color);
+        self
+    }
+
+    /// Set the [`StyleId`] these attributes were resolved from, so they can be updated by
+    /// [`Buffer::set_style_sheet`]
+    ///
+    /// [`Buffer::set_style_sheet`]: crate::Buffer::set_style_sheet
+    pub fn style_id(mut self, style_id: StyleId) -> Self {
+        self.style_id_opt = Some(style_id);
+        self
+    }
+
+    /// Apply an [`AttrsDelta`], keeping attributes it does not set
+    pub fn apply(self, delta: &AttrsDelta<'a>) -> Self {
+        Self {
+            color_opt: delta.color_opt.unwrap_or(self.color_opt),
+            family: delta.family.unwrap_or(self.family),
+            stretch: delta.stretch.unwrap_or(self.stretch),
+            style: delta.style.unwrap_or(self.style),
+            weight: delta.weight.unwrap_or(self.weight),
+            metadata: delta.metadata.unwrap_or(self.metadata),
+            cache_key_flags: delta.cache_key_flags.unwrap_or(self.cache_key_flags),
+            metrics_opt: delta.metrics_opt.unwrap_or(self.metrics_opt),
+            language_opt: delta.language_opt.unwrap_or(self.language_opt),
+            script_opt: delta.script_opt.unwrap_or(self.script_opt),
+            number_substitution: delta
+                .number_substitution
+                .unwrap_or(self.number_substitution),
+            ligatures: delta.ligatures.unwrap_or(self.ligatures),
+            kerning: delta.kerning.unwrap_or(self.kerning),
+            style_id_opt: self.style_id_opt,
+            palette_index_opt: delta.palette_index_opt.unwrap_or(self.palette_index_opt),
+            match_mono_width: delta.match_mono_width.unwrap_or(self.match_mono_width),
+            animation_opt: delta.animation_opt.unwrap_or(self.animation_opt),
+            decoration_id_opt: delta.decoration_id_opt.unwrap_or(self.decoration_id_opt),
+            padding_opt: delta.padding_opt.unwrap_or(self.padding_opt),
+            cursor_color_opt: delta.cursor_color_opt.unwrap_or(self.cursor_color_opt),
+            selection_color_opt: delta
+                .selection_color_opt
+                .unwrap_or(self.selection_color_opt),
+        }
+    }
+
     /// Check if font matches
     pub fn matches(&self, face: &fontdb::FaceInfo) -> bool {
         //TODO: smarter way of including emoji
@@ -219,6 +672,21 @@ impl<'a> Attrs<'a> {
             && self.stretch == other.stretch
             && self.style == other.style
             && self.weight == other.weight
+            && self.language_opt == other.language_opt
+            && self.script_opt == other.script_opt
+            && self.number_substitution == other.number_substitution
+            && self.ligatures == other.ligatures
+            && self.kerning == other.kerning
+    }
+
+    /// Check if text with these attributes is shaped and laid out the same as with another,
+    /// so they only differ in properties used for drawing, like colors and decorations
+    pub fn same_layout(&self, other: &Self) -> bool {
+        self.compatible(other)
+            && self.cache_key_flags == other.cache_key_flags
+            && self.metrics_opt == other.metrics_opt
+            && self.match_mono_width == other.match_mono_width
+            && self.padding_opt == other.padding_opt
     }
 }
 
@@ -254,6 +722,19 @@ pub struct AttrsOwned {
     pub metadata: usize,
     pub cache_key_flags: CacheKeyFlags,
     pub metrics_opt: Option<CacheMetrics>,
+    pub language_opt: Option<String>,
+    pub script_opt: Option<Script>,
+    pub number_substitution: NumberSubstitution,
+    pub ligatures: Ligatures,
+    pub kerning: bool,
+    pub style_id_opt: Option<StyleId>,
+    pub palette_index_opt: Option<u16>,
+    pub match_mono_width: bool,
+    pub animation_opt: Option<TextAnimation>,
+    pub decoration_id_opt: Option<u32>,
+    pub padding_opt: Option<SpanPadding>,
+    pub cursor_color_opt: Option<Color>,
+    pub selection_color_opt: Option<Color>,
 }
 
 impl AttrsOwned {
@@ -267,6 +748,19 @@ impl AttrsOwned {
             metadata: attrs.metadata,
             cache_key_flags: attrs.cache_key_flags,
             metrics_opt: attrs.metrics_opt,
+            language_opt: attrs.language_opt.map(|language| language.to_string()),
+            script_opt: attrs.script_opt,
+            number_substitution: attrs.number_substitution,
+            ligatures: attrs.ligatures,
+            kerning: attrs.kerning,
+            style_id_opt: attrs.style_id_opt,
+            palette_index_opt: attrs.palette_index_opt,
+            match_mono_width: attrs.match_mono_width,
+            animation_opt: attrs.animation_opt,
+            decoration_id_opt: attrs.decoration_id_opt,
+            padding_opt: attrs.padding_opt,
+            cursor_color_opt: attrs.cursor_color_opt,
+            selection_color_opt: attrs.selection_color_opt,
         }
     }
 
@@ -280,6 +774,276 @@ impl AttrsOwned {
             metadata: self.metadata,
             cache_key_flags: self.cache_key_flags,
             metrics_opt: self.metrics_opt,
+            language_opt: self.language_opt.as_deref(),
+            script_opt: self.script_opt,
+            number_substitution: self.number_substitution,
+            ligatures: self.ligatures,
+            kerning: self.kerning,
+            style_id_opt: self.style_id_opt,
+            palette_index_opt: self.palette_index_opt,
+            match_mono_width: self.match_mono_width,
+            animation_opt: self.animation_opt,
+            decoration_id_opt: self.decoration_id_opt,
+            padding_opt: self.padding_opt,
+            cursor_color_opt: self.cursor_color_opt,
+            selection_color_opt: self.selection_color_opt,
+        }
+    }
+}
+
+/// Changes to apply on top of inherited [Attrs], for cascading styles
+///
+/// Each field is None to inherit the value. Fields that are optional in [Attrs] use
+/// `Some(None)` to explicitly unset the inherited value. Deltas are resolved from the buffer
+/// defaults, to the paragraph, to the span:
+///
+/// ```
+/// # use cosmic_text::{Attrs, AttrsDelta, Color, Weight};
+/// let defaults = Attrs::new().color(Color::rgb(0, 0, 0));
+/// let paragraph = AttrsDelta::new().weight(Weight::BOLD);
+/// let span = AttrsDelta::new().unset_color();
+/// let attrs = defaults.apply(&paragraph).apply(&span);
+/// assert_eq!(attrs.weight, Weight::BOLD);
+/// assert_eq!(attrs.color_opt, None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AttrsDelta<'a> {
+    pub color_opt: Option<Option<Color>>,
+    pub palette_index_opt: Option<Option<u16>>,
+    pub family: Option<Family<'a>>,
+    pub stretch: Option<Stretch>,
+    pub style: Option<Style>,
+    pub weight: Option<Weight>,
+    pub metadata: Option<usize>,
+    pub cache_key_flags: Option<CacheKeyFlags>,
+    pub metrics_opt: Option<Option<CacheMetrics>>,
+    pub language_opt: Option<Option<&'a str>>,
+    pub script_opt: Option<Option<Script>>,
+    pub number_substitution: Option<NumberSubstitution>,
+    pub ligatures: Option<Ligatures>,
+    pub kerning: Option<bool>,
+    pub match_mono_width: Option<bool>,
+    pub animation_opt: Option<Option<TextAnimation>>,
+    pub decoration_id_opt: Option<Option<u32>>,
+    pub padding_opt: Option<Option<SpanPadding>>,
+    pub cursor_color_opt: Option<Option<Color>>,
+    pub selection_color_opt: Option<Option<Color>>,
+}
+
+impl<'a> AttrsDelta<'a> {
+    /// Create a new delta that inherits every attribute
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [Color]
+    pub fn color(mut self, color: Color) -> Self {
+        self.color_opt = Some(Some(color));
+        self
+    }
+
+    /// Unset [Color], instead of inheriting it
+    pub fn unset_color(mut self) -> Self {
+        self.color_opt = Some(None);
+        self
+    }
+
+    /// Set a palette color index, see [`Attrs::palette_color`]
+    pub fn palette_color(mut self, index: u16) -> Self {
+        self.palette_index_opt = Some(Some(index));
+        self
+    }
+
+    /// Unset the palette color index, instead of inheriting it
+    pub fn unset_palette_color(mut self) -> Self {
+        self.palette_index_opt = Some(None);
+        self
+    }
+
+    /// Set [Family]
+    pub fn family(mut self, family: Family<'a>) -> Self {
+        self.family = Some(family);
+        self
+    }
+
+    /// Set [Stretch]
+    pub fn stretch(mut self, stretch: Stretch) -> Self {
+        self.stretch = Some(stretch);
+        self
+    }
+
+    /// Set [Style]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Set [Weight]
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Set metadata
+    pub fn metadata(mut self, metadata: usize) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Set [`CacheKeyFlags`]
+    pub fn cache_key_flags(mut self, cache_key_flags: CacheKeyFlags) -> Self {
+        self.cache_key_flags = Some(cache_key_flags);
+        self
+    }
+
+    /// Set [`Metrics`], overriding values in buffer
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics_opt = Some(Some(metrics.into()));
+        self
+    }
+
+    /// Unset [`Metrics`], using the values in buffer instead of inheriting them
+    pub fn unset_metrics(mut self) -> Self {
+        self.metrics_opt = Some(None);
+        self
+    }
+
+    /// Set language, as a BCP 47 tag like `"ja"` or `"zh-TW"`
+    pub fn lang(mut self, language: &'a str) -> Self {
+        self.language_opt = Some(Some(language));
+        self
+    }
+
+    /// Unset language, instead of inheriting it
+    pub fn unset_lang(mut self) -> Self {
+        self.language_opt = Some(None);
+        self
+    }
+
+    /// Set [Script], overriding the script detected from the text
+    pub fn script(mut self, script: Script) -> Self {
+        self.script_opt = Some(Some(script));
+        self
+    }
+
+    /// Unset [Script], instead of inheriting it
+    pub fn unset_script(mut self) -> Self {
+        self.script_opt = Some(None);
+        self
+    }
+
+    /// Set [`NumberSubstitution`]
+    pub fn number_substitution(mut self, number_substitution: NumberSubstitution) -> Self {
+        self.number_substitution = Some(number_substitution);
+        self
+    }
+
+    /// Set [`Ligatures`]
+    pub fn ligatures(mut self, ligatures: Ligatures) -> Self {
+        self.ligatures = Some(ligatures);
+        self
+    }
+
+    /// Set if pair kerning is applied, see [`Attrs::kerning`]
+    pub fn kerning(mut self, kerning: bool) -> Self {
+        self.kerning = Some(kerning);
+        self
+    }
+
+    /// Set if monospace glyphs are resized, see [`Attrs::match_mono_width`]
+    pub fn match_mono_width(mut self, match_mono_width: bool) -> Self {
+        self.match_mono_width = Some(match_mono_width);
+        self
+    }
+
+    /// Set [`TextAnimation`]
+    pub fn animation(mut self, animation: TextAnimation) -> Self {
+        self.animation_opt = Some(Some(animation));
+        self
+    }
+
+    /// Unset [`TextAnimation`], instead of inheriting it
+    pub fn unset_animation(mut self) -> Self {
+        self.animation_opt = Some(None);
+        self
+    }
+
+    /// Set the inline decoration identifier, see [`Attrs::decoration`]
+    pub fn decoration(mut self, id: u32) -> Self {
+        self.decoration_id_opt = Some(Some(id));
+        self
+    }
+
+    /// Unset the inline decoration identifier, instead of inheriting it
+    pub fn unset_decoration(mut self) -> Self {
+        self.decoration_id_opt = Some(None);
+        self
+    }
+
+    /// Set [`SpanPadding`], see [`Attrs::padding`]
+    pub fn padding(mut self, horizontal: f32, min_width: f32) -> Self {
+        self.padding_opt = Some(Some(SpanPadding::new(horizontal, min_width)));
+        self
+    }
+
+    /// Unset [`SpanPadding`], instead of inheriting it
+    pub fn unset_padding(mut self) -> Self {
+        self.padding_opt = Some(None);
+        self
+    }
+
+    /// Set the cursor color hint, see [`Attrs::cursor_color`]
+    pub fn cursor_color(mut self, color: Color) -> Self {
+        self.cursor_color_opt = Some(Some(color));
+        self
+    }
+
+    /// Unset the cursor color hint, instead of inheriting it
+    pub fn unset_cursor_color(mut self) -> Self {
+        self.cursor_color_opt = Some(None);
+        self
+    }
+
+    /// Set the selection color hint, see [`Attrs::selection_color`]
+    pub fn selection_color(mut self, color: Color) -> Self {
+        self.selection_color_opt = Some(Some(color));
+        self
+    }
+
+    /// Unset the selection color hint, instead of inheriting it
+    pub fn unset_selection_color(mut self) -> Self {
+        self.selection_color_opt = Some(None);
+        self
+    }
+
+    /// True if every attribute is inherited
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine with a delta applied after this one, which takes precedence
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            color_opt: other.color_opt.or(self.color_opt),
+            palette_index_opt: other.palette_index_opt.or(self.palette_index_opt),
+            family: other.family.or(self.family),
+            stretch: other.stretch.or(self.stretch),
+            style: other.style.or(self.style),
+            weight: other.weight.or(self.weight),
+            metadata: other.metadata.or(self.metadata),
+            cache_key_flags: other.cache_key_flags.or(self.cache_key_flags),
+            metrics_opt: other.metrics_opt.or(self.metrics_opt),
+            language_opt: other.language_opt.or(self.language_opt),
+            script_opt: other.script_opt.or(self.script_opt),
+            number_substitution: other.number_substitution.or(self.number_substitution),
+            ligatures: other.ligatures.or(self.ligatures),
+            kerning: other.kerning.or(self.kerning),
+            match_mono_width: other.match_mono_width.or(self.match_mono_width),
+            animation_opt: other.animation_opt.or(self.animation_opt),
+            decoration_id_opt: other.decoration_id_opt.or(self.decoration_id_opt),
+            padding_opt: other.padding_opt.or(self.padding_opt),
+            cursor_color_opt: other.cursor_color_opt.or(self.cursor_color_opt),
+            selection_color_opt: other.selection_color_opt.or(self.selection_color_opt),
         }
     }
 }
@@ -326,6 +1090,26 @@ impl AttrsList {
         self.spans.insert(range, AttrsOwned::new(attrs));
     }
 
+    /// Apply an [`AttrsDelta`] to a range, on top of the spans and defaults already in it
+    pub fn apply_span(&mut self, range: Range<usize>, delta: &AttrsDelta) {
+        if range.is_empty() || delta.is_empty() {
+            return;
+        }
+
+        let mut changes: Vec<(Range<usize>, AttrsOwned)> = Vec::new();
+        for (span_range, attrs) in self.spans.overlapping(&range) {
+            let start = span_range.start.max(range.start);
+            let end = span_range.end.min(range.end);
+            changes.push((start..end, AttrsOwned::new(attrs.as_attrs().apply(delta))));
+        }
+        for gap in self.spans.gaps(&range) {
+            changes.push((gap, AttrsOwned::new(self.defaults().apply(delta))));
+        }
+        for (range, attrs) in changes {
+            self.spans.insert(range, attrs);
+        }
+    }
+
     /// Get the attribute span for an index
     ///
     /// This returns a span that contains the index
@@ -336,6 +1120,26 @@ impl AttrsList {
             .unwrap_or(self.defaults.as_attrs())
     }
 
+    /// Check if text of length `len` is shaped and laid out the same with this attributes list
+    /// as with another, see [`Attrs::same_layout`]
+    pub(crate) fn same_layout(&self, other: &Self, len: usize) -> bool {
+        // Both lists are constant between the boundaries of their spans
+        let mut boundaries: Vec<usize> = self
+            .spans
+            .iter()
+            .chain(other.spans.iter())
+            .flat_map(|(range, _)| [range.start, range.end])
+            .filter(|&index| index < len)
+            .collect();
+        boundaries.push(0);
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        self.defaults().same_layout(&other.defaults())
+            && boundaries
+                .into_iter()
+                .all(|index| self.get_span(index).same_layout(&other.get_span(index)))
+    }
+
     /// Split attributes list at an offset
     pub fn split_off(&mut self, index: usize) -> Self {
         let mut new = Self::new(self.defaults.as_attrs());
@@ -371,3 +1175,79 @@ impl AttrsList {
         new
     }
 }
+
+#[test]
+fn test_number_substitution() {
+    let native = NumberSubstitution::Native;
+    assert_eq!(native.substitute('7', Some("ar-EG")), '\u{0667}');
+    assert_eq!(native.substitute('0', Some("fa")), '\u{06F0}');
+    assert_eq!(native.substitute('3', Some("hi")), '\u{0969}');
+    assert_eq!(native.substitute('3', Some("en")), '3');
+    assert_eq!(native.substitute('3', None), '3');
+    assert_eq!(native.substitute('a', Some("ar")), 'a');
+
+    let western = NumberSubstitution::Western;
+    assert_eq!(western.substitute('\u{0667}', None), '7');
+    assert_eq!(western.substitute('\u{0969}', Some("hi")), '3');
+    assert_eq!(western.substitute('\u{066A}', None), '\u{066A}');
+    assert_eq!(western.substitute('x', None), 'x');
+
+    assert_eq!(
+        NumberSubstitution::None.substitute('\u{0667}', None),
+        '\u{0667}'
+    );
+}
+
+#[test]
+fn test_color_space() {
+    for c in [0, 1, 10, 64, 128, 200, 255] {
+        let color = Color::rgba(c, c, c, c);
+        assert_eq!(Color::from_linear(color.to_linear()), color);
+    }
+
+    let [r, g, b, a] = Color::rgba(0xFF, 0x80, 0x00, 0xFF).to_linear();
+    assert_eq!((r, b, a), (1.0, 0.0, 1.0));
+    assert!((g - 0.2158).abs() < 0.001);
+
+    let [r, g, _, a] = Color::rgba(0xFF, 0x80, 0x00, 0x80).to_linear_premultiplied();
+    assert!((a - 0.502).abs() < 0.001);
+    assert!((r - a).abs() < 0.001);
+    assert!((g - 0.2158 * a).abs() < 0.001);
+
+    assert_eq!(
+        Color::rgba(0xFF, 0x80, 0x00, 0x80).premultiply(),
+        Color::rgba(0x80, 0x40, 0x00, 0x80)
+    );
+    assert_eq!(
+        Color::rgba(0x10, 0x20, 0x30, 0x80).mul_alpha(0xFF),
+        Color::rgba(0x10, 0x20, 0x30, 0x80)
+    );
+    assert_eq!(Color::rgb(0x10, 0x20, 0x30).mul_alpha(0x40).a(), 0x40);
+    assert_eq!(
+        Color::rgba(0x10, 0x20, 0x30, 0x80).mul_alpha(0x80).a(),
+        0x40
+    );
+}
+
+#[test]
+fn test_attrs_delta() {
+    let defaults = Attrs::new().color(Color::rgb(1, 2, 3)).lang("en");
+    let paragraph = AttrsDelta::new().style(Style::Italic).unset_lang();
+    let span = AttrsDelta::new().weight(Weight::BOLD).lang("fr");
+
+    let attrs = defaults.apply(&paragraph);
+    assert_eq!(attrs.style, Style::Italic);
+    assert_eq!(attrs.language_opt, None);
+    assert_eq!(attrs.color_opt, Some(Color::rgb(1, 2, 3)));
+    assert_eq!(defaults.apply(&paragraph.merge(&span)), attrs.apply(&span));
+
+    // Deltas are applied on top of existing spans, and defaults elsewhere
+    let mut attrs_list = AttrsList::new(defaults);
+    attrs_list.add_span(2..4, defaults.style(Style::Oblique));
+    attrs_list.apply_span(0..6, &span);
+    assert_eq!(attrs_list.get_span(0), defaults.apply(&span));
+    assert_eq!(attrs_list.get_span(3).style, Style::Oblique);
+    assert_eq!(attrs_list.get_span(3).weight, Weight::BOLD);
+    assert_eq!(attrs_list.get_span(5), defaults.apply(&span));
+    assert_eq!(attrs_list.get_span(6), defaults);
+}
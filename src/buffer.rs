@@ -1,16 +1,35 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #[cfg(not(feature = "std"))]
-use alloc::{string::String, vec::Vec};
-use core::{cmp, fmt};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::{cmp, fmt, ops::Range};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    Affinity, Attrs, AttrsList, BidiParagraphs, BorrowedWithFontSystem, BufferLine, Color, Cursor,
-    FontSystem, LayoutCursor, LayoutGlyph, LayoutLine, LineEnding, LineIter, Motion, Scroll,
-    ShapeBuffer, ShapeLine, Shaping, Wrap,
+    math, word, Affinity, Align, Anchor, AnchorBias, AnchorId, Attrs, AttrsList, BidiParagraphs,
+    BorrowedWithFontSystem, BufferLine, Color, Cursor, FontSystem, GlyphEffect, GreedyLineBreaker,
+    HashMap, HeightIndex, LayoutCursor, LayoutError, LayoutGlyph, LayoutLine, LineBreaker,
+    LineEnding, LineId, LineIter, LineMarkers, Minimap, Motion, Page, PageBreaks, PageRunIter,
+    PhysicalGlyph, PreferredX, ReshapeReasons, RustybuzzShapingBackend, Scroll, ShapeBuffer,
+    ShapeLine, Shaping, ShapingBackend, StyleSheet, TextRun, WordBoundaries, WordHook, Wrap,
+    WrapIndicator, WrapIndicatorPosition,
 };
 
+/// Which whitespace is covered by decorations, see [`LayoutRun::decoration_spans_with`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DecorationWhitespace {
+    /// Decorate all whitespace, including trailing whitespace at the end of a line
+    #[default]
+    All,
+    /// Decorate whitespace between words, but not trailing whitespace at the end of a line or
+    /// at a wrap point, like CSS text decorations
+    Inner,
+    /// Do not decorate whitespace, splitting decorations at spaces and tabs
+    None,
+}
+
 /// A line of visible text for rendering
 #[derive(Debug)]
 pub struct LayoutRun<'a> {
@@ -22,6 +41,10 @@ pub struct LayoutRun<'a> {
     pub rtl: bool,
     /// The array of layout glyphs to draw
     pub glyphs: &'a [LayoutGlyph],
+    /// Glyph to draw where the line is soft wrapped, see [`Buffer::set_wrap_indicator`]
+    ///
+    /// It is not part of [`LayoutRun::glyphs`], so it is ignored when hitting and selecting text.
+    pub wrap_indicator_opt: Option<LayoutGlyph>,
     /// Y offset to baseline of line
     pub line_y: f32,
     /// Y offset to top of line
@@ -30,9 +53,24 @@ pub struct LayoutRun<'a> {
     pub line_height: f32,
     /// Width of line
     pub line_w: f32,
+    /// Index of this run in the layout of the original text line, 0 for the first visual line
+    pub layout_i: usize,
+    /// Markers of the original text line, see [`BufferLine::set_markers`]
+    pub markers: LineMarkers,
+    /// Identifier of the original text line, see [`BufferLine::id`]
+    pub line_id: LineId,
 }
 
 impl<'a> LayoutRun<'a> {
+    /// Check if this run intersects the `(x, y, w, h)` clip rectangle, in the same coordinates as
+    /// [`Buffer::draw`]
+    ///
+    /// Only the vertical extent of the run is compared, as glyphs may overhang their advance.
+    pub fn intersects(&self, clip: (i32, i32, u32, u32)) -> bool {
+        let (_, y, _, h) = clip;
+        self.line_top < (y as f32 + h as f32) && self.line_top + self.line_height > y as f32
+    }
+
     /// Return the pixel span `Some((x_left, x_width))` of the highlighted area between `cursor_start`
     /// and `cursor_end` within this run, or None if the cursor range does not intersect this run.
     /// This may return widths of zero if `cursor_start == cursor_end`, if the run is empty, or if the
@@ -43,19 +81,22 @@ impl<'a> LayoutRun<'a> {
         let rtl_factor = if self.rtl { 1. } else { 0. };
         let ltr_factor = 1. - rtl_factor;
         for glyph in self.glyphs.iter() {
-            let cursor = self.cursor_from_glyph_left(glyph);
-            if cursor >= cursor_start && cursor <= cursor_end {
-                if x_start.is_none() {
-                    x_start = Some(glyph.x + glyph.w * rtl_factor);
+            // Highlight parts of ligatures by their graphemes
+            for part in glyph.sub_clusters(self.text) {
+                let cursor = self.cursor_from_range_left(part.start, part.end);
+                if cursor >= cursor_start && cursor <= cursor_end {
+                    if x_start.is_none() {
+                        x_start = Some(part.x + part.w * rtl_factor);
+                    }
+                    x_end = Some(part.x + part.w * rtl_factor);
                 }
-                x_end = Some(glyph.x + glyph.w * rtl_factor);
-            }
-            let cursor = self.cursor_from_glyph_right(glyph);
-            if cursor >= cursor_start && cursor <= cursor_end {
-                if x_start.is_none() {
-                    x_start = Some(glyph.x + glyph.w * ltr_factor);
+                let cursor = self.cursor_from_range_right(part.start, part.end);
+                if cursor >= cursor_start && cursor <= cursor_end {
+                    if x_start.is_none() {
+                        x_start = Some(part.x + part.w * ltr_factor);
+                    }
+                    x_end = Some(part.x + part.w * ltr_factor);
                 }
-                x_end = Some(glyph.x + glyph.w * ltr_factor);
             }
         }
         if let Some(x_start) = x_start {
@@ -71,23 +112,165 @@ impl<'a> LayoutRun<'a> {
         }
     }
 
-    fn cursor_from_glyph_left(&self, glyph: &LayoutGlyph) -> Cursor {
+    /// Return the pixel spans `(x_left, x_width)` of the text between `start` and `end` within
+    /// this run, in visual order. Text that is contiguous on screen is merged into a single
+    /// span, while bidirectional text may produce several spans.
+    pub fn range_spans(&self, start: Cursor, end: Cursor) -> Vec<(f32, f32)> {
+        let start = (start.line, start.index);
+        let end = (end.line, end.index);
+        let mut parts = Vec::new();
+        for glyph in self.glyphs.iter() {
+            for part in glyph.sub_clusters(self.text) {
+                if (self.line_i, part.start) >= start && (self.line_i, part.end) <= end {
+                    parts.push((part.x, part.w));
+                }
+            }
+        }
+        parts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut spans: Vec<(f32, f32)> = Vec::new();
+        for (part_x, part_w) in parts {
+            match spans.last_mut() {
+                Some((x, w)) if (*x + *w - part_x).abs() < 0.5 => {
+                    *w = part_x + part_w - *x;
+                }
+                _ => spans.push((part_x, part_w)),
+            }
+        }
+        spans
+    }
+
+    /// Return the pixel spans `(id, x_left, x_width)` of text with an inline decoration set with
+    /// [`Attrs::decoration`], in visual order. Adjacent glyphs with the same decoration are
+    /// merged into a single span.
+    ///
+    /// All whitespace is decorated, see [`LayoutRun::decoration_spans_with`] to change this.
+    pub fn decoration_spans(&self) -> Vec<(u32, f32, f32)> {
+        self.decoration_spans_with(DecorationWhitespace::All)
+    }
+
+    /// Return the pixel spans of inline decorations like [`LayoutRun::decoration_spans`],
+    /// decorating whitespace according to `whitespace`
+    pub fn decoration_spans_with(&self, whitespace: DecorationWhitespace) -> Vec<(u32, f32, f32)> {
+        let is_whitespace = |glyph: &LayoutGlyph| {
+            self.text
+                .get(glyph.start..glyph.end)
+                .map_or(false, |text| text.chars().all(char::is_whitespace))
+        };
+        // Whitespace starting after the last visible glyph in logical order is trailing
+        let trailing_start = match whitespace {
+            DecorationWhitespace::All => usize::MAX,
+            DecorationWhitespace::Inner | DecorationWhitespace::None => self
+                .glyphs
+                .iter()
+                .filter(|glyph| !is_whitespace(glyph))
+                .map(|glyph| glyph.end)
+                .max()
+                .unwrap_or(0),
+        };
+
+        let mut glyphs: Vec<_> = self
+            .glyphs
+            .iter()
+            .filter(|glyph| match whitespace {
+                DecorationWhitespace::All => true,
+                DecorationWhitespace::Inner => {
+                    glyph.start < trailing_start || !is_whitespace(glyph)
+                }
+                DecorationWhitespace::None => !is_whitespace(glyph),
+            })
+            .filter_map(|glyph| Some((glyph.decoration_id_opt?, glyph.x, glyph.w)))
+            .collect();
+        glyphs.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let mut spans: Vec<(u32, f32, f32)> = Vec::new();
+        for (glyph_id, glyph_x, glyph_w) in glyphs {
+            match spans.last_mut() {
+                Some((id, x, w)) if *id == glyph_id && (*x + *w - glyph_x).abs() < 0.5 => {
+                    *w = glyph_x + glyph_w - *x;
+                }
+                _ => spans.push((glyph_id, glyph_x, glyph_w)),
+            }
+        }
+        spans
+    }
+
+    /// Return the runs of glyphs with the same bidi embedding level, in visual order from left to
+    /// right, for example to draw the boundaries between directions when debugging bidi text
+    pub fn bidi_runs(&self) -> Vec<BidiRun> {
+        let mut runs: Vec<BidiRun> = Vec::new();
+        for glyph_i in self.visual_order() {
+            let glyph = &self.glyphs[glyph_i];
+            match runs.last_mut() {
+                Some(run) if run.level == glyph.level => {
+                    run.glyphs.push(glyph_i);
+                    run.range.start = run.range.start.min(glyph.start);
+                    run.range.end = run.range.end.max(glyph.end);
+                    run.w = glyph.x + glyph.w - run.x;
+                }
+                _ => runs.push(BidiRun {
+                    level: glyph.level,
+                    glyphs: vec![glyph_i],
+                    range: glyph.start..glyph.end,
+                    x: glyph.x,
+                    w: glyph.w,
+                }),
+            }
+        }
+        runs
+    }
+
+    /// Return the indexes of [`LayoutRun::glyphs`] in logical order, mapping the logical position
+    /// of each glyph to its position in the run
+    pub fn logical_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.glyphs.len()).collect();
+        order.sort_by_key(|&glyph_i| self.glyphs[glyph_i].start);
+        order
+    }
+
+    /// Return the indexes of [`LayoutRun::glyphs`] in visual order from left to right
+    fn visual_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.glyphs.len()).collect();
+        order.sort_by(|&a, &b| self.glyphs[a].x.total_cmp(&self.glyphs[b].x));
+        order
+    }
+
+    fn cursor_from_glyph_right(&self, glyph: &LayoutGlyph) -> Cursor {
+        self.cursor_from_range_right(glyph.start, glyph.end)
+    }
+
+    fn cursor_from_range_left(&self, start: usize, end: usize) -> Cursor {
         if self.rtl {
-            Cursor::new_with_affinity(self.line_i, glyph.end, Affinity::Before)
+            Cursor::new_with_affinity(self.line_i, end, Affinity::Before)
         } else {
-            Cursor::new_with_affinity(self.line_i, glyph.start, Affinity::After)
+            Cursor::new_with_affinity(self.line_i, start, Affinity::After)
         }
     }
 
-    fn cursor_from_glyph_right(&self, glyph: &LayoutGlyph) -> Cursor {
+    fn cursor_from_range_right(&self, start: usize, end: usize) -> Cursor {
         if self.rtl {
-            Cursor::new_with_affinity(self.line_i, glyph.start, Affinity::After)
+            Cursor::new_with_affinity(self.line_i, start, Affinity::After)
         } else {
-            Cursor::new_with_affinity(self.line_i, glyph.end, Affinity::Before)
+            Cursor::new_with_affinity(self.line_i, end, Affinity::Before)
         }
     }
 }
 
+/// Glyphs of a [`LayoutRun`] with the same bidi embedding level, see [`LayoutRun::bidi_runs`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BidiRun {
+    /// Unicode BiDi embedding level, the run is right-to-left if it is odd
+    pub level: unicode_bidi::Level,
+    /// Indexes of the glyphs in [`LayoutRun::glyphs`], in visual order from left to right
+    pub glyphs: Vec<usize>,
+    /// Byte range of the text of the run in the original line
+    pub range: Range<usize>,
+    /// X offset of the left edge of the run
+    pub x: f32,
+    /// Width of the run
+    pub w: f32,
+}
+
 /// An iterator of visible text lines, see [`LayoutRun`]
 #[derive(Debug)]
 pub struct LayoutRunIter<'b> {
@@ -96,6 +279,16 @@ pub struct LayoutRunIter<'b> {
     layout_i: usize,
     total_height: f32,
     line_top: f32,
+    back_opt: Option<LayoutRunBack>,
+}
+
+/// End of a [`LayoutRunIter`], found when iterating from the back
+#[derive(Debug)]
+struct LayoutRunBack {
+    line_i: usize,
+    layout_i: usize,
+    line_top: f32,
+    remaining: usize,
 }
 
 impl<'b> LayoutRunIter<'b> {
@@ -106,49 +299,76 @@ impl<'b> LayoutRunIter<'b> {
             layout_i: 0,
             total_height: 0.0,
             line_top: 0.0,
+            back_opt: None,
         }
     }
+
+    /// Find the end of the visible runs, by iterating forwards from the current position
+    fn back(&mut self) -> &mut LayoutRunBack {
+        let buffer = self.buffer;
+        let (line_i, layout_i, line_top) = (self.line_i, self.layout_i, self.line_top);
+        self.back_opt.get_or_insert_with(|| {
+            let mut iter = LayoutRunIter {
+                buffer,
+                line_i,
+                layout_i,
+                total_height: 0.0,
+                line_top,
+                back_opt: None,
+            };
+            let mut back = LayoutRunBack {
+                line_i,
+                layout_i,
+                line_top,
+                remaining: 0,
+            };
+            while iter.next().is_some() {
+                back = LayoutRunBack {
+                    line_i: iter.line_i,
+                    layout_i: iter.layout_i,
+                    line_top: iter.line_top,
+                    remaining: back.remaining + 1,
+                };
+            }
+            back
+        })
+    }
 }
 
 impl<'b> Iterator for LayoutRunIter<'b> {
     type Item = LayoutRun<'b>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(back) = &self.back_opt {
+            if back.remaining == 0 {
+                return None;
+            }
+        }
+
         while let Some(line) = self.buffer.lines.get(self.line_i) {
-            let shape = line.shape_opt().as_ref()?;
             let layout = line.layout_opt().as_ref()?;
             while let Some(layout_line) = layout.get(self.layout_i) {
+                let line_top = self.line_top - self.buffer.scroll.vertical;
+                let run = self
+                    .buffer
+                    .layout_run(self.line_i, self.layout_i, line_top)?;
                 self.layout_i += 1;
+                self.total_height += run.line_height;
 
-                let line_height = layout_line
-                    .line_height_opt
-                    .unwrap_or(self.buffer.metrics.line_height);
-                self.total_height += line_height;
-
-                let line_top = self.line_top - self.buffer.scroll.vertical;
-                let glyph_height = layout_line.max_ascent + layout_line.max_descent;
-                let centering_offset = (line_height - glyph_height) / 2.0;
-                let line_y = line_top + centering_offset + layout_line.max_ascent;
                 if let Some(height) = self.buffer.height_opt {
-                    if line_top + centering_offset > height {
+                    if run.line_y - layout_line.max_ascent > height {
                         return None;
                     }
                 }
-                self.line_top += line_height;
-                if line_y < 0.0 {
+                self.line_top += run.line_height;
+                if run.line_y < 0.0 {
                     continue;
                 }
 
-                return Some(LayoutRun {
-                    line_i: self.line_i,
-                    text: line.text(),
-                    rtl: shape.rtl,
-                    glyphs: &layout_line.glyphs,
-                    line_y,
-                    line_top,
-                    line_height,
-                    line_w: layout_line.w,
-                });
+                if let Some(back) = &mut self.back_opt {
+                    back.remaining -= 1;
+                }
+                return Some(run);
             }
             self.line_i += 1;
             self.layout_i = 0;
@@ -158,6 +378,74 @@ impl<'b> Iterator for LayoutRunIter<'b> {
     }
 }
 
+impl<'b> DoubleEndedIterator for LayoutRunIter<'b> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let buffer = self.buffer;
+        let back = self.back();
+        if back.remaining == 0 {
+            return None;
+        }
+
+        while back.layout_i == 0 {
+            back.line_i = back.line_i.checked_sub(1)?;
+            back.layout_i = buffer.lines.get(back.line_i)?.layout_opt().as_ref()?.len();
+        }
+        back.layout_i -= 1;
+
+        let layout_line = buffer.lines[back.line_i]
+            .layout_opt()
+            .as_ref()?
+            .get(back.layout_i)?;
+        back.line_top -= layout_line
+            .line_height_opt
+            .unwrap_or(buffer.scaled_metrics().line_height);
+        back.remaining -= 1;
+        buffer.layout_run(
+            back.line_i,
+            back.layout_i,
+            back.line_top - buffer.scroll.vertical,
+        )
+    }
+}
+
+/// A word under a position, see [`Buffer::word_at`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordHit<'a> {
+    /// Index of the line containing the word
+    pub line_i: usize,
+    /// Byte range of the word in the line
+    pub range: Range<usize>,
+    /// Text of the word
+    pub text: &'a str,
+    /// Rectangles `(x, y, width, height)` covering the word, one per layout line it is on
+    pub rects: Vec<(f32, f32, f32, f32)>,
+}
+
+/// A link under a position or in view, see [`Buffer::link_at`] and [`Buffer::visible_links`]
+#[cfg(feature = "linkify")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkHit<'a> {
+    /// Index of the line containing the link
+    pub line_i: usize,
+    /// The link, with its byte range in the line
+    pub link: &'a crate::Link,
+    /// Rectangles `(x, y, width, height)` covering the link, one per layout line it is on
+    pub rects: Vec<(f32, f32, f32, f32)>,
+}
+
+/// A visible grapheme with its position on screen, see [`Buffer::visible_chars`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisibleChar<'a> {
+    /// Index of the line containing the grapheme
+    pub line_i: usize,
+    /// Byte range of the grapheme in the line
+    pub range: Range<usize>,
+    /// Text of the grapheme
+    pub text: &'a str,
+    /// Rectangle `(x, y, width, height)` covering the grapheme
+    pub rect: (f32, f32, f32, f32),
+}
+
 /// Metrics of text
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Metrics {
@@ -191,6 +479,22 @@ impl Metrics {
             line_height: self.line_height * scale,
         }
     }
+
+    /// Check that font size and line height are positive and finite
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::InvalidFontSize`] or [`LayoutError::InvalidLineHeight`] if the
+    /// value is zero, negative, or not finite.
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        if !(self.font_size.is_finite() && self.font_size > 0.0) {
+            return Err(LayoutError::InvalidFontSize);
+        }
+        if !(self.line_height.is_finite() && self.line_height > 0.0) {
+            return Err(LayoutError::InvalidLineHeight);
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for Metrics {
@@ -205,6 +509,7 @@ pub struct Buffer {
     /// [BufferLine]s (or paragraphs) of text in the buffer
     pub lines: Vec<BufferLine>,
     metrics: Metrics,
+    scale: f32,
     width_opt: Option<f32>,
     height_opt: Option<f32>,
     scroll: Scroll,
@@ -212,7 +517,28 @@ pub struct Buffer {
     redraw: bool,
     wrap: Wrap,
     monospace_width: Option<f32>,
+    cell_width_opt: Option<f32>,
     tab_width: u16,
+    word_boundaries: WordBoundaries,
+    word_hook_opt: Option<Arc<dyn WordHook>>,
+    line_breaker: Arc<dyn LineBreaker>,
+    shaping_backend: Arc<dyn ShapingBackend>,
+    anchors: Vec<(AnchorId, Anchor)>,
+    next_anchor_id: usize,
+    height_index: HeightIndex,
+    is_static: bool,
+    scroll_anchoring: bool,
+    scroll_margin: usize,
+    trailing_empty_line: bool,
+    /// Wrap indicator with its glyph laid out at a font size of 1
+    wrap_indicator_opt: Option<(WrapIndicator, LayoutGlyph)>,
+    bottom_up: bool,
+    /// True if a bottom up buffer is scrolled to the end and should stay there
+    stick_to_bottom: bool,
+    /// [`FontSystem::generation`] the lines were shaped with
+    font_generation: u64,
+    /// Render-time effects keyed by line index and cluster start
+    glyph_effects: HashMap<(usize, usize), GlyphEffect>,
 
     /// Scratch buffer for shaping and laying out.
     scratch: ShapeBuffer,
@@ -223,13 +549,31 @@ impl Clone for Buffer {
         Self {
             lines: self.lines.clone(),
             metrics: self.metrics,
+            scale: self.scale,
             width_opt: self.width_opt,
             height_opt: self.height_opt,
             scroll: self.scroll,
             redraw: self.redraw,
             wrap: self.wrap,
             monospace_width: self.monospace_width,
+            cell_width_opt: self.cell_width_opt,
             tab_width: self.tab_width,
+            word_boundaries: self.word_boundaries.clone(),
+            word_hook_opt: self.word_hook_opt.clone(),
+            line_breaker: self.line_breaker.clone(),
+            shaping_backend: self.shaping_backend.clone(),
+            anchors: self.anchors.clone(),
+            next_anchor_id: self.next_anchor_id,
+            height_index: self.height_index.clone(),
+            is_static: self.is_static,
+            scroll_anchoring: self.scroll_anchoring,
+            scroll_margin: self.scroll_margin,
+            trailing_empty_line: self.trailing_empty_line,
+            wrap_indicator_opt: self.wrap_indicator_opt.clone(),
+            bottom_up: self.bottom_up,
+            stick_to_bottom: self.stick_to_bottom,
+            font_generation: self.font_generation,
+            glyph_effects: self.glyph_effects.clone(),
             scratch: ShapeBuffer::default(),
         }
     }
@@ -252,6 +596,7 @@ impl Buffer {
         Self {
             lines: Vec::new(),
             metrics,
+            scale: 1.0,
             width_opt: None,
             height_opt: None,
             scroll: Scroll::default(),
@@ -259,7 +604,24 @@ impl Buffer {
             wrap: Wrap::WordOrGlyph,
             scratch: ShapeBuffer::default(),
             monospace_width: None,
+            cell_width_opt: None,
             tab_width: 8,
+            word_boundaries: WordBoundaries::default(),
+            word_hook_opt: None,
+            line_breaker: Arc::new(GreedyLineBreaker),
+            shaping_backend: Arc::new(RustybuzzShapingBackend),
+            anchors: Vec::new(),
+            next_anchor_id: 0,
+            height_index: HeightIndex::default(),
+            is_static: false,
+            scroll_anchoring: false,
+            scroll_margin: 0,
+            trailing_empty_line: false,
+            wrap_indicator_opt: None,
+            bottom_up: false,
+            stick_to_bottom: false,
+            font_generation: 0,
+            glyph_effects: HashMap::default(),
         }
     }
 
@@ -267,13 +629,26 @@ impl Buffer {
     ///
     /// # Panics
     ///
-    /// Will panic if `metrics.line_height` is zero.
+    /// Will panic if `metrics.line_height` is zero, use [`Buffer::try_new`] to get an error
+    /// instead.
     pub fn new(font_system: &mut FontSystem, metrics: Metrics) -> Self {
         let mut buffer = Self::new_empty(metrics);
         buffer.set_text(font_system, "", Attrs::new(), Shaping::Advanced);
         buffer
     }
 
+    /// Create a new [`Buffer`] like [`Buffer::new`], returning an error if `metrics` are not
+    /// valid, see [`Metrics::validate`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::InvalidFontSize`] or [`LayoutError::InvalidLineHeight`] if
+    /// `metrics` are not valid.
+    pub fn try_new(font_system: &mut FontSystem, metrics: Metrics) -> Result<Self, LayoutError> {
+        metrics.validate()?;
+        Ok(Self::new(font_system, metrics))
+    }
+
     /// Mutably borrows the buffer together with an [`FontSystem`] for more convenient methods
     pub fn borrow_with<'a>(
         &'a mut self,
@@ -289,71 +664,135 @@ impl Buffer {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
+        self.check_font_generation(font_system);
+
+        #[cfg(feature = "perf")]
+        {
+            font_system.perf_counters_mut().relayouts += 1;
+        }
+
+        let font_size = self.scaled_metrics().font_size;
         for line in &mut self.lines {
             if line.shape_opt().is_some() {
                 line.reset_layout();
-                line.layout_in_buffer(
+                line.layout_with_buffer_settings(
                     &mut self.scratch,
                     font_system,
-                    self.metrics.font_size,
+                    font_size,
                     self.width_opt,
                     self.wrap,
                     self.monospace_width,
+                    self.cell_width_opt,
                     self.tab_width,
+                    self.line_breaker.as_ref(),
+                    self.shaping_backend.as_ref(),
                 );
             }
         }
 
+        self.height_index = self.build_height_index();
         self.redraw = true;
+        self.compact_if_static();
 
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         log::debug!("relayout: {:?}", instant.elapsed());
     }
 
-    /// Shape lines until cursor, also scrolling to include cursor in view
+    /// Reset the shaping of all lines if fonts changed since they were shaped, see
+    /// [`FontSystem::generation`]
+    fn check_font_generation(&mut self, font_system: &FontSystem) {
+        let generation = font_system.generation();
+        if self.font_generation != generation {
+            self.font_generation = generation;
+            for line in self.lines.iter_mut() {
+                if line.shape_opt().is_some() {
+                    line.reset_shaping_for(ReshapeReasons::FONTS);
+                }
+            }
+            self.redraw = true;
+        }
+    }
+
+    /// Compact laid out lines and drop the scratch buffer if the buffer is static
+    fn compact_if_static(&mut self) {
+        if self.is_static {
+            for line in self.lines.iter_mut() {
+                line.compact();
+            }
+            self.scratch = ShapeBuffer::default();
+        }
+    }
+
+    /// Shape lines until cursor, also scrolling to include cursor in view, with the lines of
+    /// [`Buffer::scroll_margin`] around it
     pub fn shape_until_cursor(
         &mut self,
         font_system: &mut FontSystem,
         cursor: Cursor,
         prune: bool,
     ) {
-        let metrics = self.metrics;
+        let metrics = self.scaled_metrics();
         let old_scroll = self.scroll;
 
-        let layout_cursor = self
-            .layout_cursor(font_system, cursor)
-            .expect("shape_until_cursor invalid cursor");
+        let layout_cursor = match self.layout_cursor(font_system, cursor) {
+            Some(some) => some,
+            None => {
+                log::warn!("shape_until_cursor invalid cursor {:?}", cursor);
+                return;
+            }
+        };
 
-        let mut layout_y = 0.0;
-        let mut total_height = {
-            let layout = self
-                .line_layout(font_system, layout_cursor.line)
-                .expect("shape_until_cursor failed to scroll forwards");
-            for layout_i in 0..layout_cursor.layout {
-                layout_y += layout[layout_i]
-                    .line_height_opt
-                    .unwrap_or(metrics.line_height);
-            }
-            layout_y
-                + layout[layout_cursor.layout]
-                    .line_height_opt
-                    .unwrap_or(metrics.line_height)
+        // Keep at most half of the visible lines above and below the cursor
+        let margin = match self.height_opt {
+            Some(height) => {
+                let visible_lines = (height / metrics.line_height) as usize;
+                self.scroll_margin.min(visible_lines.saturating_sub(1) / 2)
+            }
+            None => self.scroll_margin,
         };
+        let (top_line, top_layout) = self.offset_layout_line(
+            font_system,
+            layout_cursor.line,
+            layout_cursor.layout,
+            margin,
+            false,
+        );
+        let (bottom_line, bottom_layout) = self.offset_layout_line(
+            font_system,
+            layout_cursor.line,
+            layout_cursor.layout,
+            margin,
+            true,
+        );
+
+        let mut layout_y = 0.0;
+        if let Some(layout) = self.line_layout(font_system, top_line) {
+            for layout_line in layout.iter().take(top_layout) {
+                layout_y += layout_line.line_height_opt.unwrap_or(metrics.line_height);
+            }
+        }
+        let mut total_height = 0.0;
+        if let Some(layout) = self.line_layout(font_system, bottom_line) {
+            for layout_line in layout.iter().take(bottom_layout + 1) {
+                total_height += layout_line.line_height_opt.unwrap_or(metrics.line_height);
+            }
+        }
 
-        if self.scroll.line > layout_cursor.line
-            || (self.scroll.line == layout_cursor.line && self.scroll.vertical > layout_y)
+        if self.scroll.line > top_line
+            || (self.scroll.line == top_line && self.scroll.vertical > layout_y)
         {
             // Adjust scroll backwards if cursor is before it
-            self.scroll.line = layout_cursor.line;
+            self.scroll.line = top_line;
             self.scroll.vertical = layout_y;
         } else if let Some(height) = self.height_opt {
             // Adjust scroll forwards if cursor is after it
-            let mut line_i = layout_cursor.line;
+            let mut line_i = bottom_line;
             while line_i > self.scroll.line {
                 line_i -= 1;
-                let layout = self
-                    .line_layout(font_system, line_i)
-                    .expect("shape_until_cursor failed to scroll forwards");
+                let layout = match self.line_layout(font_system, line_i) {
+                    Some(some) => some,
+                    None => continue,
+                };
                 for layout_line in layout.iter() {
                     total_height += layout_line.line_height_opt.unwrap_or(metrics.line_height);
                 }
@@ -365,6 +804,7 @@ impl Buffer {
         }
 
         if old_scroll != self.scroll {
+            self.stick_to_bottom = false;
             self.redraw = true;
         }
 
@@ -401,10 +841,55 @@ impl Buffer {
         }
     }
 
+    /// Move `count` visual lines before or after the layout line `layout_i` of line `line_i`,
+    /// stopping at the start or end of the buffer
+    fn offset_layout_line(
+        &mut self,
+        font_system: &mut FontSystem,
+        mut line_i: usize,
+        mut layout_i: usize,
+        count: usize,
+        forwards: bool,
+    ) -> (usize, usize) {
+        for _ in 0..count {
+            if forwards {
+                let layout_len = self
+                    .line_layout(font_system, line_i)
+                    .map_or(1, |layout| layout.len());
+                if layout_i + 1 < layout_len {
+                    layout_i += 1;
+                } else if line_i + 1 < self.lines.len() {
+                    line_i += 1;
+                    layout_i = 0;
+                } else {
+                    break;
+                }
+            } else if layout_i > 0 {
+                layout_i -= 1;
+            } else if line_i > 0 {
+                line_i -= 1;
+                layout_i = self
+                    .line_layout(font_system, line_i)
+                    .map_or(1, |layout| layout.len())
+                    .saturating_sub(1);
+            } else {
+                break;
+            }
+        }
+        (line_i, layout_i)
+    }
+
     /// Shape lines until scroll
     pub fn shape_until_scroll(&mut self, font_system: &mut FontSystem, prune: bool) {
-        let metrics = self.metrics;
+        let metrics = self.scaled_metrics();
         let old_scroll = self.scroll;
+        let bottom_up_height_opt = self.height_opt.filter(|_| self.bottom_up);
+
+        if bottom_up_height_opt.is_some() && self.stick_to_bottom {
+            // Scroll past the end, the loop below will move back to show the last line
+            self.scroll.line = self.lines.len();
+            self.scroll.vertical = 0.0;
+        }
 
         loop {
             // Adjust scroll.layout to be positive by moving scroll.line backwards
@@ -414,15 +899,17 @@ impl Buffer {
                     if let Some(layout) = self.line_layout(font_system, line_i) {
                         let mut layout_height = 0.0;
                         for layout_line in layout.iter() {
-                            layout_height +=
-                                layout_line.line_height_opt.unwrap_or(metrics.line_height);
+                            layout_height += layout_line
+                                .line_height_opt
+                                .unwrap_or(metrics.line_height)
+                                .max(0.0);
                         }
                         self.scroll.line = line_i;
                         self.scroll.vertical += layout_height;
                     } else {
                         // If layout is missing, just assume line height
                         self.scroll.line = line_i;
-                        self.scroll.vertical += metrics.line_height;
+                        self.scroll.vertical += metrics.line_height.max(0.0);
                     }
                 } else {
                     self.scroll.vertical = 0.0;
@@ -437,13 +924,13 @@ impl Buffer {
             for line_i in 0..self.lines.len() {
                 if line_i < self.scroll.line {
                     if prune {
-                        self.lines[line_i].reset_shaping();
+                        self.lines[line_i].reset_shaping_for(ReshapeReasons::PRUNED);
                     }
                     continue;
                 }
                 if total_height > scroll_end {
                     if prune {
-                        self.lines[line_i].reset_shaping();
+                        self.lines[line_i].reset_shaping_for(ReshapeReasons::PRUNED);
                         continue;
                     } else {
                         break;
@@ -451,14 +938,19 @@ impl Buffer {
                 }
 
                 let mut layout_height = 0.0;
-                let layout = self
-                    .line_layout(font_system, line_i)
-                    .expect("shape_until_scroll invalid line");
-                for layout_line in layout.iter() {
-                    let line_height = layout_line.line_height_opt.unwrap_or(metrics.line_height);
-                    layout_height += line_height;
-                    total_height += line_height;
+                if let Some(layout) = self.line_layout(font_system, line_i) {
+                    for layout_line in layout.iter() {
+                        // Negative or NaN heights would never fill the scroll area
+                        layout_height += layout_line
+                            .line_height_opt
+                            .unwrap_or(metrics.line_height)
+                            .max(0.0);
+                    }
+                } else {
+                    // If layout is missing, just assume line height
+                    layout_height = metrics.line_height.max(0.0);
                 }
+                total_height += layout_height;
 
                 // Adjust scroll.vertical to be smaller by moving scroll.line forwards
                 //TODO: do we want to adjust it exactly to a layout line?
@@ -477,9 +969,22 @@ impl Buffer {
             }
         }
 
+        if let Some(height) = bottom_up_height_opt {
+            let total_height = self.total_height();
+            if total_height < height {
+                // Align short content to the bottom
+                self.scroll.line = 0;
+                self.scroll.vertical = total_height - height;
+            }
+            let scroll_bottom = self.y_for_line(self.scroll.line) + self.scroll.vertical + height;
+            self.stick_to_bottom = scroll_bottom + 0.5 >= total_height;
+        }
+
         if old_scroll != self.scroll {
             self.redraw = true;
         }
+
+        self.compact_if_static();
     }
 
     /// Convert a [`Cursor`] to a [`LayoutCursor`]
@@ -514,14 +1019,81 @@ impl Buffer {
         Some(LayoutCursor::new(cursor.line, 0, 0))
     }
 
+    /// Get the X position of a [`LayoutCursor`] in pixels
+    fn layout_cursor_x(
+        &mut self,
+        font_system: &mut FontSystem,
+        layout_cursor: LayoutCursor,
+    ) -> Option<i32> {
+        let layout = self.line_layout(font_system, layout_cursor.line)?;
+        let layout_line = layout.get(layout_cursor.layout).or(layout.last())?;
+        let x = match layout_line.glyphs.get(layout_cursor.glyph) {
+            // Start of glyph
+            Some(glyph) if glyph.level.is_rtl() => glyph.x + glyph.w,
+            Some(glyph) => glyph.x,
+            // End of last glyph
+            None => match layout_line.glyphs.last() {
+                Some(glyph) if glyph.level.is_rtl() => glyph.x,
+                Some(glyph) => glyph.x + glyph.w,
+                None => 0.0,
+            },
+        };
+        Some(x as i32)
+    }
+
+    /// Find the [`LayoutCursor`] closest to an X position in pixels on a layout line, using the
+    /// last layout line if `layout_i` is out of bounds
+    fn layout_cursor_from_x(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_i: usize,
+        layout_i: usize,
+        x: i32,
+    ) -> Option<LayoutCursor> {
+        let layout = self.line_layout(font_system, line_i)?;
+        let layout_i = layout_i.min(layout.len().checked_sub(1)?);
+        let glyphs = &layout[layout_i].glyphs;
+        let x = x as f32;
+
+        // The cursor can be at the start of each glyph, or at the end of the last glyph
+        let starts = glyphs.iter().enumerate().map(|(glyph_i, glyph)| {
+            let start_x = if glyph.level.is_rtl() {
+                glyph.x + glyph.w
+            } else {
+                glyph.x
+            };
+            (glyph_i, start_x)
+        });
+        let end = glyphs.last().map(|glyph| {
+            let end_x = if glyph.level.is_rtl() {
+                glyph.x
+            } else {
+                glyph.x + glyph.w
+            };
+            (glyphs.len(), end_x)
+        });
+        let glyph_i = starts
+            .chain(end)
+            .min_by(|(_, a), (_, b)| (a - x).abs().total_cmp(&(b - x).abs()))
+            .map_or(0, |(glyph_i, _)| glyph_i);
+
+        Some(LayoutCursor::new(line_i, layout_i, glyph_i))
+    }
+
     /// Shape the provided line index and return the result
     pub fn line_shape(
         &mut self,
         font_system: &mut FontSystem,
         line_i: usize,
     ) -> Option<&ShapeLine> {
+        self.check_font_generation(font_system);
         let line = self.lines.get_mut(line_i)?;
-        Some(line.shape_in_buffer(&mut self.scratch, font_system, self.tab_width))
+        Some(line.shape_with_backend(
+            &mut self.scratch,
+            font_system,
+            self.tab_width,
+            self.shaping_backend.as_ref(),
+        ))
     }
 
     /// Lay out the provided line index and return the result
@@ -530,16 +1102,31 @@ impl Buffer {
         font_system: &mut FontSystem,
         line_i: usize,
     ) -> Option<&[LayoutLine]> {
+        self.check_font_generation(font_system);
+        let font_size = self.scaled_metrics().font_size;
         let line = self.lines.get_mut(line_i)?;
-        Some(line.layout_in_buffer(
+        line.layout_with_buffer_settings(
             &mut self.scratch,
             font_system,
-            self.metrics.font_size,
+            font_size,
             self.width_opt,
             self.wrap,
             self.monospace_width,
+            self.cell_width_opt,
             self.tab_width,
-        ))
+            self.line_breaker.as_ref(),
+            self.shaping_backend.as_ref(),
+        );
+
+        if self.height_index.len() == self.lines.len() {
+            if let Some(height) = self.line_layout_height(line_i) {
+                self.height_index.set(line_i, height);
+            }
+        } else {
+            self.height_index = self.build_height_index();
+        }
+
+        self.lines[line_i].layout_opt().as_deref()
     }
 
     /// Get the current [`Metrics`]
@@ -547,21 +1134,56 @@ impl Buffer {
         self.metrics
     }
 
+    /// Get the current [`Metrics`] multiplied by the display scale, as used for layout, see
+    /// [`Buffer::set_scale`]
+    pub fn scaled_metrics(&self) -> Metrics {
+        self.metrics.scale(self.scale)
+    }
+
     /// Set the current [`Metrics`]
     ///
     /// # Panics
     ///
-    /// Will panic if `metrics.font_size` is zero.
+    /// Will panic if `metrics.font_size` is zero, use [`Buffer::try_set_metrics_and_size`] to
+    /// get an error instead.
     pub fn set_metrics(&mut self, font_system: &mut FontSystem, metrics: Metrics) {
         self.set_metrics_and_size(font_system, metrics, self.width_opt, self.height_opt);
     }
 
+    /// Get the display scale, see [`Buffer::set_scale`]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Set the display scale, such as the scale factor of a window
+    ///
+    /// The [`Metrics`] of the buffer stay in logical pixels and are multiplied by the scale for
+    /// layout, so tab stops, which are a number of spaces, follow along. Metrics of spans set with
+    /// [`Attrs::metrics`] are not scaled. The buffer size, layout runs and drawing are in physical
+    /// pixels, and editors draw the caret as wide as the scale. Use [`Buffer::hit_logical`] to hit
+    /// test positions in logical pixels.
+    ///
+    /// A `scale` that is not positive and finite is not allowed, and will be ignored.
+    pub fn set_scale(&mut self, font_system: &mut FontSystem, scale: f32) {
+        if !(scale.is_finite() && scale > 0.0) {
+            log::warn!("set_scale ignored invalid scale {}", scale);
+            return;
+        }
+        if scale != self.scale {
+            self.scale = scale;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
     /// Get the current [`Wrap`]
     pub fn wrap(&self) -> Wrap {
         self.wrap
     }
 
     /// Set the current [`Wrap`]
+    ///
+    /// Lines can override it with [`BufferLine::set_wrap`].
     pub fn set_wrap(&mut self, font_system: &mut FontSystem, wrap: Wrap) {
         if wrap != self.wrap {
             self.wrap = wrap;
@@ -570,77 +1192,469 @@ impl Buffer {
         }
     }
 
-    /// Get the current `monospace_width`
-    pub fn monospace_width(&self) -> Option<f32> {
-        self.monospace_width
-    }
-
-    /// Set monospace width monospace glyphs should be resized to match. `None` means don't resize
-    pub fn set_monospace_width(
-        &mut self,
-        font_system: &mut FontSystem,
-        monospace_width: Option<f32>,
-    ) {
-        if monospace_width != self.monospace_width {
-            self.monospace_width = monospace_width;
-            self.relayout(font_system);
-            self.shape_until_scroll(font_system, false);
-        }
+    /// True if text ending with a newline gets an empty last line, see
+    /// [`Buffer::set_trailing_empty_line`]
+    pub fn trailing_empty_line(&self) -> bool {
+        self.trailing_empty_line
     }
 
-    /// Get the current `tab_width`
-    pub fn tab_width(&self) -> u16 {
-        self.tab_width
+    /// Add an empty line after text ending with a newline in [`Buffer::set_text`] and
+    /// [`Buffer::set_rich_text`], so the cursor can be placed after the newline like in an
+    /// editor
+    ///
+    /// When false, a trailing newline only ends the last line, so it does not add an empty line
+    /// to the height of a label. This applies to text set after changing it. Defaults to false.
+    pub fn set_trailing_empty_line(&mut self, trailing_empty_line: bool) {
+        self.trailing_empty_line = trailing_empty_line;
     }
 
-    /// Set tab width (number of spaces between tab stops)
-    pub fn set_tab_width(&mut self, font_system: &mut FontSystem, tab_width: u16) {
-        // A tab width of 0 is not allowed
-        if tab_width == 0 {
-            return;
-        }
-        if tab_width != self.tab_width {
-            self.tab_width = tab_width;
-            // Shaping must be reset when tab width is changed
-            for line in self.lines.iter_mut() {
-                if line.shape_opt().is_some() {
-                    if line.text().contains('\t') {
-                        line.reset_shaping();
-                    }
-                }
-            }
-            self.redraw = true;
-            self.shape_until_scroll(font_system, false);
+    /// Add an empty line after `text` if it ends with a newline and
+    /// [`Buffer::trailing_empty_line`] is enabled, for [`Buffer::set_text`]
+    fn push_trailing_empty_line(&mut self, text: &str, attrs: Attrs, shaping: Shaping) {
+        if self.trailing_empty_line && text.ends_with(['\n', '\r']) {
+            self.lines.push(BufferLine::new(
+                "",
+                LineEnding::None,
+                AttrsList::new(attrs),
+                shaping,
+            ));
         }
     }
 
-    /// Get the current buffer dimensions (width, height)
-    pub fn size(&self) -> (Option<f32>, Option<f32>) {
-        (self.width_opt, self.height_opt)
+    /// Get the current [`WrapIndicator`], see [`Buffer::set_wrap_indicator`]
+    pub fn wrap_indicator(&self) -> Option<&WrapIndicator> {
+        self.wrap_indicator_opt
+            .as_ref()
+            .map(|(indicator, _)| indicator)
     }
 
-    /// Set the current buffer dimensions
-    pub fn set_size(
+    /// Show a glyph where lines are soft wrapped, reported with
+    /// [`LayoutRun::wrap_indicator_opt`], or None to show nothing
+    ///
+    /// The indicator is not part of the text or its layout, so changing it does not lay out the
+    /// lines again. Nothing is shown if no font has a glyph for the character.
+    pub fn set_wrap_indicator(
         &mut self,
         font_system: &mut FontSystem,
-        width_opt: Option<f32>,
-        height_opt: Option<f32>,
+        indicator_opt: Option<WrapIndicator>,
     ) {
-        self.set_metrics_and_size(font_system, self.metrics, width_opt, height_opt);
+        self.wrap_indicator_opt = indicator_opt.and_then(|indicator| {
+            let mut text = [0; 4];
+            let text = indicator.character.encode_utf8(&mut text);
+            let shape = ShapeLine::new(
+                font_system,
+                text,
+                &AttrsList::new(indicator.attrs.as_attrs()),
+                Shaping::Advanced,
+                self.tab_width,
+            );
+            let layout = shape.layout(1.0, None, Wrap::None, None, None);
+            let glyph = layout.first()?.glyphs.first()?.clone();
+            Some((indicator, glyph))
+        });
+        self.redraw = true;
     }
 
-    /// Set the current [`Metrics`] and buffer dimensions at the same time
-    ///
-    /// # Panics
-    ///
-    /// Will panic if `metrics.font_size` is zero.
-    pub fn set_metrics_and_size(
-        &mut self,
-        font_system: &mut FontSystem,
-        metrics: Metrics,
-        width_opt: Option<f32>,
-        height_opt: Option<f32>,
-    ) {
+    /// Get the wrap indicator glyph of layout line `layout_i` in `layout`, see
+    /// [`Buffer::set_wrap_indicator`]
+    pub(crate) fn wrap_indicator_glyph(
+        &self,
+        layout: &[LayoutLine],
+        layout_i: usize,
+        rtl: bool,
+    ) -> Option<LayoutGlyph> {
+        let (indicator, em_glyph) = self.wrap_indicator_opt.as_ref()?;
+        let at_end = indicator.position == WrapIndicatorPosition::End;
+        let wrapped = if at_end {
+            layout_i + 1 < layout.len()
+        } else {
+            layout_i > 0
+        };
+        if !wrapped {
+            return None;
+        }
+
+        let layout_line = layout.get(layout_i)?;
+        let font_size = layout_line
+            .glyphs
+            .first()
+            .map_or(self.scaled_metrics().font_size, |glyph| glyph.font_size);
+        let (mut x_min, mut x_max) = (f32::MAX, f32::MIN);
+        let (mut start, mut end) = (usize::MAX, 0);
+        for glyph in layout_line.glyphs.iter() {
+            x_min = x_min.min(glyph.x);
+            x_max = x_max.max(glyph.x + glyph.w);
+            start = start.min(glyph.start);
+            end = end.max(glyph.end);
+        }
+        if layout_line.glyphs.is_empty() {
+            (x_min, x_max) = (0.0, 0.0);
+            start = 0;
+        }
+
+        let mut glyph = em_glyph.clone();
+        glyph.font_size = font_size;
+        glyph.w *= font_size;
+        glyph.x_offset *= font_size;
+        glyph.y_offset *= font_size;
+        // The end of a left-to-right line and the start of a right-to-left line are on the right
+        glyph.x = if at_end != rtl {
+            x_max
+        } else {
+            x_min - glyph.w
+        };
+        // Place it at the wrap point in the text
+        glyph.start = if at_end { end } else { start };
+        glyph.end = glyph.start;
+        glyph.level = if rtl {
+            unicode_bidi::Level::rtl()
+        } else {
+            unicode_bidi::Level::ltr()
+        };
+        Some(glyph)
+    }
+
+    /// True if the buffer is static, see [`Buffer::set_static`]
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    /// Hint that the buffer is only displayed and not edited, such as a label in a list
+    ///
+    /// A static buffer compacts its lines after layout, see [`BufferLine::compact`], and does
+    /// not keep a scratch buffer for shaping, reducing memory use. Lines are shaped again if
+    /// they need to be laid out again, for example after a resize, and then compacted. Editing
+    /// and cursor motions still work, but reshape the lines they use.
+    pub fn set_static(&mut self, is_static: bool) {
+        self.is_static = is_static;
+        self.compact_if_static();
+    }
+
+    /// True if scroll anchoring is enabled, see [`Buffer::set_scroll_anchoring`]
+    pub fn scroll_anchoring(&self) -> bool {
+        self.scroll_anchoring
+    }
+
+    /// Keep the visible text in place when lines are inserted or deleted before the scroll
+    /// position, by moving the scroll line with the text
+    ///
+    /// Edits made through an [`Edit`](crate::Edit) implementation are handled automatically.
+    /// When changing [`Buffer::lines`] directly, for example to load earlier history in a chat,
+    /// report the change with [`Buffer::shift_anchors_insert`] or
+    /// [`Buffer::shift_anchors_delete`].
+    pub fn set_scroll_anchoring(&mut self, scroll_anchoring: bool) {
+        self.scroll_anchoring = scroll_anchoring;
+    }
+
+    /// Get the number of visual lines kept visible around the cursor, see
+    /// [`Buffer::set_scroll_margin`]
+    pub fn scroll_margin(&self) -> usize {
+        self.scroll_margin
+    }
+
+    /// Keep `scroll_margin` visual lines visible above and below the cursor when scrolling to it
+    /// with [`Buffer::shape_until_cursor`], like the `scrolloff` setting of many editors
+    ///
+    /// The margin is reduced when the buffer is too short to show it on both sides of the
+    /// cursor, and at the start and end of the text. Defaults to 0.
+    pub fn set_scroll_margin(&mut self, scroll_margin: usize) {
+        self.scroll_margin = scroll_margin;
+    }
+
+    /// Get the render-time effect of the glyphs of the cluster starting at byte `start` of line
+    /// `line_i`, see [`Buffer::set_glyph_effect`]
+    pub fn glyph_effect(&self, line_i: usize, start: usize) -> Option<GlyphEffect> {
+        self.glyph_effects.get(&(line_i, start)).copied()
+    }
+
+    /// Set or remove the render-time effect of the glyphs of the cluster starting at byte
+    /// `start` of line `line_i`, such as [`LayoutGlyph::start`]
+    ///
+    /// Effects move and scale glyphs when drawing, without shaping or laying out the line
+    /// again, for animations such as hover lift. They are keyed by position and do not follow
+    /// edits, so they should be updated or cleared when the text changes.
+    pub fn set_glyph_effect(&mut self, line_i: usize, start: usize, effect: Option<GlyphEffect>) {
+        let changed = match effect {
+            Some(effect) => self.glyph_effects.insert((line_i, start), effect) != Some(effect),
+            None => self.glyph_effects.remove(&(line_i, start)).is_some(),
+        };
+        if changed {
+            self.redraw = true;
+        }
+    }
+
+    /// Remove all render-time glyph effects
+    pub fn clear_glyph_effects(&mut self) {
+        if !self.glyph_effects.is_empty() {
+            self.glyph_effects.clear();
+            self.redraw = true;
+        }
+    }
+
+    /// Convert a glyph of a run of this buffer to a [`PhysicalGlyph`], applying its glyph effect
+    pub fn physical_glyph(
+        &self,
+        run: &LayoutRun,
+        glyph: &LayoutGlyph,
+        offset: (f32, f32),
+        scale: f32,
+    ) -> PhysicalGlyph {
+        match self.glyph_effect(run.line_i, glyph.start) {
+            Some(effect) => glyph.physical_with_effect(offset, scale, effect),
+            None => glyph.physical(offset, scale),
+        }
+    }
+
+    /// True if content is anchored to the bottom, see [`Buffer::set_bottom_up`]
+    pub fn bottom_up(&self) -> bool {
+        self.bottom_up
+    }
+
+    /// Anchor content to the bottom of the buffer, so it grows upward, as in a chat input
+    /// preview or a terminal prompt
+    ///
+    /// Content shorter than the buffer height is aligned to the bottom, using a negative
+    /// [`Scroll::vertical`]. While scrolled to the end, [`Buffer::shape_until_scroll`] keeps the
+    /// last line at the bottom as lines are added, pushing earlier lines up. Scrolling away from
+    /// the end with [`Buffer::set_scroll`] or to a cursor stops this until the end is scrolled
+    /// to again. This has no effect if the buffer has no height.
+    pub fn set_bottom_up(&mut self, bottom_up: bool) {
+        if bottom_up != self.bottom_up {
+            self.bottom_up = bottom_up;
+            self.stick_to_bottom = bottom_up;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the width monospace glyphs are resized to match, see [`Buffer::set_monospace_width`]
+    pub fn monospace_width(&self) -> Option<f32> {
+        self.monospace_width
+    }
+
+    /// Set the width in pixels that glyphs of monospace fonts are resized to match. `None` means
+    /// don't resize
+    ///
+    /// When text falls back to a different monospace font, its glyphs can be wider or narrower
+    /// than those of the main font. With a monospace width, the font size of each glyph from a
+    /// monospace font is scaled so that its advance is the nearest whole multiple of the width,
+    /// keeping columns aligned in terminals and code blocks. Glyphs of proportional fonts are not
+    /// changed, and spans can opt out with [`Attrs::match_mono_width`].
+    pub fn set_monospace_width(
+        &mut self,
+        font_system: &mut FontSystem,
+        monospace_width: Option<f32>,
+    ) {
+        if monospace_width != self.monospace_width {
+            self.monospace_width = monospace_width;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the width of the cells of the monospace grid, see [`Buffer::set_cell_width`]
+    pub fn cell_width(&self) -> Option<f32> {
+        self.cell_width_opt
+    }
+
+    /// Set the width of the cells of a monospace grid that all glyphs are snapped to, for
+    /// example for a terminal emulator. `None` disables the grid
+    ///
+    /// Each grapheme takes the number of cells given by [`cell_count`](crate::cell_count), so wide East Asian
+    /// characters take two cells. Lines are wrapped at the last whole cell that fits, like a
+    /// terminal, instead of at word boundaries. Use [`Buffer::grid_cursor`] and
+    /// [`Buffer::grid_position`] to convert between cells and cursors.
+    pub fn set_cell_width(&mut self, font_system: &mut FontSystem, cell_width_opt: Option<f32>) {
+        let cell_width_opt = cell_width_opt.filter(|cell_width| *cell_width > 0.0);
+        if cell_width_opt != self.cell_width_opt {
+            self.cell_width_opt = cell_width_opt;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the `(line, column)` cell of a cursor in the monospace grid, where the column counts
+    /// cells from the start of the line, including those of wrapped visual lines
+    ///
+    /// Returns `None` if the grid is disabled or the line has no layout.
+    pub fn grid_position(&self, cursor: Cursor) -> Option<(usize, usize)> {
+        let cell_width = self.cell_width_opt?;
+        let layout = self.lines.get(cursor.line)?.layout_opt().as_ref()?;
+        let mut line_column = 0;
+        for layout_line in layout.iter() {
+            for glyph in layout_line.glyphs.iter() {
+                if cursor.index >= glyph.start && cursor.index < glyph.end {
+                    let column = math::roundf(glyph.x / cell_width) as usize;
+                    return Some((cursor.line, line_column + column));
+                }
+            }
+            line_column += math::roundf(layout_line.w / cell_width) as usize;
+        }
+        Some((cursor.line, line_column))
+    }
+
+    /// Get the cursor at a `(line, column)` cell of the monospace grid, see
+    /// [`Buffer::grid_position`]
+    ///
+    /// Columns past the end of the line give a cursor at the end of the line, and the second
+    /// cell of a wide character gives a cursor before it. Returns `None` if the grid is
+    /// disabled or the line has no layout.
+    pub fn grid_cursor(&self, line_i: usize, column: usize) -> Option<Cursor> {
+        let cell_width = self.cell_width_opt?;
+        let line = self.lines.get(line_i)?;
+        let layout = line.layout_opt().as_ref()?;
+        let mut line_column = 0;
+        for layout_line in layout.iter() {
+            for glyph in layout_line.glyphs.iter() {
+                let start = line_column + math::roundf(glyph.x / cell_width) as usize;
+                let end = line_column + math::roundf((glyph.x + glyph.w) / cell_width) as usize;
+                if column >= start && column < end {
+                    return Some(Cursor::new(line_i, glyph.start));
+                }
+            }
+            line_column += math::roundf(layout_line.w / cell_width) as usize;
+        }
+        Some(Cursor::new(line_i, line.text().len()))
+    }
+
+    /// Get the current [`WordBoundaries`]
+    pub fn word_boundaries(&self) -> &WordBoundaries {
+        &self.word_boundaries
+    }
+
+    /// Set the [`WordBoundaries`] used by word motions and word selection
+    ///
+    /// This applies to [`Motion::PreviousWord`], [`Motion::NextWord`], and [`Selection::Word`],
+    /// so word deletion built from those motions follows the same rules.
+    ///
+    /// [`Selection::Word`]: crate::Selection::Word
+    pub fn set_word_boundaries(&mut self, word_boundaries: WordBoundaries) {
+        self.word_boundaries = word_boundaries;
+    }
+
+    /// Get the current [`WordHook`]
+    pub fn word_hook(&self) -> Option<&Arc<dyn WordHook>> {
+        self.word_hook_opt.as_ref()
+    }
+
+    /// Set the [`WordHook`] that adjusts the words found with the [`WordBoundaries`] of each
+    /// line, or None to use the words as found
+    pub fn set_word_hook(&mut self, word_hook_opt: Option<Arc<dyn WordHook>>) {
+        self.word_hook_opt = word_hook_opt;
+    }
+
+    /// Get the byte ranges of the words in the line at `line_i`, found with the
+    /// [`WordBoundaries`] and adjusted by the [`WordHook`] of this buffer
+    ///
+    /// These are the words used by word motions and word selection. Returns an empty list if
+    /// there is no such line.
+    pub fn line_words(&self, line_i: usize) -> Vec<Range<usize>> {
+        let Some(line) = self.lines.get(line_i) else {
+            return Vec::new();
+        };
+        let mut words = self.word_boundaries.words(line.text());
+        if let Some(word_hook) = &self.word_hook_opt {
+            word_hook.adjust_words(line_i, line, &mut words);
+            words.sort_by_key(|word| word.start);
+        }
+        words
+    }
+
+    /// Get the current [`LineBreaker`]
+    pub fn line_breaker(&self) -> &Arc<dyn LineBreaker> {
+        &self.line_breaker
+    }
+
+    /// Set the [`LineBreaker`] used to split lines into visual lines, the default is
+    /// [`GreedyLineBreaker`]
+    ///
+    /// Lines laid out on a grid with [`Buffer::set_cell_width`] are wrapped by cells instead.
+    pub fn set_line_breaker(
+        &mut self,
+        font_system: &mut FontSystem,
+        line_breaker: Arc<dyn LineBreaker>,
+    ) {
+        if !Arc::ptr_eq(&line_breaker, &self.line_breaker) {
+            self.line_breaker = line_breaker;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current [`ShapingBackend`]
+    pub fn shaping_backend(&self) -> &Arc<dyn ShapingBackend> {
+        &self.shaping_backend
+    }
+
+    /// Set the [`ShapingBackend`] used to shape lines, the default is [`RustybuzzShapingBackend`]
+    pub fn set_shaping_backend(
+        &mut self,
+        font_system: &mut FontSystem,
+        shaping_backend: Arc<dyn ShapingBackend>,
+    ) {
+        if !Arc::ptr_eq(&shaping_backend, &self.shaping_backend) {
+            self.shaping_backend = shaping_backend;
+            for line in self.lines.iter_mut() {
+                line.reset_shaping_for(ReshapeReasons::SHAPING_BACKEND);
+            }
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current `tab_width`
+    pub fn tab_width(&self) -> u16 {
+        self.tab_width
+    }
+
+    /// Set tab width (number of spaces between tab stops)
+    pub fn set_tab_width(&mut self, font_system: &mut FontSystem, tab_width: u16) {
+        // A tab width of 0 is not allowed
+        if tab_width == 0 {
+            return;
+        }
+        if tab_width != self.tab_width {
+            self.tab_width = tab_width;
+            // Shaping must be reset when tab width is changed
+            for line in self.lines.iter_mut() {
+                if line.shape_opt().is_some() {
+                    if line.text().contains('\t') {
+                        line.reset_shaping_for(ReshapeReasons::TAB_WIDTH);
+                    }
+                }
+            }
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Get the current buffer dimensions (width, height)
+    pub fn size(&self) -> (Option<f32>, Option<f32>) {
+        (self.width_opt, self.height_opt)
+    }
+
+    /// Set the current buffer dimensions
+    pub fn set_size(
+        &mut self,
+        font_system: &mut FontSystem,
+        width_opt: Option<f32>,
+        height_opt: Option<f32>,
+    ) {
+        self.set_metrics_and_size(font_system, self.metrics, width_opt, height_opt);
+    }
+
+    /// Set the current [`Metrics`] and buffer dimensions at the same time
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `metrics.font_size` is zero, use [`Buffer::try_set_metrics_and_size`] to
+    /// get an error instead.
+    pub fn set_metrics_and_size(
+        &mut self,
+        font_system: &mut FontSystem,
+        metrics: Metrics,
+        width_opt: Option<f32>,
+        height_opt: Option<f32>,
+    ) {
         let clamped_width_opt = width_opt.map(|width| width.max(0.0));
         let clamped_height_opt = height_opt.map(|height| height.max(0.0));
 
@@ -657,6 +1671,102 @@ impl Buffer {
         }
     }
 
+    /// Set the current [`Metrics`] and buffer dimensions like [`Buffer::set_metrics_and_size`],
+    /// returning an error and leaving the buffer unchanged if `metrics` are not valid, see
+    /// [`Metrics::validate`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::InvalidFontSize`] or [`LayoutError::InvalidLineHeight`] if
+    /// `metrics` are not valid.
+    pub fn try_set_metrics_and_size(
+        &mut self,
+        font_system: &mut FontSystem,
+        metrics: Metrics,
+        width_opt: Option<f32>,
+        height_opt: Option<f32>,
+    ) -> Result<(), LayoutError> {
+        metrics.validate()?;
+        self.set_metrics_and_size(font_system, metrics, width_opt, height_opt);
+        Ok(())
+    }
+
+    /// Set the largest font size that fits the text inside of the buffer size, returning it
+    ///
+    /// Font sizes from `min_size` to `max_size`, in increments of `step`, are binary searched and
+    /// line height is scaled along with font size. If `max_lines_opt` is set, the text must also
+    /// fit in that many layout lines. If no font size fits, `min_size` is used.
+    ///
+    /// A `min_size` that is not positive and finite is not allowed. The buffer is left unchanged
+    /// and the current font size is returned.
+    pub fn fit_text(
+        &mut self,
+        font_system: &mut FontSystem,
+        min_size: f32,
+        max_size: f32,
+        step: f32,
+        max_lines_opt: Option<usize>,
+    ) -> f32 {
+        if !(min_size.is_finite() && min_size > 0.0) {
+            log::warn!("fit_text ignored invalid minimum font size {}", min_size);
+            return self.metrics.font_size;
+        }
+        let line_height_scale = self.metrics.line_height / self.metrics.font_size;
+        let metrics_at =
+            |i: usize| Metrics::relative(min_size + i as f32 * step, line_height_scale);
+        let steps = if step > 0.0 && max_size > min_size {
+            // Allow for rounding errors when step does not divide the range exactly
+            ((max_size - min_size) / step + 0.001) as usize
+        } else {
+            0
+        };
+
+        // Find the last step that fits, sizes below a fitting size are assumed to fit
+        let mut best = 0;
+        let (mut low, mut high) = (0, steps + 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.text_fits(font_system, metrics_at(mid), max_lines_opt) {
+                best = mid;
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        self.metrics = metrics_at(best);
+        self.relayout(font_system);
+        self.shape_until_scroll(font_system, false);
+        self.metrics.font_size
+    }
+
+    /// Lay out all lines with the provided [`Metrics`], checking if they fit inside the buffer
+    fn text_fits(
+        &mut self,
+        font_system: &mut FontSystem,
+        metrics: Metrics,
+        max_lines_opt: Option<usize>,
+    ) -> bool {
+        self.metrics = metrics;
+        let line_height = self.scaled_metrics().line_height;
+        let mut lines = 0;
+        let mut width: f32 = 0.0;
+        let mut height = 0.0;
+        for line_i in 0..self.lines.len() {
+            self.lines[line_i].reset_layout();
+            for layout_line in self.line_layout(font_system, line_i).unwrap_or_default() {
+                lines += 1;
+                width = width.max(layout_line.w);
+                height += layout_line.line_height_opt.unwrap_or(line_height);
+            }
+        }
+        max_lines_opt.map_or(true, |max_lines| lines <= max_lines)
+            && self.width_opt.map_or(true, |max_width| width <= max_width)
+            && self
+                .height_opt
+                .map_or(true, |max_height| height <= max_height)
+    }
+
     /// Get the current scroll location
     pub fn scroll(&self) -> Scroll {
         self.scroll
@@ -666,11 +1776,99 @@ impl Buffer {
     pub fn set_scroll(&mut self, scroll: Scroll) {
         if scroll != self.scroll {
             self.scroll = scroll;
+            self.stick_to_bottom = false;
             self.redraw = true;
         }
     }
 
+    /// Get the range of lines that are at least partly inside of the visible area
+    ///
+    /// Heights of lines that have not been laid out are estimated, see
+    /// [`Buffer::total_height`]. If the buffer has no height, all lines from the scroll position
+    /// are returned.
+    pub fn visible_line_range(&self) -> Range<usize> {
+        let start = cmp::min(self.scroll.line, self.lines.len());
+        let end = match self.height_opt {
+            Some(height) => {
+                let scroll_bottom = self.y_for_line(start) + self.scroll.vertical + height;
+                match self.line_for_y(scroll_bottom) {
+                    // A line starting exactly at the bottom is not visible
+                    Some(line_i) if self.y_for_line(line_i) >= scroll_bottom => line_i,
+                    Some(line_i) => cmp::min(line_i + 1, self.lines.len()),
+                    None => self.lines.len(),
+                }
+            }
+            None => self.lines.len(),
+        };
+        start..cmp::max(start, end)
+    }
+
+    /// Scroll so that the top of line `line_i` is `offset` pixels below the top of the visible
+    /// area, keeping the horizontal scroll
+    ///
+    /// This keeps a line stable at a position of the viewport while lines before it are added,
+    /// removed, or change height, as in a chat log where earlier messages are loaded. Use
+    /// [`Buffer::visible_line_range`] and [`Buffer::run_for_line`] to find the line and offset
+    /// before changing the lines.
+    ///
+    /// The scroll position is clamped to the buffer by the next [`Buffer::shape_until_scroll`].
+    pub fn anchor_scroll_to(&mut self, line_i: usize, offset: f32) {
+        self.set_scroll(Scroll {
+            line: line_i,
+            vertical: -offset,
+            ..self.scroll
+        });
+    }
+
+    /// Get the vertical scroll position as a fraction from 0.0 at the top to 1.0 at the bottom,
+    /// for example to position a scrollbar
+    ///
+    /// This uses the same estimates as [`Buffer::content_size`].
+    pub fn scroll_fraction(&self) -> f32 {
+        let scroll_range = self.content_size().1 - self.height_opt.unwrap_or(0.0);
+        if scroll_range <= 0.0 {
+            return 0.0;
+        }
+        let scroll_y = self.y_for_line(self.scroll.line) + self.scroll.vertical;
+        (scroll_y / scroll_range).clamp(0.0, 1.0)
+    }
+
+    /// Set the vertical scroll position as a fraction from 0.0 at the top to 1.0 at the bottom,
+    /// for example when a scrollbar is dragged
+    pub fn set_scroll_fraction(&mut self, fraction: f32) {
+        let scroll_range = self.content_size().1 - self.height_opt.unwrap_or(0.0);
+        let scroll_y = fraction.clamp(0.0, 1.0) * scroll_range.max(0.0);
+        let line = match self.line_for_y(scroll_y) {
+            Some(some) => some,
+            None => self.lines.len().saturating_sub(1),
+        };
+        let vertical = scroll_y - self.y_for_line(line);
+        self.set_scroll(Scroll {
+            line,
+            vertical,
+            ..self.scroll
+        });
+    }
+
+    /// Lay out every line and split the buffer into pages
+    ///
+    /// Use [`Buffer::page_runs`] to get the layout runs of each page.
+    pub fn paginate(&mut self, font_system: &mut FontSystem, breaks: PageBreaks) -> Vec<Page> {
+        for line_i in 0..self.lines.len() {
+            self.line_layout(font_system, line_i);
+        }
+        crate::pagination::paginate(&self.lines, self.scaled_metrics().line_height, breaks)
+    }
+
+    /// Get the layout runs of a [`Page`], with Y offsets relative to the top of the page
+    pub fn page_runs(&self, page: &Page) -> PageRunIter<'_> {
+        PageRunIter::new(self, page)
+    }
+
     /// Set text of buffer, using provided attributes for each line by default
+    ///
+    /// Text is UTF-8, so isolated surrogates from UTF-16 or WTF-8 sources must be replaced
+    /// first, for example with [`String::from_utf16_lossy`].
     pub fn set_text(
         &mut self,
         font_system: &mut FontSystem,
@@ -695,135 +1893,757 @@ impl Buffer {
                 shaping,
             ));
         }
-        self.scroll = Scroll::default();
-        self.shape_until_scroll(font_system, false);
+        self.push_trailing_empty_line(text, attrs, shaping);
+        self.scroll = Scroll::default();
+        self.height_index = HeightIndex::default();
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Set text of buffer like [`Buffer::set_text`], keeping the shape and layout of lines that
+    /// did not change
+    ///
+    /// This is useful when a whole document is set again after small changes, like a preview
+    /// that is rendered again on every edit. Unchanged lines keep their caches, properties, and
+    /// [`BufferLine::id`], and changed lines are replaced with new lines. The scroll position is
+    /// kept. Returns true if any line changed.
+    pub fn set_text_diffed(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) -> bool {
+        let mut new_lines = LineIter::new(text)
+            .map(|(range, ending)| (&text[range], ending, AttrsList::new(attrs)))
+            .collect::<Vec<_>>();
+        if new_lines.is_empty() {
+            new_lines.push(("", LineEnding::default(), AttrsList::new(attrs)));
+        }
+        if self.trailing_empty_line && text.ends_with(['\n', '\r']) {
+            new_lines.push(("", LineEnding::None, AttrsList::new(attrs)));
+        }
+        let changed = self.set_lines_diffed(new_lines, shaping);
+        self.shape_until_scroll(font_system, false);
+        changed
+    }
+
+    /// Replace the lines of the buffer with `new_lines`, keeping the lines that are the same at
+    /// the start and end, returning true if any line changed
+    ///
+    /// Lines in between are only kept if their text and line ending did not change either.
+    /// Other lines are replaced with new lines, so properties set on the old lines, like
+    /// [`BufferLine::set_wrap`], do not carry over to other text.
+    fn set_lines_diffed<T: AsRef<str> + Into<String>>(
+        &mut self,
+        new_lines: Vec<(T, LineEnding, AttrsList)>,
+        shaping: Shaping,
+    ) -> bool {
+        let same_text = |line: &BufferLine, (text, ending, _): &(T, LineEnding, AttrsList)| {
+            line.text() == text.as_ref() && line.ending() == *ending
+        };
+        let prefix = self
+            .lines
+            .iter()
+            .zip(new_lines.iter())
+            .take_while(|(line, new_line)| same_text(line, new_line))
+            .count();
+        let suffix = self
+            .lines
+            .iter()
+            .rev()
+            .zip(new_lines.iter().rev())
+            .take(self.lines.len().min(new_lines.len()) - prefix)
+            .take_while(|(line, new_line)| same_text(line, new_line))
+            .count();
+        let old_end = self.lines.len() - suffix;
+        let new_end = new_lines.len() - suffix;
+
+        let mut changed = self.lines.len() != new_lines.len();
+        let mut old_lines = core::mem::take(&mut self.lines)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.lines = new_lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, new_line)| {
+                // Lines in the suffix are matched from the end
+                let old_i = if i < new_end {
+                    Some(i).filter(|&i| i < old_end)
+                } else {
+                    Some(i - new_end + old_end)
+                };
+                let old_line_opt = old_i
+                    .and_then(|old_i| old_lines[old_i].take())
+                    .filter(|line| same_text(line, &new_line));
+                let (text, ending, attrs_list) = new_line;
+                match old_line_opt {
+                    Some(mut line) => {
+                        changed |= line.set_shaping(shaping);
+                        changed |= line.set_attrs_list(attrs_list);
+                        line
+                    }
+                    None => {
+                        changed = true;
+                        BufferLine::new(text, ending, attrs_list, shaping)
+                    }
+                }
+            })
+            .collect();
+        if changed {
+            self.redraw = true;
+        }
+        changed
+    }
+
+    /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
+    ///
+    /// ```
+    /// # use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
+    /// # let mut font_system = FontSystem::new();
+    /// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
+    /// let attrs = Attrs::new().family(Family::Serif);
+    /// buffer.set_rich_text(
+    ///     &mut font_system,
+    ///     [
+    ///         ("hello, ", attrs),
+    ///         ("cosmic\ntext", attrs.family(Family::Monospace)),
+    ///     ],
+    ///     attrs,
+    ///     Shaping::Advanced,
+    /// );
+    /// ```
+    ///
+    /// Lines with the same text, line ending, and attributes as before keep their shape and
+    /// layout, so setting the same spans again, for example on every frame, does not shape the
+    /// text again. Lines where only properties used for drawing changed, like colors, are
+    /// repainted, see [`BufferLine::set_attrs_list`]. Their alignment is reset, but other
+    /// properties set on lines, like [`BufferLine::set_markers`], are kept. Lines with other text
+    /// are replaced with new lines.
+    pub fn set_rich_text<'r, 's, I>(
+        &mut self,
+        font_system: &mut FontSystem,
+        spans: I,
+        default_attrs: Attrs,
+        shaping: Shaping,
+    ) where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
+        self.set_rich_text_lines(spans, default_attrs, shaping);
+        for line in self.lines.iter_mut() {
+            line.set_align(None);
+        }
+        self.scroll = Scroll::default();
+        self.height_index = HeightIndex::default();
+
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Set text of buffer like [`Buffer::set_rich_text`], with the alignment of each paragraph
+    ///
+    /// `aligns` are applied to paragraphs in order, and paragraphs after the last alignment use
+    /// `None`, see [`BufferLine::set_align`]. Alignments are set before shaping, so no extra layout
+    /// is done.
+    ///
+    /// ```
+    /// # use cosmic_text::{Align, Attrs, Buffer, FontSystem, Metrics, Shaping};
+    /// # let mut font_system = FontSystem::new();
+    /// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
+    /// let attrs = Attrs::new();
+    /// buffer.set_rich_text_with_align(
+    ///     &mut font_system,
+    ///     [("Title\n", attrs.weight(cosmic_text::Weight::BOLD)), ("body", attrs)],
+    ///     attrs,
+    ///     Shaping::Advanced,
+    ///     [Some(Align::Center), None],
+    /// );
+    /// assert_eq!(buffer.lines[0].align(), Some(Align::Center));
+    /// ```
+    pub fn set_rich_text_with_align<'r, 's, I, A>(
+        &mut self,
+        font_system: &mut FontSystem,
+        spans: I,
+        default_attrs: Attrs,
+        shaping: Shaping,
+        aligns: A,
+    ) where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+        A: IntoIterator<Item = Option<Align>>,
+    {
+        self.set_rich_text_lines(spans, default_attrs, shaping);
+        let mut aligns = aligns.into_iter();
+        for line in self.lines.iter_mut() {
+            line.set_align(aligns.next().flatten());
+        }
+        self.scroll = Scroll::default();
+        self.height_index = HeightIndex::default();
+
+        self.shape_until_scroll(font_system, false);
+    }
+
+    /// Replace the lines of the buffer with styled spans, keeping unchanged lines, without
+    /// shaping
+    fn set_rich_text_lines<'r, 's, I>(&mut self, spans: I, default_attrs: Attrs, shaping: Shaping)
+    where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+    {
+        let mut new_lines = Vec::new();
+
+        let mut attrs_list = AttrsList::new(default_attrs);
+        let mut line_string = String::new();
+        let mut end = 0;
+        let (string, spans_data): (String, Vec<_>) = spans
+            .into_iter()
+            .map(|(s, attrs)| {
+                let start = end;
+                end += s.len();
+                (s, (attrs, start..end))
+            })
+            .unzip();
+
+        let mut spans_iter = spans_data.into_iter();
+        let mut maybe_span = spans_iter.next();
+
+        // split the string into lines, as ranges
+        let string_start = string.as_ptr() as usize;
+        let mut lines_iter = BidiParagraphs::new(&string).map(|line: &str| {
+            let start = line.as_ptr() as usize - string_start;
+            let end = start + line.len();
+            start..end
+        });
+        let mut maybe_line = lines_iter.next();
+        //TODO: set this based on information from spans
+        let line_ending = LineEnding::default();
+
+        loop {
+            let (Some(line_range), Some((attrs, span_range))) = (&maybe_line, &maybe_span) else {
+                // this is reached only if this text is empty
+                new_lines.push((String::new(), line_ending, AttrsList::new(default_attrs)));
+                break;
+            };
+
+            // start..end is the intersection of this line and this span
+            let start = line_range.start.max(span_range.start);
+            let end = line_range.end.min(span_range.end);
+            if start < end {
+                let text = &string[start..end];
+                let text_start = line_string.len();
+                line_string.push_str(text);
+                let text_end = line_string.len();
+                // Only add attrs if they don't match the defaults
+                if *attrs != attrs_list.defaults() {
+                    attrs_list.add_span(text_start..text_end, *attrs);
+                }
+            }
+
+            // we know that at the end of a line,
+            // span text's end index is always >= line text's end index
+            // so if this span ends before this line ends,
+            // there is another span in this line.
+            // otherwise, we move on to the next line.
+            if span_range.end < line_range.end {
+                maybe_span = spans_iter.next();
+            } else {
+                maybe_line = lines_iter.next();
+                if maybe_line.is_some() {
+                    // finalize this line and start a new line
+                    let prev_attrs_list =
+                        core::mem::replace(&mut attrs_list, AttrsList::new(default_attrs));
+                    let prev_line_string = core::mem::take(&mut line_string);
+                    new_lines.push((prev_line_string, line_ending, prev_attrs_list));
+                } else {
+                    // finalize the final line
+                    new_lines.push((line_string, line_ending, attrs_list));
+                    break;
+                }
+            }
+        }
+
+        if self.trailing_empty_line && string.ends_with(['\n', '\r']) {
+            new_lines.push((
+                String::new(),
+                LineEnding::None,
+                AttrsList::new(default_attrs),
+            ));
+        }
+        self.set_lines_diffed(new_lines, shaping);
+    }
+
+    /// True if a redraw is needed
+    pub fn redraw(&self) -> bool {
+        self.redraw
+    }
+
+    /// Set redraw needed flag
+    pub fn set_redraw(&mut self, redraw: bool) {
+        self.redraw = redraw;
+    }
+
+    /// Register an anchor at `cursor`, which will be shifted by edits made through an [`Edit`]
+    /// implementation or reported with [`Buffer::shift_anchors_insert`] and
+    /// [`Buffer::shift_anchors_delete`]
+    ///
+    /// Anchors are not moved by [`Buffer::set_text`] or [`Buffer::set_rich_text`].
+    ///
+    /// [`Edit`]: crate::Edit
+    pub fn add_anchor(&mut self, cursor: Cursor, bias: AnchorBias) -> AnchorId {
+        let id = AnchorId(self.next_anchor_id);
+        self.next_anchor_id += 1;
+        self.anchors.push((id, Anchor::new(cursor, bias)));
+        id
+    }
+
+    /// Get the current position of an anchor, None if it was removed
+    pub fn anchor(&self, id: AnchorId) -> Option<Cursor> {
+        self.anchors
+            .iter()
+            .find(|(anchor_id, _)| *anchor_id == id)
+            .map(|(_, anchor)| anchor.cursor)
+    }
+
+    /// Remove an anchor, returning its last position
+    pub fn remove_anchor(&mut self, id: AnchorId) -> Option<Cursor> {
+        let i = self
+            .anchors
+            .iter()
+            .position(|(anchor_id, _)| *anchor_id == id)?;
+        Some(self.anchors.remove(i).1.cursor)
+    }
+
+    /// Iterate over all registered anchors
+    pub fn anchors(&self) -> impl Iterator<Item = (AnchorId, &Anchor)> + '_ {
+        self.anchors.iter().map(|(id, anchor)| (*id, anchor))
+    }
+
+    /// Shift anchors after text was inserted from `start` to `end`
+    ///
+    /// This also moves the scroll position if scroll anchoring is enabled, see
+    /// [`Buffer::set_scroll_anchoring`], and moves the line heights used by
+    /// [`Buffer::total_height`] along with the lines.
+    pub fn shift_anchors_insert(&mut self, start: Cursor, end: Cursor) {
+        self.splice_height_index(start.line, 0, end.line - start.line);
+
+        for (_, anchor) in self.anchors.iter_mut() {
+            anchor.shift_insert(start, end);
+        }
+
+        if self.scroll_anchoring
+            && (self.scroll.line > start.line
+                || (self.scroll.line == start.line && start.index == 0))
+        {
+            self.scroll.line += end.line - start.line;
+        }
+    }
+
+    /// Shift anchors after text was deleted from `start` to `end`
+    ///
+    /// This also moves the scroll position if scroll anchoring is enabled, see
+    /// [`Buffer::set_scroll_anchoring`], and moves the line heights used by
+    /// [`Buffer::total_height`] along with the lines.
+    pub fn shift_anchors_delete(&mut self, start: Cursor, end: Cursor) {
+        self.splice_height_index(start.line, end.line - start.line, 0);
+
+        for (_, anchor) in self.anchors.iter_mut() {
+            anchor.shift_delete(start, end);
+        }
+
+        if self.scroll_anchoring {
+            if self.scroll.line > end.line {
+                self.scroll.line -= end.line - start.line;
+            } else if self.scroll.line > start.line {
+                // The line at the scroll position was deleted
+                self.scroll.line = start.line;
+                self.scroll.vertical = 0.0;
+            }
+        }
+    }
+
+    /// Update the attributes of all text created from a [`StyleSheet`], reshaping changed lines
+    pub fn set_style_sheet(&mut self, font_system: &mut FontSystem, style_sheet: &StyleSheet) {
+        let mut changed = false;
+        for line in self.lines.iter_mut() {
+            if let Some(attrs_list) = style_sheet.restyle(line.attrs_list()) {
+                changed |= line.set_attrs_list(attrs_list);
+            }
+        }
+        if changed {
+            self.redraw = true;
+            self.shape_until_scroll(font_system, false);
+        }
+    }
+
+    /// Build a low cost [`Minimap`] of the buffer, see [`Minimap::new`]
+    pub fn minimap(&self, metrics: Metrics) -> Minimap {
+        Minimap::new(self, metrics)
+    }
+
+    /// Get the visible layout runs for rendering and other tasks
+    pub fn layout_runs(&self) -> LayoutRunIter {
+        LayoutRunIter::new(self)
+    }
+
+    /// Get the [`LayoutRun`] of a laid out line, with its top at `line_top`
+    fn layout_run(&self, line_i: usize, layout_i: usize, line_top: f32) -> Option<LayoutRun<'_>> {
+        let line = self.lines.get(line_i)?;
+        let shape = line.shape_opt().as_ref()?;
+        let layout = line.layout_opt().as_ref()?;
+        let layout_line = layout.get(layout_i)?;
+        let line_height = layout_line
+            .line_height_opt
+            .unwrap_or(self.scaled_metrics().line_height);
+        let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+        let centering_offset = (line_height - glyph_height) / 2.0;
+        Some(LayoutRun {
+            line_i,
+            text: line.text(),
+            rtl: shape.rtl,
+            glyphs: &layout_line.glyphs,
+            wrap_indicator_opt: self.wrap_indicator_glyph(layout, layout_i, shape.rtl),
+            line_y: line_top + centering_offset + layout_line.max_ascent,
+            line_top,
+            line_height,
+            line_w: layout_line.w,
+            layout_i,
+            markers: line.markers(),
+            line_id: line.id(),
+        })
+    }
+
+    /// Get the height of a laid out line, or `None` if it has no layout
+    fn line_layout_height(&self, line_i: usize) -> Option<f32> {
+        let layout = self.lines.get(line_i)?.layout_opt().as_ref()?;
+        Some(
+            layout
+                .iter()
+                .map(|layout_line| {
+                    layout_line
+                        .line_height_opt
+                        .unwrap_or(self.scaled_metrics().line_height)
+                })
+                .sum(),
+        )
+    }
+
+    /// Build the [`HeightIndex`] of all lines, estimating lines without layout as one line high
+    fn build_height_index(&self) -> HeightIndex {
+        HeightIndex::new(
+            (0..self.lines.len())
+                .map(|line_i| {
+                    self.line_layout_height(line_i)
+                        .unwrap_or(self.scaled_metrics().line_height)
+                })
+                .collect(),
+        )
+    }
+
+    /// Update the [`HeightIndex`] after the `removed` lines after `line_i` were replaced by
+    /// `inserted` lines, building it again if it was already out of date with the lines
+    fn splice_height_index(&mut self, line_i: usize, removed: usize, inserted: usize) {
+        if self.height_index.len() + inserted == self.lines.len() + removed {
+            let estimate = self.scaled_metrics().line_height;
+            self.height_index
+                .splice(line_i + 1, removed, inserted, estimate);
+            // The edited line is laid out again, estimate its height until then
+            let height = self.line_layout_height(line_i).unwrap_or(estimate);
+            self.height_index.set(line_i, height);
+        } else {
+            self.height_index = self.build_height_index();
+        }
+    }
+
+    /// Use the [`HeightIndex`], building it if it is out of date with the lines
+    fn with_height_index<T>(&self, f: impl FnOnce(&HeightIndex) -> T) -> T {
+        if self.height_index.len() == self.lines.len() {
+            f(&self.height_index)
+        } else {
+            f(&self.build_height_index())
+        }
+    }
+
+    /// Get the total height of all lines
+    ///
+    /// Lines that have not been laid out yet are estimated to be one line high, and refined when
+    /// they are laid out. This takes O(log n) time.
+    pub fn total_height(&self) -> f32 {
+        self.with_height_index(|index| index.total())
+    }
+
+    /// Get the top of a line, relative to the top of the first line, see
+    /// [`Buffer::total_height`]
+    pub fn y_for_line(&self, line_i: usize) -> f32 {
+        self.with_height_index(|index| index.prefix(line_i))
+    }
+
+    /// Get the line at a vertical position, relative to the top of the first line, see
+    /// [`Buffer::total_height`]
+    pub fn line_for_y(&self, y: f32) -> Option<usize> {
+        self.with_height_index(|index| index.find(y))
+    }
+
+    /// Get the `(width, height)` of all lines, including those outside of the visible area
+    ///
+    /// The width is the widest line that has been laid out, and the height is estimated for
+    /// lines that have not been laid out, see [`Buffer::total_height`]. Both are refined as more
+    /// lines are laid out, for example by [`Buffer::shape_until_scroll`].
+    pub fn content_size(&self) -> (f32, f32) {
+        let mut width: f32 = 0.0;
+        for line in self.lines.iter() {
+            if let Some(layout) = line.layout_opt() {
+                for layout_line in layout.iter() {
+                    width = width.max(layout_line.w);
+                }
+            }
+        }
+        (width, self.total_height())
+    }
+
+    /// Get the top of a line relative to the scroll position, in the same coordinates as
+    /// [`LayoutRun::line_top`]
+    fn line_top(&self, line_i: usize) -> f32 {
+        self.with_height_index(|index| {
+            index.prefix(line_i) - index.prefix(self.scroll.line) - self.scroll.vertical
+        })
+    }
+
+    /// Get the first [`LayoutRun`] of a line, even if it is outside of the visible area
+    ///
+    /// Returns `None` if the line has no layout.
+    pub fn run_for_line(&self, line_i: usize) -> Option<LayoutRun<'_>> {
+        self.layout_run(line_i, 0, self.line_top(line_i))
+    }
+
+    /// Get the [`LayoutRun`] at a vertical position, in the same coordinates as
+    /// [`LayoutRun::line_top`], even if it is outside of the visible area
+    ///
+    /// Returns `None` if the position is outside of all lines, or if the line at it has no layout.
+    pub fn run_at_y(&self, y: f32) -> Option<LayoutRun<'_>> {
+        let scroll_top = self.y_for_line(self.scroll.line) + self.scroll.vertical;
+        let line_i = self.line_for_y(y + scroll_top)?;
+        let mut line_top = self.line_top(line_i);
+        let layout = self.lines.get(line_i)?.layout_opt().as_ref()?;
+        for layout_i in 0..layout.len() {
+            let run = self.layout_run(line_i, layout_i, line_top)?;
+            if y < line_top + run.line_height {
+                return Some(run);
+            }
+            line_top += run.line_height;
+        }
+        None
+    }
+
+    /// Get the rectangles `(x, y, width, height)` covering the visible text between `start` and
+    /// `end`, for example to highlight the word being read by a text-to-speech engine
+    pub fn rects_for_range(&self, start: Cursor, end: Cursor) -> Vec<(f32, f32, f32, f32)> {
+        let mut rects = Vec::new();
+        for run in self.layout_runs() {
+            if run.line_i < start.line || run.line_i > end.line {
+                continue;
+            }
+            for (x, w) in run.range_spans(start, end) {
+                rects.push((x, run.line_top, w, run.line_height));
+            }
+        }
+        rects
+    }
+
+    /// Get the selection rectangles `(x, y, width, height)` between `start` and `end`, merged
+    /// per visual line
+    ///
+    /// Unlike a single rectangle from the leftmost to the rightmost selected glyph, bidirectional
+    /// text that is continuous in the selection but reordered on screen produces separate
+    /// rectangles. Like [`Editor::draw`], lines that the selection continues past are
+    /// highlighted to the edge of the buffer, and empty lines inside of the selection are
+    /// highlighted entirely.
+    ///
+    /// [`Editor::draw`]: crate::Editor::draw
+    pub fn selection_rects(&self, start: Cursor, end: Cursor) -> Vec<(f32, f32, f32, f32)> {
+        let mut rects = Vec::new();
+        for run in self.layout_runs() {
+            if run.line_i < start.line || run.line_i > end.line {
+                continue;
+            }
+            let mut spans = run.range_spans(start, end);
+            if end.line > run.line_i {
+                // Extend the selection past the end of the line
+                let width = self.width_opt.unwrap_or(run.line_w);
+                if run.rtl {
+                    let edge = run
+                        .glyphs
+                        .iter()
+                        .map(|glyph| glyph.x)
+                        .reduce(f32::min)
+                        .unwrap_or(width);
+                    match spans.first_mut() {
+                        Some((x, w)) if (*x - edge).abs() < 0.5 => {
+                            *w += *x;
+                            *x = 0.0;
+                        }
+                        _ => spans.insert(0, (0.0, edge)),
+                    }
+                } else {
+                    let edge = run
+                        .glyphs
+                        .iter()
+                        .map(|glyph| glyph.x + glyph.w)
+                        .reduce(f32::max)
+                        .unwrap_or(0.0);
+                    match spans.last_mut() {
+                        Some((x, w)) if (*x + *w - edge).abs() < 0.5 => *w = width - *x,
+                        _ => spans.push((edge, width - edge)),
+                    }
+                }
+            }
+            for (x, w) in spans {
+                rects.push((x, run.line_top, w.max(0.0), run.line_height));
+            }
+        }
+        rects
     }
 
-    /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
+    /// Get the word under the position `x`, `y`, for hover tooltips, dictionary lookup, or link
+    /// detection
     ///
-    /// ```
-    /// # use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
-    /// # let mut font_system = FontSystem::new();
-    /// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
-    /// let attrs = Attrs::new().family(Family::Serif);
-    /// buffer.set_rich_text(
-    ///     &mut font_system,
-    ///     [
-    ///         ("hello, ", attrs),
-    ///         ("cosmic\ntext", attrs.family(Family::Monospace)),
-    ///     ],
-    ///     attrs,
-    ///     Shaping::Advanced,
-    /// );
-    /// ```
-    pub fn set_rich_text<'r, 's, I>(
-        &mut self,
-        font_system: &mut FontSystem,
-        spans: I,
-        default_attrs: Attrs,
-        shaping: Shaping,
-    ) where
-        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
-    {
-        self.lines.clear();
-
-        let mut attrs_list = AttrsList::new(default_attrs);
-        let mut line_string = String::new();
-        let mut end = 0;
-        let (string, spans_data): (String, Vec<_>) = spans
+    /// Words are found with [`Buffer::line_words`]. Returns `None` if the position
+    /// is not over a glyph, or if the glyph is not part of a word, such as whitespace.
+    pub fn word_at(&self, x: f32, y: f32) -> Option<WordHit<'_>> {
+        let (line_i, index) = self.grapheme_at(x, y)?;
+        let text = self.lines[line_i].text();
+        let range = self
+            .line_words(line_i)
             .into_iter()
-            .map(|(s, attrs)| {
-                let start = end;
-                end += s.len();
-                (s, (attrs, start..end))
-            })
-            .unzip();
-
-        let mut spans_iter = spans_data.into_iter();
-        let mut maybe_span = spans_iter.next();
+            .find(|word| word.start <= index && index < word.end)?;
+        let rects = self.rects_for_range(
+            Cursor::new(line_i, range.start),
+            Cursor::new(line_i, range.end),
+        );
+        Some(WordHit {
+            line_i,
+            text: &text[range.clone()],
+            range,
+            rects,
+        })
+    }
 
-        // split the string into lines, as ranges
-        let string_start = string.as_ptr() as usize;
-        let mut lines_iter = BidiParagraphs::new(&string).map(|line: &str| {
-            let start = line.as_ptr() as usize - string_start;
-            let end = start + line.len();
-            start..end
-        });
-        let mut maybe_line = lines_iter.next();
-        //TODO: set this based on information from spans
-        let line_ending = LineEnding::default();
+    /// Get the line index and byte index of the grapheme under the position `x`, `y`, or None if
+    /// the position is not over a glyph
+    fn grapheme_at(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let run = self.run_at_y(y)?;
+        let glyph = run
+            .glyphs
+            .iter()
+            .find(|glyph| x >= glyph.x && x <= glyph.x + glyph.w)?;
+        let index = glyph
+            .sub_clusters(run.text)
+            .find(|part| x >= part.x && x <= part.x + part.w)
+            .map_or(glyph.start, |part| part.start);
+        Some((run.line_i, index))
+    }
 
-        loop {
-            let (Some(line_range), Some((attrs, span_range))) = (&maybe_line, &maybe_span) else {
-                // this is reached only if this text is empty
-                self.lines.push(BufferLine::new(
-                    String::new(),
-                    line_ending,
-                    AttrsList::new(default_attrs),
-                    shaping,
-                ));
-                break;
-            };
+    /// Get the link under the position `x`, `y`, see [`BufferLine::links`]
+    ///
+    /// Returns `None` if the position is not over a glyph, or if the glyph is not part of a link.
+    #[cfg(feature = "linkify")]
+    pub fn link_at(&self, x: f32, y: f32) -> Option<LinkHit<'_>> {
+        let (line_i, index) = self.grapheme_at(x, y)?;
+        let link = self.lines[line_i]
+            .links()
+            .iter()
+            .find(|link| link.range.contains(&index))?;
+        let rects = self.rects_for_range(
+            Cursor::new(line_i, link.range.start),
+            Cursor::new(line_i, link.range.end),
+        );
+        Some(LinkHit {
+            line_i,
+            link,
+            rects,
+        })
+    }
 
-            // start..end is the intersection of this line and this span
-            let start = line_range.start.max(span_range.start);
-            let end = line_range.end.min(span_range.end);
-            if start < end {
-                let text = &string[start..end];
-                let text_start = line_string.len();
-                line_string.push_str(text);
-                let text_end = line_string.len();
-                // Only add attrs if they don't match the defaults
-                if *attrs != attrs_list.defaults() {
-                    attrs_list.add_span(text_start..text_end, *attrs);
+    /// Get the visible links, to draw them as a highlight layer such as an underline
+    #[cfg(feature = "linkify")]
+    pub fn visible_links(&self) -> Vec<LinkHit<'_>> {
+        let mut hits: Vec<LinkHit<'_>> = Vec::new();
+        for run in self.layout_runs() {
+            for link in self.lines[run.line_i].links() {
+                let start = Cursor::new(run.line_i, link.range.start);
+                let end = Cursor::new(run.line_i, link.range.end);
+                let spans = run.range_spans(start, end);
+                if spans.is_empty() {
+                    continue;
                 }
-            }
-
-            // we know that at the end of a line,
-            // span text's end index is always >= line text's end index
-            // so if this span ends before this line ends,
-            // there is another span in this line.
-            // otherwise, we move on to the next line.
-            if span_range.end < line_range.end {
-                maybe_span = spans_iter.next();
-            } else {
-                maybe_line = lines_iter.next();
-                if maybe_line.is_some() {
-                    // finalize this line and start a new line
-                    let prev_attrs_list =
-                        core::mem::replace(&mut attrs_list, AttrsList::new(default_attrs));
-                    let prev_line_string = core::mem::take(&mut line_string);
-                    let buffer_line =
-                        BufferLine::new(prev_line_string, line_ending, prev_attrs_list, shaping);
-                    self.lines.push(buffer_line);
-                } else {
-                    // finalize the final line
-                    let buffer_line =
-                        BufferLine::new(line_string, line_ending, attrs_list, shaping);
-                    self.lines.push(buffer_line);
-                    break;
+                let rects = spans
+                    .into_iter()
+                    .map(|(x, w)| (x, run.line_top, w, run.line_height));
+                // A wrapped link continues the hit from the previous layout line
+                match hits
+                    .iter_mut()
+                    .find(|hit| hit.line_i == run.line_i && core::ptr::eq(hit.link, link))
+                {
+                    Some(hit) => hit.rects.extend(rects),
+                    None => hits.push(LinkHit {
+                        line_i: run.line_i,
+                        link,
+                        rects: rects.collect(),
+                    }),
                 }
             }
         }
+        hits
+    }
 
-        self.scroll = Scroll::default();
+    /// Get the visible graphemes with their positions, in visual order for each layout line
+    ///
+    /// Positions come from the current layout, so no shaping is done. This suits overlays such as
+    /// screen readers, hint labels, or copying the visible text.
+    pub fn visible_chars(&self) -> impl Iterator<Item = VisibleChar<'_>> + '_ {
+        self.layout_runs().flat_map(|run| {
+            let (line_i, text, line_top, line_height) =
+                (run.line_i, run.text, run.line_top, run.line_height);
+            run.glyphs.iter().flat_map(move |glyph| {
+                glyph.sub_clusters(text).map(move |part| VisibleChar {
+                    line_i,
+                    text: &text[part.start..part.end],
+                    range: part.start..part.end,
+                    rect: (part.x, line_top, part.w, line_height),
+                })
+            })
+        })
+    }
 
-        self.shape_until_scroll(font_system, false);
+    /// Get the visible text, with one line per layout line
+    pub fn visible_text(&self) -> String {
+        let mut visible = String::new();
+        for (i, run) in self.layout_runs().enumerate() {
+            if i > 0 {
+                visible.push('\n');
+            }
+            let start = run.glyphs.iter().map(|glyph| glyph.start).min();
+            let end = run.glyphs.iter().map(|glyph| glyph.end).max();
+            if let (Some(start), Some(end)) = (start, end) {
+                visible.push_str(run.text[start..end].trim_end_matches(['\r', '\n']));
+            }
+        }
+        visible
     }
 
-    /// True if a redraw is needed
-    pub fn redraw(&self) -> bool {
-        self.redraw
+    /// Get the index of the line with the identifier `id`, see [`BufferLine::id`]
+    pub fn line_index(&self, id: LineId) -> Option<usize> {
+        self.lines.iter().position(|line| line.id() == id)
     }
 
-    /// Set redraw needed flag
-    pub fn set_redraw(&mut self, redraw: bool) {
-        self.redraw = redraw;
+    /// Get the text runs of every line, in logical order and independent of layout and scrolling
+    pub fn text_runs(&self) -> impl Iterator<Item = TextRun<'_>> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line_i, line)| crate::text_run::line_text_runs(line_i, line))
     }
 
-    /// Get the visible layout runs for rendering and other tasks
-    pub fn layout_runs(&self) -> LayoutRunIter {
-        LayoutRunIter::new(self)
+    /// Convert x, y position in logical pixels to Cursor, reversing the display scale, see
+    /// [`Buffer::set_scale`]
+    pub fn hit_logical(&self, x: f32, y: f32) -> Option<Cursor> {
+        self.hit(x * self.scale, y * self.scale)
     }
 
     /// Convert x, y position to Cursor (hit detection)
@@ -861,29 +2681,25 @@ impl Buffer {
                     if x >= glyph.x && x <= glyph.x + glyph.w {
                         new_cursor_glyph = glyph_i;
 
-                        let cluster = &run.text[glyph.start..glyph.end];
-                        let total = cluster.grapheme_indices(true).count();
-                        let mut egc_x = glyph.x;
-                        let egc_w = glyph.w / (total as f32);
-                        for (egc_i, egc) in cluster.grapheme_indices(true) {
-                            if x >= egc_x && x <= egc_x + egc_w {
-                                new_cursor_char = egc_i;
+                        // Place the cursor between graphemes inside of ligatures
+                        for part in glyph.sub_clusters(run.text) {
+                            if x >= part.x && x <= part.x + part.w {
+                                new_cursor_char = part.start - glyph.start;
 
-                                let right_half = x >= egc_x + egc_w / 2.0;
+                                let right_half = x >= part.x + part.w / 2.0;
                                 if right_half != glyph.level.is_rtl() {
-                                    // If clicking on last half of glyph, move cursor past glyph
-                                    new_cursor_char += egc.len();
+                                    // If clicking on last half of grapheme, move cursor past it
+                                    new_cursor_char = part.end - glyph.start;
                                     new_cursor_affinity = Affinity::Before;
                                 }
                                 break 'hit;
                             }
-                            egc_x += egc_w;
                         }
 
                         let right_half = x >= glyph.x + glyph.w / 2.0;
                         if right_half != glyph.level.is_rtl() {
                             // If clicking on last half of glyph, move cursor past glyph
-                            new_cursor_char = cluster.len();
+                            new_cursor_char = glyph.end - glyph.start;
                             new_cursor_affinity = Affinity::Before;
                         }
                         break 'hit;
@@ -926,13 +2742,17 @@ impl Buffer {
     }
 
     /// Apply a [`Motion`] to a [`Cursor`]
+    ///
+    /// `preferred_x_opt` is the [`PreferredX`] position of the cursor, used and updated by
+    /// vertical motions so the cursor keeps its horizontal position when moving through shorter
+    /// lines. Pass None after any other change to the cursor.
     pub fn cursor_motion(
         &mut self,
         font_system: &mut FontSystem,
         mut cursor: Cursor,
-        mut cursor_x_opt: Option<i32>,
+        mut preferred_x_opt: Option<PreferredX>,
         motion: Motion,
-    ) -> Option<(Cursor, Option<i32>)> {
+    ) -> Option<(Cursor, Option<PreferredX>)> {
         match motion {
             Motion::LayoutCursor(layout_cursor) => {
                 let layout = self.line_layout(font_system, layout_cursor.line)?;
@@ -985,7 +2805,7 @@ impl Buffer {
                     cursor.index = self.lines.get(cursor.line)?.text().len();
                     cursor.affinity = Affinity::After;
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::Next => {
                 let line = self.lines.get(cursor.line)?;
@@ -1002,7 +2822,7 @@ impl Buffer {
                     cursor.index = 0;
                     cursor.affinity = Affinity::Before;
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::Left => {
                 let rtl_opt = self
@@ -1010,13 +2830,13 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) =
-                            self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Next)?;
+                        (cursor, preferred_x_opt) =
+                            self.cursor_motion(font_system, cursor, preferred_x_opt, Motion::Next)?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::Previous,
                         )?;
                     }
@@ -1028,25 +2848,79 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::Previous,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) =
-                            self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Next)?;
+                        (cursor, preferred_x_opt) =
+                            self.cursor_motion(font_system, cursor, preferred_x_opt, Motion::Next)?;
                     }
                 }
             }
+            Motion::UpParagraph => {
+                let layout_cursor = self.layout_cursor(font_system, cursor)?;
+
+                if preferred_x_opt.is_none() {
+                    preferred_x_opt = Some(PreferredX(
+                        self.layout_cursor_x(font_system, layout_cursor)?,
+                    ));
+                }
+
+                // Move to the first visual line of the previous line
+                let (line, layout) = if layout_cursor.line > 0 {
+                    (layout_cursor.line - 1, 0)
+                } else {
+                    (layout_cursor.line, layout_cursor.layout)
+                };
+
+                if let Some(PreferredX(cursor_x)) = preferred_x_opt {
+                    let layout_cursor =
+                        self.layout_cursor_from_x(font_system, line, layout, cursor_x)?;
+                    (cursor, preferred_x_opt) = self.cursor_motion(
+                        font_system,
+                        cursor,
+                        preferred_x_opt,
+                        Motion::LayoutCursor(layout_cursor),
+                    )?;
+                }
+            }
+            Motion::DownParagraph => {
+                let layout_cursor = self.layout_cursor(font_system, cursor)?;
+
+                if preferred_x_opt.is_none() {
+                    preferred_x_opt = Some(PreferredX(
+                        self.layout_cursor_x(font_system, layout_cursor)?,
+                    ));
+                }
+
+                // Move to the first visual line of the next line
+                let (line, layout) = if layout_cursor.line + 1 < self.lines.len() {
+                    (layout_cursor.line + 1, 0)
+                } else {
+                    (layout_cursor.line, layout_cursor.layout)
+                };
+
+                if let Some(PreferredX(cursor_x)) = preferred_x_opt {
+                    let layout_cursor =
+                        self.layout_cursor_from_x(font_system, line, layout, cursor_x)?;
+                    (cursor, preferred_x_opt) = self.cursor_motion(
+                        font_system,
+                        cursor,
+                        preferred_x_opt,
+                        Motion::LayoutCursor(layout_cursor),
+                    )?;
+                }
+            }
             Motion::Up => {
                 let mut layout_cursor = self.layout_cursor(font_system, cursor)?;
 
-                if cursor_x_opt.is_none() {
-                    cursor_x_opt = Some(
-                        layout_cursor.glyph as i32, //TODO: glyph x position
-                    );
+                if preferred_x_opt.is_none() {
+                    preferred_x_opt = Some(PreferredX(
+                        self.layout_cursor_x(font_system, layout_cursor)?,
+                    ));
                 }
 
                 if layout_cursor.layout > 0 {
@@ -1056,14 +2930,19 @@ impl Buffer {
                     layout_cursor.layout = usize::max_value();
                 }
 
-                if let Some(cursor_x) = cursor_x_opt {
-                    layout_cursor.glyph = cursor_x as usize; //TODO: glyph x position
+                if let Some(PreferredX(cursor_x)) = preferred_x_opt {
+                    layout_cursor = self.layout_cursor_from_x(
+                        font_system,
+                        layout_cursor.line,
+                        layout_cursor.layout,
+                        cursor_x,
+                    )?;
                 }
 
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, preferred_x_opt) = self.cursor_motion(
                     font_system,
                     cursor,
-                    cursor_x_opt,
+                    preferred_x_opt,
                     Motion::LayoutCursor(layout_cursor),
                 )?;
             }
@@ -1072,10 +2951,10 @@ impl Buffer {
 
                 let layout_len = self.line_layout(font_system, layout_cursor.line)?.len();
 
-                if cursor_x_opt.is_none() {
-                    cursor_x_opt = Some(
-                        layout_cursor.glyph as i32, //TODO: glyph x position
-                    );
+                if preferred_x_opt.is_none() {
+                    preferred_x_opt = Some(PreferredX(
+                        self.layout_cursor_x(font_system, layout_cursor)?,
+                    ));
                 }
 
                 if layout_cursor.layout + 1 < layout_len {
@@ -1085,14 +2964,19 @@ impl Buffer {
                     layout_cursor.layout = 0;
                 }
 
-                if let Some(cursor_x) = cursor_x_opt {
-                    layout_cursor.glyph = cursor_x as usize; //TODO: glyph x position
+                if let Some(PreferredX(cursor_x)) = preferred_x_opt {
+                    layout_cursor = self.layout_cursor_from_x(
+                        font_system,
+                        layout_cursor.line,
+                        layout_cursor.layout,
+                        cursor_x,
+                    )?;
                 }
 
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, preferred_x_opt) = self.cursor_motion(
                     font_system,
                     cursor,
-                    cursor_x_opt,
+                    preferred_x_opt,
                     Motion::LayoutCursor(layout_cursor),
                 )?;
             }
@@ -1101,83 +2985,93 @@ impl Buffer {
                 layout_cursor.glyph = 0;
                 #[allow(unused_assignments)]
                 {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, preferred_x_opt) = self.cursor_motion(
                         font_system,
                         cursor,
-                        cursor_x_opt,
+                        preferred_x_opt,
                         Motion::LayoutCursor(layout_cursor),
                     )?;
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
-            Motion::SoftHome => {
+            Motion::SoftHome | Motion::SmartHome => {
                 let line = self.lines.get(cursor.line)?;
-                cursor.index = line
+                let soft_home = line
                     .text()
                     .char_indices()
                     .filter_map(|(i, c)| if c.is_whitespace() { None } else { Some(i) })
                     .next()
                     .unwrap_or(0);
-                cursor_x_opt = None;
+                cursor.index = if motion == Motion::SmartHome && cursor.index == soft_home {
+                    0
+                } else {
+                    soft_home
+                };
+                preferred_x_opt = None;
             }
             Motion::End => {
                 let mut layout_cursor = self.layout_cursor(font_system, cursor)?;
                 layout_cursor.glyph = usize::max_value();
                 #[allow(unused_assignments)]
                 {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, preferred_x_opt) = self.cursor_motion(
                         font_system,
                         cursor,
-                        cursor_x_opt,
+                        preferred_x_opt,
                         Motion::LayoutCursor(layout_cursor),
                     )?;
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::ParagraphStart => {
                 cursor.index = 0;
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::ParagraphEnd => {
                 cursor.index = self.lines.get(cursor.line)?.text().len();
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::PageUp => {
                 if let Some(height) = self.height_opt {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, preferred_x_opt) = self.cursor_motion(
                         font_system,
                         cursor,
-                        cursor_x_opt,
+                        preferred_x_opt,
                         Motion::Vertical(-height as i32),
                     )?;
                 }
             }
             Motion::PageDown => {
                 if let Some(height) = self.height_opt {
-                    (cursor, cursor_x_opt) = self.cursor_motion(
+                    (cursor, preferred_x_opt) = self.cursor_motion(
                         font_system,
                         cursor,
-                        cursor_x_opt,
+                        preferred_x_opt,
                         Motion::Vertical(height as i32),
                     )?;
                 }
             }
             Motion::Vertical(px) => {
                 // TODO more efficient, use layout run line height
-                let lines = px / self.metrics().line_height as i32;
+                // Line heights below one pixel move by one line per pixel
+                let lines = px / (self.scaled_metrics().line_height as i32).max(1);
                 match lines.cmp(&0) {
                     cmp::Ordering::Less => {
                         for _ in 0..-lines {
-                            (cursor, cursor_x_opt) =
-                                self.cursor_motion(font_system, cursor, cursor_x_opt, Motion::Up)?;
+                            (cursor, preferred_x_opt) = self.cursor_motion(
+                                font_system,
+                                cursor,
+                                preferred_x_opt,
+                                Motion::Up,
+                            )?;
                         }
                     }
                     cmp::Ordering::Greater => {
                         for _ in 0..lines {
-                            (cursor, cursor_x_opt) = self.cursor_motion(
+                            (cursor, preferred_x_opt) = self.cursor_motion(
                                 font_system,
                                 cursor,
-                                cursor_x_opt,
+                                preferred_x_opt,
                                 Motion::Down,
                             )?;
                         }
@@ -1186,35 +3080,29 @@ impl Buffer {
                 }
             }
             Motion::PreviousWord => {
-                let line = self.lines.get(cursor.line)?;
+                self.lines.get(cursor.line)?;
                 if cursor.index > 0 {
-                    cursor.index = line
-                        .text()
-                        .unicode_word_indices()
-                        .rev()
-                        .map(|(i, _)| i)
-                        .find(|&i| i < cursor.index)
-                        .unwrap_or(0);
+                    cursor.index =
+                        word::previous_word_start(&self.line_words(cursor.line), cursor.index);
                 } else if cursor.line > 0 {
                     cursor.line -= 1;
                     cursor.index = self.lines.get(cursor.line)?.text().len();
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::NextWord => {
                 let line = self.lines.get(cursor.line)?;
                 if cursor.index < line.text().len() {
-                    cursor.index = line
-                        .text()
-                        .unicode_word_indices()
-                        .map(|(i, word)| i + word.len())
-                        .find(|&i| i > cursor.index)
-                        .unwrap_or(line.text().len());
+                    cursor.index = word::next_word_end(
+                        &self.line_words(cursor.line),
+                        cursor.index,
+                        line.text().len(),
+                    );
                 } else if cursor.line + 1 < self.lines.len() {
                     cursor.line += 1;
                     cursor.index = 0;
                 }
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::LeftWord => {
                 let rtl_opt = self
@@ -1222,17 +3110,17 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::NextWord,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::PreviousWord,
                         )?;
                     }
@@ -1244,17 +3132,17 @@ impl Buffer {
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::PreviousWord,
                         )?;
                     } else {
-                        (cursor, cursor_x_opt) = self.cursor_motion(
+                        (cursor, preferred_x_opt) = self.cursor_motion(
                             font_system,
                             cursor,
-                            cursor_x_opt,
+                            preferred_x_opt,
                             Motion::NextWord,
                         )?;
                     }
@@ -1263,25 +3151,25 @@ impl Buffer {
             Motion::BufferStart => {
                 cursor.line = 0;
                 cursor.index = 0;
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::BufferEnd => {
                 cursor.line = self.lines.len() - 1;
                 cursor.index = self.lines.get(cursor.line)?.text().len();
-                cursor_x_opt = None;
+                preferred_x_opt = None;
             }
             Motion::GotoLine(line) => {
                 let mut layout_cursor = self.layout_cursor(font_system, cursor)?;
                 layout_cursor.line = line;
-                (cursor, cursor_x_opt) = self.cursor_motion(
+                (cursor, preferred_x_opt) = self.cursor_motion(
                     font_system,
                     cursor,
-                    cursor_x_opt,
+                    preferred_x_opt,
                     Motion::LayoutCursor(layout_cursor),
                 )?;
             }
         }
-        Some((cursor, cursor_x_opt))
+        Some((cursor, preferred_x_opt))
     }
 
     /// Draw the buffer
@@ -1291,15 +3179,59 @@ impl Buffer {
         font_system: &mut FontSystem,
         cache: &mut crate::SwashCache,
         color: Color,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_with_palette(font_system, cache, color, &[], f);
+    }
+
+    /// Draw the buffer, resolving palette color indexes set with [`Attrs::palette_color`]
+    /// from `palette`
+    ///
+    /// [`Attrs::palette_color`]: crate::Attrs::palette_color
+    #[cfg(feature = "swash")]
+    pub fn draw_with_palette<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_clipped(font_system, cache, color, None, palette, f);
+    }
+
+    /// Draw the buffer like [`Buffer::draw_with_palette`], skipping runs outside of a clip
+    /// rectangle
+    ///
+    /// If `clip_opt` is an `(x, y, w, h)` rectangle, only runs intersecting it are drawn. This
+    /// allows drawing part of a buffer that is larger than the visible area.
+    #[cfg(feature = "swash")]
+    pub fn draw_clipped<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
         mut f: F,
     ) where
         F: FnMut(i32, i32, u32, u32, Color),
     {
         for run in self.layout_runs() {
-            for glyph in run.glyphs.iter() {
-                let physical_glyph = glyph.physical((0., 0.), 1.0);
+            if let Some(clip) = clip_opt {
+                if !run.intersects(clip) {
+                    continue;
+                }
+            }
+
+            for glyph in run.glyphs.iter().chain(run.wrap_indicator_opt.iter()) {
+                let physical_glyph = self.physical_glyph(&run, glyph, (0., 0.), 1.0);
 
-                let glyph_color = match glyph.color_opt {
+                let glyph_color = match glyph.color(palette) {
                     Some(some) => some,
                     None => color,
                 };
@@ -1349,16 +3281,54 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     ///
     /// # Panics
     ///
-    /// Will panic if `metrics.font_size` is zero.
+    /// Will panic if `metrics.font_size` is zero, use [`Buffer::try_set_metrics_and_size`] to
+    /// get an error instead.
     pub fn set_metrics(&mut self, metrics: Metrics) {
         self.inner.set_metrics(self.font_system, metrics);
     }
 
+    /// Set the display scale, see [`Buffer::set_scale`]
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `scale` is not positive and finite.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.inner.set_scale(self.font_system, scale);
+    }
+
     /// Set the current [`Wrap`]
     pub fn set_wrap(&mut self, wrap: Wrap) {
         self.inner.set_wrap(self.font_system, wrap);
     }
 
+    /// Set the [`WrapIndicator`], see [`Buffer::set_wrap_indicator`]
+    pub fn set_wrap_indicator(&mut self, indicator_opt: Option<WrapIndicator>) {
+        self.inner
+            .set_wrap_indicator(self.font_system, indicator_opt);
+    }
+
+    /// Set the [`LineBreaker`], see [`Buffer::set_line_breaker`]
+    pub fn set_line_breaker(&mut self, line_breaker: Arc<dyn LineBreaker>) {
+        self.inner.set_line_breaker(self.font_system, line_breaker);
+    }
+
+    /// Set the [`ShapingBackend`], see [`Buffer::set_shaping_backend`]
+    pub fn set_shaping_backend(&mut self, shaping_backend: Arc<dyn ShapingBackend>) {
+        self.inner
+            .set_shaping_backend(self.font_system, shaping_backend);
+    }
+
+    /// Set the width monospace glyphs are resized to match, see [`Buffer::set_monospace_width`]
+    pub fn set_monospace_width(&mut self, monospace_width: Option<f32>) {
+        self.inner
+            .set_monospace_width(self.font_system, monospace_width);
+    }
+
+    /// Set the width of the cells of a monospace grid, see [`Buffer::set_cell_width`]
+    pub fn set_cell_width(&mut self, cell_width_opt: Option<f32>) {
+        self.inner.set_cell_width(self.font_system, cell_width_opt);
+    }
+
     /// Set the current buffer dimensions
     pub fn set_size(&mut self, width_opt: Option<f32>, height_opt: Option<f32>) {
         self.inner.set_size(self.font_system, width_opt, height_opt);
@@ -1368,7 +3338,8 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     ///
     /// # Panics
     ///
-    /// Will panic if `metrics.font_size` is zero.
+    /// Will panic if `metrics.font_size` is zero, use [`Buffer::try_set_metrics_and_size`] to
+    /// get an error instead.
     pub fn set_metrics_and_size(
         &mut self,
         metrics: Metrics,
@@ -1379,11 +3350,61 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
             .set_metrics_and_size(self.font_system, metrics, width_opt, height_opt);
     }
 
+    /// Set the current [`Metrics`] and buffer dimensions, see
+    /// [`Buffer::try_set_metrics_and_size`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::InvalidFontSize`] or [`LayoutError::InvalidLineHeight`] if
+    /// `metrics` are not valid.
+    pub fn try_set_metrics_and_size(
+        &mut self,
+        metrics: Metrics,
+        width_opt: Option<f32>,
+        height_opt: Option<f32>,
+    ) -> Result<(), LayoutError> {
+        self.inner
+            .try_set_metrics_and_size(self.font_system, metrics, width_opt, height_opt)
+    }
+
+    /// Set the largest font size that fits the text inside of the buffer size, returning it
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `min_size` is zero.
+    pub fn fit_text(
+        &mut self,
+        min_size: f32,
+        max_size: f32,
+        step: f32,
+        max_lines_opt: Option<usize>,
+    ) -> f32 {
+        self.inner
+            .fit_text(self.font_system, min_size, max_size, step, max_lines_opt)
+    }
+
+    /// Update the attributes of all text created from a [`StyleSheet`], reshaping changed lines
+    pub fn set_style_sheet(&mut self, style_sheet: &StyleSheet) {
+        self.inner.set_style_sheet(self.font_system, style_sheet);
+    }
+
+    /// Lay out every line and split the buffer into pages
+    pub fn paginate(&mut self, breaks: PageBreaks) -> Vec<Page> {
+        self.inner.paginate(self.font_system, breaks)
+    }
+
     /// Set text of buffer, using provided attributes for each line by default
     pub fn set_text(&mut self, text: &str, attrs: Attrs, shaping: Shaping) {
         self.inner.set_text(self.font_system, text, attrs, shaping);
     }
 
+    /// Set text of buffer, keeping the caches of unchanged lines, see
+    /// [`Buffer::set_text_diffed`]
+    pub fn set_text_diffed(&mut self, text: &str, attrs: Attrs, shaping: Shaping) -> bool {
+        self.inner
+            .set_text_diffed(self.font_system, text, attrs, shaping)
+    }
+
     /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
     ///
     /// ```
@@ -1409,15 +3430,36 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
             .set_rich_text(self.font_system, spans, default_attrs, shaping);
     }
 
+    /// Set text of buffer like [`Buffer::set_rich_text`], with the alignment of each paragraph,
+    /// see [`Buffer::set_rich_text_with_align`]
+    pub fn set_rich_text_with_align<'r, 's, I, A>(
+        &mut self,
+        spans: I,
+        default_attrs: Attrs,
+        shaping: Shaping,
+        aligns: A,
+    ) where
+        I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
+        A: IntoIterator<Item = Option<Align>>,
+    {
+        self.inner.set_rich_text_with_align(
+            self.font_system,
+            spans,
+            default_attrs,
+            shaping,
+            aligns,
+        );
+    }
+
     /// Apply a [`Motion`] to a [`Cursor`]
     pub fn cursor_motion(
         &mut self,
         cursor: Cursor,
-        cursor_x_opt: Option<i32>,
+        preferred_x_opt: Option<PreferredX>,
         motion: Motion,
-    ) -> Option<(Cursor, Option<i32>)> {
+    ) -> Option<(Cursor, Option<PreferredX>)> {
         self.inner
-            .cursor_motion(self.font_system, cursor, cursor_x_opt, motion)
+            .cursor_motion(self.font_system, cursor, preferred_x_opt, motion)
     }
 
     /// Draw the buffer
@@ -1428,4 +3470,35 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     {
         self.inner.draw(self.font_system, cache, color, f);
     }
+
+    /// Draw the buffer, resolving palette color indexes from `palette`
+    #[cfg(feature = "swash")]
+    pub fn draw_with_palette<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner
+            .draw_with_palette(self.font_system, cache, color, palette, f);
+    }
+
+    /// Draw the buffer, only drawing runs intersecting the clip rectangle if `clip_opt` is set
+    #[cfg(feature = "swash")]
+    pub fn draw_clipped<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner
+            .draw_clipped(self.font_system, cache, color, clip_opt, palette, f);
+    }
 }
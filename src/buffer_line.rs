@@ -1,21 +1,124 @@
 #[cfg(not(feature = "std"))]
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::any::{Any, TypeId};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use once_cell::race::OnceBox;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 use crate::{
-    Align, AttrsList, FontSystem, LayoutLine, LineEnding, ShapeBuffer, ShapeLine, Shaping, Wrap,
+    Align, AttrsList, BreakOverrides, FontSystem, GreedyLineBreaker, Justification, LayoutError,
+    LayoutLine, LineBidi, LineBreaker, LineEnding, RustybuzzShapingBackend, Segmentation,
+    ShapeBuffer, ShapeLine, Shaping, ShapingBackend, Wrap,
 };
 
+bitflags::bitflags! {
+    /// Markers attached to a [`BufferLine`] and reported with each of its [`LayoutRun`]s, for
+    /// drawing gutter marks and line backgrounds
+    ///
+    /// Bits not defined here are kept, and can be used for application specific markers.
+    ///
+    /// [`LayoutRun`]: crate::LayoutRun
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    #[repr(transparent)]
+    pub struct LineMarkers: u32 {
+        /// Line was added
+        const ADDED = 1 << 0;
+        /// Line was modified
+        const MODIFIED = 1 << 1;
+        /// Lines were removed after this line
+        const REMOVED = 1 << 2;
+        /// Line has a breakpoint
+        const BREAKPOINT = 1 << 3;
+        /// Line is the current execution point
+        const EXECUTION_POINT = 1 << 4;
+
+        const _ = !0;
+    }
+}
+
+bitflags::bitflags! {
+    /// Reasons a [`BufferLine`] was shaped again, see [`BufferLine::reshape_reasons`]
+    ///
+    /// These help to find changes that discard the shaping cache by accident, such as setting
+    /// a different but equivalent [`AttrsList`] every frame.
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct ReshapeReasons: u16 {
+        /// Line was created and had not been shaped yet
+        const NEW = 1 << 0;
+        /// Text changed, including appending and splitting lines
+        const TEXT = 1 << 1;
+        /// Attributes list changed
+        const ATTRS = 1 << 2;
+        /// Line ending changed
+        const ENDING = 1 << 3;
+        /// Line break overrides changed
+        const BREAK_OVERRIDES = 1 << 4;
+        /// Tab width of the buffer changed and the line contains tabs
+        const TAB_WIDTH = 1 << 5;
+        /// Shaping backend of the buffer changed
+        const SHAPING_BACKEND = 1 << 6;
+        /// Shape was pruned by [`Buffer::shape_until_scroll`](crate::Buffer::shape_until_scroll)
+        const PRUNED = 1 << 7;
+        /// Shape was dropped by [`BufferLine::compact`]
+        const COMPACTED = 1 << 8;
+        /// [`BufferLine::reset`] or [`BufferLine::reset_shaping`] was called directly
+        const RESET = 1 << 9;
+        /// Fonts of the [`FontSystem`] changed, see [`FontSystem::generation`]
+        const FONTS = 1 << 10;
+        /// Shaping strategy changed, see [`BufferLine::set_shaping`]
+        const SHAPING = 1 << 11;
+    }
+}
+
+/// Identifier of a [`BufferLine`] that stays the same while the line moves, see
+/// [`BufferLine::id`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LineId(usize);
+
+impl LineId {
+    /// Get a new identifier, different from all identifiers returned before
+    fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 /// A line (or paragraph) of text that is shaped and laid out
 #[derive(Clone, Debug)]
 pub struct BufferLine {
+    id: LineId,
     text: String,
     ending: LineEnding,
     attrs_list: AttrsList,
     align: Option<Align>,
+    wrap: Option<Wrap>,
+    justification: Justification,
+    bidi_opt: Option<LineBidi>,
     shape_opt: Option<ShapeLine>,
     layout_opt: Option<Vec<LayoutLine>>,
+    /// Shared copy of `layout_opt`, made by [`BufferLine::layout_arc`]
+    layout_arc: OnceBox<Arc<Vec<LayoutLine>>>,
     shaping: Shaping,
     metadata: Option<usize>,
+    data: Vec<LineData>,
+    markers: LineMarkers,
+    keep_with_next: bool,
+    break_overrides: BreakOverrides,
+    compact: bool,
+    pending_reshape_reasons: ReshapeReasons,
+    reshape_reasons: ReshapeReasons,
+    segmentation: Segmentation,
+    #[cfg(feature = "linkify")]
+    links: Vec<crate::Link>,
+}
+
+/// Typed data attached to a [`BufferLine`]
+#[derive(Clone, Debug)]
+struct LineData {
+    type_id: TypeId,
+    persistent: bool,
+    value: Arc<dyn Any + Send + Sync>,
 }
 
 impl BufferLine {
@@ -29,17 +132,43 @@ impl BufferLine {
         shaping: Shaping,
     ) -> Self {
         Self {
+            id: LineId::next(),
             text: text.into(),
             ending,
             attrs_list,
             align: None,
+            wrap: None,
+            justification: Justification::Spaces,
+            bidi_opt: None,
             shape_opt: None,
             layout_opt: None,
+            layout_arc: OnceBox::new(),
             shaping,
             metadata: None,
+            data: Vec::new(),
+            markers: LineMarkers::empty(),
+            keep_with_next: false,
+            break_overrides: BreakOverrides::default(),
+            compact: false,
+            pending_reshape_reasons: ReshapeReasons::NEW,
+            reshape_reasons: ReshapeReasons::empty(),
+            segmentation: Segmentation::default(),
+            #[cfg(feature = "linkify")]
+            links: Vec::new(),
         }
     }
 
+    /// Get the identifier of the line, for keeping state such as syntax caches or diagnostics
+    /// with the line while lines are inserted or removed before it
+    ///
+    /// Each new line gets a new identifier. The identifier stays with the text of the line: when a
+    /// line is split, the first part keeps it unless it is empty, and when a line is appended to
+    /// another, the line it is appended to keeps it unless it was empty. Cloned lines keep the
+    /// identifier of the original.
+    pub fn id(&self) -> LineId {
+        self.id
+    }
+
     /// Get current text
     pub fn text(&self) -> &str {
         &self.text
@@ -48,6 +177,7 @@ impl BufferLine {
     /// Set text and attributes list
     ///
     /// Will reset shape and layout if it differs from current text and attributes list.
+    /// Line break overrides are cleared if the text differs.
     /// Returns true if the line was reset
     pub fn set_text<T: AsRef<str>>(
         &mut self,
@@ -56,18 +186,30 @@ impl BufferLine {
         attrs_list: AttrsList,
     ) -> bool {
         let text = text.as_ref();
-        if text != self.text || ending != self.ending || attrs_list != self.attrs_list {
+        let mut reasons = ReshapeReasons::empty();
+        reasons.set(ReshapeReasons::TEXT, text != self.text);
+        reasons.set(ReshapeReasons::ENDING, ending != self.ending);
+        reasons.set(ReshapeReasons::ATTRS, attrs_list != self.attrs_list);
+        if !reasons.is_empty() {
+            if text != self.text {
+                self.break_overrides = BreakOverrides::default();
+            }
             self.text.clear();
             self.text.push_str(text);
             self.ending = ending;
             self.attrs_list = attrs_list;
-            self.reset();
+            self.reset_for(reasons);
             true
         } else {
             false
         }
     }
 
+    /// Release memory held by text that was removed from the line
+    pub(crate) fn shrink_text(&mut self) {
+        self.text.shrink_to_fit();
+    }
+
     /// Consume this line, returning only its text contents as a String.
     pub fn into_text(self) -> String {
         self.text
@@ -85,7 +227,26 @@ impl BufferLine {
     pub fn set_ending(&mut self, ending: LineEnding) -> bool {
         if ending != self.ending {
             self.ending = ending;
-            self.reset_shaping();
+            self.reset_shaping_for(ReshapeReasons::ENDING);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the [`Shaping`] strategy of the line
+    pub fn shaping(&self) -> Shaping {
+        self.shaping
+    }
+
+    /// Set the [`Shaping`] strategy of the line
+    ///
+    /// Will reset shape and layout if it differs from the current strategy.
+    /// Returns true if the line was reset
+    pub fn set_shaping(&mut self, shaping: Shaping) -> bool {
+        if shaping != self.shaping {
+            self.shaping = shaping;
+            self.reset_shaping_for(ReshapeReasons::SHAPING);
             true
         } else {
             false
@@ -99,18 +260,50 @@ impl BufferLine {
 
     /// Set attributes list
     ///
-    /// Will reset shape and layout if it differs from current attributes list.
-    /// Returns true if the line was reset
+    /// Will reset shape and layout if it differs from current attributes list in properties
+    /// that change them. If only properties used for drawing differ, like colors, the glyphs of
+    /// the current shape and layout are updated instead, see [`Attrs::same_layout`].
+    /// Returns true if the attributes list changed
+    ///
+    /// [`Attrs::same_layout`]: crate::Attrs::same_layout
     pub fn set_attrs_list(&mut self, attrs_list: AttrsList) -> bool {
         if attrs_list != self.attrs_list {
+            let repaint = self.attrs_list.same_layout(&attrs_list, self.text.len());
             self.attrs_list = attrs_list;
-            self.reset_shaping();
+            if repaint {
+                self.repaint();
+            } else {
+                self.reset_shaping_for(ReshapeReasons::ATTRS);
+            }
             true
         } else {
             false
         }
     }
 
+    /// Update the properties used for drawing of the shaped and laid out glyphs from the
+    /// attributes list
+    fn repaint(&mut self) {
+        let attrs_list = &self.attrs_list;
+        if let Some(shape) = &mut self.shape_opt {
+            for span in shape.spans.iter_mut() {
+                for word in span.words.iter_mut() {
+                    for glyph in word.glyphs.iter_mut() {
+                        glyph.set_paint(&attrs_list.get_span(glyph.start));
+                    }
+                }
+            }
+        }
+        if let Some(layout) = &mut self.layout_opt {
+            for layout_line in layout.iter_mut() {
+                for glyph in layout_line.glyphs.iter_mut() {
+                    glyph.set_paint(&attrs_list.get_span(glyph.start));
+                }
+            }
+            self.layout_arc = OnceBox::new();
+        }
+    }
+
     /// Get the Text alignment
     pub fn align(&self) -> Option<Align> {
         self.align
@@ -131,11 +324,101 @@ impl BufferLine {
         }
     }
 
+    /// Get the wrap mode of this line, overriding the wrap mode of the buffer
+    pub fn wrap(&self) -> Option<Wrap> {
+        self.wrap
+    }
+
+    /// Set the wrap mode of this line, for example [`Wrap::None`] for a table row in a buffer
+    /// that wraps
+    ///
+    /// Will reset layout if it differs from current wrap mode.
+    /// Setting to None will use the wrap mode of the buffer.
+    /// Returns true if the line was reset
+    pub fn set_wrap(&mut self, wrap: Option<Wrap>) -> bool {
+        if wrap != self.wrap {
+            self.wrap = wrap;
+            self.reset_layout();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the justification method
+    pub fn justification(&self) -> Justification {
+        self.justification
+    }
+
+    /// Set the justification method, used when the alignment is [`Align::Justified`]
+    ///
+    /// Will reset layout if it differs from current justification method.
+    /// Returns true if the line was reset
+    pub fn set_justification(&mut self, justification: Justification) -> bool {
+        if justification != self.justification {
+            self.justification = justification;
+            self.reset_layout();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the line markers
+    pub fn markers(&self) -> LineMarkers {
+        self.markers
+    }
+
+    /// Set the line markers
+    ///
+    /// Markers do not affect shaping or layout, and are kept when the line is reset. When a line
+    /// is split, the markers stay with the first line.
+    pub fn set_markers(&mut self, markers: LineMarkers) {
+        self.markers = markers;
+    }
+
+    /// True if this line should be placed on the same page as the next line
+    pub fn keep_with_next(&self) -> bool {
+        self.keep_with_next
+    }
+
+    /// Set whether this line should be placed on the same page as the next line, for example for
+    /// headings, see [`Buffer::paginate`]
+    ///
+    /// [`Buffer::paginate`]: crate::Buffer::paginate
+    pub fn set_keep_with_next(&mut self, keep_with_next: bool) {
+        self.keep_with_next = keep_with_next;
+    }
+
+    /// Get the line break opportunity overrides
+    pub fn break_overrides(&self) -> &BreakOverrides {
+        &self.break_overrides
+    }
+
+    /// Set line break opportunities that override the default Unicode line breaking, for example
+    /// from a dictionary based segmenter for Thai
+    ///
+    /// Overrides are byte indexes, so they should be set again when the text changes. Will reset
+    /// shape and layout if they differ from the current overrides.
+    /// Returns true if the line was reset
+    pub fn set_break_overrides(&mut self, break_overrides: BreakOverrides) -> bool {
+        if break_overrides != self.break_overrides {
+            self.break_overrides = break_overrides;
+            self.reset_shaping_for(ReshapeReasons::BREAK_OVERRIDES);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Append line at end of this line
     ///
     /// The wrap setting of the appended line will be lost
     pub fn append(&mut self, other: Self) {
         let len = self.text.len();
+        if len == 0 {
+            self.id = other.id;
+        }
         self.text.push_str(other.text());
 
         if other.attrs_list.defaults() != self.attrs_list.defaults() {
@@ -150,35 +433,148 @@ impl BufferLine {
             self.attrs_list.add_span(range, attrs.as_attrs());
         }
 
-        self.reset();
+        self.break_overrides.append(&other.break_overrides, len);
+
+        self.reset_for(ReshapeReasons::TEXT);
     }
 
     /// Split off new line at index
     pub fn split_off(&mut self, index: usize) -> Self {
         let text = self.text.split_off(index);
         let attrs_list = self.attrs_list.split_off(index);
-        self.reset();
+        let break_overrides = self.break_overrides.split_off(index);
+        self.reset_for(ReshapeReasons::TEXT);
 
         let mut new = Self::new(text, self.ending, attrs_list, self.shaping);
+        if index == 0 {
+            core::mem::swap(&mut self.id, &mut new.id);
+        }
+        new.break_overrides = break_overrides;
         new.align = self.align;
+        new.wrap = self.wrap;
+        new.justification = self.justification;
+        new.keep_with_next = self.keep_with_next;
         new
     }
 
     /// Reset shaping, layout, and metadata caches
+    ///
+    /// Data set with [`BufferLine::set_data`] is cleared, while data set with
+    /// [`BufferLine::set_persistent_data`] is kept.
     pub fn reset(&mut self) {
+        self.reset_for(ReshapeReasons::RESET);
+    }
+
+    /// Reset shaping, layout, and metadata caches, recording why
+    fn reset_for(&mut self, reasons: ReshapeReasons) {
         self.metadata = None;
-        self.reset_shaping();
+        self.data.retain(|data| data.persistent);
+        self.reset_shaping_for(reasons);
     }
 
     /// Reset shaping and layout caches
     pub fn reset_shaping(&mut self) {
+        self.reset_shaping_for(ReshapeReasons::RESET);
+    }
+
+    /// Reset shaping and layout caches, recording why for [`BufferLine::reshape_reasons`]
+    pub(crate) fn reset_shaping_for(&mut self, reasons: ReshapeReasons) {
+        // The bidirectional analysis only depends on the text
+        if reasons.intersects(ReshapeReasons::TEXT | ReshapeReasons::RESET) {
+            self.bidi_opt = None;
+        }
         self.shape_opt = None;
+        self.pending_reshape_reasons |= reasons;
         self.reset_layout();
     }
 
     /// Reset only layout cache
+    ///
+    /// The shape is also reset if the line was compacted, see [`BufferLine::compact`].
     pub fn reset_layout(&mut self) {
         self.layout_opt = None;
+        self.layout_arc = OnceBox::new();
+        if self.compact {
+            self.shape_opt = None;
+            self.pending_reshape_reasons |= ReshapeReasons::COMPACTED;
+            self.compact = false;
+        }
+    }
+
+    /// Get the reasons the current shape of the line was created, empty if it was never shaped
+    ///
+    /// Reasons are collected from every change that discarded the shape since the line was
+    /// shaped before, see [`BufferLine::pending_reshape_reasons`].
+    pub fn reshape_reasons(&self) -> ReshapeReasons {
+        self.reshape_reasons
+    }
+
+    /// Get the reasons the line will be shaped again, empty if its shape is cached
+    pub fn pending_reshape_reasons(&self) -> ReshapeReasons {
+        self.pending_reshape_reasons
+    }
+
+    /// Record the reasons for shaping when a new shape is created, and segment the text and find
+    /// links if it changed
+    fn take_reshape_reasons(&mut self) {
+        if self.pending_reshape_reasons.intersects(
+            ReshapeReasons::NEW
+                | ReshapeReasons::TEXT
+                | ReshapeReasons::RESET
+                | ReshapeReasons::BREAK_OVERRIDES,
+        ) {
+            self.segmentation = Segmentation::new(&self.text, &self.break_overrides);
+        }
+        #[cfg(feature = "linkify")]
+        if self
+            .pending_reshape_reasons
+            .intersects(ReshapeReasons::NEW | ReshapeReasons::TEXT | ReshapeReasons::RESET)
+        {
+            self.links = crate::find_links(&self.text);
+        }
+        self.reshape_reasons = core::mem::take(&mut self.pending_reshape_reasons);
+    }
+
+    /// Get the grapheme, word, and line break boundaries of the text, found when the line is
+    /// shaped
+    ///
+    /// Boundaries are kept while the text is unchanged, and found again the next time the line
+    /// is shaped after its text or line break overrides change.
+    pub fn segmentation(&self) -> &Segmentation {
+        &self.segmentation
+    }
+
+    /// Get the URLs and email addresses in the text, found when the line is shaped
+    ///
+    /// Links are kept while the text is unchanged, and found again the next time the line is
+    /// shaped after its text changes.
+    #[cfg(feature = "linkify")]
+    pub fn links(&self) -> &[crate::Link] {
+        &self.links
+    }
+
+    /// Drop shaping data that is not needed to draw the current layout, and shrink the layout
+    ///
+    /// Only the paragraph direction of the shape is kept. The line is shaped again when it is
+    /// laid out again or its shape is requested. Does nothing if the line is not laid out.
+    pub fn compact(&mut self) {
+        if self.compact {
+            return;
+        }
+        if let (Some(shape), Some(layout)) = (&mut self.shape_opt, &mut self.layout_opt) {
+            shape.spans = Vec::new();
+            self.bidi_opt = None;
+            for layout_line in layout.iter_mut() {
+                layout_line.glyphs.shrink_to_fit();
+            }
+            layout.shrink_to_fit();
+            self.compact = true;
+        }
+    }
+
+    /// True if the line was compacted, see [`BufferLine::compact`]
+    pub fn is_compact(&self) -> bool {
+        self.compact
     }
 
     /// Shape line, will cache results
@@ -186,6 +582,47 @@ impl BufferLine {
         self.shape_in_buffer(&mut ShapeBuffer::default(), font_system, tab_width)
     }
 
+    /// Shape line like [`BufferLine::shape`], returning [`LayoutError::NoFont`] instead of
+    /// caching the shape if no font was found for some of the text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::NoFont`] if no font, including fallbacks, could shape the text.
+    pub fn try_shape(
+        &mut self,
+        font_system: &mut FontSystem,
+        tab_width: u16,
+    ) -> Result<&ShapeLine, LayoutError> {
+        if self.compact {
+            self.reset_shaping_for(ReshapeReasons::COMPACTED);
+        }
+        let shape = match self.shape_opt.take() {
+            Some(shape) => shape,
+            None => {
+                let mut scratch = ShapeBuffer::default();
+                let shape = ShapeLine::new_with_bidi(
+                    &mut scratch,
+                    font_system,
+                    &self.text,
+                    &mut self.bidi_opt,
+                    &self.attrs_list,
+                    self.shaping,
+                    tab_width,
+                    &self.break_overrides,
+                    &RustybuzzShapingBackend,
+                );
+                if scratch.missing_font {
+                    return Err(LayoutError::NoFont);
+                }
+                self.layout_opt = None;
+                self.layout_arc = OnceBox::new();
+                self.take_reshape_reasons();
+                shape
+            }
+        };
+        Ok(self.shape_opt.insert(shape))
+    }
+
     /// Shape a line using a pre-existing shape buffer, will cache results
     pub fn shape_in_buffer(
         &mut self,
@@ -193,21 +630,43 @@ impl BufferLine {
         font_system: &mut FontSystem,
         tab_width: u16,
     ) -> &ShapeLine {
+        self.shape_with_backend(scratch, font_system, tab_width, &RustybuzzShapingBackend)
+    }
+
+    /// Shape a line like [`BufferLine::shape_in_buffer`], using `shaping_backend` instead of
+    /// [`RustybuzzShapingBackend`], will cache results
+    pub fn shape_with_backend(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        tab_width: u16,
+        shaping_backend: &dyn ShapingBackend,
+    ) -> &ShapeLine {
+        if self.compact {
+            self.reset_shaping_for(ReshapeReasons::COMPACTED);
+        }
         if self.shape_opt.is_none() {
-            self.shape_opt = Some(ShapeLine::new_in_buffer(
+            self.shape_opt = Some(ShapeLine::new_with_bidi(
                 scratch,
                 font_system,
                 &self.text,
+                &mut self.bidi_opt,
                 &self.attrs_list,
                 self.shaping,
                 tab_width,
+                &self.break_overrides,
+                shaping_backend,
             ));
             self.layout_opt = None;
+            self.layout_arc = OnceBox::new();
+            self.take_reshape_reasons();
         }
         self.shape_opt.as_ref().expect("shape not found")
     }
 
     /// Get line shaping cache
+    ///
+    /// If the line is compacted, the shape has no spans, see [`BufferLine::compact`].
     pub fn shape_opt(&self) -> &Option<ShapeLine> {
         &self.shape_opt
     }
@@ -233,7 +692,38 @@ impl BufferLine {
         )
     }
 
+    /// Layout line like [`BufferLine::layout`], returning an error instead of laying out the
+    /// line if `font_size` is not valid or no font was found for some of the text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::InvalidFontSize`] if `font_size` is zero, negative, or not finite,
+    /// or [`LayoutError::NoFont`] if no font, including fallbacks, could shape the text.
+    pub fn try_layout(
+        &mut self,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        match_mono_width: Option<f32>,
+        tab_width: u16,
+    ) -> Result<&[LayoutLine], LayoutError> {
+        if !(font_size.is_finite() && font_size > 0.0) {
+            return Err(LayoutError::InvalidFontSize);
+        }
+        self.try_shape(font_system, tab_width)?;
+        Ok(self.layout(
+            font_system,
+            font_size,
+            width_opt,
+            wrap,
+            match_mono_width,
+            tab_width,
+        ))
+    }
+
     /// Layout a line using a pre-existing shape buffer, will cache results
+    #[allow(clippy::too_many_arguments)]
     pub fn layout_in_buffer(
         &mut self,
         scratch: &mut ShapeBuffer,
@@ -243,20 +733,81 @@ impl BufferLine {
         wrap: Wrap,
         match_mono_width: Option<f32>,
         tab_width: u16,
+    ) -> &[LayoutLine] {
+        self.layout_with_buffer_settings(
+            scratch,
+            font_system,
+            font_size,
+            width_opt,
+            wrap,
+            match_mono_width,
+            None,
+            tab_width,
+            &GreedyLineBreaker,
+            &RustybuzzShapingBackend,
+        )
+    }
+
+    /// Layout a line like [`BufferLine::layout_in_buffer`], with the cell width, [`LineBreaker`]
+    /// and [`ShapingBackend`] of a [`Buffer`](crate::Buffer), will cache results
+    ///
+    /// If `cell_width_opt` is set, glyphs are snapped to a grid of cells of that width.
+    #[allow(clippy::too_many_arguments)]
+    pub fn layout_with_buffer_settings(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        match_mono_width: Option<f32>,
+        cell_width_opt: Option<f32>,
+        tab_width: u16,
+        line_breaker: &dyn LineBreaker,
+        shaping_backend: &dyn ShapingBackend,
     ) -> &[LayoutLine] {
         if self.layout_opt.is_none() {
+            #[cfg(feature = "perf")]
+            {
+                font_system.perf_counters_mut().lines_laid_out += 1;
+            }
             let align = self.align;
-            let shape = self.shape_in_buffer(scratch, font_system, tab_width);
+            let wrap = self.wrap.unwrap_or(wrap);
+            let justification = self.justification;
+            let shape = self.shape_with_backend(scratch, font_system, tab_width, shaping_backend);
             let mut layout = Vec::with_capacity(1);
-            shape.layout_to_buffer(
-                scratch,
-                font_size,
-                width_opt,
-                wrap,
-                align,
-                &mut layout,
-                match_mono_width,
-            );
+            match cell_width_opt {
+                Some(cell_width) => {
+                    // Lay out a single line to be snapped to the grid and wrapped by cells
+                    shape.layout_to_buffer(
+                        scratch,
+                        font_size,
+                        None,
+                        Wrap::None,
+                        align,
+                        &mut layout,
+                        match_mono_width,
+                        justification,
+                    );
+                    if let Some(line) = layout.pop() {
+                        layout =
+                            crate::grid::grid_layout(line, &self.text, cell_width, width_opt, wrap);
+                    }
+                }
+                None => {
+                    shape.layout_to_buffer_with_breaker(
+                        scratch,
+                        font_size,
+                        width_opt,
+                        wrap,
+                        align,
+                        &mut layout,
+                        match_mono_width,
+                        justification,
+                        line_breaker,
+                    );
+                }
+            }
             self.layout_opt = Some(layout);
         }
         self.layout_opt.as_ref().expect("layout not found")
@@ -267,6 +818,19 @@ impl BufferLine {
         &self.layout_opt
     }
 
+    /// Get line layout cache as an [`Arc`], which can be cloned cheaply, for example to send a
+    /// snapshot to a render thread
+    ///
+    /// The layout is copied once after each change to it, later calls share that copy. Changes
+    /// to the line do not affect a snapshot that was already taken.
+    pub fn layout_arc(&self) -> Option<&Arc<Vec<LayoutLine>>> {
+        let layout = self.layout_opt.as_ref()?;
+        Some(
+            self.layout_arc
+                .get_or_init(|| Box::new(Arc::new(layout.clone()))),
+        )
+    }
+
     /// Get line metadata. This will be None if [`BufferLine::set_metadata`] has not been called
     /// after the last reset of shaping and layout caches
     pub fn metadata(&self) -> Option<usize> {
@@ -277,4 +841,43 @@ impl BufferLine {
     pub fn set_metadata(&mut self, metadata: usize) {
         self.metadata = Some(metadata);
     }
+
+    /// Get line data of type `T`, if it was set with [`BufferLine::set_data`] or
+    /// [`BufferLine::set_persistent_data`]
+    pub fn data<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.data
+            .iter()
+            .find(|data| data.type_id == TypeId::of::<T>())
+            .and_then(|data| data.value.downcast_ref())
+    }
+
+    /// Set line data of type `T`, replacing any previous data of the same type.
+    /// This is stored until the next line reset
+    pub fn set_data<T: Any + Send + Sync>(&mut self, value: T) {
+        self.insert_data(value, false);
+    }
+
+    /// Set line data of type `T`, replacing any previous data of the same type.
+    /// This is kept when the line is reset, for example when its text is edited, and is only
+    /// removed by [`BufferLine::remove_data`]. When a line is split, the data stays with the
+    /// first line.
+    pub fn set_persistent_data<T: Any + Send + Sync>(&mut self, value: T) {
+        self.insert_data(value, true);
+    }
+
+    /// Remove line data of type `T`. Returns true if there was data to remove
+    pub fn remove_data<T: Any + Send + Sync>(&mut self) -> bool {
+        let len = self.data.len();
+        self.data.retain(|data| data.type_id != TypeId::of::<T>());
+        self.data.len() != len
+    }
+
+    fn insert_data<T: Any + Send + Sync>(&mut self, value: T, persistent: bool) {
+        self.remove_data::<T>();
+        self.data.push(LineData {
+            type_id: TypeId::of::<T>(),
+            persistent,
+            value: Arc::new(value),
+        });
+    }
 }
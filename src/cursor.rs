@@ -1,3 +1,7 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Buffer;
+
 /// Current cursor location
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Cursor {
@@ -24,8 +28,46 @@ impl Cursor {
             affinity,
         }
     }
+
+    /// Move the cursor to the nearest valid position in `buffer`, at the start of a grapheme
+    ///
+    /// A cursor past the last line moves to the end of the text, and a cursor past the end of
+    /// its line or inside of a grapheme moves back to the start of that grapheme. Use this for
+    /// cursors that may be stale, like positions restored from a previous session or kept while
+    /// applying remote edits.
+    pub fn clamp_to(self, buffer: &Buffer) -> Self {
+        let mut cursor = self;
+        let last_line = buffer.lines.len().saturating_sub(1);
+        let text = match buffer.lines.get(cursor.line.min(last_line)) {
+            Some(line) => line.text(),
+            None => "",
+        };
+        if cursor.line > last_line {
+            cursor.line = last_line;
+            cursor.index = text.len();
+        }
+        if cursor.index < text.len() {
+            cursor.index = text
+                .grapheme_indices(true)
+                .map(|(i, _)| i)
+                .take_while(|&i| i <= cursor.index)
+                .last()
+                .unwrap_or(0);
+        } else {
+            cursor.index = text.len();
+        }
+        cursor
+    }
 }
 
+/// Preferred X position of a cursor in pixels, see [`Buffer::cursor_motion`]
+///
+/// Vertical motions move to the position closest to it on the new line, so the cursor keeps its
+/// horizontal position when moving through shorter lines. This was a glyph index in earlier
+/// versions, so a value kept from one of them must be discarded.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PreferredX(pub i32);
+
 /// Whether to associate cursors placed at a boundary between runs with the run before or after it.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Affinity {
@@ -95,14 +137,21 @@ pub enum Motion {
     Left,
     /// Move cursor right
     Right,
-    /// Move cursor up
+    /// Move cursor up one visual line, staying in the same paragraph if it is wrapped
     Up,
-    /// Move cursor down
+    /// Move cursor down one visual line, staying in the same paragraph if it is wrapped
     Down,
+    /// Move cursor up one line (paragraph) in [`Buffer::lines`], skipping its wrapped lines
+    UpParagraph,
+    /// Move cursor down one line (paragraph) in [`Buffer::lines`], skipping its wrapped lines
+    DownParagraph,
     /// Move cursor to start of line
     Home,
     /// Move cursor to start of line, skipping whitespace
     SoftHome,
+    /// Move cursor to start of line, skipping whitespace, or to the very start of the line if it
+    /// is already after the whitespace
+    SmartHome,
     /// Move cursor to end of line
     End,
     /// Move cursor to start of paragraph
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Dictionary based word segmentation for scripts written without spaces between words
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::ops::Range;
+use once_cell::race::OnceBox;
+use unicode_segmentation::UnicodeSegmentation;
+
+static THAI: &str = include_str!("thai.txt");
+static LAO: &str = include_str!("lao.txt");
+static KHMER: &str = include_str!("khmer.txt");
+static BURMESE: &str = include_str!("burmese.txt");
+
+static THAI_WORDS: OnceBox<Vec<String>> = OnceBox::new();
+static LAO_WORDS: OnceBox<Vec<String>> = OnceBox::new();
+static KHMER_WORDS: OnceBox<Vec<String>> = OnceBox::new();
+static BURMESE_WORDS: OnceBox<Vec<String>> = OnceBox::new();
+
+/// Longest dictionary word to look for, in graphemes
+const MAX_WORD_GRAPHEMES: usize = 20;
+
+/// A script whose words are found with a dictionary, see [`set_dictionary`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DictionaryScript {
+    Thai,
+    Lao,
+    Khmer,
+    Burmese,
+}
+
+impl DictionaryScript {
+    /// Get the script of a character, if it is segmented by dictionary
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '\u{0E00}'..='\u{0E7F}' => Some(Self::Thai),
+            '\u{0E80}'..='\u{0EFF}' => Some(Self::Lao),
+            '\u{1780}'..='\u{17FF}' | '\u{19E0}'..='\u{19FF}' => Some(Self::Khmer),
+            '\u{1000}'..='\u{109F}' | '\u{A9E0}'..='\u{A9FF}' | '\u{AA60}'..='\u{AA7F}' => {
+                Some(Self::Burmese)
+            }
+            _ => None,
+        }
+    }
+
+    fn words(self) -> &'static OnceBox<Vec<String>> {
+        match self {
+            Self::Thai => &THAI_WORDS,
+            Self::Lao => &LAO_WORDS,
+            Self::Khmer => &KHMER_WORDS,
+            Self::Burmese => &BURMESE_WORDS,
+        }
+    }
+
+    fn builtin(self) -> &'static str {
+        match self {
+            Self::Thai => THAI,
+            Self::Lao => LAO,
+            Self::Khmer => KHMER,
+            Self::Burmese => BURMESE,
+        }
+    }
+
+    /// Get the sorted dictionary of the script, loading the built in word list on first use
+    fn dictionary(self) -> &'static [String] {
+        self.words()
+            .get_or_init(|| Box::new(sorted(self.builtin().lines().map(String::from))))
+    }
+}
+
+fn sorted(words: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut words: Vec<String> = words.into_iter().filter(|word| !word.is_empty()).collect();
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+/// Replace the dictionary used to segment text in `script`
+///
+/// The built in word lists only hold a small set of common words, which is enough for short UI
+/// strings but will leave most words of longer text unknown. Applications that display such text
+/// should load a full word list, such as the ones shipped with ICU or libthai, with one word per
+/// item.
+///
+/// The dictionary of a script can only be set once, before any text in that script is segmented.
+/// Returns `false` if the dictionary was already set or the built in one was already loaded.
+pub fn set_dictionary(script: DictionaryScript, words: impl IntoIterator<Item = String>) -> bool {
+    script.words().set(Box::new(sorted(words))).is_ok()
+}
+
+/// Get the runs of `text` in scripts that are segmented by dictionary, with their script
+fn dictionary_runs(text: &str) -> Vec<(Range<usize>, DictionaryScript)> {
+    let mut runs: Vec<(Range<usize>, DictionaryScript)> = Vec::new();
+    for (i, c) in text.char_indices() {
+        let script = match DictionaryScript::from_char(c) {
+            Some(some) => some,
+            None => continue,
+        };
+        match runs.last_mut() {
+            Some((range, run_script)) if range.end == i && *run_script == script => {
+                range.end = i + c.len_utf8();
+            }
+            _ => runs.push((i..i + c.len_utf8(), script)),
+        }
+    }
+    runs
+}
+
+/// Segment one run into words, preferring the fewest unknown graphemes, then the fewest words
+///
+/// Unknown graphemes next to each other are kept in one segment, so text that is not in the
+/// dictionary is not broken at arbitrary points.
+fn segment_run(run: &str, words: &[String]) -> Vec<Range<usize>> {
+    let mut bounds: Vec<usize> = run.grapheme_indices(true).map(|(i, _)| i).collect();
+    bounds.push(run.len());
+
+    // Cost of the best segmentation up to each bound, and the previous bound and if the segment
+    // ending there is a known word
+    let mut best = vec![((0, 0), 0, true); bounds.len()];
+    for j in 1..bounds.len() {
+        let prev = &best[j - 1];
+        best[j] = ((prev.0 .0 + 1, prev.0 .1 + 1), j - 1, false);
+        for i in j.saturating_sub(MAX_WORD_GRAPHEMES)..j {
+            let word = &run[bounds[i]..bounds[j]];
+            if words.binary_search_by(|x| x.as_str().cmp(word)).is_ok() {
+                let cost = (best[i].0 .0, best[i].0 .1 + 1);
+                if cost < best[j].0 {
+                    best[j] = (cost, i, true);
+                }
+            }
+        }
+    }
+
+    let mut segments: Vec<(Range<usize>, bool)> = Vec::new();
+    let mut j = bounds.len() - 1;
+    while j > 0 {
+        let (_, i, known) = best[j];
+        match segments.last_mut() {
+            // Join unknown graphemes
+            Some((range, false)) if !known => *range = bounds[i]..range.end,
+            _ => segments.push((bounds[i]..bounds[j], known)),
+        }
+        j = i;
+    }
+    segments.reverse();
+    segments.into_iter().map(|(range, _)| range).collect()
+}
+
+/// Get the words of `text` in scripts segmented by dictionary, and the runs of those scripts
+///
+/// Text in other scripts is not included.
+pub(crate) fn dictionary_words(text: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let mut words = Vec::new();
+    let mut runs = Vec::new();
+    for (run, script) in dictionary_runs(text) {
+        for word in segment_run(&text[run.clone()], script.dictionary()) {
+            words.push(run.start + word.start..run.start + word.end);
+        }
+        runs.push(run);
+    }
+    (words, runs)
+}
+
+#[test]
+fn test_dictionary_words() {
+    let text = "hi สวัสดีครับ ขอบคุณมาก";
+    let (words, runs) = dictionary_words(text);
+    let words: Vec<&str> = words.into_iter().map(|range| &text[range]).collect();
+    assert_eq!(words, ["สวัสดี", "ครับ", "ขอบคุณ", "มาก"]);
+    assert_eq!(runs.len(), 2);
+
+    // Unknown text is kept together
+    let text = "ฮฮฮไป";
+    let (words, _) = dictionary_words(text);
+    assert_eq!(words, [0..9, 9..15]);
+}
@@ -2,29 +2,67 @@
 
 #[cfg(not(feature = "std"))]
 use alloc::{
+    boxed::Box,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
-use core::{cmp, iter::once};
+use core::{cmp, fmt, iter::once, mem};
 use unicode_segmentation::UnicodeSegmentation;
 
-#[cfg(feature = "swash")]
-use crate::Color;
 use crate::{
-    Action, Attrs, AttrsList, BorrowedWithFontSystem, BufferLine, BufferRef, Change, ChangeItem,
-    Cursor, Edit, FontSystem, LayoutRun, Selection, Shaping,
+    math, Action, ActionResult, ActionState, Attrs, AttrsList, BackspaceMode,
+    BorrowedWithFontSystem, Buffer, BufferLine, BufferRef, Change, ChangeItem, Color, Cursor,
+    CursorShape, Edit, FontSystem, HandlePosition, LayoutRun, Motion, PreferredX, Scroll,
+    Selection, SelectionHandle, Shaping, UndoGrouping, UndoHistory,
 };
 
+type ChangeObserver = Box<dyn FnMut(&Change) + Send + Sync>;
+type CursorObserver = Box<dyn FnMut(Cursor) + Send + Sync>;
+type ScrollObserver = Box<dyn FnMut(Scroll) + Send + Sync>;
+
+/// Callbacks registered with [`Editor::on_change`], [`Editor::on_cursor_move`], and
+/// [`Editor::on_scroll`], with the last reported cursor and scroll
+#[derive(Default)]
+struct Observers {
+    change: Vec<ChangeObserver>,
+    cursor_move: Vec<CursorObserver>,
+    scroll: Vec<ScrollObserver>,
+    cursor: Cursor,
+    scroll_position: Scroll,
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("change", &self.change.len())
+            .field("cursor_move", &self.cursor_move.len())
+            .field("scroll", &self.scroll.len())
+            .finish()
+    }
+}
+
 /// A wrapper of [`Buffer`] for easy editing
 #[derive(Debug)]
 pub struct Editor<'buffer> {
     buffer_ref: BufferRef<'buffer>,
     cursor: Cursor,
-    cursor_x_opt: Option<i32>,
+    /// Preferred cursor X position, relative to the font size so it is kept when zooming
+    cursor_x_opt: Option<f32>,
     selection: Selection,
     cursor_moved: bool,
     auto_indent: bool,
+    overtype: bool,
+    backspace_mode: BackspaceMode,
     change: Option<Change>,
+    batch_depth: usize,
+    batch_redraw: bool,
+    observers: Observers,
+    undo_history: UndoHistory,
+    /// Number of recorded change items, to find if an action changed the text
+    edit_count: usize,
+    line_highlight_opt: Option<Color>,
+    cursor_shape: CursorShape,
 }
 
 fn cursor_glyph_opt(cursor: &Cursor, run: &LayoutRun) -> Option<(usize, f32)> {
@@ -33,19 +71,17 @@ fn cursor_glyph_opt(cursor: &Cursor, run: &LayoutRun) -> Option<(usize, f32)> {
             if cursor.index == glyph.start {
                 return Some((glyph_i, 0.0));
             } else if cursor.index > glyph.start && cursor.index < glyph.end {
-                // Guess x offset based on characters
-                let mut before = 0;
-                let mut total = 0;
-
-                let cluster = &run.text[glyph.start..glyph.end];
-                for (i, _) in cluster.grapheme_indices(true) {
-                    if glyph.start + i < cursor.index {
-                        before += 1;
-                    }
-                    total += 1;
-                }
-
-                let offset = glyph.w * (before as f32) / (total as f32);
+                // Find offset from the start of the glyph to the grapheme inside of the ligature
+                let offset = glyph
+                    .sub_clusters(run.text)
+                    .find(|part| part.start >= cursor.index)
+                    .map_or(glyph.w, |part| {
+                        if glyph.level.is_rtl() {
+                            glyph.x + glyph.w - (part.x + part.w)
+                        } else {
+                            part.x - glyph.x
+                        }
+                    });
                 return Some((glyph_i, offset));
             }
         }
@@ -63,6 +99,41 @@ fn cursor_glyph_opt(cursor: &Cursor, run: &LayoutRun) -> Option<(usize, f32)> {
     None
 }
 
+/// Move `cursor` to the nearest position that is inside of `buffer` and on a character boundary
+fn clamp_cursor(buffer: &Buffer, mut cursor: Cursor) -> Cursor {
+    let last_line = buffer.lines.len().saturating_sub(1);
+    let text = match buffer.lines.get(cursor.line.min(last_line)) {
+        Some(line) => line.text(),
+        None => "",
+    };
+    if cursor.line > last_line {
+        cursor.line = last_line;
+        cursor.index = text.len();
+    }
+    cursor.index = cursor.index.min(text.len());
+    while !text.is_char_boundary(cursor.index) {
+        cursor.index -= 1;
+    }
+    cursor
+}
+
+/// Check if a grapheme is an emoji sequence that displays as a single glyph
+fn is_emoji_sequence(grapheme: &str) -> bool {
+    grapheme.chars().any(|c| {
+        matches!(
+            c,
+            // Zero width joiner, emoji presentation selector, and combining keycap
+            '\u{200D}' | '\u{FE0F}' | '\u{20E3}'
+            // Regional indicators for flags
+            | '\u{1F1E6}'..='\u{1F1FF}'
+            // Skin tone modifiers
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            // Tags for subdivision flags
+            | '\u{E0020}'..='\u{E007F}'
+        )
+    })
+}
+
 fn cursor_position(cursor: &Cursor, run: &LayoutRun) -> Option<(i32, i32)> {
     let (cursor_glyph, cursor_glyph_offset) = cursor_glyph_opt(cursor, run)?;
     let x = match run.glyphs.get(cursor_glyph) {
@@ -93,6 +164,73 @@ fn cursor_position(cursor: &Cursor, run: &LayoutRun) -> Option<(i32, i32)> {
     Some((x, run.line_top as i32))
 }
 
+/// Find the visual line index and X position of `cursor`, see [`Edit::cursor_visual_position`]
+pub(crate) fn visual_cursor_position(buffer: &Buffer, cursor: &Cursor) -> Option<(usize, i32)> {
+    let mut visual_line = 0;
+    for line in buffer.lines.get(..cursor.line)? {
+        visual_line += line.layout_opt().as_ref()?.len();
+    }
+
+    let line = buffer.lines.get(cursor.line)?;
+    let rtl = line.shape_opt().as_ref()?.rtl;
+    for (layout_i, layout_line) in line.layout_opt().as_ref()?.iter().enumerate() {
+        let run = LayoutRun {
+            line_i: cursor.line,
+            text: line.text(),
+            rtl,
+            glyphs: &layout_line.glyphs,
+            wrap_indicator_opt: None,
+            line_y: 0.0,
+            line_top: 0.0,
+            line_height: 0.0,
+            line_w: layout_line.w,
+            layout_i,
+            markers: line.markers(),
+            line_id: line.id(),
+        };
+        if let Some((x, _)) = cursor_position(cursor, &run) {
+            return Some((visual_line + layout_i, x));
+        }
+    }
+    None
+}
+
+/// Width of the caret, one pixel per unit of display scale
+fn caret_width(buffer: &Buffer) -> u32 {
+    math::roundf(buffer.scale()).max(1.0) as u32
+}
+
+fn cursor_rect(
+    cursor: &Cursor,
+    run: &LayoutRun,
+    shape: CursorShape,
+    scale: f32,
+    block_width: f32,
+) -> Option<(i32, i32, u32, u32)> {
+    let (x, y) = cursor_position(cursor, run)?;
+    let h = run.line_height as u32;
+    let line_width = |width: f32| math::roundf(width * scale).max(1.0) as u32;
+    let thickness = match shape {
+        CursorShape::Bar(width) => return Some((x, y, line_width(width), h)),
+        CursorShape::Block => h,
+        CursorShape::Underline(thickness) => line_width(thickness).min(h),
+    };
+
+    // Cover the grapheme at the cursor, which may be a wide glyph, or an empty cell at the end
+    // of the line
+    let part_opt = run
+        .glyphs
+        .iter()
+        .flat_map(|glyph| glyph.sub_clusters(run.text))
+        .find(|part| part.start == cursor.index);
+    let (x, w) = match part_opt {
+        Some(part) => (part.x as i32, part.w as u32),
+        None if run.rtl => (x - block_width as i32, block_width as u32),
+        None => (x, block_width as u32),
+    };
+    Some((x, y + (h - thickness) as i32, w, thickness))
+}
+
 impl<'buffer> Editor<'buffer> {
     /// Create a new [`Editor`] with the provided [`Buffer`]
     pub fn new(buffer: impl Into<BufferRef<'buffer>>) -> Self {
@@ -103,12 +241,286 @@ impl<'buffer> Editor<'buffer> {
             selection: Selection::None,
             cursor_moved: false,
             auto_indent: false,
+            overtype: false,
+            backspace_mode: BackspaceMode::default(),
             change: None,
+            batch_depth: 0,
+            batch_redraw: false,
+            observers: Observers::default(),
+            undo_history: UndoHistory::default(),
+            edit_count: 0,
+            line_highlight_opt: None,
+            cursor_shape: CursorShape::default(),
+        }
+    }
+
+    /// Get the shape of the caret, see [`Editor::set_cursor_shape`]
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.cursor_shape
+    }
+
+    /// Set the shape of the caret, used by [`Editor::draw`] and [`Edit::cursor_rect`]
+    ///
+    /// In overtype mode the caret is always a block covering the grapheme that will be
+    /// replaced.
+    pub fn set_cursor_shape(&mut self, cursor_shape: CursorShape) {
+        if self.cursor_shape != cursor_shape {
+            self.cursor_shape = cursor_shape;
+            self.request_redraw();
+        }
+    }
+
+    /// Shape of the caret, taking overtype mode into account
+    fn effective_cursor_shape(&self) -> CursorShape {
+        if self.overtype {
+            CursorShape::Block
+        } else {
+            self.cursor_shape
+        }
+    }
+
+    /// Get the color of the current line highlight, see [`Editor::set_line_highlight`]
+    pub fn line_highlight(&self) -> Option<Color> {
+        self.line_highlight_opt
+    }
+
+    /// Set the color of a full width background drawn behind the visual line of the cursor, or
+    /// None to disable it
+    ///
+    /// When a line is wrapped, only the visual line containing the cursor is highlighted.
+    pub fn set_line_highlight(&mut self, color_opt: Option<Color>) {
+        if self.line_highlight_opt != color_opt {
+            self.line_highlight_opt = color_opt;
+            self.request_redraw();
+        }
+    }
+
+    /// Call `f` with every change to the text, such as an insertion or deletion, right after it
+    /// is made
+    ///
+    /// Each change has a single item. Changes applied with [`Edit::apply_change`], like undo and
+    /// redo, are reported item by item as well.
+    pub fn on_change<F: FnMut(&Change) + Send + Sync + 'static>(&mut self, f: F) {
+        self.observers.change.push(Box::new(f));
+    }
+
+    /// Call `f` with the new cursor when an action or [`Edit::set_cursor`] moves the cursor
+    pub fn on_cursor_move<F: FnMut(Cursor) + Send + Sync + 'static>(&mut self, f: F) {
+        self.observers.cursor = self.cursor;
+        self.observers.cursor_move.push(Box::new(f));
+    }
+
+    /// Call `f` with the new scroll position when an action or [`Edit::shape_as_needed`]
+    /// scrolls the buffer
+    pub fn on_scroll<F: FnMut(Scroll) + Send + Sync + 'static>(&mut self, f: F) {
+        self.observers.scroll_position = self.with_buffer(|buffer| buffer.scroll());
+        self.observers.scroll.push(Box::new(f));
+    }
+
+    /// Remove all callbacks registered with [`Editor::on_change`], [`Editor::on_cursor_move`],
+    /// and [`Editor::on_scroll`]
+    pub fn clear_observers(&mut self) {
+        self.observers = Observers::default();
+    }
+
+    /// Report a change item to observers and add it to the current change
+    fn record_change(&mut self, change_item: ChangeItem) {
+        self.undo_history.record(&change_item);
+        self.edit_count = self.edit_count.wrapping_add(1);
+
+        if !self.observers.change.is_empty() {
+            let change = Change {
+                items: vec![change_item.clone()],
+            };
+            for f in self.observers.change.iter_mut() {
+                f(&change);
+            }
+        }
+
+        if let Some(ref mut change) = self.change {
+            change.items.push(change_item);
+        }
+    }
+
+    /// Report the cursor to observers if it moved since it was last reported
+    fn notify_cursor_move(&mut self) {
+        if self.observers.cursor != self.cursor {
+            self.observers.cursor = self.cursor;
+            for f in self.observers.cursor_move.iter_mut() {
+                f(self.cursor);
+            }
+        }
+    }
+
+    /// Report the scroll position to observers if it changed since it was last reported
+    fn notify_scroll(&mut self) {
+        if self.observers.scroll.is_empty() {
+            return;
+        }
+        let scroll = self.with_buffer(|buffer| buffer.scroll());
+        if self.observers.scroll_position != scroll {
+            self.observers.scroll_position = scroll;
+            for f in self.observers.scroll.iter_mut() {
+                f(scroll);
+            }
+        }
+    }
+
+    /// Undo the last step of edits, returning false if there is nothing to undo or if a change
+    /// is being collected with [`Edit::start_change`] and has items
+    ///
+    /// Consecutive typing is merged into one step following [`Editor::undo_grouping`], and
+    /// edits between [`Editor::begin_undo_group`] and [`Editor::end_undo_group`] are one step.
+    /// Undoing is reported to [`Editor::on_change`] callbacks and collected changes like other
+    /// edits.
+    pub fn undo(&mut self) -> bool {
+        if self.has_pending_change() {
+            return false;
+        }
+        match self.undo_history.take_undo() {
+            Some(change) => {
+                self.apply_history_change(&change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone step of edits, returning false if there is nothing to redo or if a
+    /// change is being collected, see [`Editor::undo`]
+    pub fn redo(&mut self) -> bool {
+        if self.has_pending_change() {
+            return false;
+        }
+        match self.undo_history.take_redo() {
+            Some(change) => {
+                self.apply_history_change(&change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// True if there are edits to undo
+    pub fn can_undo(&self) -> bool {
+        self.undo_history.can_undo()
+    }
+
+    /// True if there are undone edits to redo
+    pub fn can_redo(&self) -> bool {
+        self.undo_history.can_redo()
+    }
+
+    /// Forget all edits to undo and redo, for example after loading a file
+    pub fn clear_undo_history(&mut self) {
+        self.undo_history.clear();
+    }
+
+    /// True if edits are recorded for undo, see [`Editor::set_undo_enabled`]
+    pub fn undo_enabled(&self) -> bool {
+        self.undo_history.enabled()
+    }
+
+    /// Start or stop recording edits for undo, for example when a wrapper like `ViEditor` keeps
+    /// its own history
+    ///
+    /// Stopping also forgets all edits to undo and redo.
+    pub fn set_undo_enabled(&mut self, enabled: bool) {
+        self.undo_history.set_enabled(enabled);
+    }
+
+    /// Get the largest number of undo steps that are kept, see [`Editor::set_undo_limit`]
+    pub fn undo_limit(&self) -> Option<usize> {
+        self.undo_history.limit()
+    }
+
+    /// Set the largest number of undo steps that are kept, dropping the oldest steps when there
+    /// are more, or None to keep every step
+    ///
+    /// The default limit is 1000 steps.
+    pub fn set_undo_limit(&mut self, limit_opt: Option<usize>) {
+        self.undo_history.set_limit(limit_opt);
+    }
+
+    /// Get the rules for merging consecutive edits into one undo step
+    pub fn undo_grouping(&self) -> UndoGrouping {
+        self.undo_history.grouping()
+    }
+
+    /// Set the rules for merging consecutive edits into one undo step
+    pub fn set_undo_grouping(&mut self, grouping: UndoGrouping) {
+        self.undo_history.set_grouping(grouping);
+    }
+
+    /// Start a group of edits that is undone as one step, such as pasting and formatting or
+    /// replacing every match of a search
+    ///
+    /// Groups may be nested, and end when the outermost group ends with
+    /// [`Editor::end_undo_group`]. Edits before and after the group are never merged into it.
+    pub fn begin_undo_group(&mut self) {
+        self.undo_history.begin_group(true);
+    }
+
+    /// End a group of edits started with [`Editor::begin_undo_group`]
+    pub fn end_undo_group(&mut self) {
+        self.undo_history.end_group();
+    }
+
+    /// True if a change is being collected and has items, which prevents applying changes
+    fn has_pending_change(&self) -> bool {
+        self.change
+            .as_ref()
+            .map_or(false, |change| !change.items.is_empty())
+    }
+
+    /// Apply a change from the undo history without recording it again
+    fn apply_history_change(&mut self, change: &Change) {
+        self.undo_history.set_applying(true);
+        self.apply_change(change);
+        self.undo_history.set_applying(false);
+        self.selection = Selection::None;
+        self.request_redraw();
+        self.notify_cursor_move();
+    }
+
+    /// Run several edits as one batch, deferring layout and the redraw flag until the batch ends
+    ///
+    /// Actions inside the batch only shape and lay out what they need to find cursor positions.
+    /// When the outermost batch ends, the buffer redraw flag is set if any action requested it,
+    /// and [`Edit::shape_as_needed`] is called once.
+    pub fn batch<F, T>(&mut self, font_system: &mut FontSystem, f: F) -> T
+    where
+        F: FnOnce(&mut Self, &mut FontSystem) -> T,
+    {
+        self.batch_depth += 1;
+        let result = f(self, font_system);
+        self.batch_depth -= 1;
+        if self.batch_depth == 0 {
+            if mem::take(&mut self.batch_redraw) {
+                self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+            }
+            self.shape_as_needed(font_system, false);
+        }
+        result
+    }
+
+    /// True if called from inside of [`Editor::batch`]
+    pub fn is_batching(&self) -> bool {
+        self.batch_depth > 0
+    }
+
+    /// Set the buffer redraw flag, or defer it to the end of the current batch
+    fn request_redraw(&mut self) {
+        if self.batch_depth > 0 {
+            self.batch_redraw = true;
+        } else {
+            self.with_buffer_mut(|buffer| buffer.set_redraw(true));
         }
     }
 
     /// Draw the editor
     #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw<F>(
         &self,
         font_system: &mut FontSystem,
@@ -117,60 +529,198 @@ impl<'buffer> Editor<'buffer> {
         cursor_color: Color,
         selection_color: Color,
         selected_text_color: Color,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_with_palette(
+            font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            &[],
+            f,
+        );
+    }
+
+    /// Draw the editor, resolving palette color indexes set with [`Attrs::palette_color`]
+    /// from `palette`
+    ///
+    /// [`Attrs::palette_color`]: crate::Attrs::palette_color
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with_palette<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_clipped(
+            font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            None,
+            palette,
+            f,
+        );
+    }
+
+    /// Draw the editor like [`Self::draw_with_palette`], skipping runs outside of a clip
+    /// rectangle
+    ///
+    /// If `clip_opt` is an `(x, y, w, h)` rectangle, only runs intersecting it are drawn, see
+    /// [`Buffer::draw_clipped`](crate::Buffer::draw_clipped).
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_clipped<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.draw_with_decorations(
+            font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            clip_opt,
+            palette,
+            |_, _, _, _, _| {},
+            f,
+        );
+    }
+
+    /// Draw the editor like [`Self::draw_with_palette`], calling `decoration_f` with the
+    /// identifier and `(x, y, w, h)` rectangle of each span with an inline decoration set with
+    /// [`Attrs::decoration`]
+    ///
+    /// Decorations are called back before the selection, cursor, and text of their run, so they
+    /// can be drawn behind them. A span wrapped over several lines has one rectangle per line.
+    ///
+    /// [`Attrs::decoration`]: crate::Attrs::decoration
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with_decorations<D, F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
+        mut decoration_f: D,
         mut f: F,
     ) where
+        D: FnMut(u32, i32, i32, u32, u32),
         F: FnMut(i32, i32, u32, u32, Color),
     {
         let selection_bounds = self.selection_bounds();
         self.with_buffer(|buffer| {
             for run in buffer.layout_runs() {
+                if let Some(clip) = clip_opt {
+                    if !run.intersects(clip) {
+                        continue;
+                    }
+                }
+
                 let line_i = run.line_i;
                 let line_y = run.line_y;
                 let line_top = run.line_top;
                 let line_height = run.line_height;
+                let attrs_list = buffer.lines[line_i].attrs_list();
+
+                // Highlight the visual line of the cursor, behind everything else
+                if let Some(color) = self.line_highlight_opt {
+                    if cursor_position(&self.cursor, &run).is_some() {
+                        let width = buffer.size().0.unwrap_or(run.line_w);
+                        f(0, line_top as i32, width as u32, line_height as u32, color);
+                    }
+                }
+
+                for (id, x, w) in run.decoration_spans() {
+                    decoration_f(id, x as i32, line_top as i32, w as u32, line_height as u32);
+                }
 
                 // Highlight selection
                 if let Some((start, end)) = selection_bounds {
                     if line_i >= start.line && line_i <= end.line {
                         let mut range_opt = None;
                         for glyph in run.glyphs.iter() {
-                            // Guess x offset based on characters
-                            let cluster = &run.text[glyph.start..glyph.end];
-                            let total = cluster.grapheme_indices(true).count();
-                            let mut c_x = glyph.x;
-                            let c_w = glyph.w / total as f32;
-                            for (i, c) in cluster.grapheme_indices(true) {
-                                let c_start = glyph.start + i;
-                                let c_end = glyph.start + i + c.len();
-                                if (start.line != line_i || c_end > start.index)
-                                    && (end.line != line_i || c_start < end.index)
+                            // Select graphemes inside of ligatures
+                            for part in glyph.sub_clusters(run.text) {
+                                if (start.line != line_i || part.end > start.index)
+                                    && (end.line != line_i || part.start < end.index)
                                 {
+                                    // Spans may hint a different selection color
+                                    let color = attrs_list
+                                        .get_span(part.start)
+                                        .selection_color_opt
+                                        .unwrap_or(selection_color);
                                     range_opt = match range_opt.take() {
-                                        Some((min, max)) => Some((
-                                            cmp::min(min, c_x as i32),
-                                            cmp::max(max, (c_x + c_w) as i32),
-                                        )),
-                                        None => Some((c_x as i32, (c_x + c_w) as i32)),
+                                        Some((min, max, range_color)) if range_color == color => {
+                                            Some((
+                                                cmp::min(min, part.x as i32),
+                                                cmp::max(max, (part.x + part.w) as i32),
+                                                color,
+                                            ))
+                                        }
+                                        Some((min, max, range_color)) => {
+                                            f(
+                                                min,
+                                                line_top as i32,
+                                                cmp::max(0, max - min) as u32,
+                                                line_height as u32,
+                                                range_color,
+                                            );
+                                            Some((part.x as i32, (part.x + part.w) as i32, color))
+                                        }
+                                        None => {
+                                            Some((part.x as i32, (part.x + part.w) as i32, color))
+                                        }
                                     };
-                                } else if let Some((min, max)) = range_opt.take() {
+                                } else if let Some((min, max, color)) = range_opt.take() {
                                     f(
                                         min,
                                         line_top as i32,
                                         cmp::max(0, max - min) as u32,
                                         line_height as u32,
-                                        selection_color,
+                                        color,
                                     );
                                 }
-                                c_x += c_w;
                             }
                         }
 
                         if run.glyphs.is_empty() && end.line > line_i {
                             // Highlight all of internal empty lines
-                            range_opt = Some((0, buffer.size().0.unwrap_or(0.0) as i32));
+                            range_opt =
+                                Some((0, buffer.size().0.unwrap_or(0.0) as i32, selection_color));
                         }
 
-                        if let Some((mut min, mut max)) = range_opt.take() {
+                        if let Some((mut min, mut max, color)) = range_opt.take() {
                             if end.line > line_i {
                                 // Draw to end of line
                                 if run.rtl {
@@ -184,21 +734,36 @@ impl<'buffer> Editor<'buffer> {
                                 line_top as i32,
                                 cmp::max(0, max - min) as u32,
                                 line_height as u32,
-                                selection_color,
+                                color,
                             );
                         }
                     }
                 }
 
-                // Draw cursor
-                if let Some((x, y)) = cursor_position(&self.cursor, &run) {
-                    f(x, y, 1, line_height as u32, cursor_color);
+                // Draw cursor, as a block behind the glyph to be replaced in overtype mode
+                let block_width = buffer.scaled_metrics().font_size / 2.0;
+                if let Some((x, y, w, h)) = cursor_rect(
+                    &self.cursor,
+                    &run,
+                    self.effective_cursor_shape(),
+                    buffer.scale(),
+                    block_width,
+                ) {
+                    // Use the hints of the text before the cursor, as that is where typed text
+                    // takes its attributes from
+                    let attrs = attrs_list.get_span(self.cursor.index.saturating_sub(1));
+                    let color = if self.overtype {
+                        attrs.selection_color_opt.unwrap_or(selection_color)
+                    } else {
+                        attrs.cursor_color_opt.unwrap_or(cursor_color)
+                    };
+                    f(x, y, w, h, color);
                 }
 
-                for glyph in run.glyphs.iter() {
-                    let physical_glyph = glyph.physical((0., 0.), 1.0);
+                for glyph in run.glyphs.iter().chain(run.wrap_indicator_opt.iter()) {
+                    let physical_glyph = buffer.physical_glyph(&run, glyph, (0., 0.), 1.0);
 
-                    let mut glyph_color = match glyph.color_opt {
+                    let mut glyph_color = match glyph.color(palette) {
                         Some(some) => some,
                         None => text_color,
                     };
@@ -248,10 +813,12 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
     }
 
     fn set_cursor(&mut self, cursor: Cursor) {
+        let cursor = self.with_buffer(|buffer| clamp_cursor(buffer, cursor));
         if self.cursor != cursor {
             self.cursor = cursor;
             self.cursor_moved = true;
-            self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+            self.request_redraw();
+            self.notify_cursor_move();
         }
     }
 
@@ -260,9 +827,15 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
     }
 
     fn set_selection(&mut self, selection: Selection) {
+        let selection = self.with_buffer(|buffer| match selection {
+            Selection::None => Selection::None,
+            Selection::Normal(cursor) => Selection::Normal(clamp_cursor(buffer, cursor)),
+            Selection::Line(cursor) => Selection::Line(clamp_cursor(buffer, cursor)),
+            Selection::Word(cursor) => Selection::Word(clamp_cursor(buffer, cursor)),
+        });
         if self.selection != selection {
             self.selection = selection;
-            self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+            self.request_redraw();
         }
     }
 
@@ -274,6 +847,25 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         self.auto_indent = auto_indent;
     }
 
+    fn overtype(&self) -> bool {
+        self.overtype
+    }
+
+    fn set_overtype(&mut self, overtype: bool) {
+        if self.overtype != overtype {
+            self.overtype = overtype;
+            self.request_redraw();
+        }
+    }
+
+    fn backspace_mode(&self) -> BackspaceMode {
+        self.backspace_mode
+    }
+
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        self.backspace_mode = backspace_mode;
+    }
+
     fn tab_width(&self) -> u16 {
         self.with_buffer(|buffer| buffer.tab_width())
     }
@@ -290,6 +882,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         } else {
             self.with_buffer_mut(|buffer| buffer.shape_until_scroll(font_system, prune));
         }
+        self.notify_scroll();
     }
 
     fn delete_range(&mut self, start: Cursor, end: Cursor) {
@@ -341,6 +934,8 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 }
             }
 
+            buffer.shift_anchors_delete(start, end);
+
             ChangeItem {
                 start,
                 end,
@@ -349,9 +944,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             }
         });
 
-        if let Some(ref mut change) = self.change {
-            change.items.push(change_item);
-        }
+        self.record_change(change_item);
     }
 
     fn insert_at(
@@ -461,6 +1054,8 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             // Append the text after insertion
             cursor.index = buffer.lines[cursor.line].text().len() - after_len;
 
+            buffer.shift_anchors_insert(start, cursor);
+
             ChangeItem {
                 start,
                 end: cursor,
@@ -469,9 +1064,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             }
         });
 
-        if let Some(ref mut change) = self.change {
-            change.items.push(change_item);
-        }
+        self.record_change(change_item);
 
         cursor
     }
@@ -525,6 +1118,15 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         true
     }
 
+    fn insert_string(&mut self, data: &str, attrs_list: Option<AttrsList>) {
+        // Replacing the selection is undone together with the insertion
+        self.undo_history.begin_group(false);
+        self.delete_selection();
+        let new_cursor = self.insert_at(self.cursor, data, attrs_list);
+        self.set_cursor(new_cursor);
+        self.undo_history.end_group();
+    }
+
     fn apply_change(&mut self, change: &Change) -> bool {
         // Cannot apply changes if there is a pending change
         match self.change.take() {
@@ -561,24 +1163,48 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         self.change.take()
     }
 
-    fn action(&mut self, font_system: &mut FontSystem, action: Action) {
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult {
         let old_cursor = self.cursor;
+        let old_state = ActionState::new(self);
+        let old_edit_count = self.edit_count;
+        // Edits of one action are undone together
+        self.undo_history.begin_group(false);
 
         match action {
             Action::Motion(motion) => {
                 let cursor = self.cursor;
                 let cursor_x_opt = self.cursor_x_opt;
                 if let Some((new_cursor, new_cursor_x_opt)) = self.with_buffer_mut(|buffer| {
-                    buffer.cursor_motion(font_system, cursor, cursor_x_opt, motion)
+                    let font_size = buffer.scaled_metrics().font_size;
+                    let cursor_x_opt =
+                        cursor_x_opt.map(|x| PreferredX(math::roundf(x * font_size) as i32));
+                    buffer
+                        .cursor_motion(font_system, cursor, cursor_x_opt, motion)
+                        .map(|(new_cursor, new_cursor_x_opt)| {
+                            (
+                                new_cursor,
+                                new_cursor_x_opt.map(|PreferredX(x)| x as f32 / font_size),
+                            )
+                        })
                 }) {
                     self.cursor = new_cursor;
                     self.cursor_x_opt = new_cursor_x_opt;
                 }
             }
+            Action::Select(motion) => {
+                if self.selection == Selection::None {
+                    self.selection = Selection::Normal(self.cursor);
+                }
+                self.action(font_system, Action::Motion(motion));
+                self.request_redraw();
+            }
+            Action::SmartHome => {
+                self.action(font_system, Action::Motion(Motion::SmartHome));
+            }
             Action::Escape => {
                 match self.selection {
                     Selection::None => {}
-                    _ => self.with_buffer_mut(|buffer| buffer.set_redraw(true)),
+                    _ => self.request_redraw(),
                 }
                 self.selection = Selection::None;
             }
@@ -589,11 +1215,28 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 } else if character == '\n' {
                     self.action(font_system, Action::Enter);
                 } else {
+                    if self.overtype && !self.delete_selection() {
+                        // Replace the grapheme after the cursor, if not at the end of the line
+                        let cursor = self.cursor;
+                        let end_opt = self.with_buffer(|buffer| {
+                            let text = buffer.lines[cursor.line].text();
+                            text[cursor.index..]
+                                .graphemes(true)
+                                .next()
+                                .map(|grapheme| cursor.index + grapheme.len())
+                        });
+                        if let Some(end) = end_opt {
+                            self.delete_range(cursor, Cursor::new(cursor.line, end));
+                        }
+                    }
                     let mut str_buf = [0u8; 8];
                     let str_ref = character.encode_utf8(&mut str_buf);
                     self.insert_string(str_ref, None);
                 }
             }
+            Action::ToggleOvertype => {
+                self.set_overtype(!self.overtype);
+            }
             Action::Enter => {
                 //TODO: what about indenting more after opening brackets or parentheses?
                 if self.auto_indent {
@@ -614,11 +1257,14 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                     self.insert_string("\n", None);
                 }
 
-                // Ensure line is properly shaped and laid out (for potential immediate commands)
-                let line_i = self.cursor.line;
-                self.with_buffer_mut(|buffer| {
-                    buffer.line_layout(font_system, line_i);
-                });
+                // Ensure line is properly shaped and laid out (for potential immediate commands),
+                // batches do this when they end
+                if self.batch_depth == 0 {
+                    let line_i = self.cursor.line;
+                    self.with_buffer_mut(|buffer| {
+                        buffer.line_layout(font_system, line_i);
+                    });
+                }
             }
             Action::Backspace => {
                 if self.delete_selection() {
@@ -628,12 +1274,17 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                     let end = self.cursor;
 
                     if self.cursor.index > 0 {
-                        // Move cursor to previous character index
+                        // Move cursor to previous grapheme or character index
                         self.cursor.index = self.with_buffer(|buffer| {
-                            buffer.lines[self.cursor.line].text()[..self.cursor.index]
-                                .char_indices()
-                                .next_back()
-                                .map_or(0, |(i, _)| i)
+                            let text = &buffer.lines[self.cursor.line].text()[..self.cursor.index];
+                            let (grapheme_i, grapheme) =
+                                text.grapheme_indices(true).next_back().unwrap_or((0, ""));
+                            match self.backspace_mode {
+                                BackspaceMode::Codepoint if !is_emoji_sequence(grapheme) => {
+                                    text.char_indices().next_back().map_or(0, |(i, _)| i)
+                                }
+                                _ => grapheme_i,
+                            }
                         });
                     } else if self.cursor.line > 0 {
                         // Move cursor to previous line
@@ -743,7 +1394,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                     }
 
                     // Request redraw
-                    self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                    self.request_redraw();
                 }
             }
             Action::Unindent => {
@@ -786,9 +1437,18 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                         Cursor::new(line_i, after_whitespace),
                     );
 
+                    // Move an index after the deleted indent back, or to its start if inside of it
+                    let unindent = |index: usize| {
+                        if index >= after_whitespace {
+                            index - (after_whitespace - last_indent)
+                        } else {
+                            index.min(last_indent)
+                        }
+                    };
+
                     // Adjust cursor
-                    if self.cursor.line == line_i && self.cursor.index > last_indent {
-                        self.cursor.index -= after_whitespace - last_indent;
+                    if self.cursor.line == line_i {
+                        self.cursor.index = unindent(self.cursor.index);
                     }
 
                     // Adjust selection
@@ -797,14 +1457,14 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                         Selection::Normal(ref mut select)
                         | Selection::Line(ref mut select)
                         | Selection::Word(ref mut select) => {
-                            if select.line == line_i && select.index > last_indent {
-                                select.index -= after_whitespace - last_indent;
+                            if select.line == line_i {
+                                select.index = unindent(select.index);
                             }
                         }
                     }
 
                     // Request redraw
-                    self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                    self.request_redraw();
                 }
             }
             Action::Click { x, y } => {
@@ -814,7 +1474,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 {
                     if new_cursor != self.cursor {
                         self.cursor = new_cursor;
-                        self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                        self.request_redraw();
                     }
                 }
             }
@@ -825,10 +1485,20 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 {
                     if new_cursor != self.cursor {
                         self.cursor = new_cursor;
-                        self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                        self.request_redraw();
                     }
                     self.selection = Selection::Word(self.cursor);
-                    self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                    self.request_redraw();
+                }
+            }
+            Action::LongPress { x, y } => {
+                self.set_selection(Selection::None);
+
+                if let Some(new_cursor) = self.with_buffer(|buffer| buffer.hit(x as f32, y as f32))
+                {
+                    self.cursor = new_cursor;
+                    self.selection = Selection::Word(self.cursor);
+                    self.request_redraw();
                 }
             }
             Action::TripleClick { x, y } => {
@@ -840,28 +1510,60 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                         self.cursor = new_cursor;
                     }
                     self.selection = Selection::Line(self.cursor);
-                    self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                    self.request_redraw();
                 }
             }
             Action::Drag { x, y } => {
                 if self.selection == Selection::None {
                     self.selection = Selection::Normal(self.cursor);
-                    self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                    self.request_redraw();
                 }
 
                 if let Some(new_cursor) = self.with_buffer(|buffer| buffer.hit(x as f32, y as f32))
                 {
                     if new_cursor != self.cursor {
                         self.cursor = new_cursor;
-                        self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+                        self.request_redraw();
                     }
                 }
             }
+            Action::DragHandle { which, x, y } => {
+                // The other handle stays in place as the anchor
+                let anchor = match (self.selection_bounds(), which) {
+                    (Some((_, end)), SelectionHandle::Start) => end,
+                    (Some((start, _)), SelectionHandle::End) => start,
+                    (None, _) => self.cursor,
+                };
+
+                let new_cursor_opt = self.with_buffer(|buffer| {
+                    let mut cursor = buffer.hit(x as f32, y as f32)?;
+                    // Snap to the start or end of the word under the handle
+                    let word_opt = buffer
+                        .line_words(cursor.line)
+                        .into_iter()
+                        .find(|word| word.start < cursor.index && cursor.index < word.end);
+                    if let Some(word) = word_opt {
+                        let start_side = match anchor.line.cmp(&cursor.line) {
+                            cmp::Ordering::Less => false,
+                            cmp::Ordering::Greater => true,
+                            cmp::Ordering::Equal => anchor.index > cursor.index,
+                        };
+                        cursor.index = if start_side { word.start } else { word.end };
+                    }
+                    Some(cursor)
+                });
+
+                if let Some(new_cursor) = new_cursor_opt {
+                    self.selection = Selection::Normal(anchor);
+                    self.cursor = new_cursor;
+                    self.request_redraw();
+                }
+            }
             Action::Scroll { lines } => {
                 self.with_buffer_mut(|buffer| {
                     let mut scroll = buffer.scroll();
                     //TODO: align to layout lines
-                    scroll.vertical += lines as f32 * buffer.metrics().line_height;
+                    scroll.vertical += lines as f32 * buffer.scaled_metrics().line_height;
                     buffer.set_scroll(scroll);
                 });
             }
@@ -869,7 +1571,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
 
         if old_cursor != self.cursor {
             self.cursor_moved = true;
-            self.with_buffer_mut(|buffer| buffer.set_redraw(true));
+            self.request_redraw();
 
             /*TODO
             if let Some(glyph) = run.glyphs.get(new_cursor_glyph) {
@@ -887,6 +1589,14 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             }
             */
         }
+
+        self.undo_history.end_group();
+        self.notify_cursor_move();
+        self.notify_scroll();
+
+        let mut result = old_state.result(self);
+        result.set(ActionResult::TEXT, self.edit_count != old_edit_count);
+        result
     }
 
     fn cursor_position(&self) -> Option<(i32, i32)> {
@@ -896,9 +1606,89 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 .find_map(|run| cursor_position(&self.cursor, &run))
         })
     }
+
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        let shape = self.effective_cursor_shape();
+        self.with_buffer(|buffer| {
+            let block_width = buffer.scaled_metrics().font_size / 2.0;
+            buffer
+                .layout_runs()
+                .find_map(|run| cursor_rect(&self.cursor, &run, shape, buffer.scale(), block_width))
+        })
+    }
+
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        let (start, end) = match self.selection_bounds() {
+            Some(bounds) => bounds,
+            None => (self.cursor, self.cursor),
+        };
+        self.with_buffer(|buffer| {
+            buffer.layout_runs().find_map(|run| {
+                let (start_x, y) = cursor_position(&start, &run)?;
+                let h = run.line_height as u32;
+                match cursor_position(&end, &run) {
+                    Some((end_x, _)) if end != start => {
+                        let x = start_x.min(end_x);
+                        Some((x, y, (start_x.max(end_x) - x) as u32, h))
+                    }
+                    _ => Some((start_x, y, caret_width(buffer), h)),
+                }
+            })
+        })
+    }
+
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        let (start, end) = match self.selection_bounds() {
+            Some(bounds) => bounds,
+            None => return (None, None),
+        };
+        self.with_buffer(|buffer| {
+            let handle = |cursor: &Cursor| {
+                buffer.layout_runs().find_map(|run| {
+                    let (x, y) = cursor_position(cursor, &run)?;
+                    Some(HandlePosition {
+                        x,
+                        y,
+                        height: run.line_height as u32,
+                    })
+                })
+            };
+            (handle(&start), handle(&end))
+        })
+    }
+
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        let (start, end) = self.selection_bounds()?;
+        self.with_buffer(|buffer| {
+            buffer.layout_runs().find_map(|run| {
+                if run.line_i < start.line || run.line_i > end.line {
+                    return None;
+                }
+                let spans = run.range_spans(start, end);
+                let left = spans.iter().map(|&(x, _)| x).reduce(f32::min)?;
+                let right = spans.iter().map(|&(x, w)| x + w).reduce(f32::max)?;
+                Some((
+                    left as i32,
+                    run.line_top as i32,
+                    (right - left) as u32,
+                    run.line_height as u32,
+                ))
+            })
+        })
+    }
 }
 
 impl<'font_system, 'buffer> BorrowedWithFontSystem<'font_system, Editor<'buffer>> {
+    /// Run several edits as one batch, see [`Editor::batch`]
+    pub fn batch<F, T>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&mut BorrowedWithFontSystem<Editor<'buffer>>) -> T,
+    {
+        self.inner.batch(self.font_system, |editor, font_system| {
+            f(&mut editor.borrow_with(font_system))
+        })
+    }
+
     #[cfg(feature = "swash")]
     pub fn draw<F>(
         &mut self,
@@ -921,4 +1711,89 @@ impl<'font_system, 'buffer> BorrowedWithFontSystem<'font_system, Editor<'buffer>
             f,
         );
     }
+
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with_palette<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner.draw_with_palette(
+            self.font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            palette,
+            f,
+        );
+    }
+
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_clipped<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner.draw_clipped(
+            self.font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            clip_opt,
+            palette,
+            f,
+        );
+    }
+
+    #[cfg(feature = "swash")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with_decorations<D, F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        text_color: Color,
+        cursor_color: Color,
+        selection_color: Color,
+        selected_text_color: Color,
+        clip_opt: Option<(i32, i32, u32, u32)>,
+        palette: &[Color],
+        decoration_f: D,
+        f: F,
+    ) where
+        D: FnMut(u32, i32, i32, u32, u32),
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner.draw_with_decorations(
+            self.font_system,
+            cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            selected_text_color,
+            clip_opt,
+            palette,
+            decoration_f,
+            f,
+        );
+    }
 }
@@ -0,0 +1,203 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::{
+    Action, ActionResult, ActionState, AttrsList, BackspaceMode, BufferRef, Change, Cursor, Edit,
+    FontSystem, HandlePosition, Selection,
+};
+
+/// Hooks called by a [`HookedEditor`] around every [`Action`]
+///
+/// Both hooks have default implementations that do nothing, so only the needed ones have to be
+/// implemented.
+pub trait EditHooks<E> {
+    /// Called before `action` is performed on `editor`
+    ///
+    /// Return the action to perform, which may be a different one, or None to drop it.
+    fn pre_action(
+        &mut self,
+        editor: &mut E,
+        font_system: &mut FontSystem,
+        action: Action,
+    ) -> Option<Action> {
+        let _ = (editor, font_system);
+        Some(action)
+    }
+
+    /// Called after `action` was performed on `editor`
+    fn post_action(&mut self, editor: &mut E, font_system: &mut FontSystem, action: Action) {
+        let _ = (editor, font_system, action);
+    }
+}
+
+/// A wrapper of any [`Edit`] implementation that calls [`EditHooks`] around each action, for
+/// example to filter input, record macros, or send edits to collaborators
+///
+/// All other [`Edit`] methods are passed through to the wrapped editor, so this can be stacked
+/// with [`Editor`](crate::Editor), `SyntaxEditor`, `ViEditor`, or other wrappers.
+#[derive(Debug)]
+pub struct HookedEditor<E, H> {
+    editor: E,
+    hooks: H,
+}
+
+impl<E, H> HookedEditor<E, H> {
+    /// Create a new [`HookedEditor`] wrapping `editor`
+    pub fn new(editor: E, hooks: H) -> Self {
+        Self { editor, hooks }
+    }
+
+    /// Get the wrapped editor
+    pub fn editor(&self) -> &E {
+        &self.editor
+    }
+
+    /// Get the wrapped editor, mutably
+    ///
+    /// Actions performed directly on the wrapped editor do not call the hooks.
+    pub fn editor_mut(&mut self) -> &mut E {
+        &mut self.editor
+    }
+
+    /// Get the hooks
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    /// Get the hooks, mutably
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// Consume this wrapper, returning the editor and hooks
+    pub fn into_inner(self) -> (E, H) {
+        (self.editor, self.hooks)
+    }
+}
+
+impl<'buffer, E: Edit<'buffer>, H: EditHooks<E>> Edit<'buffer> for HookedEditor<E, H> {
+    fn buffer_ref(&self) -> &BufferRef<'buffer> {
+        self.editor.buffer_ref()
+    }
+
+    fn buffer_ref_mut(&mut self) -> &mut BufferRef<'buffer> {
+        self.editor.buffer_ref_mut()
+    }
+
+    fn cursor(&self) -> Cursor {
+        self.editor.cursor()
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.editor.set_cursor(cursor);
+    }
+
+    fn selection(&self) -> Selection {
+        self.editor.selection()
+    }
+
+    fn set_selection(&mut self, selection: Selection) {
+        self.editor.set_selection(selection);
+    }
+
+    fn auto_indent(&self) -> bool {
+        self.editor.auto_indent()
+    }
+
+    fn set_auto_indent(&mut self, auto_indent: bool) {
+        self.editor.set_auto_indent(auto_indent);
+    }
+
+    fn overtype(&self) -> bool {
+        self.editor.overtype()
+    }
+
+    fn set_overtype(&mut self, overtype: bool) {
+        self.editor.set_overtype(overtype);
+    }
+
+    fn backspace_mode(&self) -> BackspaceMode {
+        self.editor.backspace_mode()
+    }
+
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        self.editor.set_backspace_mode(backspace_mode);
+    }
+
+    fn tab_width(&self) -> u16 {
+        self.editor.tab_width()
+    }
+
+    fn set_tab_width(&mut self, font_system: &mut FontSystem, tab_width: u16) {
+        self.editor.set_tab_width(font_system, tab_width);
+    }
+
+    fn shape_as_needed(&mut self, font_system: &mut FontSystem, prune: bool) {
+        self.editor.shape_as_needed(font_system, prune);
+    }
+
+    fn delete_range(&mut self, start: Cursor, end: Cursor) {
+        self.editor.delete_range(start, end);
+    }
+
+    fn insert_at(&mut self, cursor: Cursor, data: &str, attrs_list: Option<AttrsList>) -> Cursor {
+        self.editor.insert_at(cursor, data, attrs_list)
+    }
+
+    fn copy_selection(&self) -> Option<String> {
+        self.editor.copy_selection()
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        self.editor.delete_selection()
+    }
+
+    fn insert_string(&mut self, data: &str, attrs_list: Option<AttrsList>) {
+        self.editor.insert_string(data, attrs_list);
+    }
+
+    fn apply_change(&mut self, change: &Change) -> bool {
+        self.editor.apply_change(change)
+    }
+
+    fn start_change(&mut self) {
+        self.editor.start_change();
+    }
+
+    fn finish_change(&mut self) -> Option<Change> {
+        self.editor.finish_change()
+    }
+
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult {
+        // Hooks may change the editor as well. Text edits made inside hooks are not reported in
+        // the returned ActionResult
+        let state = ActionState::new(&self.editor);
+        let mut result = ActionResult::empty();
+        if let Some(action) = self.hooks.pre_action(&mut self.editor, font_system, action) {
+            result = self.editor.action(font_system, action);
+            self.hooks
+                .post_action(&mut self.editor, font_system, action);
+        }
+        result | state.result(&self.editor)
+    }
+
+    fn cursor_position(&self) -> Option<(i32, i32)> {
+        self.editor.cursor_position()
+    }
+
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.cursor_rect()
+    }
+
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.ime_cursor_area()
+    }
+
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        self.editor.selection_handles()
+    }
+
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.context_menu_anchor()
+    }
+}
@@ -2,13 +2,21 @@ use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec};
 use core::cmp;
-use unicode_segmentation::UnicodeSegmentation;
 
-use crate::{AttrsList, BorrowedWithFontSystem, Buffer, Cursor, FontSystem, Motion};
+use crate::{AttrsList, BorrowedWithFontSystem, Buffer, Cursor, FontSystem, Motion, Scroll};
 
 pub use self::editor::*;
 mod editor;
 
+pub use self::hooked::*;
+mod hooked;
+
+pub use self::source::*;
+mod source;
+
+pub use self::undo::*;
+mod undo;
+
 #[cfg(feature = "syntect")]
 pub use self::syntect::*;
 #[cfg(feature = "syntect")]
@@ -24,6 +32,12 @@ mod vi;
 pub enum Action {
     /// Move the cursor with some motion
     Motion(Motion),
+    /// Move the cursor with some motion, extending the selection, or selecting from the cursor
+    /// if there is no selection (typically Shift with the motion key)
+    Select(Motion),
+    /// Move the cursor to the first non-whitespace character of the line, or to the start of
+    /// the line if it is already there (typically Home), see [`Motion::SmartHome`]
+    SmartHome,
     /// Escape, clears selection
     Escape,
     /// Insert character at cursor
@@ -58,10 +72,70 @@ pub enum Action {
         x: i32,
         y: i32,
     },
+    /// Touch long press at specified position, selects the word under it, see
+    /// [`Edit::context_menu_anchor`]
+    LongPress {
+        x: i32,
+        y: i32,
+    },
     /// Scroll specified number of lines
     Scroll {
         lines: i32,
     },
+    /// Switch between inserting text and overwriting the text after the cursor
+    ToggleOvertype,
+    /// Touch drag of a selection handle to specified position, snapping to word boundaries
+    DragHandle {
+        which: SelectionHandle,
+        x: i32,
+        y: i32,
+    },
+}
+
+bitflags::bitflags! {
+    /// What an [`Action`] changed, returned by [`Edit::action`] so that hosts know what to
+    /// invalidate without comparing the editor before and after
+    ///
+    /// An empty result means the action did nothing, such as moving left at the start of the text.
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct ActionResult: u8 {
+        /// Text was inserted or deleted
+        const TEXT = 1 << 0;
+        /// Cursor moved
+        const CURSOR = 1 << 1;
+        /// Selection changed
+        const SELECTION = 1 << 2;
+        /// Scroll position of the buffer changed
+        const SCROLL = 1 << 3;
+    }
+}
+
+/// The state of an editor that actions change besides its text, to find an [`ActionResult`]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ActionState {
+    cursor: Cursor,
+    selection: Selection,
+    scroll: Scroll,
+}
+
+impl ActionState {
+    pub fn new<'buffer, E: Edit<'buffer>>(editor: &E) -> Self {
+        Self {
+            cursor: editor.cursor(),
+            selection: editor.selection(),
+            scroll: editor.with_buffer(|buffer| buffer.scroll()),
+        }
+    }
+
+    /// Get what changed from this state to `editor`, except for text
+    pub fn result<'buffer, E: Edit<'buffer>>(&self, editor: &E) -> ActionResult {
+        let new = Self::new(editor);
+        let mut result = ActionResult::empty();
+        result.set(ActionResult::CURSOR, new.cursor != self.cursor);
+        result.set(ActionResult::SELECTION, new.selection != self.selection);
+        result.set(ActionResult::SCROLL, new.scroll != self.scroll);
+        result
+    }
 }
 
 #[derive(Debug)]
@@ -140,7 +214,87 @@ pub enum Selection {
     //TODO: Select block
 }
 
+impl Selection {
+    /// Move the anchor of the selection to the nearest valid position in `buffer`, see
+    /// [`Cursor::clamp_to`]
+    pub fn normalized(self, buffer: &Buffer) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Normal(cursor) => Self::Normal(cursor.clamp_to(buffer)),
+            Self::Line(cursor) => Self::Line(cursor.clamp_to(buffer)),
+            Self::Word(cursor) => Self::Word(cursor.clamp_to(buffer)),
+        }
+    }
+}
+
+/// How [`Action::Backspace`] deletes the text before the cursor
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BackspaceMode {
+    /// Delete the whole grapheme, like a base letter with all of its combining marks
+    #[default]
+    Grapheme,
+    /// Delete the last code point, so combining marks are removed one at a time
+    ///
+    /// Emoji sequences such as flags, skin tones, and ZWJ sequences like family emoji are still
+    /// deleted whole, as they display as a single glyph.
+    Codepoint,
+}
+
+/// Shape of the caret drawn by an [`Editor`], see [`Editor::set_cursor_shape`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorShape {
+    /// A vertical bar before the grapheme at the cursor, with a width in logical pixels
+    Bar(f32),
+    /// A block covering the grapheme at the cursor
+    Block,
+    /// A line at the bottom of the visual line, under the grapheme at the cursor, with a
+    /// thickness in logical pixels
+    Underline(f32),
+}
+
+impl Default for CursorShape {
+    fn default() -> Self {
+        Self::Bar(1.0)
+    }
+}
+
+/// One of the grab handles at the ends of a selection, see [`Edit::selection_handles`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SelectionHandle {
+    /// The handle at the start of the selection
+    Start,
+    /// The handle at the end of the selection
+    End,
+}
+
+/// Position of a [`SelectionHandle`], see [`Edit::selection_handles`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HandlePosition {
+    /// X position of the edge of the selection
+    pub x: i32,
+    /// Y position of the top of the visual line
+    pub y: i32,
+    /// Height of the visual line
+    pub height: u32,
+}
+
 /// A trait to allow easy replacements of [`Editor`], like `SyntaxEditor`
+///
+/// Editors are usually stacked: `ViEditor` wraps `SyntaxEditor`, which wraps [`Editor`]. A
+/// wrapper owns the editor below it and implements this trait by passing each method through,
+/// overriding only the ones it extends. The extension points are:
+///
+/// - [`Edit::action`], to handle input. Wrappers may translate an action into several actions
+///   on the wrapped editor, or use [`HookedEditor`] to run [`EditHooks`] before and after it.
+/// - [`Edit::insert_at`], [`Edit::delete_range`] and [`Edit::apply_change`], which every text
+///   change goes through, to keep state like syntax highlighting or a remote copy in sync.
+/// - [`Edit::start_change`] and [`Edit::finish_change`], to group changes, for example into
+///   undo history.
+/// - [`Edit::shape_as_needed`], to do extra work on the lines that are about to be shown.
+///
+/// Provided methods like [`Edit::insert_string`] and [`Edit::selection_bounds`] are built on
+/// the required ones. A wrapper around an editor that overrides them should pass them through
+/// as well.
 pub trait Edit<'buffer> {
     /// Mutably borrows `self` together with an [`FontSystem`] for more convenient methods
     fn borrow_with<'font_system>(
@@ -245,25 +399,20 @@ pub trait Edit<'buffer> {
 
                     // Move start to beginning of word
                     {
-                        let line = &buffer.lines[start.line];
-                        start.index = line
-                            .text()
-                            .unicode_word_indices()
-                            .rev()
-                            .map(|(i, _)| i)
-                            .find(|&i| i < start.index)
-                            .unwrap_or(0);
+                        start.index = crate::word::previous_word_start(
+                            &buffer.line_words(start.line),
+                            start.index,
+                        );
                     }
 
                     // Move end to end of word
                     {
-                        let line = &buffer.lines[end.line];
-                        end.index = line
-                            .text()
-                            .unicode_word_indices()
-                            .map(|(i, word)| i + word.len())
-                            .find(|&i| i > end.index)
-                            .unwrap_or(line.text().len());
+                        let len = buffer.lines[end.line].text().len();
+                        end.index = crate::word::next_word_end(
+                            &buffer.line_words(end.line),
+                            end.index,
+                            len,
+                        );
                     }
 
                     Some((start, end))
@@ -272,12 +421,50 @@ pub trait Edit<'buffer> {
         })
     }
 
+    /// Get the rectangles `(x, y, width, height)` of the current selection, merged per visual
+    /// line, see [`Buffer::selection_rects`]
+    fn selection_rects(&self) -> Vec<(f32, f32, f32, f32)> {
+        match self.selection_bounds() {
+            Some((start, end)) => self.with_buffer(|buffer| buffer.selection_rects(start, end)),
+            None => Vec::new(),
+        }
+    }
+
     /// Get the current automatic indentation setting
     fn auto_indent(&self) -> bool;
 
     /// Enable or disable automatic indentation
     fn set_auto_indent(&mut self, auto_indent: bool);
 
+    /// Get the current overtype setting
+    ///
+    /// Editors that do not support overtype always return false.
+    fn overtype(&self) -> bool {
+        false
+    }
+
+    /// Enable or disable overtype, where [`Action::Insert`] replaces the grapheme after the
+    /// cursor instead of inserting before it
+    ///
+    /// Editors that do not support overtype ignore this.
+    fn set_overtype(&mut self, overtype: bool) {
+        let _ = overtype;
+    }
+
+    /// Get the current [`BackspaceMode`]
+    ///
+    /// Editors that do not support changing it always return the default mode.
+    fn backspace_mode(&self) -> BackspaceMode {
+        BackspaceMode::default()
+    }
+
+    /// Set the [`BackspaceMode`] used by [`Action::Backspace`]
+    ///
+    /// Editors that do not support changing it ignore this.
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        let _ = backspace_mode;
+    }
+
     /// Get the current tab width
     fn tab_width(&self) -> u16;
 
@@ -317,11 +504,69 @@ pub trait Edit<'buffer> {
     /// Get completed change
     fn finish_change(&mut self) -> Option<Change>;
 
-    /// Perform an [Action] on the editor
-    fn action(&mut self, font_system: &mut FontSystem, action: Action);
+    /// Perform an [Action] on the editor, returning what it changed
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult;
 
     /// Get X and Y position of the top left corner of the cursor
     fn cursor_position(&self) -> Option<(i32, i32)>;
+
+    /// Get the `(x, y, w, h)` rectangle of the cursor, following the [`CursorShape`] of the
+    /// editor, or in overtype mode a block covering the grapheme that will be replaced
+    ///
+    /// A block or underline at the end of a line is half of the font size wide.
+    ///
+    /// The default is a one pixel wide bar at [`Edit::cursor_position`], as tall as the line
+    /// height of the buffer.
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        let (x, y) = self.cursor_position()?;
+        let line_height = self.with_buffer(|buffer| buffer.scaled_metrics().line_height);
+        Some((x, y, 1, line_height as u32))
+    }
+
+    /// Get the `(x, y, w, h)` rectangle where an input method should place its candidate window,
+    /// in the same scrolled coordinates as [`Edit::cursor_rect`]
+    ///
+    /// This is the caret, or if text is selected, such as a preedit string that was inserted and
+    /// selected while composing, the start of the selection, extended to its end if both are on
+    /// the same visual line. Call this after scrolling to pass the area to the windowing system,
+    /// for example with winit's `Window::set_ime_cursor_area`.
+    ///
+    /// Returns None if the area is scrolled out of view. The default is [`Edit::cursor_rect`].
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        self.cursor_rect()
+    }
+
+    /// Get the positions of the [`SelectionHandle::Start`] and [`SelectionHandle::End`] grab
+    /// handles
+    ///
+    /// Handles are typically drawn below `y + height`. A handle is None if there is no selection or its
+    /// end is scrolled out of view. Move handles with [`Action::DragHandle`].
+    ///
+    /// Editors that do not support handles return `(None, None)`.
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        (None, None)
+    }
+
+    /// Get the `(x, y, w, h)` rectangle to anchor a context menu to, such as after
+    /// [`Action::LongPress`]
+    ///
+    /// This covers the selected text on the first visual line of the selection. In right-to-left
+    /// text that line starts at its right edge, so the rectangle is there rather than at the left
+    /// edge of the run. Returns None if there is no selection or it is scrolled out of view.
+    ///
+    /// Editors that do not support context menus always return None.
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        None
+    }
+
+    /// Get the index of the visual line of the cursor, counting the wrapped lines of all lines
+    /// before it, and the X position of the cursor in that visual line
+    ///
+    /// Returns None if the lines up to the cursor have not been laid out.
+    fn cursor_visual_position(&self) -> Option<(usize, i32)> {
+        let cursor = self.cursor();
+        self.with_buffer(|buffer| crate::visual_cursor_position(buffer, &cursor))
+    }
 }
 
 impl<'font_system, 'buffer, E: Edit<'buffer>> BorrowedWithFontSystem<'font_system, E> {
@@ -349,8 +594,8 @@ impl<'font_system, 'buffer, E: Edit<'buffer>> BorrowedWithFontSystem<'font_syste
         self.inner.shape_as_needed(self.font_system, prune);
     }
 
-    /// Perform an [Action] on the editor
-    pub fn action(&mut self, action: Action) {
-        self.inner.action(self.font_system, action);
+    /// Perform an [Action] on the editor, returning what it changed
+    pub fn action(&mut self, action: Action) -> ActionResult {
+        self.inner.action(self.font_system, action)
     }
 }
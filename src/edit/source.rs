@@ -0,0 +1,590 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+use crate::{
+    Action, ActionResult, Attrs, AttrsList, BackspaceMode, BufferLine, BufferRef, Change,
+    ChangeItem, Cursor, Edit, FontSystem, HandlePosition, LineEnding, Motion, Selection, Shaping,
+};
+
+/// Text storage owned by an application, such as a piece table or a collaborative document,
+/// that a [`SourceEditor`] loads lines from and applies changes to
+///
+/// Lines are addressed like [`Cursor`]s: by line index, and by byte index in the text of the
+/// line without its line ending.
+pub trait TextSource {
+    /// Get the number of lines
+    fn line_count(&self) -> usize;
+
+    /// Get the text of the line at `line_i`, without its line ending
+    fn line(&self, line_i: usize) -> Cow<'_, str>;
+
+    /// Insert `text` at `cursor`, where `text` may contain newlines that split the line
+    fn insert(&mut self, cursor: Cursor, text: &str);
+
+    /// Delete the text from `start` to `end`, joining lines if they are different
+    fn delete(&mut self, start: Cursor, end: Cursor);
+}
+
+impl TextSource for Vec<String> {
+    fn line_count(&self) -> usize {
+        self.len()
+    }
+
+    fn line(&self, line_i: usize) -> Cow<'_, str> {
+        Cow::Borrowed(self.get(line_i).map_or("", |line| line.as_str()))
+    }
+
+    fn insert(&mut self, cursor: Cursor, text: &str) {
+        while cursor.line >= self.len() {
+            self.push(String::new());
+        }
+        let after = self[cursor.line].split_off(cursor.index);
+        let mut line_i = cursor.line;
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                line_i += 1;
+                Vec::insert(self, line_i, String::new());
+            }
+            self[line_i].push_str(part);
+        }
+        self[line_i].push_str(&after);
+    }
+
+    fn delete(&mut self, start: Cursor, end: Cursor) {
+        if start.line >= self.len() {
+            return;
+        }
+        let end_line = end.line.min(self.len() - 1);
+        let after = self[end_line]
+            .get(end.index..)
+            .unwrap_or_default()
+            .to_string();
+        self[start.line].truncate(start.index);
+        self[start.line].push_str(&after);
+        self.drain(start.line + 1..=end_line.max(start.line));
+    }
+}
+
+/// A wrapper of any [`Edit`] implementation that keeps its text in a [`TextSource`], so an
+/// application can keep its own document model while the editor handles shaping, layout, and
+/// cursors
+///
+/// The buffer has a line for every line of the source, but only the lines near the view, the
+/// cursor, and the selection hold their text. The other lines are empty placeholders, which are
+/// loaded from the source when they are scrolled into view or edited, and unloaded again by
+/// [`Edit::shape_as_needed`] when `prune` is true. Placeholders are laid out as one line, so
+/// heights of lines far from the view are estimates. Without a buffer height, every line is
+/// loaded.
+///
+/// Changes are applied to the source as they happen, including those made by actions and
+/// [`Edit::apply_change`], so text changes should go through this wrapper rather than the
+/// wrapped editor. They are collected with [`Edit::start_change`] and [`Edit::finish_change`] of
+/// the wrapped editor, so it should not group changes across calls itself.
+///
+/// When the source is changed by someone else, such as a collaborator, update the buffer with
+/// [`SourceEditor::reload_line`] or [`SourceEditor::reload`].
+///
+/// Code that reads the lines of the buffer, including wrapped editors that highlight every line,
+/// sees placeholders as empty lines. Read the text from the source instead.
+#[derive(Debug)]
+pub struct SourceEditor<E, S> {
+    editor: E,
+    source: S,
+    change: Option<Change>,
+}
+
+impl<E, S> SourceEditor<E, S> {
+    /// Create a new [`SourceEditor`] wrapping `editor`, which should have the same text as
+    /// `source`, see [`SourceEditor::reload`]
+    pub fn new(editor: E, source: S) -> Self {
+        Self {
+            editor,
+            source,
+            change: None,
+        }
+    }
+
+    /// Get the wrapped editor
+    pub fn editor(&self) -> &E {
+        &self.editor
+    }
+
+    /// Get the wrapped editor, mutably
+    ///
+    /// Text changed directly in the wrapped editor is not applied to the source, and is lost when
+    /// its line is unloaded.
+    pub fn editor_mut(&mut self) -> &mut E {
+        &mut self.editor
+    }
+
+    /// Get the text source
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get the text source, mutably
+    ///
+    /// Text changed directly in the source is not applied to the buffer until it is reloaded.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Consume this wrapper, returning the editor and source
+    pub fn into_inner(self) -> (E, S) {
+        (self.editor, self.source)
+    }
+}
+
+/// Marker for lines of the buffer that are placeholders for lines of the source
+#[derive(Debug)]
+struct Unloaded;
+
+impl<'buffer, E: Edit<'buffer>, S: TextSource> SourceEditor<E, S> {
+    /// Replace the lines of the buffer with placeholders for the lines of the source, using
+    /// `attrs` for every line, and load the lines in view
+    ///
+    /// The shaping and line ending of the first line of the buffer are used for every line, and
+    /// like [`Buffer::set_text`](crate::Buffer::set_text) the last line has no line ending.
+    pub fn reload(&mut self, font_system: &mut FontSystem, attrs: Attrs) {
+        let line_count = self.source.line_count().max(1);
+        self.editor.with_buffer_mut(|buffer| {
+            let (shaping, ending) = match buffer.lines.first() {
+                Some(line) => (line.shaping(), line.ending()),
+                None => (Shaping::Advanced, LineEnding::default()),
+            };
+            let ending = match ending {
+                LineEnding::None => LineEnding::default(),
+                ending => ending,
+            };
+            buffer.lines = (0..line_count)
+                .map(|line_i| {
+                    let ending = if line_i + 1 < line_count {
+                        ending
+                    } else {
+                        LineEnding::None
+                    };
+                    let mut line =
+                        BufferLine::new(String::new(), ending, AttrsList::new(attrs), shaping);
+                    line.set_persistent_data(Unloaded);
+                    line
+                })
+                .collect();
+            buffer.set_redraw(true);
+        });
+        let cursor = self.editor.cursor();
+        self.load_around(cursor.line, 1);
+        self.editor.set_cursor(self.clamp(cursor));
+        self.editor.set_selection(Selection::None);
+        self.shape_as_needed(font_system, false);
+    }
+
+    /// Replace the text of the line at `line_i` in the buffer with the text of the same line in
+    /// the source, keeping its attributes, returning false if the buffer has no such line
+    ///
+    /// Placeholders are left as they are, they get the text of the source when they are loaded.
+    pub fn reload_line(&mut self, line_i: usize) -> bool {
+        let text = self.source.line(line_i);
+        let reloaded = self.editor.with_buffer_mut(|buffer| {
+            let Some(line) = buffer.lines.get_mut(line_i) else {
+                return false;
+            };
+            if line.data::<Unloaded>().is_none() {
+                let attrs_list = AttrsList::new(line.attrs_list().defaults());
+                line.set_text(text, line.ending(), attrs_list);
+                buffer.set_redraw(true);
+            }
+            true
+        });
+        let cursor = self.editor.cursor();
+        self.editor.set_cursor(self.clamp(cursor));
+        reloaded
+    }
+
+    /// Check if the line at `line_i` of the buffer holds its text, returning false if it is a
+    /// placeholder or there is no such line
+    pub fn is_line_loaded(&self, line_i: usize) -> bool {
+        self.editor.with_buffer(|buffer| {
+            buffer
+                .lines
+                .get(line_i)
+                .map_or(false, |line| line.data::<Unloaded>().is_none())
+        })
+    }
+
+    /// Load the text of the lines from `start` to `end`, inclusive, that are placeholders,
+    /// returning true if any line was loaded
+    fn load_lines(&mut self, start: usize, end: usize) -> bool {
+        let source = &self.source;
+        self.editor.with_buffer_mut(|buffer| {
+            let mut loaded = false;
+            for line_i in start..=end {
+                let Some(line) = buffer.lines.get_mut(line_i) else {
+                    break;
+                };
+                if line.remove_data::<Unloaded>() {
+                    let attrs_list = AttrsList::new(line.attrs_list().defaults());
+                    line.set_text(source.line(line_i), line.ending(), attrs_list);
+                    loaded = true;
+                }
+            }
+            if loaded {
+                buffer.set_redraw(true);
+            }
+            loaded
+        })
+    }
+
+    /// Load the lines up to `count` lines before and after `line_i`
+    fn load_around(&mut self, line_i: usize, count: usize) -> bool {
+        self.load_lines(line_i.saturating_sub(count), line_i.saturating_add(count))
+    }
+
+    /// Get the number of lines that fit in the view, or None if the buffer has no height
+    fn page_lines(&self) -> Option<usize> {
+        self.editor.with_buffer(|buffer| {
+            let height = buffer.size().1?;
+            let line_height = buffer.scaled_metrics().line_height.max(1.0);
+            Some(((height / line_height) as usize).saturating_add(1))
+        })
+    }
+
+    /// Get the first and last lines of the selection, or of the cursor if there is none
+    fn selection_lines(&self) -> (usize, usize) {
+        let cursor = self.editor.cursor();
+        match self.editor.selection() {
+            Selection::None => (cursor.line, cursor.line),
+            Selection::Normal(anchor) | Selection::Line(anchor) | Selection::Word(anchor) => {
+                (anchor.line.min(cursor.line), anchor.line.max(cursor.line))
+            }
+        }
+    }
+
+    /// Get the ranges of lines to keep loaded: the lines in view, a page before and after them,
+    /// and the lines around the cursor and the anchor of the selection
+    fn keep_ranges(&self, page: usize) -> [(usize, usize); 3] {
+        let scroll_line = self.editor.with_buffer(|buffer| buffer.scroll().line);
+        let cursor = self.editor.cursor();
+        let anchor = match self.editor.selection() {
+            Selection::None => cursor,
+            Selection::Normal(anchor) | Selection::Line(anchor) | Selection::Word(anchor) => anchor,
+        };
+        let around = |line_i: usize| (line_i.saturating_sub(page), line_i.saturating_add(page));
+        [
+            (
+                scroll_line.saturating_sub(page),
+                scroll_line.saturating_add(page.saturating_mul(2)),
+            ),
+            around(cursor.line),
+            around(anchor.line),
+        ]
+    }
+
+    /// Load the lines that `action` may read or change
+    fn load_for_action(&mut self, action: Action) {
+        let page = match self.page_lines() {
+            Some(some) => some,
+            None => {
+                self.load_lines(0, usize::MAX);
+                return;
+            }
+        };
+        for (start, end) in self.keep_ranges(page) {
+            self.load_lines(start, end);
+        }
+        let cursor = self.editor.cursor();
+        match action {
+            Action::Motion(motion) | Action::Select(motion) => match motion {
+                Motion::BufferStart => {
+                    self.load_lines(0, 0);
+                }
+                Motion::BufferEnd => {
+                    let last = self.editor.with_buffer(|buffer| buffer.lines.len());
+                    self.load_lines(last.saturating_sub(1), last);
+                }
+                Motion::GotoLine(line_i) => {
+                    self.load_lines(line_i, line_i);
+                }
+                Motion::Vertical(px) => {
+                    let line_height = self
+                        .editor
+                        .with_buffer(|buffer| buffer.scaled_metrics().line_height.max(1.0));
+                    let lines = (px.unsigned_abs() as f32 / line_height) as usize + 1;
+                    self.load_around(cursor.line, lines);
+                }
+                _ => {}
+            },
+            Action::Insert(_)
+            | Action::Enter
+            | Action::Backspace
+            | Action::Delete
+            | Action::Indent
+            | Action::Unindent => {
+                let (start, end) = self.selection_lines();
+                self.load_lines(start, end);
+            }
+            _ => {}
+        }
+    }
+
+    /// Turn lines outside of `keep` that hold their text back into placeholders
+    fn unload_lines(&mut self, keep: &[(usize, usize)]) {
+        self.editor.with_buffer_mut(|buffer| {
+            for (line_i, line) in buffer.lines.iter_mut().enumerate() {
+                if line.data::<Unloaded>().is_some()
+                    || keep
+                        .iter()
+                        .any(|&(start, end)| start <= line_i && line_i <= end)
+                {
+                    continue;
+                }
+                let attrs_list = AttrsList::new(line.attrs_list().defaults());
+                line.set_text("", line.ending(), attrs_list);
+                line.shrink_text();
+                line.set_persistent_data(Unloaded);
+            }
+        });
+    }
+
+    /// Clamp `cursor` to the text of the buffer
+    fn clamp(&self, mut cursor: Cursor) -> Cursor {
+        self.editor.with_buffer(|buffer| {
+            cursor.line = cursor.line.min(buffer.lines.len().saturating_sub(1));
+            let text = buffer.lines.get(cursor.line).map_or("", |line| line.text());
+            cursor.index = cursor.index.min(text.len());
+            while !text.is_char_boundary(cursor.index) {
+                cursor.index -= 1;
+            }
+            cursor
+        })
+    }
+
+    /// Run `f` on the wrapped editor, applying the text changes it makes to the source
+    fn mirror<T>(&mut self, f: impl FnOnce(&mut E) -> T) -> T {
+        self.editor.start_change();
+        let result = f(&mut self.editor);
+        if let Some(change) = self.editor.finish_change() {
+            for item in change.items.iter() {
+                apply_item(&mut self.source, item);
+            }
+            if let Some(ref mut pending) = self.change {
+                pending.items.extend(change.items);
+            }
+        }
+        result
+    }
+}
+
+fn apply_item<S: TextSource>(source: &mut S, item: &ChangeItem) {
+    if item.insert {
+        source.insert(item.start, &item.text);
+    } else {
+        source.delete(item.start, item.end);
+    }
+}
+
+impl<'buffer, E: Edit<'buffer>, S: TextSource> Edit<'buffer> for SourceEditor<E, S> {
+    fn buffer_ref(&self) -> &BufferRef<'buffer> {
+        self.editor.buffer_ref()
+    }
+
+    fn buffer_ref_mut(&mut self) -> &mut BufferRef<'buffer> {
+        self.editor.buffer_ref_mut()
+    }
+
+    fn cursor(&self) -> Cursor {
+        self.editor.cursor()
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.load_around(cursor.line, 1);
+        self.editor.set_cursor(cursor);
+    }
+
+    fn selection(&self) -> Selection {
+        self.editor.selection()
+    }
+
+    fn set_selection(&mut self, selection: Selection) {
+        match selection {
+            Selection::None => {}
+            Selection::Normal(anchor) | Selection::Line(anchor) | Selection::Word(anchor) => {
+                self.load_lines(anchor.line, anchor.line);
+            }
+        }
+        self.editor.set_selection(selection);
+    }
+
+    fn auto_indent(&self) -> bool {
+        self.editor.auto_indent()
+    }
+
+    fn set_auto_indent(&mut self, auto_indent: bool) {
+        self.editor.set_auto_indent(auto_indent);
+    }
+
+    fn overtype(&self) -> bool {
+        self.editor.overtype()
+    }
+
+    fn set_overtype(&mut self, overtype: bool) {
+        self.editor.set_overtype(overtype);
+    }
+
+    fn backspace_mode(&self) -> BackspaceMode {
+        self.editor.backspace_mode()
+    }
+
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        self.editor.set_backspace_mode(backspace_mode);
+    }
+
+    fn tab_width(&self) -> u16 {
+        self.editor.tab_width()
+    }
+
+    fn set_tab_width(&mut self, font_system: &mut FontSystem, tab_width: u16) {
+        self.editor.set_tab_width(font_system, tab_width);
+    }
+
+    fn shape_as_needed(&mut self, font_system: &mut FontSystem, prune: bool) {
+        let cursor = self.editor.cursor();
+        self.load_around(cursor.line, 1);
+        // Scroll to the cursor first, then shape again if the lines in view had to be loaded
+        self.editor.shape_as_needed(font_system, prune);
+        let Some(page) = self.page_lines() else {
+            if self.load_lines(0, usize::MAX) {
+                self.editor.shape_as_needed(font_system, prune);
+            }
+            return;
+        };
+        let scroll_line = self.editor.with_buffer(|buffer| buffer.scroll().line);
+        if self.load_around(scroll_line, page) {
+            self.editor.shape_as_needed(font_system, prune);
+        }
+        if prune {
+            let keep = self.keep_ranges(page);
+            self.unload_lines(&keep);
+        }
+    }
+
+    fn delete_range(&mut self, start: Cursor, end: Cursor) {
+        self.load_lines(start.line, end.line);
+        self.mirror(|editor| editor.delete_range(start, end));
+    }
+
+    fn insert_at(&mut self, cursor: Cursor, data: &str, attrs_list: Option<AttrsList>) -> Cursor {
+        self.load_lines(cursor.line, cursor.line);
+        self.mirror(|editor| editor.insert_at(cursor, data, attrs_list))
+    }
+
+    fn copy_selection(&self) -> Option<String> {
+        // Lines inside of the selection may be placeholders, so take the text from the source
+        let (start, end) = self.editor.selection_bounds()?;
+        let mut selection = String::new();
+        for line_i in start.line..=end.line {
+            let text = self.source.line(line_i);
+            let line_start = if line_i == start.line { start.index } else { 0 };
+            let line_end = if line_i == end.line {
+                end.index
+            } else {
+                text.len()
+            };
+            if line_i > start.line {
+                selection.push('\n');
+            }
+            selection.push_str(text.get(line_start..line_end).unwrap_or_default());
+        }
+        Some(selection)
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let (start, end) = self.selection_lines();
+        self.load_lines(start, end);
+        self.mirror(|editor| editor.delete_selection())
+    }
+
+    fn insert_string(&mut self, data: &str, attrs_list: Option<AttrsList>) {
+        let (start, end) = self.selection_lines();
+        self.load_lines(start, end);
+        self.mirror(|editor| editor.insert_string(data, attrs_list));
+    }
+
+    fn apply_change(&mut self, change: &Change) -> bool {
+        // Cannot apply changes if there is a pending change, like Editor
+        if self
+            .change
+            .as_ref()
+            .map_or(false, |pending| !pending.items.is_empty())
+        {
+            return false;
+        }
+        // Load every line the change may touch, including lines moved up by deleting lines
+        if let Some(start) = change.items.iter().map(|item| item.start.line).min() {
+            let end = change
+                .items
+                .iter()
+                .map(|item| item.end.line)
+                .max()
+                .unwrap_or(start);
+            let deleted: usize = change
+                .items
+                .iter()
+                .filter(|item| !item.insert)
+                .map(|item| item.end.line.saturating_sub(item.start.line))
+                .sum();
+            self.load_lines(start, end.saturating_add(deleted));
+        }
+        // The change is applied as is, so its items are applied instead of collecting them
+        if !self.editor.apply_change(change) {
+            return false;
+        }
+        for item in change.items.iter() {
+            apply_item(&mut self.source, item);
+        }
+        if let Some(ref mut pending) = self.change {
+            pending.items.extend(change.items.iter().cloned());
+        }
+        true
+    }
+
+    fn start_change(&mut self) {
+        if self.change.is_none() {
+            self.change = Some(Change::default());
+        }
+    }
+
+    fn finish_change(&mut self) -> Option<Change> {
+        self.change.take()
+    }
+
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult {
+        self.load_for_action(action);
+        self.mirror(|editor| editor.action(font_system, action))
+    }
+
+    fn cursor_position(&self) -> Option<(i32, i32)> {
+        self.editor.cursor_position()
+    }
+
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.cursor_rect()
+    }
+
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.ime_cursor_area()
+    }
+
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        self.editor.selection_handles()
+    }
+
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.context_menu_anchor()
+    }
+}
@@ -8,8 +8,8 @@ use syntect::highlighting::{
 use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
 
 use crate::{
-    Action, AttrsList, BorrowedWithFontSystem, BufferRef, Change, Color, Cursor, Edit, Editor,
-    FontSystem, Selection, Shaping, Style, Weight,
+    Action, ActionResult, AttrsList, BackspaceMode, BorrowedWithFontSystem, BufferRef, Change,
+    Color, Cursor, Edit, Editor, FontSystem, HandlePosition, Selection, Shaping, Style, Weight,
 };
 
 pub use syntect::highlighting::Theme as SyntaxTheme;
@@ -68,6 +68,16 @@ impl<'syntax_system, 'buffer> SyntaxEditor<'syntax_system, 'buffer> {
         })
     }
 
+    /// Get the wrapped editor
+    pub fn editor(&self) -> &Editor<'buffer> {
+        &self.editor
+    }
+
+    /// Get the wrapped editor, mutably
+    pub fn editor_mut(&mut self) -> &mut Editor<'buffer> {
+        &mut self.editor
+    }
+
     /// Modifies the theme of the [`SyntaxEditor`], returning false if the theme is missing
     pub fn update_theme(&mut self, theme_name: &str) -> bool {
         if let Some(theme) = self.syntax_system.theme_set.themes.get(theme_name) {
@@ -268,6 +278,22 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for SyntaxEditor<'syntax_system, 'bu
         self.editor.set_auto_indent(auto_indent);
     }
 
+    fn overtype(&self) -> bool {
+        self.editor.overtype()
+    }
+
+    fn set_overtype(&mut self, overtype: bool) {
+        self.editor.set_overtype(overtype);
+    }
+
+    fn backspace_mode(&self) -> BackspaceMode {
+        self.editor.backspace_mode()
+    }
+
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        self.editor.set_backspace_mode(backspace_mode);
+    }
+
     fn tab_width(&self) -> u16 {
         self.editor.tab_width()
     }
@@ -282,7 +308,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for SyntaxEditor<'syntax_system, 'bu
 
         let cursor = self.cursor();
         self.editor.with_buffer_mut(|buffer| {
-            let metrics = buffer.metrics();
+            let metrics = buffer.scaled_metrics();
             let scroll = buffer.scroll();
             let scroll_end = scroll.vertical + buffer.size().1.unwrap_or(f32::INFINITY);
             let mut total_height = 0.0;
@@ -431,13 +457,29 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for SyntaxEditor<'syntax_system, 'bu
         self.editor.finish_change()
     }
 
-    fn action(&mut self, font_system: &mut FontSystem, action: Action) {
-        self.editor.action(font_system, action);
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult {
+        self.editor.action(font_system, action)
     }
 
     fn cursor_position(&self) -> Option<(i32, i32)> {
         self.editor.cursor_position()
     }
+
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.cursor_rect()
+    }
+
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.ime_cursor_area()
+    }
+
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        self.editor.selection_handles()
+    }
+
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.context_menu_anchor()
+    }
 }
 
 impl<'font_system, 'syntax_system, 'buffer>
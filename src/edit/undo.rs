@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{Change, ChangeItem, Cursor};
+
+/// Rules for merging consecutive edits of an [`Editor`] into one undo step, see
+/// [`Editor::set_undo_grouping`]
+///
+/// Only typing and deleting single graphemes next to each other is merged. Other edits, like
+/// pasting, and edits inside of [`Editor::begin_undo_group`] are separate steps.
+///
+/// [`Editor`]: crate::Editor
+/// [`Editor::set_undo_grouping`]: crate::Editor::set_undo_grouping
+/// [`Editor::begin_undo_group`]: crate::Editor::begin_undo_group
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UndoGrouping {
+    /// Start a new step when an edit is made longer than this after the previous one, or None
+    /// to merge edits regardless of time
+    ///
+    /// Time is only measured with the `std` feature.
+    pub max_gap: Option<Duration>,
+    /// Start a new step when whitespace is typed after other characters, so each typed word is
+    /// undone separately
+    pub break_at_whitespace: bool,
+}
+
+impl Default for UndoGrouping {
+    fn default() -> Self {
+        Self {
+            max_gap: Some(Duration::from_secs(1)),
+            break_at_whitespace: true,
+        }
+    }
+}
+
+/// Default number of undo steps kept by an [`Editor`](crate::Editor)
+const DEFAULT_UNDO_LIMIT: usize = 1000;
+
+/// Undo and redo steps of an [`Editor`](crate::Editor)
+#[derive(Debug)]
+pub(crate) struct UndoHistory {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    grouping: UndoGrouping,
+    /// False if edits are not recorded
+    enabled: bool,
+    /// Largest number of undo steps to keep, dropping the oldest ones
+    limit_opt: Option<usize>,
+    /// Depth of nested groups, each action is an implicit group
+    depth: usize,
+    /// True if the outermost group was started with [`Editor::begin_undo_group`]
+    ///
+    /// [`Editor::begin_undo_group`]: crate::Editor::begin_undo_group
+    explicit: bool,
+    /// True if the outermost group already started a step
+    group_started: bool,
+    /// True if the last step may be extended by typing
+    open: bool,
+    #[cfg(feature = "std")]
+    last_edit: Option<Instant>,
+    /// True while undoing or redoing, so the applied items are not recorded
+    applying: bool,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            grouping: UndoGrouping::default(),
+            enabled: true,
+            limit_opt: Some(DEFAULT_UNDO_LIMIT),
+            depth: 0,
+            explicit: false,
+            group_started: false,
+            open: false,
+            #[cfg(feature = "std")]
+            last_edit: None,
+            applying: false,
+        }
+    }
+}
+
+impl UndoHistory {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start or stop recording edits, clearing the history when stopping
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit_opt
+    }
+
+    pub fn set_limit(&mut self, limit_opt: Option<usize>) {
+        self.limit_opt = limit_opt;
+        self.truncate();
+    }
+
+    /// Drop the oldest undo steps over the limit
+    fn truncate(&mut self) {
+        if let Some(limit) = self.limit_opt {
+            if self.undo.len() > limit {
+                self.undo.drain(..self.undo.len() - limit);
+                if self.undo.is_empty() {
+                    self.open = false;
+                }
+            }
+        }
+    }
+
+    pub fn grouping(&self) -> UndoGrouping {
+        self.grouping
+    }
+
+    pub fn set_grouping(&mut self, grouping: UndoGrouping) {
+        self.grouping = grouping;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open = false;
+    }
+
+    /// Start a group of edits that is undone as one step
+    pub fn begin_group(&mut self, explicit: bool) {
+        if self.depth == 0 {
+            self.explicit = explicit;
+            self.group_started = false;
+        }
+        self.depth += 1;
+    }
+
+    /// End a group started with [`UndoHistory::begin_group`]
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 && self.explicit {
+            self.explicit = false;
+            self.open = false;
+        }
+    }
+
+    /// Add an edit to the last step or start a new one
+    pub fn record(&mut self, item: &ChangeItem) {
+        if self.applying || !self.enabled {
+            return;
+        }
+        self.redo.clear();
+
+        let typing = is_typing(item);
+        let in_group = self.depth > 0 && self.group_started;
+        let merge = in_group || (!self.explicit && self.open && typing && self.continues(item));
+        match self.undo.last_mut() {
+            Some(step) if merge => step.items.push(item.clone()),
+            _ => self.undo.push(Change {
+                items: vec![item.clone()],
+            }),
+        }
+        if self.depth > 0 {
+            self.group_started = true;
+        }
+        self.open = !self.explicit && typing && (!in_group || self.open);
+        self.truncate();
+        #[cfg(feature = "std")]
+        {
+            self.last_edit = Some(Instant::now());
+        }
+    }
+
+    /// True if typing `item` continues the last step, following the grouping rules
+    fn continues(&self, item: &ChangeItem) -> bool {
+        #[cfg(feature = "std")]
+        if let (Some(max_gap), Some(last_edit)) = (self.grouping.max_gap, self.last_edit) {
+            if last_edit.elapsed() > max_gap {
+                return false;
+            }
+        }
+        let Some(last) = self.undo.last().and_then(|step| step.items.last()) else {
+            return false;
+        };
+        if last.insert != item.insert {
+            return false;
+        }
+        if item.insert {
+            let whitespace = |text: &str| text.chars().all(char::is_whitespace);
+            if self.grouping.break_at_whitespace
+                && whitespace(&item.text)
+                && !whitespace(&last.text)
+            {
+                return false;
+            }
+            same_position(item.start, last.end)
+        } else {
+            // Backspace deletes before the last deletion, delete after it
+            same_position(item.end, last.start) || same_position(item.start, last.start)
+        }
+    }
+
+    /// Take the last step to undo, returning its reversed change
+    pub fn take_undo(&mut self) -> Option<Change> {
+        let step = self.undo.pop()?;
+        let mut change = step.clone();
+        change.reverse();
+        self.redo.push(step);
+        self.open = false;
+        Some(change)
+    }
+
+    /// Take the last undone step to redo
+    pub fn take_redo(&mut self) -> Option<Change> {
+        let step = self.redo.pop()?;
+        self.undo.push(step.clone());
+        self.open = false;
+        self.truncate();
+        Some(step)
+    }
+
+    pub fn set_applying(&mut self, applying: bool) {
+        self.applying = applying;
+    }
+}
+
+/// True if the cursors are at the same position, ignoring their affinity
+fn same_position(a: Cursor, b: Cursor) -> bool {
+    (a.line, a.index) == (b.line, b.index)
+}
+
+/// True if `item` inserts or deletes a single grapheme on one line, like typing
+fn is_typing(item: &ChangeItem) -> bool {
+    item.start.line == item.end.line && item.text.graphemes(true).count() == 1
+}
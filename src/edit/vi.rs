@@ -1,11 +1,11 @@
 use alloc::{collections::BTreeMap, string::String};
 use core::cmp;
 use modit::{Event, Key, Parser, TextObject, WordIter};
-use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    Action, AttrsList, BorrowedWithFontSystem, BufferRef, Change, Color, Cursor, Edit, FontSystem,
-    Motion, Selection, SyntaxEditor, SyntaxTheme,
+    Action, ActionResult, ActionState, AttrsList, BackspaceMode, BorrowedWithFontSystem, BufferRef,
+    Change, Color, Cursor, Edit, FontSystem, HandlePosition, Motion, Selection, SyntaxEditor,
+    SyntaxTheme,
 };
 
 pub use modit::{ViMode, ViParser};
@@ -190,7 +190,9 @@ pub struct ViEditor<'syntax_system, 'buffer> {
 }
 
 impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
-    pub fn new(editor: SyntaxEditor<'syntax_system, 'buffer>) -> Self {
+    pub fn new(mut editor: SyntaxEditor<'syntax_system, 'buffer>) -> Self {
+        // Undo and redo use the commands of the vi editor instead of the editor history
+        editor.editor_mut().set_undo_enabled(false);
         Self {
             editor,
             parser: ViParser::new(),
@@ -315,7 +317,7 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                     f(0, 0, width as u32, height as u32, background_color);
                 }
             }
-            let font_size = buffer.metrics().font_size;
+            let font_size = buffer.scaled_metrics().font_size;
             for run in buffer.layout_runs() {
                 let line_i = run.line_i;
                 let line_y = run.line_y;
@@ -328,21 +330,19 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                     if cursor.line == line_i {
                         for (glyph_i, glyph) in run.glyphs.iter().enumerate() {
                             if cursor.index >= glyph.start && cursor.index < glyph.end {
-                                // Guess x offset based on characters
-                                let mut before = 0;
-                                let mut total = 0;
-
-                                let cluster = &run.text[glyph.start..glyph.end];
-                                for (i, _) in cluster.grapheme_indices(true) {
-                                    if glyph.start + i < cursor.index {
-                                        before += 1;
-                                    }
-                                    total += 1;
+                                // Find the grapheme inside of the ligature
+                                if let Some(part) = glyph
+                                    .sub_clusters(run.text)
+                                    .find(|part| cursor.index < part.end)
+                                {
+                                    let offset = if glyph.level.is_rtl() {
+                                        glyph.x + glyph.w - (part.x + part.w)
+                                    } else {
+                                        part.x - glyph.x
+                                    };
+                                    return Some((glyph_i, offset, part.w));
                                 }
-
-                                let width = glyph.w / (total as f32);
-                                let offset = (before as f32) * width;
-                                return Some((glyph_i, offset, width));
+                                return Some((glyph_i, 0.0, glyph.w));
                             }
                         }
                         match run.glyphs.last() {
@@ -364,23 +364,17 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                     if line_i >= start.line && line_i <= end.line {
                         let mut range_opt = None;
                         for glyph in run.glyphs.iter() {
-                            // Guess x offset based on characters
-                            let cluster = &run.text[glyph.start..glyph.end];
-                            let total = cluster.grapheme_indices(true).count();
-                            let mut c_x = glyph.x;
-                            let c_w = glyph.w / total as f32;
-                            for (i, c) in cluster.grapheme_indices(true) {
-                                let c_start = glyph.start + i;
-                                let c_end = glyph.start + i + c.len();
-                                if (start.line != line_i || c_end > start.index)
-                                    && (end.line != line_i || c_start < end.index)
+                            // Select graphemes inside of ligatures
+                            for part in glyph.sub_clusters(run.text) {
+                                if (start.line != line_i || part.end > start.index)
+                                    && (end.line != line_i || part.start < end.index)
                                 {
                                     range_opt = match range_opt.take() {
                                         Some((min, max)) => Some((
-                                            cmp::min(min, c_x as i32),
-                                            cmp::max(max, (c_x + c_w) as i32),
+                                            cmp::min(min, part.x as i32),
+                                            cmp::max(max, (part.x + part.w) as i32),
                                         )),
-                                        None => Some((c_x as i32, (c_x + c_w) as i32)),
+                                        None => Some((part.x as i32, (part.x + part.w) as i32)),
                                     };
                                 } else if let Some((min, max)) = range_opt.take() {
                                     f(
@@ -391,7 +385,6 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                                         selection_color,
                                     );
                                 }
-                                c_x += c_w;
                             }
                         }
 
@@ -489,8 +482,8 @@ impl<'syntax_system, 'buffer> ViEditor<'syntax_system, 'buffer> {
                     }
                 }
 
-                for glyph in run.glyphs.iter() {
-                    let physical_glyph = glyph.physical((0., 0.), 1.0);
+                for glyph in run.glyphs.iter().chain(run.wrap_indicator_opt.iter()) {
+                    let physical_glyph = buffer.physical_glyph(&run, glyph, (0., 0.), 1.0);
 
                     let glyph_color = match glyph.color_opt {
                         Some(some) => some,
@@ -550,6 +543,22 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
         self.editor.set_auto_indent(auto_indent);
     }
 
+    fn overtype(&self) -> bool {
+        self.editor.overtype()
+    }
+
+    fn set_overtype(&mut self, overtype: bool) {
+        self.editor.set_overtype(overtype);
+    }
+
+    fn backspace_mode(&self) -> BackspaceMode {
+        self.editor.backspace_mode()
+    }
+
+    fn set_backspace_mode(&mut self, backspace_mode: BackspaceMode) {
+        self.editor.set_backspace_mode(backspace_mode);
+    }
+
     fn tab_width(&self) -> u16 {
         self.editor.tab_width()
     }
@@ -595,16 +604,17 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
         )
     }
 
-    fn action(&mut self, font_system: &mut FontSystem, action: Action) {
+    fn action(&mut self, font_system: &mut FontSystem, action: Action) -> ActionResult {
         log::debug!("Action {:?}", action);
 
+        let state = ActionState::new(&self.editor);
         let editor = &mut self.editor;
 
         // Ensure a change is always started
         editor.start_change();
 
         if self.passthrough {
-            editor.action(font_system, action);
+            let result = editor.action(font_system, action);
             // Always finish change when passing through (TODO: group changes)
             finish_change(
                 editor,
@@ -612,7 +622,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
                 &mut self.changed,
                 self.save_pivot,
             );
-            return;
+            return result | state.result(editor);
         }
 
         let key = match action {
@@ -634,7 +644,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
             Action::Motion(Motion::Up) => Key::Up,
             _ => {
                 log::debug!("Pass through action {:?}", action);
-                editor.action(font_system, action);
+                let result = editor.action(font_system, action);
                 // Always finish change when passing through (TODO: group changes)
                 finish_change(
                     editor,
@@ -642,7 +652,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
                     &mut self.changed,
                     self.save_pivot,
                 );
-                return;
+                return result | state.result(editor);
             }
         };
 
@@ -651,6 +661,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
             _ => true,
         };
 
+        let mut result = ActionResult::empty();
         self.parser.parse(key, has_selection, |event| {
             log::debug!("  Event {:?}", event);
             let action = match event {
@@ -744,6 +755,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
                                 }
                             }
                         }
+                        result |= ActionResult::TEXT;
                         finish_change(
                             editor,
                             &mut self.commands,
@@ -834,6 +846,7 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
                 Event::Undo => {
                     for action in self.commands.undo() {
                         undo_2_action(editor, action);
+                        result |= ActionResult::TEXT;
                     }
                     return;
                 }
@@ -1158,13 +1171,30 @@ impl<'syntax_system, 'buffer> Edit<'buffer> for ViEditor<'syntax_system, 'buffer
                     }
                 }
             };
-            editor.action(font_system, action);
+            result |= editor.action(font_system, action);
         });
+        result | state.result(&self.editor)
     }
 
     fn cursor_position(&self) -> Option<(i32, i32)> {
         self.editor.cursor_position()
     }
+
+    fn cursor_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.cursor_rect()
+    }
+
+    fn ime_cursor_area(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.ime_cursor_area()
+    }
+
+    fn selection_handles(&self) -> (Option<HandlePosition>, Option<HandlePosition>) {
+        self.editor.selection_handles()
+    }
+
+    fn context_menu_anchor(&self) -> Option<(i32, i32, u32, u32)> {
+        self.editor.context_menu_anchor()
+    }
 }
 
 impl<'font_system, 'syntax_system, 'buffer>
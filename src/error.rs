@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::fmt;
+
+/// An error from shaping or laying out text
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LayoutError {
+    /// The font size is zero, negative, or not finite
+    InvalidFontSize,
+    /// The line height is zero, negative, or not finite
+    InvalidLineHeight,
+    /// No font was found for some of the text, which was left without glyphs
+    NoFont,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFontSize => write!(f, "font size must be positive and finite"),
+            Self::InvalidLineHeight => write!(f, "line height must be positive and finite"),
+            Self::NoFont => write!(f, "no font found"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LayoutError {}
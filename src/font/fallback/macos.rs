@@ -2,6 +2,8 @@
 
 use unicode_script::Script;
 
+use crate::GenericFamily;
+
 // Fallbacks to use after any script specific fallbacks
 pub fn common_fallback() -> &'static [&'static str] {
     &[
@@ -18,6 +20,20 @@ pub fn forbidden_fallback() -> &'static [&'static str] {
     &[".LastResort"]
 }
 
+// Fallbacks to use for generic families
+pub fn generic_fallback(generic: GenericFamily) -> &'static [&'static str] {
+    match generic {
+        GenericFamily::SystemUi => &["SF Pro", "Helvetica Neue"],
+        GenericFamily::UiSerif => &["New York", "Times"],
+        GenericFamily::UiSansSerif => &["SF Pro", "Helvetica Neue"],
+        GenericFamily::UiMonospace => &["SF Mono", "Menlo"],
+        GenericFamily::UiRounded => &["SF Pro Rounded"],
+        GenericFamily::Emoji => &["Apple Color Emoji"],
+        GenericFamily::Math => &["STIX Two Math"],
+        GenericFamily::Fangsong => &["STFangsong"],
+    }
+}
+
 fn han_unification(locale: &str) -> &'static [&'static str] {
     match locale {
         // Japan
@@ -6,7 +6,7 @@ use alloc::vec::Vec;
 use fontdb::Family;
 use unicode_script::Script;
 
-use crate::{Font, FontMatchKey, FontSystem, ShapePlanCache};
+use crate::{FamilyOwned, Font, FontMatchKey, FontSystem, GenericFamily, ShapePlanCache};
 
 use self::platform::*;
 
@@ -31,13 +31,35 @@ use log::debug as missing_warn;
 #[cfg(feature = "warn_on_missing_glyphs")]
 use log::warn as missing_warn;
 
-// Match on lowest font_weight_diff, then script_non_matches, then font_weight
+/// Get the platform default substitutes of a generic family, followed by the closest fontdb
+/// generic family
+pub(crate) fn generic_substitutes(generic: GenericFamily) -> Vec<FamilyOwned> {
+    let mut substitutes: Vec<_> = generic_fallback(generic)
+        .iter()
+        .map(|name| FamilyOwned::Name((*name).into()))
+        .collect();
+    match generic {
+        GenericFamily::SystemUi | GenericFamily::UiSansSerif | GenericFamily::UiRounded => {
+            substitutes.push(FamilyOwned::SansSerif);
+        }
+        GenericFamily::UiSerif | GenericFamily::Math | GenericFamily::Fangsong => {
+            substitutes.push(FamilyOwned::Serif);
+        }
+        GenericFamily::UiMonospace => substitutes.push(FamilyOwned::Monospace),
+        GenericFamily::Emoji => {}
+    }
+    substitutes
+}
+
+// Match on lowest font_weight_diff, then codepoint_non_matches, then the rank of the family in
+// the preferred monospace fallback families, then font_weight
 // Default font gets None for both `weight_offset` and `script_non_matches`, and thus, it is
 // always the first to be popped from the set.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct MonospaceFallbackInfo {
     font_weight_diff: Option<u16>,
     codepoint_non_matches: Option<usize>,
+    family_rank: usize,
     font_weight: u16,
     id: fontdb::ID,
 }
@@ -49,6 +71,7 @@ pub struct FontFallbackIter<'a> {
     monospace_fallbacks: BTreeSet<MonospaceFallbackInfo>,
     default_i: usize,
     scripts: &'a [Script],
+    locale_opt: Option<&'a str>,
     word: &'a str,
     script_i: (usize, usize),
     common_i: usize,
@@ -62,6 +85,7 @@ impl<'a> FontFallbackIter<'a> {
         font_match_keys: &'a [FontMatchKey],
         default_families: &'a [&'a Family<'a>],
         scripts: &'a [Script],
+        locale_opt: Option<&'a str>,
         word: &'a str,
     ) -> Self {
         Self {
@@ -71,6 +95,7 @@ impl<'a> FontFallbackIter<'a> {
             monospace_fallbacks: BTreeSet::new(),
             default_i: 0,
             scripts,
+            locale_opt,
             word,
             script_i: (0, 0),
             common_i: 0,
@@ -107,6 +132,11 @@ impl<'a> FontFallbackIter<'a> {
         }
     }
 
+    /// Locale used for script fallbacks, preferring the language of the text
+    pub fn locale(&self) -> &str {
+        self.locale_opt.unwrap_or(self.font_system.locale())
+    }
+
     pub fn face_name(&self, id: fontdb::ID) -> &str {
         if let Some(face) = self.font_system.db().face(id) {
             if let Some((name, _)) = face.families.first() {
@@ -131,14 +161,39 @@ impl<'a> FontFallbackIter<'a> {
         }
     }
 
-    fn default_font_match_key(&self) -> Option<&FontMatchKey> {
-        let default_family = self.default_families[self.default_i - 1];
-        let default_family_name = self.font_system.db().family_name(default_family);
+    fn font_match_key(&self, family: &Family) -> Option<&FontMatchKey> {
+        let family_name = self.font_system.db().family_name(family);
 
         self.font_match_keys
             .iter()
             .filter(|m_key| m_key.font_weight_diff == 0)
-            .find(|m_key| self.face_contains_family(m_key.id, default_family_name))
+            .find(|m_key| self.face_contains_family(m_key.id, family_name))
+    }
+
+    /// Get the family to use for the current default family and its font match key
+    ///
+    /// If no font has the default family, its substitutes are tried in order. If none of them
+    /// has a font either, the first monospace substitute is used so monospace fallback applies.
+    fn default_font_match_key(&self) -> (FamilyOwned, Option<FontMatchKey>) {
+        let default_family = self.default_families[self.default_i - 1];
+        if let Some(m_key) = self.font_match_key(default_family) {
+            return (FamilyOwned::new(*default_family), Some(*m_key));
+        }
+
+        let substitutes = match default_family {
+            Family::Name(name) => self.font_system.family_substitutes(name),
+            _ => &[],
+        };
+        for substitute in substitutes {
+            if let Some(m_key) = self.font_match_key(&substitute.as_family()) {
+                return (substitute.clone(), Some(*m_key));
+            }
+        }
+        if substitutes.contains(&FamilyOwned::Monospace) {
+            (FamilyOwned::Monospace, None)
+        } else {
+            (FamilyOwned::new(*default_family), None)
+        }
     }
 }
 
@@ -159,8 +214,8 @@ impl<'a> Iterator for FontFallbackIter<'a> {
 
         'DEF_FAM: while self.default_i < self.default_families.len() {
             self.default_i += 1;
-            let is_mono = self.default_families[self.default_i - 1] == &Family::Monospace;
-            let default_font_match_key = self.default_font_match_key().cloned();
+            let (default_family, default_font_match_key) = self.default_font_match_key();
+            let is_mono = default_family == FamilyOwned::Monospace;
             let word_chars_count = self.word.chars().count();
 
             macro_rules! mk_mono_fallback_info {
@@ -175,6 +230,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                         MonospaceFallbackInfo {
                             font_weight_diff: Some($m_key.font_weight_diff),
                             codepoint_non_matches: Some(codepoint_non_matches),
+                            family_rank: self.font_system.monospace_fallback_rank($m_key.id),
                             font_weight: $m_key.font_weight,
                             id: $m_key.id,
                         }
@@ -239,6 +295,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                             let fallback_info = MonospaceFallbackInfo {
                                 font_weight_diff: Some(m_key.font_weight_diff),
                                 codepoint_non_matches: Some(codepoint_non_matches),
+                                family_rank: self.font_system.monospace_fallback_rank(m_key.id),
                                 font_weight: m_key.font_weight,
                                 id: m_key.id,
                             };
@@ -258,7 +315,11 @@ impl<'a> Iterator for FontFallbackIter<'a> {
         while self.script_i.0 < self.scripts.len() {
             let script = self.scripts[self.script_i.0];
 
-            let script_families = script_fallback(script, self.font_system.locale());
+            let script_families = if self.font_system.is_deterministic() {
+                &[]
+            } else {
+                script_fallback(script, self.locale())
+            };
             while self.script_i.1 < script_families.len() {
                 let script_family = script_families[self.script_i.1];
                 self.script_i.1 += 1;
@@ -273,7 +334,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                     "failed to find family '{}' for script {:?} and locale '{}'",
                     script_family,
                     script,
-                    self.font_system.locale(),
+                    self.locale(),
                 );
             }
 
@@ -281,7 +342,11 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             self.script_i.1 = 0;
         }
 
-        let common_families = common_fallback();
+        let common_families = if self.font_system.is_deterministic() {
+            &[]
+        } else {
+            common_fallback()
+        };
         while self.common_i < common_families.len() {
             let common_family = common_families[self.common_i];
             self.common_i += 1;
@@ -297,7 +362,11 @@ impl<'a> Iterator for FontFallbackIter<'a> {
 
         //TODO: do we need to do this?
         //TODO: do not evaluate fonts more than once!
-        let forbidden_families = forbidden_fallback();
+        let forbidden_families = if self.font_system.is_deterministic() {
+            &[]
+        } else {
+            forbidden_fallback()
+        };
         while self.other_i < self.font_match_keys.len() {
             let id = self.font_match_keys[self.other_i].id;
             self.other_i += 1;
@@ -2,6 +2,8 @@
 
 use unicode_script::Script;
 
+use crate::GenericFamily;
+
 // Fallbacks to use after any script specific fallbacks
 pub fn common_fallback() -> &'static [&'static str] {
     &[]
@@ -12,6 +14,11 @@ pub fn forbidden_fallback() -> &'static [&'static str] {
     &[]
 }
 
+// Fallbacks to use for generic families
+pub fn generic_fallback(_generic: GenericFamily) -> &'static [&'static str] {
+    &[]
+}
+
 // Fallbacks to use per script
 pub fn script_fallback(_script: Script, _locale: &str) -> &'static [&'static str] {
     &[]
@@ -2,6 +2,8 @@
 
 use unicode_script::Script;
 
+use crate::GenericFamily;
+
 // Fallbacks to use after any script specific fallbacks
 pub fn common_fallback() -> &'static [&'static str] {
     //TODO: abstract style (sans/serif/monospaced)
@@ -29,6 +31,20 @@ pub fn forbidden_fallback() -> &'static [&'static str] {
     &[]
 }
 
+// Fallbacks to use for generic families
+pub fn generic_fallback(generic: GenericFamily) -> &'static [&'static str] {
+    match generic {
+        GenericFamily::SystemUi => &["Cantarell", "Ubuntu", "Noto Sans", "DejaVu Sans"],
+        GenericFamily::UiSerif => &["Noto Serif", "DejaVu Serif"],
+        GenericFamily::UiSansSerif => &["Noto Sans", "DejaVu Sans"],
+        GenericFamily::UiMonospace => &["Noto Sans Mono", "DejaVu Sans Mono"],
+        GenericFamily::UiRounded => &[],
+        GenericFamily::Emoji => &["Noto Color Emoji"],
+        GenericFamily::Math => &["STIX Two Math", "Latin Modern Math", "DejaVu Math TeX Gyre"],
+        GenericFamily::Fangsong => &["AR PL UKai CN", "FangSong"],
+    }
+}
+
 fn han_unification(locale: &str) -> &'static [&'static str] {
     match locale {
         // Japan
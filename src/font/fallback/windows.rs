@@ -2,6 +2,8 @@
 
 use unicode_script::Script;
 
+use crate::GenericFamily;
+
 // Fallbacks to use after any script specific fallbacks
 pub fn common_fallback() -> &'static [&'static str] {
     //TODO: abstract style (sans/serif/monospaced)
@@ -19,6 +21,20 @@ pub fn forbidden_fallback() -> &'static [&'static str] {
     &[]
 }
 
+// Fallbacks to use for generic families
+pub fn generic_fallback(generic: GenericFamily) -> &'static [&'static str] {
+    match generic {
+        GenericFamily::SystemUi => &["Segoe UI"],
+        GenericFamily::UiSerif => &["Cambria", "Times New Roman"],
+        GenericFamily::UiSansSerif => &["Segoe UI", "Arial"],
+        GenericFamily::UiMonospace => &["Cascadia Mono", "Consolas"],
+        GenericFamily::UiRounded => &[],
+        GenericFamily::Emoji => &["Segoe UI Emoji"],
+        GenericFamily::Math => &["Cambria Math"],
+        GenericFamily::Fangsong => &["FangSong"],
+    }
+}
+
 fn han_unification(locale: &str) -> &'static [&'static str] {
     //TODO!
     match locale {
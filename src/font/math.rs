@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::Font;
+
+/// Constants of the OpenType MATH table of a font at a given size, in pixels
+///
+/// These are the constants needed to lay out simple inline formulas: script placement,
+/// fractions and radicals. Distances are positive, in the direction given by their name.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MathConstants {
+    /// Scale of the font size for first level scripts, such as `0.7`
+    pub script_scale_down: f32,
+    /// Scale of the font size for second level scripts, such as `0.5`
+    pub script_script_scale_down: f32,
+    /// Minimum height of a sub formula for delimiters to be stretched around it
+    pub delimited_sub_formula_min_height: f32,
+    /// Minimum height of large operators in display style
+    pub display_operator_min_height: f32,
+    /// Height of the math axis above the baseline, where fraction bars are centered
+    pub axis_height: f32,
+    /// Maximum height of a base that does not need accents to be moved up
+    pub accent_base_height: f32,
+    /// Shift down of a subscript from the baseline
+    pub subscript_shift_down: f32,
+    /// Maximum height of the top of a subscript above the baseline
+    pub subscript_top_max: f32,
+    /// Minimum drop of the subscript baseline below the bottom of the base
+    pub subscript_baseline_drop_min: f32,
+    /// Shift up of a superscript from the baseline
+    pub superscript_shift_up: f32,
+    /// Shift up of a superscript from the baseline in cramped style
+    pub superscript_shift_up_cramped: f32,
+    /// Minimum height of the bottom of a superscript above the baseline
+    pub superscript_bottom_min: f32,
+    /// Maximum drop of the superscript baseline below the top of the base
+    pub superscript_baseline_drop_max: f32,
+    /// Minimum gap between a subscript and a superscript on the same base
+    pub sub_superscript_gap_min: f32,
+    /// Extra space after a subscript or superscript
+    pub space_after_script: f32,
+    /// Shift up of the numerator of a fraction from the baseline
+    pub fraction_numerator_shift_up: f32,
+    /// Shift up of the numerator of a fraction from the baseline in display style
+    pub fraction_numerator_display_style_shift_up: f32,
+    /// Shift down of the denominator of a fraction from the baseline
+    pub fraction_denominator_shift_down: f32,
+    /// Shift down of the denominator of a fraction from the baseline in display style
+    pub fraction_denominator_display_style_shift_down: f32,
+    /// Minimum gap between the numerator and the fraction bar
+    pub fraction_numerator_gap_min: f32,
+    /// Minimum gap between the denominator and the fraction bar
+    pub fraction_denominator_gap_min: f32,
+    /// Thickness of the fraction bar
+    pub fraction_rule_thickness: f32,
+    /// Gap between the radicand and the radical rule
+    pub radical_vertical_gap: f32,
+    /// Gap between the radicand and the radical rule in display style
+    pub radical_display_style_vertical_gap: f32,
+    /// Thickness of the radical rule
+    pub radical_rule_thickness: f32,
+    /// Extra space above the radical rule
+    pub radical_extra_ascender: f32,
+    /// Horizontal space before the degree of a radical
+    pub radical_kern_before_degree: f32,
+    /// Horizontal space after the degree of a radical, usually negative
+    pub radical_kern_after_degree: f32,
+    /// Raise of the bottom of the degree of a radical, relative to the height of the radical
+    pub radical_degree_bottom_raise: f32,
+}
+
+/// A larger variant of a glyph from the OpenType MATH table, used to stretch delimiters and
+/// radicals or to size large operators
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MathGlyphVariant {
+    /// Glyph ID of the variant in the same font
+    pub glyph_id: u16,
+    /// Advance of the variant in the direction it grows, in font units
+    pub advance: u16,
+}
+
+impl Font {
+    /// Get the constants of the MATH table of this font at `font_size`, or None if the font
+    /// has no MATH table
+    pub fn math_constants(&self, font_size: f32) -> Option<MathConstants> {
+        let face = self.rustybuzz();
+        let constants = face.tables().math?.constants?;
+        let scale = font_size / face.units_per_em() as f32;
+        let px = |value: ttf_parser::math::MathValue| f32::from(value.value) * scale;
+        let percent = |value: i16| f32::from(value) / 100.0;
+
+        Some(MathConstants {
+            script_scale_down: percent(constants.script_percent_scale_down()),
+            script_script_scale_down: percent(constants.script_script_percent_scale_down()),
+            delimited_sub_formula_min_height: f32::from(
+                constants.delimited_sub_formula_min_height(),
+            ) * scale,
+            display_operator_min_height: f32::from(constants.display_operator_min_height()) * scale,
+            axis_height: px(constants.axis_height()),
+            accent_base_height: px(constants.accent_base_height()),
+            subscript_shift_down: px(constants.subscript_shift_down()),
+            subscript_top_max: px(constants.subscript_top_max()),
+            subscript_baseline_drop_min: px(constants.subscript_baseline_drop_min()),
+            superscript_shift_up: px(constants.superscript_shift_up()),
+            superscript_shift_up_cramped: px(constants.superscript_shift_up_cramped()),
+            superscript_bottom_min: px(constants.superscript_bottom_min()),
+            superscript_baseline_drop_max: px(constants.superscript_baseline_drop_max()),
+            sub_superscript_gap_min: px(constants.sub_superscript_gap_min()),
+            space_after_script: px(constants.space_after_script()),
+            fraction_numerator_shift_up: px(constants.fraction_numerator_shift_up()),
+            fraction_numerator_display_style_shift_up: px(
+                constants.fraction_numerator_display_style_shift_up()
+            ),
+            fraction_denominator_shift_down: px(constants.fraction_denominator_shift_down()),
+            fraction_denominator_display_style_shift_down: px(
+                constants.fraction_denominator_display_style_shift_down()
+            ),
+            fraction_numerator_gap_min: px(constants.fraction_numerator_gap_min()),
+            fraction_denominator_gap_min: px(constants.fraction_denominator_gap_min()),
+            fraction_rule_thickness: px(constants.fraction_rule_thickness()),
+            radical_vertical_gap: px(constants.radical_vertical_gap()),
+            radical_display_style_vertical_gap: px(constants.radical_display_style_vertical_gap()),
+            radical_rule_thickness: px(constants.radical_rule_thickness()),
+            radical_extra_ascender: px(constants.radical_extra_ascender()),
+            radical_kern_before_degree: px(constants.radical_kern_before_degree()),
+            radical_kern_after_degree: px(constants.radical_kern_after_degree()),
+            radical_degree_bottom_raise: percent(constants.radical_degree_bottom_raise_percent()),
+        })
+    }
+
+    /// Get the larger variants of a glyph from the MATH table, from smallest to largest
+    ///
+    /// Vertical variants are used for delimiters, radicals and large operators, horizontal
+    /// variants for accents and arrows. The list is empty if the font has no variants for the
+    /// glyph.
+    pub fn math_variants(&self, glyph_id: u16, vertical: bool) -> Vec<MathGlyphVariant> {
+        let variants = match self
+            .rustybuzz()
+            .tables()
+            .math
+            .and_then(|math| math.variants)
+        {
+            Some(variants) => variants,
+            None => return Vec::new(),
+        };
+        let constructions = if vertical {
+            variants.vertical_constructions
+        } else {
+            variants.horizontal_constructions
+        };
+        match constructions.get(ttf_parser::GlyphId(glyph_id)) {
+            Some(construction) => construction
+                .variants
+                .into_iter()
+                .map(|variant| MathGlyphVariant {
+                    glyph_id: variant.variant_glyph.0,
+                    advance: variant.advance_measurement,
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
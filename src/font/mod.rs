@@ -6,13 +6,19 @@ pub use ttf_parser;
 
 use core::fmt;
 
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+use once_cell::race::OnceBox;
+
 use rustybuzz::Face as RustybuzzFace;
 use self_cell::self_cell;
 
+pub use self::math::*;
+mod math;
+
 pub use self::system::*;
 mod system;
 
@@ -25,6 +31,22 @@ self_cell!(
     }
 );
 
+/// Underline and strikethrough placement of a font at a given size, in pixels
+///
+/// Offsets are the distance from the baseline to the top of the line, with positive values
+/// below the baseline, matching the y axis of [`LayoutRun`](crate::LayoutRun) coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecorationMetrics {
+    /// Offset of the top of the underline from the baseline
+    pub underline_offset: f32,
+    /// Thickness of the underline
+    pub underline_thickness: f32,
+    /// Offset of the top of the strikethrough from the baseline, usually negative
+    pub strikethrough_offset: f32,
+    /// Thickness of the strikethrough
+    pub strikethrough_thickness: f32,
+}
+
 /// A font
 pub struct Font {
     #[cfg(feature = "swash")]
@@ -34,7 +56,7 @@ pub struct Font {
     id: fontdb::ID,
     monospace_em_width: Option<f32>,
     scripts: Vec<[u8; 4]>,
-    unicode_codepoints: Vec<u32>,
+    unicode_codepoints: OnceBox<Vec<u32>>,
 }
 
 impl fmt::Debug for Font {
@@ -58,8 +80,36 @@ impl Font {
         &self.scripts
     }
 
+    /// Get all Unicode codepoints this font has a glyph for, in cmap order
+    ///
+    /// The cmap is only walked the first time this is called. Use [`Font::has_codepoint`] to
+    /// check single codepoints without collecting all of them.
     pub fn unicode_codepoints(&self) -> &[u32] {
-        &self.unicode_codepoints
+        self.unicode_codepoints.get_or_init(|| {
+            let mut unicode_codepoints = Vec::new();
+            if let Some(cmap) = self.rustybuzz().tables().cmap {
+                cmap.subtables
+                    .into_iter()
+                    .filter(|subtable| subtable.is_unicode())
+                    .for_each(|subtable| {
+                        unicode_codepoints.reserve(1024);
+                        subtable.codepoints(|code_point| {
+                            if subtable.glyph_index(code_point).is_some() {
+                                unicode_codepoints.push(code_point);
+                            }
+                        });
+                    });
+            }
+            unicode_codepoints.shrink_to_fit();
+            Box::new(unicode_codepoints)
+        })
+    }
+
+    /// Check if this font has a glyph for `codepoint`, using a cmap lookup
+    pub fn has_codepoint(&self, codepoint: u32) -> bool {
+        char::from_u32(codepoint)
+            .and_then(|c| self.rustybuzz().glyph_index(c))
+            .is_some()
     }
 
     pub fn data(&self) -> &[u8] {
@@ -70,6 +120,44 @@ impl Font {
         self.rustybuzz.borrow_dependent()
     }
 
+    /// Get the underline and strikethrough metrics of this font at `font_size`
+    ///
+    /// Fonts without these metrics fall back to a thickness of 1/14 em, an underline one
+    /// thickness below the baseline, and a strikethrough at half the x-height.
+    pub fn decoration_metrics(&self, font_size: f32) -> DecorationMetrics {
+        let face = self.rustybuzz();
+        let scale = font_size / face.units_per_em() as f32;
+        let default_thickness = font_size / 14.0;
+
+        let (underline_offset, underline_thickness) = match face.underline_metrics() {
+            Some(metrics) => (
+                -f32::from(metrics.position) * scale,
+                f32::from(metrics.thickness) * scale,
+            ),
+            None => (default_thickness, default_thickness),
+        };
+
+        let (strikethrough_offset, strikethrough_thickness) = match face.strikeout_metrics() {
+            Some(metrics) => (
+                -f32::from(metrics.position) * scale,
+                f32::from(metrics.thickness) * scale,
+            ),
+            None => {
+                let x_height = face
+                    .x_height()
+                    .map_or(font_size / 2.0, |x_height| f32::from(x_height) * scale);
+                (-x_height / 2.0, default_thickness)
+            }
+        };
+
+        DecorationMetrics {
+            underline_offset,
+            underline_thickness,
+            strikethrough_offset,
+            strikethrough_thickness,
+        }
+    }
+
     #[cfg(feature = "swash")]
     pub fn as_swash(&self) -> swash::FontRef<'_> {
         let swash = &self.swash;
@@ -85,7 +173,7 @@ impl Font {
     pub fn new(db: &fontdb::Database, id: fontdb::ID) -> Option<Self> {
         let info = db.face(id)?;
 
-        let (monospace_em_width, scripts, unicode_codepoints) = {
+        let (monospace_em_width, scripts) = {
             db.with_face_data(id, |font_data, face_index| {
                 let face = ttf_parser::Face::parse(font_data, face_index).ok()?;
                 let monospace_em_width = info
@@ -101,34 +189,10 @@ impl Font {
                     None?;
                 }
 
-                let scripts = face
-                    .tables()
-                    .gpos
-                    .into_iter()
-                    .chain(face.tables().gsub)
-                    .flat_map(|table| table.scripts)
-                    .map(|script| script.tag.to_bytes())
-                    .collect();
+                // Fonts without a cmap cannot be used for shaping
+                face.tables().cmap?;
 
-                let mut unicode_codepoints = Vec::new();
-
-                face.tables()
-                    .cmap?
-                    .subtables
-                    .into_iter()
-                    .filter(|subtable| subtable.is_unicode())
-                    .for_each(|subtable| {
-                        unicode_codepoints.reserve(1024);
-                        subtable.codepoints(|code_point| {
-                            if subtable.glyph_index(code_point).is_some() {
-                                unicode_codepoints.push(code_point);
-                            }
-                        });
-                    });
-
-                unicode_codepoints.shrink_to_fit();
-
-                Some((monospace_em_width, scripts, unicode_codepoints))
+                Some((monospace_em_width, face_scripts(&face)))
             })?
         }?;
 
@@ -147,7 +211,7 @@ impl Font {
             id: info.id,
             monospace_em_width,
             scripts,
-            unicode_codepoints,
+            unicode_codepoints: OnceBox::new(),
             #[cfg(feature = "swash")]
             swash: {
                 let swash = swash::FontRef::from_index((*data).as_ref(), info.index as usize)?;
@@ -162,6 +226,17 @@ impl Font {
     }
 }
 
+/// Get the OpenType script tags of the GPOS and GSUB tables of a face
+pub(crate) fn face_scripts(face: &ttf_parser::Face) -> Vec<[u8; 4]> {
+    face.tables()
+        .gpos
+        .into_iter()
+        .chain(face.tables().gsub)
+        .flat_map(|table| table.scripts)
+        .map(|script| script.tag.to_bytes())
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -1,8 +1,15 @@
-use crate::{Attrs, Font, FontMatchAttrs, HashMap, ShapePlanCache};
+use super::face_scripts;
+use super::fallback::generic_substitutes;
+use crate::{
+    Attrs, AttrsList, BreakOverrides, DecorationMetrics, FamilyOwned, Font, FontMatchAttrs,
+    GenericFamily, HashMap, Justification, MathConstants, Metrics, RustybuzzShapingBackend,
+    ShapeBuffer, ShapeLine, ShapePlanCache, Shaping, Wrap,
+};
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::fmt;
+use core::mem;
 use core::ops::{Deref, DerefMut};
 
 // re-export fontdb and rustybuzz
@@ -35,12 +42,12 @@ impl FontCachedCodepointSupportInfo {
     #[inline(always)]
     fn unknown_has_codepoint(
         &mut self,
-        font_codepoints: &[u32],
+        font: &Font,
         codepoint: u32,
         supported_insert_pos: usize,
         not_supported_insert_pos: usize,
     ) -> bool {
-        let ret = font_codepoints.contains(&codepoint);
+        let ret = font.has_codepoint(codepoint);
         if ret {
             // don't bother inserting if we are going to truncate the entry away
             if supported_insert_pos != Self::SUPPORTED_MAX_SZ {
@@ -59,13 +66,13 @@ impl FontCachedCodepointSupportInfo {
     }
 
     #[inline(always)]
-    fn has_codepoint(&mut self, font_codepoints: &[u32], codepoint: u32) -> bool {
+    fn has_codepoint(&mut self, font: &Font, codepoint: u32) -> bool {
         match self.supported.binary_search(&codepoint) {
             Ok(_) => true,
             Err(supported_insert_pos) => match self.not_supported.binary_search(&codepoint) {
                 Ok(_) => false,
                 Err(not_supported_insert_pos) => self.unknown_has_codepoint(
-                    font_codepoints,
+                    font,
                     codepoint,
                     supported_insert_pos,
                     not_supported_insert_pos,
@@ -106,6 +113,29 @@ pub struct FontSystem {
     /// Cache for shaped runs
     #[cfg(feature = "shape-run-cache")]
     pub shape_run_cache: crate::ShapeRunCache,
+
+    /// True if platform font fallback lists are not used
+    deterministic: bool,
+
+    /// Families preferred for monospace fallback, in order of preference
+    monospace_fallback_families: Vec<String>,
+
+    /// Maximum number of entries in the font matches cache
+    font_matches_cache_size: usize,
+
+    /// Families used in order when no font has the family name used as the key, including the
+    /// platform defaults for [`GenericFamily`]
+    family_substitutes: HashMap<String, Vec<FamilyOwned>>,
+
+    /// Scratch buffer reused by [`crate::layout_label`]
+    label_scratch: ShapeBuffer,
+
+    /// Incremented when the font database may have changed
+    generation: u64,
+
+    /// Counters of work done, not including the shape plan cache counters
+    #[cfg(feature = "perf")]
+    perf_counters: crate::PerfCounters,
 }
 
 impl fmt::Debug for FontSystem {
@@ -127,24 +157,33 @@ impl FontSystem {
     /// while debug builds can take up to ten times longer. For this reason, it should only be
     /// called once, and the resulting [`FontSystem`] should be shared.
     pub fn new() -> Self {
-        Self::new_with_fonts(core::iter::empty())
+        FontSystemBuilder::new().build()
+    }
+
+    /// Create a [`FontSystemBuilder`] to configure which fonts are loaded
+    pub fn builder() -> FontSystemBuilder {
+        FontSystemBuilder::new()
     }
 
     /// Create a new [`FontSystem`] with a pre-specified set of fonts.
     pub fn new_with_fonts(fonts: impl IntoIterator<Item = fontdb::Source>) -> Self {
-        let locale = Self::get_locale();
-        log::debug!("Locale: {}", locale);
-
-        let mut db = fontdb::Database::new();
-
-        //TODO: configurable default fonts
-        db.set_monospace_family("Fira Mono");
-        db.set_sans_serif_family("Fira Sans");
-        db.set_serif_family("DejaVu Serif");
-
-        Self::load_fonts(&mut db, fonts.into_iter());
+        FontSystemBuilder::new().font_sources(fonts).build()
+    }
 
-        Self::new_with_locale_and_db(locale, db)
+    /// Create a new [`FontSystem`] that lays out text the same way on every platform, for
+    /// snapshot tests of layout output
+    ///
+    /// Only `fonts` are loaded, without scanning system fonts, the locale is always `en-US`,
+    /// and font fallback only uses `fonts`, in the order they are loaded, instead of the
+    /// platform fallback lists.
+    pub fn new_deterministic(fonts: impl IntoIterator<Item = fontdb::Source>) -> Self {
+        FontSystemBuilder::new()
+            .locale("en-US")
+            .system_fonts(false)
+            .embedded_fonts(&[])
+            .font_sources(fonts)
+            .deterministic(true)
+            .build()
     }
 
     /// Create a new [`FontSystem`] with a pre-specified locale and font database.
@@ -158,8 +197,6 @@ impl FontSystem {
             .collect::<Vec<_>>();
         monospace_font_ids.sort();
 
-        let cloned_monospace_font_ids = monospace_font_ids.clone();
-
         let mut ret = Self {
             locale,
             db,
@@ -169,20 +206,39 @@ impl FontSystem {
             font_matches_cache: Default::default(),
             font_codepoint_support_info_cache: Default::default(),
             shape_plan_cache: ShapePlanCache::default(),
+            deterministic: false,
+            monospace_fallback_families: Vec::new(),
+            font_matches_cache_size: Self::FONT_MATCHES_CACHE_SIZE_LIMIT,
+            family_substitutes: GenericFamily::ALL
+                .into_iter()
+                .map(|generic| (generic.name().into(), generic_substitutes(generic)))
+                .collect(),
+            label_scratch: ShapeBuffer::default(),
+            generation: 0,
             #[cfg(feature = "shape-run-cache")]
             shape_run_cache: crate::ShapeRunCache::default(),
+            #[cfg(feature = "perf")]
+            perf_counters: crate::PerfCounters::default(),
         };
-        ret.cache_fonts(cloned_monospace_font_ids.clone());
-        cloned_monospace_font_ids.into_iter().for_each(|id| {
-            if let Some(font) = ret.get_font(id) {
-                font.scripts().iter().copied().for_each(|script| {
-                    ret.per_script_monospace_font_ids
-                        .entry(script)
-                        .or_default()
-                        .push(font.id);
-                });
+        // Only the script tables of monospace fonts are read here, fonts are loaded and their
+        // faces parsed the first time they are used for shaping
+        for id in ret.monospace_font_ids.clone() {
+            #[cfg(feature = "std")]
+            unsafe {
+                ret.db.make_shared_face_data(id);
+            }
+            let scripts = ret.db.with_face_data(id, |font_data, face_index| {
+                ttf_parser::Face::parse(font_data, face_index)
+                    .ok()
+                    .map(|face| face_scripts(&face))
+            });
+            for script in scripts.flatten().unwrap_or_default() {
+                ret.per_script_monospace_font_ids
+                    .entry(script)
+                    .or_default()
+                    .push(id);
             }
-        });
+        }
         ret
     }
 
@@ -191,6 +247,12 @@ impl FontSystem {
         &self.locale
     }
 
+    /// True if this [`FontSystem`] was created with [`FontSystem::new_deterministic`] or
+    /// [`FontSystemBuilder::deterministic`]
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     /// Get the database.
     pub fn db(&self) -> &fontdb::Database {
         &self.db
@@ -201,12 +263,140 @@ impl FontSystem {
         &mut self.shape_plan_cache
     }
 
+    /// Get the counters of work done with this [`FontSystem`], see [`crate::PerfCounters`]
+    #[cfg(feature = "perf")]
+    pub fn perf_counters(&self) -> crate::PerfCounters {
+        crate::PerfCounters {
+            shape_plan_cache_hits: self.shape_plan_cache.hits,
+            shape_plan_cache_misses: self.shape_plan_cache.misses,
+            ..self.perf_counters
+        }
+    }
+
+    /// Set all counters of work done to zero
+    #[cfg(feature = "perf")]
+    pub fn reset_perf_counters(&mut self) {
+        self.perf_counters = crate::PerfCounters::default();
+        self.shape_plan_cache.hits = 0;
+        self.shape_plan_cache.misses = 0;
+    }
+
+    /// Measure a single line of text, returning its `(width, height, ascent, descent)`, without
+    /// creating a [`Buffer`](crate::Buffer)
+    ///
+    /// This is meant for quick measurements, like sizing buttons, tooltips, or table columns.
+    /// The text is not wrapped, and line endings do not start new lines. The height is the line
+    /// height of `metrics`, or the largest line height of a span with its own metrics. Nothing
+    /// is cached, but the scratch memory of [`crate::layout_label`] is reused.
+    pub fn measure_text(
+        &mut self,
+        text: &str,
+        attrs: Attrs,
+        metrics: Metrics,
+        shaping: Shaping,
+    ) -> (f32, f32, f32, f32) {
+        let mut scratch = mem::take(&mut self.label_scratch);
+        let shape = ShapeLine::new_in_buffer(
+            &mut scratch,
+            self,
+            text,
+            &AttrsList::new(attrs),
+            shaping,
+            8,
+            &BreakOverrides::default(),
+            &RustybuzzShapingBackend,
+        );
+        let mut lines = Vec::with_capacity(1);
+        shape.layout_to_buffer(
+            &mut scratch,
+            metrics.font_size,
+            None,
+            Wrap::None,
+            None,
+            &mut lines,
+            None,
+            Justification::Spaces,
+        );
+        self.label_scratch = scratch;
+
+        lines
+            .first()
+            .map_or((0.0, metrics.line_height, 0.0, 0.0), |line| {
+                (
+                    line.w,
+                    line.line_height_opt.unwrap_or(metrics.line_height),
+                    line.max_ascent,
+                    line.max_descent,
+                )
+            })
+    }
+
+    /// Get the scratch buffer used by [`crate::layout_label`]
+    pub(crate) fn label_scratch_mut(&mut self) -> &mut ShapeBuffer {
+        &mut self.label_scratch
+    }
+
+    /// Get the counters of work done to increment them
+    #[cfg(feature = "perf")]
+    pub(crate) fn perf_counters_mut(&mut self) -> &mut crate::PerfCounters {
+        &mut self.perf_counters
+    }
+
     /// Get a mutable reference to the database.
+    ///
+    /// This increments the [`FontSystem::generation`], so buffers shape their lines again with
+    /// the fonts that were added or removed.
     pub fn db_mut(&mut self) -> &mut fontdb::Database {
         self.font_matches_cache.clear();
+        self.fonts_changed();
         &mut self.db
     }
 
+    /// Get the families used in order when no font has the family `name`
+    pub fn family_substitutes(&self, name: &str) -> &[FamilyOwned] {
+        self.family_substitutes
+            .get(name)
+            .map_or(&[], |substitutes| substitutes.as_slice())
+    }
+
+    /// Set the families used in order when no font has the family `name`, an empty list removes
+    /// the substitution
+    ///
+    /// For example, `"Helvetica"` can be substituted with `"Liberation Sans"`, or
+    /// `"ui-monospace"` with [`FamilyOwned::Monospace`]. This increments the
+    /// [`FontSystem::generation`], so buffers shape their lines again with the new fonts.
+    pub fn set_family_substitutes(
+        &mut self,
+        name: impl Into<String>,
+        substitutes: Vec<FamilyOwned>,
+    ) {
+        let name = name.into();
+        if substitutes.is_empty() {
+            self.family_substitutes.remove(&name);
+        } else {
+            self.family_substitutes.insert(name, substitutes);
+        }
+        self.fonts_changed();
+    }
+
+    /// Invalidate shaping done with the previous fonts
+    fn fonts_changed(&mut self) {
+        #[cfg(feature = "shape-run-cache")]
+        {
+            self.shape_run_cache = crate::ShapeRunCache::default();
+        }
+        self.generation += 1;
+    }
+
+    /// Get the generation of the font database, which is incremented by every call to
+    /// [`FontSystem::db_mut`] and [`FontSystem::set_family_substitutes`]
+    ///
+    /// A [`Buffer`](crate::Buffer) remembers the generation its lines were shaped with, and
+    /// shapes them again when it is used with a newer generation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Consume this [`FontSystem`] and return the locale and database.
     pub fn into_locale_and_db(self) -> (String, fontdb::Database) {
         (self.locale, self.db)
@@ -279,10 +469,47 @@ impl FontSystem {
             .clone()
     }
 
+    /// Get the underline and strikethrough metrics of a font at `font_size`, see
+    /// [`Font::decoration_metrics`]
+    ///
+    /// Custom renderers can use this to draw decorations that match the font, returning None if
+    /// the font could not be loaded.
+    pub fn decoration_metrics(
+        &mut self,
+        id: fontdb::ID,
+        font_size: f32,
+    ) -> Option<DecorationMetrics> {
+        self.get_font(id)
+            .map(|font| font.decoration_metrics(font_size))
+    }
+
+    /// Get the constants of the OpenType MATH table of a font at `font_size`, see
+    /// [`Font::math_constants`]
+    ///
+    /// A formula layout layer can use these to place scripts, fractions and radicals, returning
+    /// None if the font could not be loaded or has no MATH table.
+    pub fn math_constants(&mut self, id: fontdb::ID, font_size: f32) -> Option<MathConstants> {
+        self.get_font(id)
+            .and_then(|font| font.math_constants(font_size))
+    }
+
     pub fn is_monospace(&self, id: fontdb::ID) -> bool {
         self.monospace_font_ids.binary_search(&id).is_ok()
     }
 
+    /// Get the position of the first family of a font in the preferred monospace fallback
+    /// families, or the number of preferred families if it is not one of them
+    pub(crate) fn monospace_fallback_rank(&self, id: fontdb::ID) -> usize {
+        let face = match self.db.face(id) {
+            Some(face) => face,
+            None => return self.monospace_fallback_families.len(),
+        };
+        self.monospace_fallback_families
+            .iter()
+            .position(|family| face.families.iter().any(|(name, _)| name == family))
+            .unwrap_or(self.monospace_fallback_families.len())
+    }
+
     pub fn get_monospace_ids_for_scripts(
         &self,
         scripts: impl Iterator<Item = [u8; 4]>,
@@ -303,20 +530,19 @@ impl FontSystem {
         word: &str,
     ) -> Option<usize> {
         self.get_font(id).map(|font| {
-            let code_points = font.unicode_codepoints();
             let cache = self
                 .font_codepoint_support_info_cache
                 .entry(id)
                 .or_insert_with(FontCachedCodepointSupportInfo::new);
             word.chars()
-                .filter(|ch| cache.has_codepoint(code_points, u32::from(*ch)))
+                .filter(|ch| cache.has_codepoint(&font, u32::from(*ch)))
                 .count()
         })
     }
 
     pub fn get_font_matches(&mut self, attrs: Attrs<'_>) -> Arc<Vec<FontMatchKey>> {
         // Clear the cache first if it reached the size limit
-        if self.font_matches_cache.len() >= Self::FONT_MATCHES_CACHE_SIZE_LIMIT {
+        if self.font_matches_cache.len() >= self.font_matches_cache_size {
             log::trace!("clear font mache cache");
             self.font_matches_cache.clear();
         }
@@ -365,31 +591,182 @@ impl FontSystem {
     fn get_locale() -> String {
         String::from("en-US")
     }
+}
 
+/// Builder for a [`FontSystem`], see [`FontSystem::builder`]
+///
+/// Fonts are loaded in this order: system fonts, font directories, font sources, then embedded
+/// fonts. Embedded fonts are font files compiled into the application, usually with
+/// [`include_bytes!`], so they can be used for fallback when the system has no fonts for some
+/// text.
+#[derive(Clone, Debug)]
+pub struct FontSystemBuilder {
+    locale_opt: Option<String>,
+    system_fonts: bool,
     #[cfg(feature = "std")]
-    fn load_fonts(db: &mut fontdb::Database, fonts: impl Iterator<Item = fontdb::Source>) {
-        #[cfg(not(target_arch = "wasm32"))]
+    font_dirs: Vec<std::path::PathBuf>,
+    font_sources: Vec<fontdb::Source>,
+    embedded_fonts: Vec<&'static [u8]>,
+    deterministic: bool,
+    monospace_fallback_families: Vec<String>,
+    font_matches_cache_size: usize,
+    family_substitutes: Vec<(String, Vec<FamilyOwned>)>,
+}
+
+impl FontSystemBuilder {
+    /// Fonts embedded by default, which is currently an empty set
+    pub const DEFAULT_EMBEDDED_FONTS: &'static [&'static [u8]] = &[];
+
+    /// Create a builder that loads system fonts and [`Self::DEFAULT_EMBEDDED_FONTS`]
+    pub fn new() -> Self {
+        Self {
+            locale_opt: None,
+            system_fonts: true,
+            #[cfg(feature = "std")]
+            font_dirs: Vec::new(),
+            font_sources: Vec::new(),
+            embedded_fonts: Self::DEFAULT_EMBEDDED_FONTS.to_vec(),
+            deterministic: false,
+            monospace_fallback_families: Vec::new(),
+            font_matches_cache_size: FontSystem::FONT_MATCHES_CACHE_SIZE_LIMIT,
+            family_substitutes: Vec::new(),
+        }
+    }
+
+    /// Set the locale, instead of detecting the system locale
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale_opt = Some(locale.into());
+        self
+    }
+
+    /// Set whether installed system fonts are loaded, which is ignored without the `std` feature
+    pub fn system_fonts(mut self, system_fonts: bool) -> Self {
+        self.system_fonts = system_fonts;
+        self
+    }
+
+    /// Load all fonts in a directory and its subdirectories
+    #[cfg(feature = "std")]
+    pub fn font_dir(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.font_dirs.push(path.into());
+        self
+    }
+
+    /// Load fonts from sources
+    pub fn font_sources(mut self, sources: impl IntoIterator<Item = fontdb::Source>) -> Self {
+        self.font_sources.extend(sources);
+        self
+    }
+
+    /// Replace the embedded fonts, an empty slice removes them entirely
+    pub fn embedded_fonts(mut self, fonts: &[&'static [u8]]) -> Self {
+        self.embedded_fonts = fonts.to_vec();
+        self
+    }
+
+    /// Add fonts after the current embedded fonts
+    pub fn add_embedded_fonts(mut self, fonts: &[&'static [u8]]) -> Self {
+        self.embedded_fonts.extend_from_slice(fonts);
+        self
+    }
+
+    /// Set whether font fallback only uses the loaded fonts, in the order they are loaded,
+    /// instead of the platform fallback lists, see [`FontSystem::new_deterministic`]
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Set the families preferred for monospace fallback, in order of preference
+    ///
+    /// When text in a monospace family needs a fallback font, monospace fonts supporting the
+    /// same number of characters are tried in this order before other monospace fonts.
+    pub fn monospace_fallback_families<I, S>(mut self, families: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.monospace_fallback_families = families.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the maximum number of entries in the font matches cache, which is cleared when full
+    pub fn font_matches_cache_size(mut self, size: usize) -> Self {
+        self.font_matches_cache_size = size;
+        self
+    }
+
+    /// Set the families used in order when no font has the family `name`, see
+    /// [`FontSystem::set_family_substitutes`]
+    pub fn family_substitutes(
+        mut self,
+        name: impl Into<String>,
+        substitutes: Vec<FamilyOwned>,
+    ) -> Self {
+        self.family_substitutes.push((name.into(), substitutes));
+        self
+    }
+
+    /// Load the fonts and create the [`FontSystem`]
+    pub fn build(self) -> FontSystem {
+        let locale = self.locale_opt.unwrap_or_else(FontSystem::get_locale);
+        log::debug!("Locale: {}", locale);
+
+        #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let now = std::time::Instant::now();
 
-        db.load_system_fonts();
+        let mut db = fontdb::Database::new();
+
+        //TODO: configurable default fonts
+        db.set_monospace_family("Fira Mono");
+        db.set_sans_serif_family("Fira Sans");
+        db.set_serif_family("DejaVu Serif");
 
-        for source in fonts {
+        #[cfg(feature = "std")]
+        {
+            if self.system_fonts {
+                db.load_system_fonts();
+            }
+            for dir in &self.font_dirs {
+                db.load_fonts_dir(dir);
+            }
+        }
+
+        for source in self.font_sources {
             db.load_font_source(source);
         }
 
-        #[cfg(not(target_arch = "wasm32"))]
+        for data in self.embedded_fonts {
+            db.load_font_source(fontdb::Source::Binary(Arc::new(data)));
+        }
+
+        #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         log::debug!(
             "Parsed {} font faces in {}ms.",
             db.len(),
             now.elapsed().as_millis()
         );
-    }
 
-    #[cfg(not(feature = "std"))]
-    fn load_fonts(db: &mut fontdb::Database, fonts: impl Iterator<Item = fontdb::Source>) {
-        for source in fonts {
-            db.load_font_source(source);
+        let mut font_system = FontSystem::new_with_locale_and_db(locale, db);
+        font_system.deterministic = self.deterministic;
+        if self.deterministic {
+            // Platform defaults for generic families are not used either
+            font_system.family_substitutes.clear();
         }
+        font_system.monospace_fallback_families = self.monospace_fallback_families;
+        font_system.font_matches_cache_size = self.font_matches_cache_size;
+        font_system.family_substitutes.extend(
+            self.family_substitutes
+                .into_iter()
+                .filter(|(_, substitutes)| !substitutes.is_empty()),
+        );
+        font_system
+    }
+}
+
+impl Default for FontSystemBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    Action, AnchorBias, AnchorId, Attrs, AttrsList, AttrsOwned, Buffer, BufferLine, Cursor, Edit,
+    EditHooks, FontSystem, Selection, Shaping,
+};
+
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// Format `number` using Unicode superscript digits
+fn superscript(number: usize) -> String {
+    let mut digits = Vec::new();
+    let mut number = number;
+    loop {
+        digits.push(SUPERSCRIPT_DIGITS[number % 10]);
+        number /= 10;
+        if number == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+/// Identifier of a footnote marker, see [`FootnoteMarkers::insert`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FootnoteId(usize);
+
+#[derive(Clone, Debug)]
+struct FootnoteMarker {
+    id: FootnoteId,
+    start: AnchorId,
+    end: AnchorId,
+    number: usize,
+    attrs: AttrsOwned,
+}
+
+/// Auto-numbered footnote markers in a [`Buffer`], displayed as superscript digits
+///
+/// Each marker is a span of text tracked with a pair of anchors. [`FootnoteMarkers::resolve`]
+/// numbers the markers in document order and only rewrites the markers whose number changed, so
+/// lines without changed markers keep their shaping.
+///
+/// Used as the [`EditHooks`] of a [`HookedEditor`](crate::HookedEditor), markers are edited
+/// atomically: the cursor skips over them, backspace and delete remove a whole marker, and
+/// numbering is resolved after every action. Numbering is derived state, so undo and redo
+/// restore the marker text and the next resolve fixes up the numbers.
+#[derive(Clone, Debug, Default)]
+pub struct FootnoteMarkers {
+    markers: Vec<FootnoteMarker>,
+    next_id: usize,
+    cursor_before: Cursor,
+}
+
+impl FootnoteMarkers {
+    /// Create an empty set of footnote markers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a new marker at `cursor` using `attrs`, and renumber all markers
+    pub fn insert(&mut self, buffer: &mut Buffer, cursor: Cursor, attrs: Attrs) -> FootnoteId {
+        let id = FootnoteId(self.next_id);
+        self.next_id += 1;
+        let cursor = Cursor::new(cursor.line, cursor.index);
+        let start = buffer.add_anchor(cursor, AnchorBias::After);
+        let end = buffer.add_anchor(cursor, AnchorBias::Before);
+        self.markers.push(FootnoteMarker {
+            id,
+            start,
+            end,
+            // Numbers start at 1, so this marker is always written by resolve
+            number: 0,
+            attrs: AttrsOwned::new(attrs),
+        });
+        self.resolve(buffer);
+        id
+    }
+
+    /// Remove a marker and its text, and renumber the remaining markers
+    ///
+    /// Returns false if there was no marker with this id.
+    pub fn remove(&mut self, buffer: &mut Buffer, id: FootnoteId) -> bool {
+        let i = match self.markers.iter().position(|marker| marker.id == id) {
+            Some(i) => i,
+            None => return false,
+        };
+        let marker = self.markers.remove(i);
+        if let (Some(start), Some(end)) = (
+            buffer.remove_anchor(marker.start),
+            buffer.remove_anchor(marker.end),
+        ) {
+            if start.line == end.line && start.index < end.index {
+                Self::replace(buffer, start, end, "", marker.attrs.as_attrs());
+            }
+        }
+        self.resolve(buffer);
+        true
+    }
+
+    /// Get the number of a marker, as of the last resolve
+    pub fn number(&self, id: FootnoteId) -> Option<usize> {
+        self.markers
+            .iter()
+            .find(|marker| marker.id == id)
+            .map(|marker| marker.number)
+    }
+
+    /// Get the start and end of the text of a marker
+    pub fn range(&self, buffer: &Buffer, id: FootnoteId) -> Option<(Cursor, Cursor)> {
+        let marker = self.markers.iter().find(|marker| marker.id == id)?;
+        Some((buffer.anchor(marker.start)?, buffer.anchor(marker.end)?))
+    }
+
+    /// Get the marker whose text contains `cursor`, including both ends
+    pub fn marker_at(&self, buffer: &Buffer, cursor: Cursor) -> Option<FootnoteId> {
+        self.find(buffer, |start, end| {
+            start <= pos(cursor) && pos(cursor) <= end
+        })
+        .map(|(id, _, _)| id)
+    }
+
+    /// Iterate over the ids and numbers of all markers in document order, as of the last resolve
+    pub fn iter(&self) -> impl Iterator<Item = (FootnoteId, usize)> + '_ {
+        self.markers.iter().map(|marker| (marker.id, marker.number))
+    }
+
+    /// Renumber markers in document order, returning true if any marker text was rewritten
+    ///
+    /// Markers whose text was deleted or split across lines are dropped. Only markers whose
+    /// number or text changed are rewritten, so only their lines need to be shaped again.
+    pub fn resolve(&mut self, buffer: &mut Buffer) -> bool {
+        let mut positions = Vec::with_capacity(self.markers.len());
+        for marker in self.markers.drain(..) {
+            match (buffer.anchor(marker.start), buffer.anchor(marker.end)) {
+                (Some(start), Some(end))
+                    if start.line == end.line
+                        && (start.index < end.index || marker.number == 0) =>
+                {
+                    positions.push(((pos(start), pos(end)), marker));
+                }
+                _ => {
+                    buffer.remove_anchor(marker.start);
+                    buffer.remove_anchor(marker.end);
+                }
+            }
+        }
+        positions.sort_by_key(|(range, marker)| (*range, marker.id));
+
+        let mut changed = false;
+        for (i, (_, mut marker)) in positions.into_iter().enumerate() {
+            let number = i + 1;
+            let text = superscript(number);
+            // Positions are read again, as rewriting earlier markers may have moved this one
+            let (start, end) = match (buffer.anchor(marker.start), buffer.anchor(marker.end)) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            if marker.number != number
+                || buffer.lines[start.line].text().get(start.index..end.index) != Some(&text)
+            {
+                buffer.remove_anchor(marker.start);
+                buffer.remove_anchor(marker.end);
+                let end = Self::replace(buffer, start, end, &text, marker.attrs.as_attrs());
+                marker.start = buffer.add_anchor(start, AnchorBias::After);
+                marker.end = buffer.add_anchor(end, AnchorBias::Before);
+                marker.number = number;
+                changed = true;
+            }
+            self.markers.push(marker);
+        }
+        if changed {
+            buffer.set_redraw(true);
+        }
+        changed
+    }
+
+    /// Replace the text from `start` to `end` on one line, returning the new end
+    fn replace(
+        buffer: &mut Buffer,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs: Attrs,
+    ) -> Cursor {
+        let line = &mut buffer.lines[start.line];
+        let after = line.split_off(end.index);
+        line.split_off(start.index);
+        let ending = line.ending();
+        line.append(BufferLine::new(
+            text,
+            ending,
+            AttrsList::new(attrs),
+            Shaping::Advanced,
+        ));
+        line.append(after);
+
+        let new_end = Cursor::new(start.line, start.index + text.len());
+        buffer.shift_anchors_delete(start, end);
+        buffer.shift_anchors_insert(start, new_end);
+        new_end
+    }
+
+    /// Find the first marker matching `f`, which is called with the start and end positions
+    fn find<F: Fn((usize, usize), (usize, usize)) -> bool>(
+        &self,
+        buffer: &Buffer,
+        f: F,
+    ) -> Option<(FootnoteId, Cursor, Cursor)> {
+        self.markers.iter().find_map(|marker| {
+            let start = buffer.anchor(marker.start)?;
+            let end = buffer.anchor(marker.end)?;
+            f(pos(start), pos(end)).then_some((marker.id, start, end))
+        })
+    }
+}
+
+/// A position tracked across [`FootnoteMarkers::resolve`]
+enum Tracked {
+    /// At the end of a marker, which moves with the rewritten marker text
+    MarkerEnd(FootnoteId),
+    /// Anywhere else
+    Anchor(AnchorId),
+}
+
+impl FootnoteMarkers {
+    fn track(&self, buffer: &mut Buffer, cursor: Cursor) -> Tracked {
+        let c = pos(cursor);
+        match self.find(buffer, |start, end| start < c && c == end) {
+            Some((id, _, _)) => Tracked::MarkerEnd(id),
+            None => Tracked::Anchor(buffer.add_anchor(cursor, AnchorBias::Before)),
+        }
+    }
+
+    fn untrack(&self, buffer: &mut Buffer, tracked: Tracked) -> Option<Cursor> {
+        match tracked {
+            Tracked::MarkerEnd(id) => self.range(buffer, id).map(|(_, end)| end),
+            Tracked::Anchor(anchor) => buffer.remove_anchor(anchor),
+        }
+    }
+}
+
+fn pos(cursor: Cursor) -> (usize, usize) {
+    (cursor.line, cursor.index)
+}
+
+impl<'buffer, E: Edit<'buffer>> EditHooks<E> for FootnoteMarkers {
+    fn pre_action(
+        &mut self,
+        editor: &mut E,
+        _font_system: &mut FontSystem,
+        action: Action,
+    ) -> Option<Action> {
+        let cursor = editor.cursor();
+        self.cursor_before = cursor;
+        let c = pos(cursor);
+        let inside = editor.with_buffer(|buffer| match action {
+            Action::Backspace => self.find(buffer, |start, end| start < c && c <= end),
+            Action::Delete => self.find(buffer, |start, end| start <= c && c < end),
+            Action::Insert(_) | Action::Enter => {
+                self.find(buffer, |start, end| start < c && c < end)
+            }
+            _ => None,
+        });
+        if let Some((_, start, end)) = inside {
+            match action {
+                Action::Backspace | Action::Delete => {
+                    if editor.selection() == Selection::None {
+                        // Select the whole marker so it is deleted at once
+                        editor.set_selection(Selection::Normal(start));
+                        editor.set_cursor(end);
+                    }
+                }
+                _ => editor.set_cursor(end),
+            }
+        }
+        Some(action)
+    }
+
+    fn post_action(&mut self, editor: &mut E, _font_system: &mut FontSystem, _action: Action) {
+        let cursor = editor.cursor();
+        let c = pos(cursor);
+        let inside =
+            editor.with_buffer(|buffer| self.find(buffer, |start, end| start < c && c < end));
+        if let Some((_, start, end)) = inside {
+            // Snap to the side of the marker in the direction the cursor moved
+            if c > pos(self.cursor_before) {
+                editor.set_cursor(end);
+            } else {
+                editor.set_cursor(start);
+            }
+        }
+
+        // Track the cursor and selection while markers are rewritten
+        let cursor = editor.cursor();
+        let select = match editor.selection() {
+            Selection::Normal(select) => Some(select),
+            _ => None,
+        };
+        let changed = editor.with_buffer_mut(|buffer| {
+            let cursor_track = self.track(buffer, cursor);
+            let select_track = select.map(|select| self.track(buffer, select));
+            let changed = self.resolve(buffer);
+            let cursor = self.untrack(buffer, cursor_track);
+            let select = select_track.and_then(|track| self.untrack(buffer, track));
+            changed.then_some((cursor, select))
+        });
+        if let Some((cursor, select)) = changed {
+            if let Some(cursor) = cursor {
+                editor.set_cursor(cursor);
+            }
+            if let Some(select) = select {
+                editor.set_selection(Selection::Normal(select));
+            }
+        }
+    }
+}
@@ -11,6 +11,9 @@ bitflags::bitflags! {
 }
 
 /// Key for building a glyph cache
+///
+/// The key does not contain a color, so changing the color of text uses the same glyph images,
+/// see [`SwashCache::image_content`](crate::SwashCache::image_content).
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct CacheKey {
     /// Font ID
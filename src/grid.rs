@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::vec::Vec;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{math, LayoutLine, Wrap};
+
+/// Ranges of characters that are wide in East Asian text or presented as emoji by default
+static WIDE_RANGES: &[(u32, u32)] = &[
+    (0x1100, 0x115F),
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA),
+    (0x26F2, 0x26F3),
+    (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x274C, 0x274C),
+    (0x274E, 0x274E),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE10, 0xFE19),
+    (0xFE30, 0xFE6F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x16FE0, 0x16FE4),
+    (0x17000, 0x18AFF),
+    (0x1B000, 0x1B2FF),
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F200, 0x1F2FF),
+    (0x1F300, 0x1F64F),
+    (0x1F680, 0x1F6FF),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F90C, 0x1F9FF),
+    (0x1FA70, 0x1FAFF),
+    (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+];
+
+fn is_wide(c: char) -> bool {
+    let c = c as u32;
+    WIDE_RANGES
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                core::cmp::Ordering::Less
+            } else if start > c {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Get the number of cells `text` occupies in a monospace grid, see [`Buffer::set_cell_width`]
+///
+/// Each grapheme takes two cells if it is wide in East Asian text or presented as an emoji, and
+/// one cell otherwise. This is an approximation of the Unicode East Asian Width property.
+///
+/// [`Buffer::set_cell_width`]: crate::Buffer::set_cell_width
+pub fn cell_count(text: &str) -> usize {
+    text.graphemes(true)
+        .map(|grapheme| {
+            let wide =
+                grapheme.chars().next().map_or(false, is_wide) || grapheme.contains('\u{FE0F}');
+            if wide {
+                2
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+/// Snap an unwrapped layout line to a grid of `cell_width` cells, wrapping at the last whole cell
+/// that fits in `width_opt` like a terminal
+pub(crate) fn grid_layout(
+    line: LayoutLine,
+    text: &str,
+    cell_width: f32,
+    width_opt: Option<f32>,
+    wrap: Wrap,
+) -> Vec<LayoutLine> {
+    let columns_opt = match (wrap, width_opt) {
+        (Wrap::None, _) | (_, None) => None,
+        (_, Some(width)) => Some(math::floorf(width / cell_width).max(1.0) as usize),
+    };
+
+    // Clusters as glyph index ranges, in visual order
+    let mut glyphs = line.glyphs;
+    glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+    let mut clusters = Vec::new();
+    let mut cluster_start = 0;
+    for i in 1..=glyphs.len() {
+        if i == glyphs.len()
+            || glyphs[i].start != glyphs[cluster_start].start
+            || glyphs[i].end != glyphs[cluster_start].end
+        {
+            clusters.push(cluster_start..i);
+            cluster_start = i;
+        }
+    }
+
+    let new_line = |glyphs: Vec<_>, columns: usize| LayoutLine {
+        w: columns as f32 * cell_width,
+        max_ascent: line.max_ascent,
+        max_descent: line.max_descent,
+        line_height_opt: line.line_height_opt,
+        glyphs,
+    };
+
+    let mut lines = Vec::with_capacity(1);
+    let mut row = Vec::new();
+    let mut column = 0;
+    for range in clusters {
+        let first = &glyphs[range.start];
+        let cells = if text.get(first.start..first.end) == Some("\t") {
+            math::roundf(first.w / cell_width).max(1.0) as usize
+        } else {
+            cell_count(text.get(first.start..first.end).unwrap_or_default()).max(1)
+        };
+
+        if let Some(columns) = columns_opt {
+            if column > 0 && column + cells > columns {
+                lines.push(new_line(core::mem::take(&mut row), column));
+                column = 0;
+            }
+        }
+
+        // Every glyph of the cluster covers its cells, drawing is centered in them with offsets
+        let cluster_x = first.x;
+        let cluster_w = glyphs[range.clone()]
+            .iter()
+            .map(|glyph| glyph.x + glyph.w - cluster_x)
+            .fold(0.0, f32::max);
+        let x = column as f32 * cell_width;
+        let w = cells as f32 * cell_width;
+        let padding = (w - cluster_w) / 2.0;
+        for glyph in &glyphs[range] {
+            let mut glyph = glyph.clone();
+            glyph.x_offset += (glyph.x - cluster_x + padding) / glyph.font_size;
+            glyph.x = x;
+            glyph.w = w;
+            row.push(glyph);
+        }
+        column += cells;
+    }
+    lines.push(new_line(row, column));
+
+    lines
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::vec::Vec;
+use core::{iter, mem};
+
+/// Heights of the lines of a [`Buffer`](crate::Buffer), stored in a Fenwick tree so that the
+/// position of a line and the line at a position can be found in O(log n)
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HeightIndex {
+    heights: Vec<f32>,
+    tree: Vec<f32>,
+}
+
+impl HeightIndex {
+    /// Build an index from line heights in O(n)
+    pub fn new(heights: Vec<f32>) -> Self {
+        let mut tree = heights.clone();
+        for i in 0..tree.len() {
+            let parent = i | (i + 1);
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+        Self { heights, tree }
+    }
+
+    /// Number of lines in the index
+    pub fn len(&self) -> usize {
+        self.heights.len()
+    }
+
+    /// Set the height of a line
+    pub fn set(&mut self, line_i: usize, height: f32) {
+        let old = match self.heights.get_mut(line_i) {
+            Some(some) => some,
+            None => return,
+        };
+        let delta = height - *old;
+        if delta == 0.0 {
+            return;
+        }
+        *old = height;
+        let mut i = line_i;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i |= i + 1;
+        }
+    }
+
+    /// Replace `removed` lines starting at `line_i` with `inserted` lines of `height`, rebuilding
+    /// the index in O(n)
+    pub fn splice(&mut self, line_i: usize, removed: usize, inserted: usize, height: f32) {
+        let mut heights = mem::take(&mut self.heights);
+        let start = line_i.min(heights.len());
+        let end = start.saturating_add(removed).min(heights.len());
+        heights.splice(start..end, iter::repeat(height).take(inserted));
+        *self = Self::new(heights);
+    }
+
+    /// Get the sum of the heights of the lines before `line_i`
+    pub fn prefix(&self, line_i: usize) -> f32 {
+        let mut sum = 0.0;
+        let mut i = line_i.min(self.tree.len());
+        while i > 0 {
+            sum += self.tree[i - 1];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// Get the sum of the heights of all lines
+    pub fn total(&self) -> f32 {
+        self.prefix(self.len())
+    }
+
+    /// Find the line containing `y`, or `None` if `y` is outside of all lines
+    pub fn find(&self, y: f32) -> Option<usize> {
+        if y < 0.0 {
+            return None;
+        }
+
+        // Descend the tree, keeping the sum of the lines before `pos` at most `y`
+        let mut pos = 0;
+        let mut remaining = y;
+        let mut step = self.tree.len().checked_next_power_of_two()?;
+        while step > 0 {
+            if pos + step <= self.tree.len() && self.tree[pos + step - 1] <= remaining {
+                pos += step;
+                remaining -= self.tree[pos - 1];
+            }
+            step /= 2;
+        }
+
+        if pos < self.len() {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_height_index() {
+    let heights = [20.0, 40.0, 20.0, 0.0, 30.0, 20.0, 20.0];
+    let mut index = HeightIndex::new(heights.to_vec());
+    assert_eq!(index.total(), 150.0);
+
+    let mut y = 0.0;
+    for (line_i, height) in heights.iter().enumerate() {
+        assert_eq!(index.prefix(line_i), y);
+        if *height > 0.0 {
+            assert_eq!(index.find(y), Some(line_i));
+            assert_eq!(index.find(y + height - 1.0), Some(line_i));
+        }
+        y += height;
+    }
+    assert_eq!(index.find(-1.0), None);
+    assert_eq!(index.find(150.0), None);
+
+    index.set(1, 10.0);
+    assert_eq!(index.total(), 120.0);
+    assert_eq!(index.prefix(5), 80.0);
+    assert_eq!(index.find(30.0), Some(2));
+    assert_eq!(index.find(85.0), Some(5));
+
+    index.splice(1, 2, 1, 5.0);
+    assert_eq!(index.len(), 6);
+    assert_eq!(index.total(), 95.0);
+    assert_eq!(index.prefix(2), 25.0);
+    assert_eq!(index.find(25.0), Some(3));
+
+    assert_eq!(HeightIndex::default().find(0.0), None);
+}
@@ -0,0 +1,163 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::{
+    Attrs, AttrsList, BreakOverrides, FontSystem, Justification, LayoutLine, Metrics,
+    RustybuzzShapingBackend, ShapeLine, Shaping, Wrap,
+};
+
+/// A laid out label, see [`layout_label`]
+#[derive(Clone, Debug)]
+pub struct LabelLayout {
+    /// Laid out lines, more than one if the label was wrapped
+    pub lines: Vec<LayoutLine>,
+    /// True if the paragraph direction is right to left
+    pub rtl: bool,
+    /// Width of the widest line
+    pub w: f32,
+    /// Total height of all lines
+    pub h: f32,
+    line_height: f32,
+}
+
+impl LabelLayout {
+    /// Iterate over the lines together with the top of each line and the Y position of its
+    /// baseline, matching [`LayoutRun::line_top`] and [`LayoutRun::line_y`]
+    ///
+    /// [`LayoutRun::line_top`]: crate::LayoutRun::line_top
+    /// [`LayoutRun::line_y`]: crate::LayoutRun::line_y
+    pub fn lines_with_position(&self) -> impl Iterator<Item = (&LayoutLine, f32, f32)> + '_ {
+        let mut line_top = 0.0;
+        self.lines.iter().map(move |layout_line| {
+            let line_height = layout_line.line_height_opt.unwrap_or(self.line_height);
+            let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+            let centering_offset = (line_height - glyph_height) / 2.0;
+            let top = line_top;
+            line_top += line_height;
+            (
+                layout_line,
+                top,
+                top + centering_offset + layout_line.max_ascent,
+            )
+        })
+    }
+
+    /// Draw the label with its top left corner at `(x, y)`, see [`Buffer::draw`]
+    ///
+    /// [`Buffer::draw`]: crate::Buffer::draw
+    #[cfg(feature = "swash")]
+    pub fn draw<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        x: f32,
+        y: f32,
+        color: crate::Color,
+        mut f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, crate::Color),
+    {
+        for (layout_line, _line_top, line_y) in self.lines_with_position() {
+            for glyph in layout_line.glyphs.iter() {
+                let physical_glyph = glyph.physical((x, y + line_y), 1.0);
+                let glyph_color = glyph.color_opt.unwrap_or(color);
+                cache.with_pixels(
+                    font_system,
+                    physical_glyph.cache_key,
+                    glyph_color,
+                    |x, y, color| {
+                        f(physical_glyph.x + x, physical_glyph.y + y, 1, 1, color);
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Shape and lay out a single paragraph of static text, without creating a [`Buffer`]
+///
+/// This is a lighter alternative to a [`Buffer`] for labels in widget toolkits. The text is
+/// shaped with [`Shaping::Advanced`] and wrapped with [`Wrap::WordOrGlyph`] if `max_width` is
+/// set. Line endings in `text` do not start new paragraphs, use a [`Buffer`] for that. Scratch
+/// memory is kept in the [`FontSystem`] and reused by later calls.
+///
+/// [`Buffer`]: crate::Buffer
+pub fn layout_label(
+    font_system: &mut FontSystem,
+    text: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+    max_width: Option<f32>,
+) -> LabelLayout {
+    let mut scratch = mem::take(font_system.label_scratch_mut());
+
+    let shape = ShapeLine::new_in_buffer(
+        &mut scratch,
+        font_system,
+        text,
+        &AttrsList::new(attrs),
+        Shaping::Advanced,
+        8,
+        &BreakOverrides::default(),
+        &RustybuzzShapingBackend,
+    );
+    let mut lines = Vec::with_capacity(1);
+    shape.layout_to_buffer(
+        &mut scratch,
+        metrics.font_size,
+        max_width,
+        Wrap::WordOrGlyph,
+        None,
+        &mut lines,
+        None,
+        Justification::Spaces,
+    );
+
+    *font_system.label_scratch_mut() = scratch;
+
+    let mut w: f32 = 0.0;
+    let mut h = 0.0;
+    for layout_line in lines.iter() {
+        w = w.max(layout_line.w);
+        h += layout_line.line_height_opt.unwrap_or(metrics.line_height);
+    }
+
+    LabelLayout {
+        lines,
+        rtl: shape.rtl,
+        w,
+        h,
+        line_height: metrics.line_height,
+    }
+}
+
+/// Lay out and draw a single line of text with its top left corner at `(x, y)`, returning its
+/// width and height
+///
+/// This is an immediate mode helper for overlays, debug displays, and simple games, which
+/// draw text every frame without keeping a [`Buffer`]. The text is laid out with
+/// [`layout_label`] without wrapping and drawn with [`LabelLayout::draw`]. Glyphs are rasterized
+/// with `cache`, so keep it between frames.
+///
+/// [`Buffer`]: crate::Buffer
+#[cfg(feature = "swash")]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text<F>(
+    font_system: &mut FontSystem,
+    cache: &mut crate::SwashCache,
+    x: f32,
+    y: f32,
+    text: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+    color: crate::Color,
+    f: F,
+) -> (f32, f32)
+where
+    F: FnMut(i32, i32, u32, u32, crate::Color),
+{
+    let label = layout_label(font_system, text, attrs, metrics, None);
+    label.draw(font_system, cache, x, y, color, f);
+    (label.w, label.h)
+}
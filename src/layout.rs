@@ -1,11 +1,15 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use core::fmt::Display;
+use core::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-use crate::{math, CacheKey, CacheKeyFlags, Color};
+use crate::{
+    math, Attrs, AttrsOwned, CacheKey, CacheKeyFlags, Color, Font, FontSystem, TextAnimation,
+};
 
 /// A laid out glyph
 #[derive(Clone, Debug)]
@@ -50,12 +54,31 @@ pub struct LayoutGlyph {
     pub y_offset: f32,
     /// Optional color override
     pub color_opt: Option<Color>,
+    /// Optional palette color index, see [`LayoutGlyph::color`]
+    pub palette_index_opt: Option<u16>,
+    /// Optional animated emphasis, see [`TextAnimation`]
+    pub animation_opt: Option<TextAnimation>,
+    /// Optional inline decoration identifier, see [`Attrs::decoration`](crate::Attrs::decoration)
+    pub decoration_id_opt: Option<u32>,
     /// Metadata from `Attrs`
     pub metadata: usize,
     /// [`CacheKeyFlags`]
     pub cache_key_flags: CacheKeyFlags,
 }
 
+/// A grapheme inside the cluster of a [`LayoutGlyph`], see [`LayoutGlyph::sub_clusters`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubCluster {
+    /// Start index of grapheme in original line
+    pub start: usize,
+    /// End index of grapheme in original line
+    pub end: usize,
+    /// X offset of hitbox
+    pub x: f32,
+    /// Width of hitbox
+    pub w: f32,
+}
+
 #[derive(Clone, Debug)]
 pub struct PhysicalGlyph {
     /// Cache key, see [CacheKey]
@@ -66,24 +89,156 @@ pub struct PhysicalGlyph {
     pub y: i32,
 }
 
+/// A transient render-time transform of a glyph, see [`Buffer::set_glyph_effect`]
+///
+/// Effects are applied when converting a glyph with [`LayoutGlyph::physical_with_effect`], so
+/// they can change every frame for animations without shaping or laying out lines again.
+///
+/// [`Buffer::set_glyph_effect`]: crate::Buffer::set_glyph_effect
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphEffect {
+    /// Horizontal offset, in unscaled pixels
+    pub x: f32,
+    /// Vertical offset, in unscaled pixels, with positive values moving the glyph down
+    pub y: f32,
+    /// Scale of the glyph around its origin on the baseline
+    pub scale: f32,
+}
+
+impl Default for GlyphEffect {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
 impl LayoutGlyph {
+    /// Update the properties used for drawing from `attrs`, see [`Attrs::same_layout`]
+    pub(crate) fn set_paint(&mut self, attrs: &Attrs) {
+        self.color_opt = attrs.color_opt;
+        self.palette_index_opt = attrs.palette_index_opt;
+        self.animation_opt = attrs.animation_opt;
+        self.decoration_id_opt = attrs.decoration_id_opt;
+        self.metadata = attrs.metadata;
+    }
+
     pub fn physical(&self, offset: (f32, f32), scale: f32) -> PhysicalGlyph {
-        let x_offset = self.font_size * self.x_offset;
-        let y_offset = self.font_size * self.y_offset;
+        self.physical_with_effect(offset, scale, GlyphEffect::default())
+    }
+
+    /// Like [`LayoutGlyph::physical`], moving and scaling the glyph by `effect`
+    pub fn physical_with_effect(
+        &self,
+        offset: (f32, f32),
+        scale: f32,
+        effect: GlyphEffect,
+    ) -> PhysicalGlyph {
+        let font_size = self.font_size * effect.scale;
+        let x_offset = font_size * self.x_offset;
+        let y_offset = font_size * self.y_offset;
 
         let (cache_key, x, y) = CacheKey::new(
             self.font_id,
             self.glyph_id,
-            self.font_size * scale,
+            font_size * scale,
             (
-                (self.x + x_offset) * scale + offset.0,
-                math::truncf((self.y - y_offset) * scale + offset.1), // Hinting in Y axis
+                (self.x + x_offset + effect.x) * scale + offset.0,
+                math::truncf((self.y - y_offset + effect.y) * scale + offset.1), // Hinting in Y axis
             ),
             self.cache_key_flags,
         );
 
         PhysicalGlyph { cache_key, x, y }
     }
+
+    /// Color of the glyph, taken from `palette` if [`Self::palette_index_opt`] is within it,
+    /// otherwise from [`Self::color_opt`]
+    pub fn color(&self, palette: &[Color]) -> Option<Color> {
+        self.palette_index_opt
+            .and_then(|index| palette.get(usize::from(index)).copied())
+            .or(self.color_opt)
+    }
+
+    /// Byte range of the cluster in the original line
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Split the cluster into its graphemes, dividing the width of the glyph evenly
+    ///
+    /// A ligature such as "fi" is a single glyph covering multiple graphemes, the returned
+    /// [`SubCluster`]s allow carets to be placed inside of it. `line` must be the text the glyph
+    /// was laid out from, such as [`LayoutRun::text`](crate::LayoutRun::text). Sub-clusters are
+    /// returned in logical order, so the first one is on the right for right-to-left glyphs.
+    pub fn sub_clusters<'a>(&self, line: &'a str) -> impl Iterator<Item = SubCluster> + 'a {
+        let cluster = line.get(self.start..self.end).unwrap_or_default();
+        let total = cluster.graphemes(true).count().max(1);
+        let w = self.w / total as f32;
+        let (start, x, end_x, rtl) = (self.start, self.x, self.x + self.w, self.level.is_rtl());
+        cluster
+            .grapheme_indices(true)
+            .enumerate()
+            .map(move |(i, (egc_i, egc))| SubCluster {
+                start: start + egc_i,
+                end: start + egc_i + egc.len(),
+                x: if rtl {
+                    end_x - (i + 1) as f32 * w
+                } else {
+                    x + i as f32 * w
+                },
+                w,
+            })
+    }
+}
+
+/// Extents of the ink of glyph outlines, in pixels
+///
+/// X is relative to the start of the line and Y is relative to the baseline, pointing down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InkExtents {
+    /// Minimum X of ink
+    pub x_min: f32,
+    /// Minimum Y of ink, negative values are above the baseline
+    pub y_min: f32,
+    /// Maximum X of ink
+    pub x_max: f32,
+    /// Maximum Y of ink, positive values are below the baseline
+    pub y_max: f32,
+}
+
+impl InkExtents {
+    /// Get the smallest extents containing both `self` and `other`
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+
+    /// Scale by `scale`, then move by `x` and `y`
+    pub fn transform(self, scale: f32, x: f32, y: f32) -> Self {
+        Self {
+            x_min: self.x_min * scale + x,
+            y_min: self.y_min * scale + y,
+            x_max: self.x_max * scale + x,
+            y_max: self.y_max * scale + y,
+        }
+    }
+
+    /// Width of ink
+    pub fn width(&self) -> f32 {
+        self.x_max - self.x_min
+    }
+
+    /// Height of ink
+    pub fn height(&self) -> f32 {
+        self.y_max - self.y_min
+    }
 }
 
 /// A line of laid out glyphs
@@ -101,6 +256,49 @@ pub struct LayoutLine {
     pub glyphs: Vec<LayoutGlyph>,
 }
 
+impl LayoutLine {
+    /// Get the extents of the glyph outlines in the line, including overhangs outside of the
+    /// glyph advances, or None if no glyph in the line has an outline
+    ///
+    /// Outlines are looked up in the fonts of the glyphs on every call, so this costs nothing
+    /// for layouts that never need ink extents.
+    pub fn ink(&self, font_system: &mut FontSystem) -> Option<InkExtents> {
+        self.glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let font = font_system.get_font(glyph.font_id)?;
+                let ink = glyph_ink(&font, glyph.glyph_id, glyph.cache_key_flags)?;
+                Some(ink.transform(
+                    glyph.font_size,
+                    glyph.x + glyph.font_size * glyph.x_offset,
+                    glyph.y - glyph.font_size * glyph.y_offset,
+                ))
+            })
+            .reduce(InkExtents::union)
+    }
+}
+
+/// Get the ink extents of a glyph outline in em units, accounting for fake italic skew
+fn glyph_ink(font: &Font, glyph_id: u16, cache_key_flags: CacheKeyFlags) -> Option<InkExtents> {
+    let face = font.rustybuzz();
+    let rect = face.glyph_bounding_box(rustybuzz::ttf_parser::GlyphId(glyph_id))?;
+    let font_scale = face.units_per_em() as f32;
+    let (x_min, x_max) = (rect.x_min as f32, rect.x_max as f32);
+    let (y_min, y_max) = (rect.y_min as f32, rect.y_max as f32);
+    // Same skew as used when rendering, in font units with Y pointing up
+    let skew = if cache_key_flags.contains(CacheKeyFlags::FAKE_ITALIC) {
+        0.249_328
+    } else {
+        0.0
+    };
+    Some(InkExtents {
+        x_min: (x_min + skew * y_min.min(0.0)) / font_scale,
+        y_min: -y_max / font_scale,
+        x_max: (x_max + skew * y_max.max(0.0)) / font_scale,
+        y_max: -y_min / font_scale,
+    })
+}
+
 /// Wrapping mode
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum Wrap {
@@ -125,6 +323,51 @@ impl Display for Wrap {
     }
 }
 
+/// Where a [`WrapIndicator`] is shown on soft wrapped lines
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum WrapIndicatorPosition {
+    /// After the text of each visual line that wraps onto the next one
+    #[default]
+    End,
+    /// Before the text of each visual line that continues the previous one
+    Start,
+}
+
+/// A glyph shown where lines are soft wrapped, see [`Buffer::set_wrap_indicator`]
+///
+/// The glyph is reported with [`LayoutRun::wrap_indicator_opt`] and drawn just outside of the
+/// text, so there should be room for it, for example with padding. It is not part of the text,
+/// so it is not selected or hit by cursors.
+///
+/// [`Buffer::set_wrap_indicator`]: crate::Buffer::set_wrap_indicator
+/// [`LayoutRun::wrap_indicator_opt`]: crate::LayoutRun::wrap_indicator_opt
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct WrapIndicator {
+    /// Character to show, like `'↩'` or `'⤷'`
+    pub character: char,
+    /// Where to show the character
+    pub position: WrapIndicatorPosition,
+    /// Attributes to shape the character with, the font size is taken from the wrapped line
+    pub attrs: AttrsOwned,
+}
+
+impl WrapIndicator {
+    /// Create a new [`WrapIndicator`] showing `character` at `position` with default attributes
+    pub fn new(character: char, position: WrapIndicatorPosition) -> Self {
+        Self {
+            character,
+            position,
+            attrs: AttrsOwned::new(Attrs::new()),
+        }
+    }
+
+    /// Set the attributes to shape the character with, for example to dim its color
+    pub fn attrs(mut self, attrs: Attrs) -> Self {
+        self.attrs = AttrsOwned::new(attrs);
+        self
+    }
+}
+
 /// Align or justify
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum Align {
@@ -146,3 +389,76 @@ impl Display for Align {
         }
     }
 }
+
+/// Method used to fill lines aligned with [`Align::Justified`]
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum Justification {
+    /// Expand the spaces between words
+    #[default]
+    Spaces,
+    /// Elongate joined Arabic letters with kashida (U+0640 ARABIC TATWEEL), expanding spaces
+    /// instead on lines without any kashida insertion points
+    Kashida,
+}
+
+impl Display for Justification {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Spaces => write!(f, "Spaces"),
+            Self::Kashida => write!(f, "Kashida"),
+        }
+    }
+}
+
+#[test]
+fn test_sub_clusters() {
+    let glyph = |start, end, level| LayoutGlyph {
+        start,
+        end,
+        font_size: 10.0,
+        line_height_opt: None,
+        font_id: fontdb::ID::dummy(),
+        glyph_id: 0,
+        x: 10.0,
+        y: 0.0,
+        w: 30.0,
+        level: unicode_bidi::Level::new(level).expect("invalid bidi level"),
+        x_offset: 0.0,
+        y_offset: 0.0,
+        color_opt: None,
+        palette_index_opt: None,
+        animation_opt: None,
+        decoration_id_opt: None,
+        metadata: 0,
+        cache_key_flags: CacheKeyFlags::empty(),
+    };
+    let part = |start, end, x, w| SubCluster { start, end, x, w };
+
+    // Ligature with three graphemes
+    let line = "a ffi";
+    let ltr = glyph(2, 5, 0);
+    assert_eq!(ltr.range(), 2..5);
+    assert_eq!(
+        ltr.sub_clusters(line).collect::<Vec<_>>(),
+        [
+            part(2, 3, 10.0, 10.0),
+            part(3, 4, 20.0, 10.0),
+            part(4, 5, 30.0, 10.0),
+        ]
+    );
+
+    // Right-to-left graphemes start on the right
+    let line = "لا";
+    let rtl = glyph(0, 4, 1);
+    assert_eq!(
+        rtl.sub_clusters(line).collect::<Vec<_>>(),
+        [part(0, 2, 25.0, 15.0), part(2, 4, 10.0, 15.0)]
+    );
+
+    // Combining marks stay with their base
+    let line = "e\u{301}x";
+    assert_eq!(
+        glyph(0, 3, 0).sub_clusters(line).collect::<Vec<_>>(),
+        [part(0, 3, 10.0, 30.0)]
+    );
+}
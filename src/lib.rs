@@ -96,6 +96,9 @@ extern crate alloc;
 #[cfg(not(any(feature = "std", feature = "no_std")))]
 compile_error!("Either the `std` or `no_std` feature must be enabled");
 
+pub use self::anchor::*;
+mod anchor;
+
 pub use self::attrs::*;
 mod attrs;
 
@@ -114,27 +117,86 @@ mod glyph_cache;
 pub use self::cursor::*;
 mod cursor;
 
+#[cfg(feature = "dictionary-segmentation")]
+pub use self::dictionary::*;
+#[cfg(feature = "dictionary-segmentation")]
+mod dictionary;
+
 pub use self::edit::*;
 mod edit;
 
+pub use self::error::*;
+mod error;
+
 pub use self::font::*;
 mod font;
 
+pub use self::footnote::*;
+mod footnote;
+
+pub use self::grid::*;
+mod grid;
+
+use self::height_index::*;
+mod height_index;
+
+pub use self::label::*;
+mod label;
+
 pub use self::layout::*;
 mod layout;
 
+pub use self::line_break::*;
+mod line_break;
+
+#[cfg(feature = "linkify")]
+pub use self::linkify::*;
+#[cfg(feature = "linkify")]
+mod linkify;
+
 pub use self::line_ending::*;
 mod line_ending;
 
+pub use self::minimap::*;
+mod minimap;
+
+pub use self::pagination::*;
+mod pagination;
+
+#[cfg(feature = "perf")]
+pub use self::perf::*;
+#[cfg(feature = "perf")]
+mod perf;
+
+pub use self::segmentation::*;
+mod segmentation;
+
 pub use self::shape::*;
 mod shape;
 
+#[cfg(feature = "shaping-parity")]
+pub use self::shaping_parity::*;
+#[cfg(feature = "shaping-parity")]
+mod shaping_parity;
+
 use self::shape_plan_cache::*;
 mod shape_plan_cache;
 
 pub use self::shape_run_cache::*;
 mod shape_run_cache;
 
+pub use self::style_sheet::*;
+mod style_sheet;
+
+pub use self::text_path::*;
+mod text_path;
+
+pub use self::text_run::*;
+mod text_run;
+
+pub use self::word::*;
+mod word;
+
 #[cfg(feature = "swash")]
 pub use self::swash::*;
 #[cfg(feature = "swash")]
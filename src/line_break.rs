@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Line break opportunities that override the default Unicode line breaking of a
+/// [`BufferLine`], as byte indexes of the text after the break
+///
+/// Scripts like Thai, Khmer, and Lao do not separate words with spaces, so they need a dictionary
+/// based segmenter such as ICU to find where lines can wrap. The `dictionary-segmentation`
+/// feature includes a small dictionary for Thai, Lao, Khmer, and Burmese that is used by default
+/// and can be replaced with `set_dictionary`, and these overrides apply on top of it. A break at the end of the line is always allowed.
+///
+/// [`BufferLine`]: crate::BufferLine
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreakOverrides {
+    /// Indexes where a line may break, in addition to the default opportunities
+    pub allowed: Vec<usize>,
+    /// Indexes where a line may not break, even if the default rules allow it
+    pub forbidden: Vec<usize>,
+}
+
+impl BreakOverrides {
+    /// Create empty overrides, using only the default opportunities
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow a break before the byte at `index`
+    pub fn allow(mut self, index: usize) -> Self {
+        self.allowed.push(index);
+        self
+    }
+
+    /// Forbid a break before the byte at `index`
+    pub fn forbid(mut self, index: usize) -> Self {
+        self.forbidden.push(index);
+        self
+    }
+
+    /// True if no opportunities are overridden
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.forbidden.is_empty()
+    }
+
+    /// Get the break opportunities of `text`, which starts at byte `offset` of the line, relative
+    /// to the start of `text` and in increasing order
+    pub(crate) fn linebreaks(&self, text: &str, offset: usize) -> Vec<usize> {
+        let mut breaks: Vec<usize> = unicode_linebreak::linebreaks(text)
+            .map(|(i, _)| i)
+            .collect();
+
+        // Break between dictionary words instead of inside runs of their scripts
+        #[cfg(feature = "dictionary-segmentation")]
+        {
+            let (words, runs) = crate::dictionary::dictionary_words(text);
+            if !runs.is_empty() {
+                breaks.retain(|&i| !runs.iter().any(|run| run.start < i && i < run.end));
+                breaks.extend(
+                    words
+                        .iter()
+                        .map(|word| word.start)
+                        .filter(|&i| runs.iter().any(|run| run.start < i && i < run.end)),
+                );
+                breaks.sort_unstable();
+                breaks.dedup();
+            }
+        }
+
+        breaks.retain(|&i| i == text.len() || !self.forbidden.contains(&(offset + i)));
+        if !self.allowed.is_empty() {
+            breaks.extend(
+                self.allowed
+                    .iter()
+                    .filter_map(|&i| i.checked_sub(offset))
+                    .filter(|&i| i > 0 && i < text.len() && text.is_char_boundary(i)),
+            );
+            breaks.sort_unstable();
+            breaks.dedup();
+        }
+        breaks
+    }
+
+    /// Move the overrides at and after `index` to a new set, relative to `index`
+    pub(crate) fn split_off(&mut self, index: usize) -> Self {
+        let split = |indexes: &mut Vec<usize>| {
+            let mut after = Vec::new();
+            indexes.retain(|&i| {
+                if i >= index {
+                    after.push(i - index);
+                    false
+                } else {
+                    true
+                }
+            });
+            after
+        };
+        Self {
+            allowed: split(&mut self.allowed),
+            forbidden: split(&mut self.forbidden),
+        }
+    }
+
+    /// Add the overrides of a line appended at `offset`
+    pub(crate) fn append(&mut self, other: &Self, offset: usize) {
+        self.allowed
+            .extend(other.allowed.iter().map(|i| i + offset));
+        self.forbidden
+            .extend(other.forbidden.iter().map(|i| i + offset));
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+use core::ops::Range;
+
+/// Kind of a [`Link`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LinkKind {
+    /// A URL with a scheme such as `https://`, or starting with `www.`
+    Url,
+    /// An email address
+    Email,
+}
+
+/// A link detected in the text of a line, see [`find_links`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Link {
+    /// Kind of the link
+    pub kind: LinkKind,
+    /// Byte range of the link in the line
+    pub range: Range<usize>,
+}
+
+impl Link {
+    /// Get the target of the link in `text`, the line it was found in
+    ///
+    /// URLs starting with `www.` get an `https://` scheme and emails a `mailto:` scheme.
+    pub fn target(&self, text: &str) -> String {
+        let link = &text[self.range.clone()];
+        match self.kind {
+            LinkKind::Url if starts_with_ignore_case(link, "www.") => format!("https://{}", link),
+            LinkKind::Url => link.into(),
+            LinkKind::Email => format!("mailto:{}", link),
+        }
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .map_or(false, |start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn is_scheme_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')
+}
+
+fn is_url_char(c: char) -> bool {
+    !c.is_whitespace() && !c.is_control() && !matches!(c, '<' | '>' | '"' | '`')
+}
+
+fn is_email_local_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-')
+}
+
+fn is_email_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-')
+}
+
+/// True if `text` can start a link at byte `i`, so words like `awww.` are not links
+fn is_link_start(text: &str, i: usize) -> bool {
+    text[..i]
+        .chars()
+        .next_back()
+        .map_or(true, |c| !c.is_alphanumeric())
+}
+
+/// Get the end of a URL starting at `start`, trimming trailing punctuation and unbalanced
+/// closing brackets so that links in sentences and parentheses are detected as written
+fn url_end(text: &str, start: usize) -> usize {
+    let mut end = text[start..]
+        .char_indices()
+        .find(|(_, c)| !is_url_char(*c))
+        .map_or(text.len(), |(i, _)| start + i);
+    loop {
+        let url = &text[start..end];
+        let last = match url.chars().next_back() {
+            Some(last) => last,
+            None => return end,
+        };
+        let trim = match last {
+            '.' | ',' | ':' | ';' | '!' | '?' | '\'' => true,
+            ')' => url.matches('(').count() < url.matches(')').count(),
+            ']' => url.matches('[').count() < url.matches(']').count(),
+            '}' => url.matches('{').count() < url.matches('}').count(),
+            _ => false,
+        };
+        if !trim {
+            return end;
+        }
+        end -= last.len_utf8();
+    }
+}
+
+/// Find a URL starting at byte `i`, returning its end
+fn url_at(text: &str, i: usize) -> Option<usize> {
+    let rest = &text[i..];
+    let prefix_len = if starts_with_ignore_case(rest, "www.") {
+        "www.".len()
+    } else {
+        let scheme_len = rest.find(|c: char| !is_scheme_char(c))?;
+        if scheme_len == 0 || !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        if rest[scheme_len..].starts_with("://") {
+            scheme_len + "://".len()
+        } else {
+            return None;
+        }
+    };
+    let end = url_end(text, i);
+    // Require something after the scheme or www.
+    (end > i + prefix_len).then_some(end)
+}
+
+/// Find an email address around the `@` at byte `at`
+fn email_at(text: &str, at: usize) -> Option<Range<usize>> {
+    let start = text[..at]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_email_local_char(*c))
+        .last()
+        .map(|(i, _)| i)?;
+    let start = start + text[start..at].find(|c: char| c != '.')?;
+    let domain_start = at + 1;
+    let mut end = text[domain_start..]
+        .char_indices()
+        .find(|(_, c)| !is_email_domain_char(*c))
+        .map_or(text.len(), |(i, _)| domain_start + i);
+    while text[domain_start..end].ends_with(['.', '-']) {
+        end -= 1;
+    }
+    let domain = &text[domain_start..end];
+    let valid_domain = domain.contains('.') && domain.split('.').all(|label| !label.is_empty());
+    (start < at && valid_domain).then_some(start..end)
+}
+
+/// Find the URLs and email addresses in `text`, in order
+pub fn find_links(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let c = match text[i..].chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        if c.is_ascii_alphabetic() && is_link_start(text, i) {
+            if let Some(end) = url_at(text, i) {
+                links.push(Link {
+                    kind: LinkKind::Url,
+                    range: i..end,
+                });
+                i = end;
+                continue;
+            }
+        }
+        if c == '@' {
+            if let Some(range) = email_at(text, i) {
+                // The local part may already have been scanned, but was not a link
+                let end = range.end;
+                links.push(Link {
+                    kind: LinkKind::Email,
+                    range,
+                });
+                i = end;
+                continue;
+            }
+        }
+        i += c.len_utf8();
+    }
+    links
+}
@@ -1,5 +1,23 @@
 #[cfg(not(feature = "std"))]
-pub use libm::{floorf, roundf, truncf};
+pub use libm::{atan2f, ceilf, cosf, floorf, powf, roundf, sinf, sqrtf, truncf};
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn atan2f(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn ceilf(x: f32) -> f32 {
+    x.ceil()
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn cosf(x: f32) -> f32 {
+    x.cos()
+}
 
 #[cfg(feature = "std")]
 #[inline]
@@ -7,12 +25,30 @@ pub fn floorf(x: f32) -> f32 {
     x.floor()
 }
 
+#[cfg(feature = "std")]
+#[inline]
+pub fn powf(x: f32, y: f32) -> f32 {
+    x.powf(y)
+}
+
 #[cfg(feature = "std")]
 #[inline]
 pub fn roundf(x: f32) -> f32 {
     x.round()
 }
 
+#[cfg(feature = "std")]
+#[inline]
+pub fn sinf(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub fn sqrtf(x: f32) -> f32 {
+    x.sqrt()
+}
+
 #[cfg(feature = "std")]
 #[inline]
 pub fn truncf(x: f32) -> f32 {
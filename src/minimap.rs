@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{math, Buffer, BufferLine, Color, Metrics};
+
+/// Estimated advance of characters in lines that have not been shaped, in em units
+const ESTIMATED_ADVANCE: f32 = 0.6;
+
+/// A placeholder block for a word in a [`Minimap`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MinimapBlock {
+    /// X offset of the block
+    pub x: f32,
+    /// Width of the block
+    pub w: f32,
+    /// Color of the start of the word, if set in its attributes
+    pub color_opt: Option<Color>,
+}
+
+/// A low cost overview of a [`Buffer`], for code editor minimaps
+///
+/// Every line of the buffer is placed on a single minimap line, and every word is replaced by a
+/// block. Words of lines that were already shaped use their shaped width, while other lines use
+/// an estimated width so that building a minimap never shapes text.
+#[derive(Clone, Debug)]
+pub struct Minimap {
+    metrics: Metrics,
+    lines: Vec<Vec<MinimapBlock>>,
+}
+
+impl Minimap {
+    /// Build a minimap of a [`Buffer`], using `metrics` for the minimap font size and line height
+    pub fn new(buffer: &Buffer, metrics: Metrics) -> Self {
+        let lines = buffer
+            .lines
+            .iter()
+            .map(|line| line_blocks(line, metrics.font_size, buffer.tab_width()))
+            .collect();
+        Self { metrics, lines }
+    }
+
+    /// Get the metrics used by the minimap
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Get the total height of the minimap
+    pub fn height(&self) -> f32 {
+        self.lines.len() as f32 * self.metrics.line_height
+    }
+
+    /// Get the blocks of a buffer line
+    pub fn line_blocks(&self, line_i: usize) -> Option<&[MinimapBlock]> {
+        self.lines.get(line_i).map(Vec::as_slice)
+    }
+
+    /// Get the Y offset of the top of a buffer line in the minimap
+    pub fn line_top(&self, line_i: usize) -> f32 {
+        line_i as f32 * self.metrics.line_height
+    }
+
+    /// Get the buffer line at a Y offset in the minimap, clamped to the last line
+    ///
+    /// Returns None if the buffer has no lines.
+    pub fn line_at(&self, y: f32) -> Option<usize> {
+        let last = self.lines.len().checked_sub(1)?;
+        let line_i = math::floorf(y / self.metrics.line_height).max(0.0) as usize;
+        Some(line_i.min(last))
+    }
+
+    /// Draw the minimap blocks, using `color` for blocks without a color of their own
+    pub fn draw<F>(&self, color: Color, mut f: F)
+    where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        let h = math::ceilf(self.metrics.font_size).max(1.0) as u32;
+        for (line_i, blocks) in self.lines.iter().enumerate() {
+            // Center the blocks in their line
+            let y = self.line_top(line_i) + (self.metrics.line_height - h as f32) / 2.0;
+            for block in blocks.iter() {
+                f(
+                    block.x as i32,
+                    y as i32,
+                    math::ceilf(block.w).max(1.0) as u32,
+                    h,
+                    block.color_opt.unwrap_or(color),
+                );
+            }
+        }
+    }
+}
+
+fn line_blocks(line: &BufferLine, font_size: f32, tab_width: u16) -> Vec<MinimapBlock> {
+    let mut blocks = Vec::new();
+    let mut x = 0.0;
+    match line.shape_opt() {
+        Some(shape) if !line.is_compact() => {
+            for word in shape.spans.iter().flat_map(|span| span.words.iter()) {
+                let w = word.width(font_size);
+                if !word.blank {
+                    blocks.push(MinimapBlock {
+                        x,
+                        w,
+                        color_opt: word.glyphs.first().and_then(|glyph| glyph.color_opt),
+                    });
+                }
+                x += w;
+            }
+        }
+        _ => {
+            let advance = font_size * ESTIMATED_ADVANCE;
+            let mut word_start_opt = None;
+            for (i, c) in line.text().char_indices() {
+                if c.is_whitespace() {
+                    if let Some((start, start_x)) = word_start_opt.take() {
+                        blocks.push(estimated_block(line, start, start_x, x));
+                    }
+                    x += if c == '\t' {
+                        advance * f32::from(tab_width)
+                    } else {
+                        advance
+                    };
+                } else {
+                    if word_start_opt.is_none() {
+                        word_start_opt = Some((i, x));
+                    }
+                    x += advance;
+                }
+            }
+            if let Some((start, start_x)) = word_start_opt {
+                blocks.push(estimated_block(line, start, start_x, x));
+            }
+        }
+    }
+    blocks
+}
+
+fn estimated_block(line: &BufferLine, start: usize, start_x: f32, end_x: f32) -> MinimapBlock {
+    MinimapBlock {
+        x: start_x,
+        w: end_x - start_x,
+        color_opt: line.attrs_list().get_span(start).color_opt,
+    }
+}
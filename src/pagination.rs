@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Buffer, BufferLine, LayoutRun};
+
+/// Rules for breaking a [`Buffer`] into pages, see [`Buffer::paginate`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageBreaks {
+    /// Height of each page
+    pub page_height: f32,
+    /// Minimum number of layout lines of a paragraph left at the bottom of a page
+    pub orphans: usize,
+    /// Minimum number of layout lines of a paragraph carried to the top of the next page
+    pub widows: usize,
+}
+
+impl PageBreaks {
+    /// Create page break rules for a page height, with the CSS default of 2 orphans and widows
+    pub const fn new(page_height: f32) -> Self {
+        Self {
+            page_height,
+            orphans: 2,
+            widows: 2,
+        }
+    }
+
+    /// Set the minimum number of lines left at the bottom of a page
+    pub const fn orphans(mut self, orphans: usize) -> Self {
+        self.orphans = orphans;
+        self
+    }
+
+    /// Set the minimum number of lines carried to the top of the next page
+    pub const fn widows(mut self, widows: usize) -> Self {
+        self.widows = widows;
+        self
+    }
+}
+
+/// A page of a [`Buffer`], produced by [`Buffer::paginate`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Page {
+    /// Index of the [`BufferLine`] of the first layout line on the page
+    pub start_line: usize,
+    /// Index of the first [`LayoutLine`] on the page
+    ///
+    /// [`LayoutLine`]: crate::LayoutLine
+    pub start_layout: usize,
+    /// Number of layout lines on the page
+    pub layout_lines: usize,
+}
+
+/// Split laid out lines into pages
+///
+/// Pages are filled greedily, then the break is moved up to keep orphans, widows and lines set
+/// with [`BufferLine::set_keep_with_next`]. If the rules cannot be honored without leaving the
+/// page empty, the greedy break is used.
+pub(crate) fn paginate(lines: &[BufferLine], line_height: f32, breaks: PageBreaks) -> Vec<Page> {
+    // Every layout line, as (line index, layout index, height)
+    let mut items = Vec::new();
+    for (line_i, line) in lines.iter().enumerate() {
+        if let Some(layout) = line.layout_opt() {
+            for (layout_i, layout_line) in layout.iter().enumerate() {
+                items.push((
+                    line_i,
+                    layout_i,
+                    layout_line.line_height_opt.unwrap_or(line_height),
+                ));
+            }
+        }
+    }
+    let layout_len = |line_i: usize| lines[line_i].layout_opt().as_ref().map_or(0, Vec::len);
+
+    let mut pages = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        // Fill the page, always placing at least one line
+        let mut end = start;
+        let mut height = 0.0;
+        while end < items.len() && (end == start || height + items[end].2 <= breaks.page_height) {
+            height += items[end].2;
+            end += 1;
+        }
+
+        if end < items.len() {
+            let mut break_i = end;
+
+            // Move lines of a split paragraph to the next page for widows and orphans
+            let (line_i, layout_i, _) = items[break_i];
+            if layout_i > 0 {
+                let after = layout_len(line_i) - layout_i;
+                let mut before = layout_i.saturating_sub(breaks.widows.saturating_sub(after));
+                if before < breaks.orphans {
+                    before = 0;
+                }
+                break_i = break_i - layout_i + before;
+            }
+
+            // Keep paragraphs with the paragraph following them
+            while break_i > start
+                && items[break_i].1 == 0
+                && lines[items[break_i - 1].0].keep_with_next()
+            {
+                break_i = break_i.saturating_sub(items[break_i - 1].1 + 1);
+            }
+
+            if break_i > start {
+                end = break_i;
+            }
+        }
+
+        pages.push(Page {
+            start_line: items[start].0,
+            start_layout: items[start].1,
+            layout_lines: end - start,
+        });
+        start = end;
+    }
+    pages
+}
+
+/// An iterator of the [`LayoutRun`]s of a [`Page`], see [`Buffer::page_runs`]
+#[derive(Debug)]
+pub struct PageRunIter<'b> {
+    buffer: &'b Buffer,
+    line_i: usize,
+    layout_i: usize,
+    remaining: usize,
+    line_top: f32,
+}
+
+impl<'b> PageRunIter<'b> {
+    pub fn new(buffer: &'b Buffer, page: &Page) -> Self {
+        Self {
+            buffer,
+            line_i: page.start_line,
+            layout_i: page.start_layout,
+            remaining: page.layout_lines,
+            line_top: 0.0,
+        }
+    }
+}
+
+impl<'b> Iterator for PageRunIter<'b> {
+    type Item = LayoutRun<'b>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let line = self.buffer.lines.get(self.line_i)?;
+            // Lines without a layout are not part of any page, skip them like paginate does
+            let (shape, layout) = match (line.shape_opt().as_ref(), line.layout_opt()) {
+                (Some(shape), Some(layout)) => (shape, layout),
+                _ => {
+                    self.line_i += 1;
+                    self.layout_i = 0;
+                    continue;
+                }
+            };
+            let layout_line = match layout.get(self.layout_i) {
+                Some(some) => some,
+                None => {
+                    self.line_i += 1;
+                    self.layout_i = 0;
+                    continue;
+                }
+            };
+            self.remaining -= 1;
+            self.layout_i += 1;
+
+            let line_height = layout_line
+                .line_height_opt
+                .unwrap_or(self.buffer.scaled_metrics().line_height);
+            let line_top = self.line_top;
+            let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+            let centering_offset = (line_height - glyph_height) / 2.0;
+            self.line_top += line_height;
+
+            return Some(LayoutRun {
+                line_i: self.line_i,
+                text: line.text(),
+                rtl: shape.rtl,
+                glyphs: &layout_line.glyphs,
+                wrap_indicator_opt: self.buffer.wrap_indicator_glyph(
+                    layout,
+                    self.layout_i - 1,
+                    shape.rtl,
+                ),
+                line_y: line_top + centering_offset + layout_line.max_ascent,
+                line_top,
+                line_height,
+                line_w: layout_line.w,
+                layout_i: self.layout_i - 1,
+                markers: line.markers(),
+                line_id: line.id(),
+            });
+        }
+        None
+    }
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Counters of the work done by a [`FontSystem`] and the buffers using it, see
+/// [`FontSystem::perf_counters`]
+///
+/// Counters only increase until [`FontSystem::reset_perf_counters`] is called, so the work done
+/// by an operation can be found by subtracting the counters from before it, see
+/// [`PerfCounters::since`].
+///
+/// [`FontSystem`]: crate::FontSystem
+/// [`FontSystem::perf_counters`]: crate::FontSystem::perf_counters
+/// [`FontSystem::reset_perf_counters`]: crate::FontSystem::reset_perf_counters
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PerfCounters {
+    /// Number of lines shaped
+    pub lines_shaped: u64,
+    /// Number of lines whose bidirectional text was analyzed, which is skipped when only the
+    /// attributes of a line changed
+    pub bidi_resolved: u64,
+    /// Number of lines laid out
+    pub lines_laid_out: u64,
+    /// Number of times a [`Buffer`] laid out all of its shaped lines again, for example after
+    /// its size changed
+    ///
+    /// [`Buffer`]: crate::Buffer
+    pub relayouts: u64,
+    /// Number of shape plans found in the cache
+    pub shape_plan_cache_hits: u64,
+    /// Number of shape plans created and added to the cache
+    pub shape_plan_cache_misses: u64,
+    /// Number of runs found in the cache, only used with the `shape-run-cache` feature
+    pub shape_run_cache_hits: u64,
+    /// Number of runs shaped and added to the cache, only used with the `shape-run-cache`
+    /// feature
+    pub shape_run_cache_misses: u64,
+    /// Number of glyph images rendered
+    pub glyphs_rasterized: u64,
+}
+
+impl PerfCounters {
+    /// Get the difference between these counters and `earlier` ones
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            lines_shaped: self.lines_shaped.saturating_sub(earlier.lines_shaped),
+            bidi_resolved: self.bidi_resolved.saturating_sub(earlier.bidi_resolved),
+            lines_laid_out: self.lines_laid_out.saturating_sub(earlier.lines_laid_out),
+            relayouts: self.relayouts.saturating_sub(earlier.relayouts),
+            shape_plan_cache_hits: self
+                .shape_plan_cache_hits
+                .saturating_sub(earlier.shape_plan_cache_hits),
+            shape_plan_cache_misses: self
+                .shape_plan_cache_misses
+                .saturating_sub(earlier.shape_plan_cache_misses),
+            shape_run_cache_hits: self
+                .shape_run_cache_hits
+                .saturating_sub(earlier.shape_run_cache_hits),
+            shape_run_cache_misses: self
+                .shape_run_cache_misses
+                .saturating_sub(earlier.shape_run_cache_misses),
+            glyphs_rasterized: self
+                .glyphs_rasterized
+                .saturating_sub(earlier.glyphs_rasterized),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::BreakOverrides;
+
+/// Grapheme, word, and line break boundaries of the text of a line, see
+/// [`BufferLine::segmentation`]
+///
+/// Boundaries are byte indexes in the text, in increasing order. They are found once when the
+/// text of a line changes, so hosts implementing their own selection or cursor motions can use
+/// them without segmenting the text again.
+///
+/// [`BufferLine::segmentation`]: crate::BufferLine::segmentation
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Segmentation {
+    len: usize,
+    graphemes: Vec<usize>,
+    words: Vec<usize>,
+    line_breaks: Vec<usize>,
+}
+
+impl Segmentation {
+    /// Segment `text`, applying `break_overrides` to its line break opportunities
+    pub fn new(text: &str, break_overrides: &BreakOverrides) -> Self {
+        Self {
+            len: text.len(),
+            graphemes: text.grapheme_indices(true).map(|(i, _)| i).collect(),
+            words: text.split_word_bound_indices().map(|(i, _)| i).collect(),
+            line_breaks: break_overrides.linebreaks(text, 0),
+        }
+    }
+
+    /// Get the start of each extended grapheme cluster
+    pub fn graphemes(&self) -> &[usize] {
+        &self.graphemes
+    }
+
+    /// Get the start of each segment between word boundaries, as defined by Unicode Standard
+    /// Annex #29, including segments of whitespace and punctuation
+    pub fn words(&self) -> &[usize] {
+        &self.words
+    }
+
+    /// Get the line break opportunities, as indexes of the text after the break, ending with the
+    /// length of the text
+    pub fn line_breaks(&self) -> &[usize] {
+        &self.line_breaks
+    }
+
+    /// Get the start of the grapheme cluster before `index`, or 0
+    pub fn previous_grapheme(&self, index: usize) -> usize {
+        let i = self.graphemes.partition_point(|&start| start < index);
+        i.checked_sub(1).map_or(0, |i| self.graphemes[i])
+    }
+
+    /// Get the end of the grapheme cluster containing or starting at `index`, or the length of
+    /// the text
+    pub fn next_grapheme(&self, index: usize) -> usize {
+        let i = self.graphemes.partition_point(|&start| start <= index);
+        self.graphemes.get(i).copied().unwrap_or(self.len)
+    }
+}
@@ -13,8 +13,9 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::fallback::FontFallbackIter;
 use crate::{
-    math, Align, AttrsList, CacheKeyFlags, Color, Font, FontSystem, LayoutGlyph, LayoutLine,
-    Metrics, ShapePlanCache, Wrap,
+    math, Align, Attrs, AttrsList, BreakOverrides, CacheKeyFlags, Color, Font, FontSystem,
+    Justification, LayoutError, LayoutGlyph, LayoutLine, Metrics, NumberSubstitution,
+    ShapePlanCache, SpanPadding, TextAnimation, Wrap,
 };
 
 /// The shaping strategy of some text.
@@ -27,6 +28,10 @@ pub enum Shaping {
     ///
     /// You should use this strategy when you have complete control of the text
     /// and the font you are displaying in your application.
+    ///
+    /// Each character is mapped to exactly one glyph of the first matching font, with its
+    /// nominal advance and no kerning or ligatures, and each glyph covers the bytes of its
+    /// character.
     #[cfg(feature = "swash")]
     Basic,
     /// Advanced text shaping and font fallback.
@@ -35,9 +40,31 @@ pub enum Shaping {
     /// script, the font used needs it, and/or multiple fonts in your system
     /// may be needed to display all of the glyphs.
     Advanced,
+    /// Basic shaping for lines that only contain Latin-1 characters, and advanced shaping for
+    /// all other lines, see [`Shaping::resolve`].
+    ///
+    /// This is useful for mostly ASCII text such as logs, where most lines can take the cheap
+    /// path without breaking lines in complex scripts.
+    Auto,
 }
 
 impl Shaping {
+    /// Get the shaping strategy used for `line`
+    ///
+    /// [`Shaping::Auto`] becomes [`Shaping::Basic`] if every character in `line` is in Latin-1,
+    /// which has no combining marks or right-to-left characters, and [`Shaping::Advanced`]
+    /// otherwise. Without the `swash` feature it is always [`Shaping::Advanced`]. Other
+    /// strategies are returned unchanged.
+    #[cfg_attr(not(feature = "swash"), allow(unused_variables))]
+    pub fn resolve(self, line: &str) -> Self {
+        match self {
+            #[cfg(feature = "swash")]
+            Self::Auto if line.chars().all(|c| c <= '\u{FF}') => Self::Basic,
+            Self::Auto => Self::Advanced,
+            _ => self,
+        }
+    }
+
     fn run(
         self,
         scratch: &mut ShapeBuffer,
@@ -50,8 +77,26 @@ impl Shaping {
         span_rtl: bool,
     ) {
         match self {
+            Self::Auto => self.resolve(&line[start_run..end_run]).run(
+                scratch,
+                glyphs,
+                font_system,
+                line,
+                attrs_list,
+                start_run,
+                end_run,
+                span_rtl,
+            ),
             #[cfg(feature = "swash")]
-            Self::Basic => shape_skip(font_system, glyphs, line, attrs_list, start_run, end_run),
+            Self::Basic => shape_skip(
+                scratch,
+                font_system,
+                glyphs,
+                line,
+                attrs_list,
+                start_run,
+                end_run,
+            ),
             #[cfg(not(feature = "shape-run-cache"))]
             Self::Advanced => shape_run(
                 scratch,
@@ -78,6 +123,65 @@ impl Shaping {
     }
 }
 
+/// Shapes runs of text into glyphs, see [`Buffer::set_shaping_backend`]
+///
+/// Implement this to compare against another shaper, or to use a cheaper one where complex
+/// scripts are not needed. The default is [`RustybuzzShapingBackend`].
+///
+/// [`Buffer::set_shaping_backend`]: crate::Buffer::set_shaping_backend
+pub trait ShapingBackend: fmt::Debug + Send + Sync {
+    /// Shape `line[start_run..end_run]`, which has a single direction and compatible attributes,
+    /// pushing its glyphs to `glyphs` in visual order
+    ///
+    /// Glyph `start` and `end` are byte offsets in `line`, and advances and offsets are in em
+    /// units. `span_rtl` is the direction of the run, and `shaping` is the strategy requested
+    /// for the line.
+    #[allow(clippy::too_many_arguments)]
+    fn shape(
+        &self,
+        scratch: &mut ShapeBuffer,
+        glyphs: &mut Vec<ShapeGlyph>,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        start_run: usize,
+        end_run: usize,
+        span_rtl: bool,
+        shaping: Shaping,
+    );
+}
+
+/// The default [`ShapingBackend`], which shapes with rustybuzz and falls back to other fonts
+/// for missing glyphs, following the requested [`Shaping`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RustybuzzShapingBackend;
+
+impl ShapingBackend for RustybuzzShapingBackend {
+    fn shape(
+        &self,
+        scratch: &mut ShapeBuffer,
+        glyphs: &mut Vec<ShapeGlyph>,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        start_run: usize,
+        end_run: usize,
+        span_rtl: bool,
+        shaping: Shaping,
+    ) {
+        shaping.run(
+            scratch,
+            glyphs,
+            font_system,
+            line,
+            attrs_list,
+            start_run,
+            end_run,
+            span_rtl,
+        );
+    }
+}
+
 /// A set of buffers containing allocations for shaped text.
 #[derive(Default)]
 pub struct ShapeBuffer {
@@ -89,6 +193,9 @@ pub struct ShapeBuffer {
 
     /// Buffer for visual lines.
     visual_lines: Vec<VisualLine>,
+
+    /// Set when a run could not be shaped because no font was found
+    pub(crate) missing_font: bool,
 }
 
 impl fmt::Debug for ShapeBuffer {
@@ -120,7 +227,18 @@ fn shape_fallback(
     } else {
         rustybuzz::Direction::LeftToRight
     });
-    if run.contains('\t') {
+    let attrs = attrs_list.get_span(start_run);
+    if attrs.number_substitution != NumberSubstitution::None {
+        // Push characters one by one, keeping clusters as byte offsets into the original text
+        for (i, c) in run.char_indices() {
+            let c = if c == '\t' {
+                ' '
+            } else {
+                attrs.number_substitution.substitute(c, attrs.language_opt)
+            };
+            buffer.add(c, i as u32);
+        }
+    } else if run.contains('\t') {
         // Push string to buffer, replacing tabs with spaces
         //TODO: Find a way to do this with minimal allocating, calling
         // UnicodeBuffer::push_str multiple times causes issues and
@@ -131,10 +249,28 @@ fn shape_fallback(
     }
     buffer.guess_segment_properties();
 
-    let rtl = matches!(buffer.direction(), rustybuzz::Direction::RightToLeft);
-    assert_eq!(rtl, span_rtl);
+    if let Some(script) = attrs.script_opt.and_then(|script| {
+        let tag = rustybuzz::ttf_parser::Tag::from_bytes_lossy(script.short_name().as_bytes());
+        rustybuzz::Script::from_iso15924_tag(tag)
+    }) {
+        buffer.set_script(script);
+    }
+    if let Some(language) = attrs
+        .language_opt
+        .and_then(|language| language.parse::<rustybuzz::Language>().ok())
+    {
+        buffer.set_language(language);
+    }
+
+    // The guessed direction can differ from the bidi level, for example in runs of only
+    // neutral characters, so always use the direction of the span
+    buffer.set_direction(if span_rtl {
+        rustybuzz::Direction::RightToLeft
+    } else {
+        rustybuzz::Direction::LeftToRight
+    });
 
-    let shape_plan = shape_plan_cache.get(font, &buffer);
+    let shape_plan = shape_plan_cache.get(font, &buffer, &attrs);
     let glyph_buffer = rustybuzz::shape_with_plan(font.rustybuzz(), shape_plan, buffer);
     let glyph_infos = glyph_buffer.glyph_infos();
     let glyph_positions = glyph_buffer.glyph_positions();
@@ -150,7 +286,9 @@ fn shape_fallback(
 
         let start_glyph = start_run + info.cluster as usize;
 
-        if info.glyph_id == 0 {
+        // Glyph IDs always fit in u16, treat any that do not as missing
+        let glyph_id = u16::try_from(info.glyph_id).unwrap_or(0);
+        if glyph_id == 0 {
             missing.push(start_glyph);
         }
 
@@ -166,17 +304,22 @@ fn shape_fallback(
             descent,
             font_monospace_em_width: font.monospace_em_width(),
             font_id: font.id(),
-            glyph_id: info.glyph_id.try_into().expect("failed to cast glyph ID"),
+            glyph_id,
             //TODO: color should not be related to shaping
             color_opt: attrs.color_opt,
+            palette_index_opt: attrs.palette_index_opt,
+            match_mono_width: attrs.match_mono_width,
+            animation_opt: attrs.animation_opt,
+            decoration_id_opt: attrs.decoration_id_opt,
             metadata: attrs.metadata,
             cache_key_flags: attrs.cache_key_flags,
             metrics_opt: attrs.metrics_opt.map(|x| x.into()),
+            kashida_opt: None,
         });
     }
 
     // Adjust end of glyphs
-    if rtl {
+    if span_rtl {
         for i in glyph_start + 1..glyphs.len() {
             let next_start = glyphs[i - 1].start;
             let next_end = glyphs[i - 1].end;
@@ -200,12 +343,100 @@ fn shape_fallback(
         }
     }
 
+    // Find glyphs that may be followed by a kashida, looked up only when needed
+    let mut tatweel_opt = None;
+    for glyph in glyphs[glyph_start..].iter_mut() {
+        // Clusters out of order may leave a glyph with an empty or reversed range
+        let cluster = match line.get(glyph.start..glyph.end) {
+            Some(some) => some,
+            None => continue,
+        };
+        if glyph.x_advance > 0.0
+            && kashida_allowed(cluster, line[glyph.end..end_run].chars().next())
+        {
+            glyph.kashida_opt = *tatweel_opt.get_or_insert_with(|| {
+                let face = font.rustybuzz();
+                let glyph_id = face.glyph_index('\u{0640}')?;
+                let x_advance = face.glyph_hor_advance(glyph_id)? as f32 / font_scale;
+                Some((glyph_id.0, x_advance))
+            });
+        }
+    }
+
     // Restore the buffer to save an allocation.
     scratch.rustybuzz_buffer = Some(glyph_buffer.clear());
 
     missing
 }
 
+/// Joining type of Arabic letters, used to find kashida insertion points
+#[derive(Clone, Copy, PartialEq)]
+enum ArabicJoining {
+    /// Joins on both sides
+    Dual,
+    /// Joins only to the preceding letter
+    Right,
+    /// Does not join
+    None,
+}
+
+impl ArabicJoining {
+    fn new(c: char) -> Self {
+        match c {
+            '\u{0622}'..='\u{0625}'
+            | '\u{0627}'
+            | '\u{0629}'
+            | '\u{062F}'..='\u{0632}'
+            | '\u{0648}'
+            | '\u{0671}'..='\u{0673}'
+            | '\u{0675}'..='\u{0677}'
+            | '\u{0688}'..='\u{0699}'
+            | '\u{06C0}'
+            | '\u{06C3}'..='\u{06CB}'
+            | '\u{06CD}'
+            | '\u{06CF}'
+            | '\u{06D2}'
+            | '\u{06D3}'
+            | '\u{06D5}' => Self::Right,
+            '\u{0620}'
+            | '\u{0626}'
+            | '\u{0628}'
+            | '\u{062A}'..='\u{062E}'
+            | '\u{0633}'..='\u{063F}'
+            | '\u{0641}'..='\u{0647}'
+            | '\u{0649}'
+            | '\u{064A}'
+            | '\u{066E}'
+            | '\u{066F}'
+            | '\u{0678}'..='\u{0687}'
+            | '\u{069A}'..='\u{06BF}'
+            | '\u{06C1}'
+            | '\u{06C2}'
+            | '\u{06CC}'
+            | '\u{06CE}'
+            | '\u{06D0}'
+            | '\u{06D1}'
+            | '\u{06FA}'..='\u{06FC}'
+            | '\u{06FF}' => Self::Dual,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Check if a kashida can be inserted between a cluster and the character following it without
+/// breaking the joined forms chosen by shaping
+fn kashida_allowed(cluster: &str, next_opt: Option<char>) -> bool {
+    let is_mark =
+        |c: &char| matches!(c, '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{06D6}'..='\u{06ED}');
+    match (cluster.chars().rev().find(|c| !is_mark(c)), next_opt) {
+        (Some(last), Some(next)) => {
+            ArabicJoining::new(last) == ArabicJoining::Dual
+                && ArabicJoining::new(next) != ArabicJoining::None
+        }
+        _ => false,
+    }
+}
+
 fn shape_run(
     scratch: &mut ShapeBuffer,
     glyphs: &mut Vec<ShapeGlyph>,
@@ -222,6 +453,10 @@ fn shape_run(
         scripts.clear();
         scripts
     };
+    let attrs = attrs_list.get_span(start_run);
+    if let Some(script) = attrs.script_opt {
+        scripts.push(script);
+    }
     for c in line[start_run..end_run].chars() {
         match c.script() {
             Script::Common | Script::Inherited | Script::Latin | Script::Unknown => (),
@@ -235,8 +470,6 @@ fn shape_run(
 
     log::trace!("      Run {:?}: '{}'", &scripts, &line[start_run..end_run],);
 
-    let attrs = attrs_list.get_span(start_run);
-
     let fonts = font_system.get_font_matches(attrs);
 
     let default_families = [&attrs.family];
@@ -245,10 +478,19 @@ fn shape_run(
         &fonts,
         &default_families,
         &scripts,
+        attrs.language_opt,
         &line[start_run..end_run],
     );
 
-    let font = font_iter.next().expect("no default font found");
+    let font = match font_iter.next() {
+        Some(some) => some,
+        None => {
+            log::warn!("no font found for '{}'", &line[start_run..end_run]);
+            scratch.missing_font = true;
+            scratch.scripts = scripts;
+            return;
+        }
+    };
 
     let glyph_start = glyphs.len();
     let mut missing = shape_fallback(
@@ -397,10 +639,19 @@ fn shape_run_cached(
             glyph.end += start_run;
             glyphs.push(glyph);
         }
+        #[cfg(feature = "perf")]
+        {
+            font_system.perf_counters_mut().shape_run_cache_hits += 1;
+        }
         return;
     }
 
     // Fill in cache if not already set
+    #[cfg(feature = "perf")]
+    {
+        font_system.perf_counters_mut().shape_run_cache_misses += 1;
+    }
+    let missing_font = mem::take(&mut scratch.missing_font);
     let mut cache_glyphs = Vec::new();
     shape_run(
         scratch,
@@ -418,11 +669,16 @@ fn shape_run_cached(
         glyph.start -= start_run;
         glyph.end -= start_run;
     }
-    font_system.shape_run_cache.insert(key, cache_glyphs);
+    // Do not cache runs without glyphs from a missing font, fonts may be added later
+    if !scratch.missing_font {
+        font_system.shape_run_cache.insert(key, cache_glyphs);
+    }
+    scratch.missing_font |= missing_font;
 }
 
 #[cfg(feature = "swash")]
 fn shape_skip(
+    scratch: &mut ShapeBuffer,
     font_system: &mut FontSystem,
     glyphs: &mut Vec<ShapeGlyph>,
     line: &str,
@@ -434,9 +690,17 @@ fn shape_skip(
     let fonts = font_system.get_font_matches(attrs);
 
     let default_families = [&attrs.family];
-    let mut font_iter = FontFallbackIter::new(font_system, &fonts, &default_families, &[], "");
-
-    let font = font_iter.next().expect("no default font found");
+    let mut font_iter =
+        FontFallbackIter::new(font_system, &fonts, &default_families, &[], None, "");
+
+    let font = match font_iter.next() {
+        Some(some) => some,
+        None => {
+            log::warn!("no font found for '{}'", &line[start_run..end_run]);
+            scratch.missing_font = true;
+            return;
+        }
+    };
     let font_id = font.id();
     let font_monospace_em_width = font.monospace_em_width();
     let font = font.as_swash();
@@ -450,16 +714,16 @@ fn shape_skip(
 
     glyphs.extend(
         line[start_run..end_run]
-            .chars()
-            .enumerate()
+            .char_indices()
             .map(|(i, codepoint)| {
+                let start = start_run + i;
                 let glyph_id = charmap.map(codepoint);
                 let x_advance = glyph_metrics.advance_width(glyph_id);
-                let attrs = attrs_list.get_span(i);
+                let attrs = attrs_list.get_span(start);
 
                 ShapeGlyph {
-                    start: i,
-                    end: i + 1,
+                    start,
+                    end: start + codepoint.len_utf8(),
                     x_advance,
                     y_advance: 0.0,
                     x_offset: 0.0,
@@ -470,35 +734,79 @@ fn shape_skip(
                     font_id,
                     glyph_id,
                     color_opt: attrs.color_opt,
+                    palette_index_opt: attrs.palette_index_opt,
+                    match_mono_width: attrs.match_mono_width,
+                    animation_opt: attrs.animation_opt,
+                    decoration_id_opt: attrs.decoration_id_opt,
                     metadata: attrs.metadata,
                     cache_key_flags: attrs.cache_key_flags,
                     metrics_opt: attrs.metrics_opt.map(|x| x.into()),
+                    kashida_opt: None,
                 }
             }),
     );
 }
 
 /// A shaped glyph
+///
+/// Advances, offsets, and vertical metrics are in em units, multiply them by the font size to
+/// get pixels, see [`ShapeGlyph::width`].
 #[derive(Clone, Debug)]
 pub struct ShapeGlyph {
+    /// Start of the cluster in the line, in bytes
     pub start: usize,
+    /// End of the cluster in the line, in bytes
     pub end: usize,
+    /// Horizontal advance, including tab expansion and span padding
     pub x_advance: f32,
+    /// Vertical advance
     pub y_advance: f32,
+    /// Horizontal offset from the pen position
     pub x_offset: f32,
+    /// Vertical offset from the pen position
     pub y_offset: f32,
+    /// Ascent of the font
     pub ascent: f32,
+    /// Descent of the font
     pub descent: f32,
+    /// Advance of the font if it is monospace
     pub font_monospace_em_width: Option<f32>,
+    /// Font the glyph was shaped with
     pub font_id: fontdb::ID,
+    /// Glyph in the font
     pub glyph_id: u16,
+    /// Color from the attributes
     pub color_opt: Option<Color>,
+    /// Palette color index from the attributes
+    pub palette_index_opt: Option<u16>,
+    /// Resize to match the monospace width of the layout, see [`Attrs::match_mono_width`]
+    ///
+    /// [`Attrs::match_mono_width`]: crate::Attrs::match_mono_width
+    pub match_mono_width: bool,
+    /// Animated emphasis from the attributes
+    pub animation_opt: Option<TextAnimation>,
+    /// Inline decoration identifier from the attributes
+    pub decoration_id_opt: Option<u32>,
+    /// Metadata from the attributes
     pub metadata: usize,
+    /// [`CacheKeyFlags`] from the attributes
     pub cache_key_flags: CacheKeyFlags,
+    /// Metrics override from the attributes
     pub metrics_opt: Option<Metrics>,
+    /// Glyph ID and advance of the kashida that may follow this glyph when justifying
+    pub kashida_opt: Option<(u16, f32)>,
 }
 
 impl ShapeGlyph {
+    /// Update the properties used for drawing from `attrs`, see [`Attrs::same_layout`]
+    pub(crate) fn set_paint(&mut self, attrs: &Attrs) {
+        self.color_opt = attrs.color_opt;
+        self.palette_index_opt = attrs.palette_index_opt;
+        self.animation_opt = attrs.animation_opt;
+        self.decoration_id_opt = attrs.decoration_id_opt;
+        self.metadata = attrs.metadata;
+    }
+
     fn layout(
         &self,
         font_size: f32,
@@ -522,6 +830,9 @@ impl ShapeGlyph {
             x_offset: self.x_offset,
             y_offset: self.y_offset,
             color_opt: self.color_opt,
+            palette_index_opt: self.palette_index_opt,
+            animation_opt: self.animation_opt,
+            decoration_id_opt: self.decoration_id_opt,
             metadata: self.metadata,
             cache_key_flags: self.cache_key_flags,
         }
@@ -532,12 +843,87 @@ impl ShapeGlyph {
     pub fn width(&self, font_size: f32) -> f32 {
         self.metrics_opt.map_or(font_size, |x| x.font_size) * self.x_advance
     }
+
+    /// Byte range of the cluster in the line, shared by all glyphs of the cluster
+    pub fn cluster(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Fill `expansion` pixels next to `base` with copies of the kashida glyph
+fn push_kashida(
+    glyphs: &mut Vec<LayoutGlyph>,
+    x: &mut f32,
+    rtl: bool,
+    base: &LayoutGlyph,
+    (glyph_id, x_advance): (u16, f32),
+    expansion: f32,
+) {
+    // Copies overlap when the expansion is not a multiple of the kashida width
+    let kashida_w = base.font_size * x_advance;
+    let count = if kashida_w > 0. {
+        math::ceilf(expansion / kashida_w).max(1.)
+    } else {
+        1.
+    };
+    let step = expansion / count;
+    for _ in 0..count as usize {
+        if rtl {
+            *x -= step;
+        }
+        glyphs.push(LayoutGlyph {
+            glyph_id,
+            x: *x,
+            w: step,
+            x_offset: 0.,
+            y_offset: 0.,
+            ..base.clone()
+        });
+        if !rtl {
+            *x += step;
+        }
+    }
+}
+
+/// Add `padding` to the advances of the glyphs at both ends of `range`, where glyphs are in
+/// visual order from the start of the line
+fn pad_glyphs(spans: &mut [ShapeSpan], range: &Range<usize>, padding: SpanPadding, rtl: bool) {
+    let mut glyphs: Vec<&mut ShapeGlyph> = spans
+        .iter_mut()
+        .flat_map(|span| span.words.iter_mut())
+        .flat_map(|word| word.glyphs.iter_mut())
+        .filter(|glyph| range.contains(&glyph.start))
+        .collect();
+    let x_advance: f32 = glyphs.iter().map(|glyph| glyph.x_advance).sum();
+    let side = padding
+        .horizontal()
+        .max((padding.min_width() - x_advance) / 2.0);
+    if side <= 0.0 {
+        return;
+    }
+
+    if let Some(first) = glyphs.first_mut() {
+        first.x_advance += side;
+        if !rtl {
+            first.x_offset += side;
+        }
+    }
+    if let Some(last) = glyphs.last_mut() {
+        last.x_advance += side;
+        if rtl {
+            last.x_offset += side;
+        }
+    }
 }
 
 /// A shaped word (for word wrapping)
+///
+/// Lines can only wrap between words, or between glyphs with [`Wrap::Glyph`].
 #[derive(Clone, Debug)]
 pub struct ShapeWord {
+    /// True if the word is whitespace, which may hang past the end of a wrapped line
     pub blank: bool,
+    /// Glyphs of the word, in visual order from the start of the line
     pub glyphs: Vec<ShapeGlyph>,
 }
 
@@ -560,6 +946,7 @@ impl ShapeWord {
             level,
             blank,
             shaping,
+            &RustybuzzShapingBackend,
         )
     }
 
@@ -574,6 +961,7 @@ impl ShapeWord {
         level: unicode_bidi::Level,
         blank: bool,
         shaping: Shaping,
+        shaping_backend: &dyn ShapingBackend,
     ) -> Self {
         let word = &line[word_range.clone()];
 
@@ -592,7 +980,7 @@ impl ShapeWord {
             let start_egc = word_range.start + egc_i;
             let attrs_egc = attrs_list.get_span(start_egc);
             if !attrs.compatible(&attrs_egc) {
-                shaping.run(
+                shaping_backend.shape(
                     scratch,
                     &mut glyphs,
                     font_system,
@@ -601,6 +989,7 @@ impl ShapeWord {
                     start_run,
                     start_egc,
                     span_rtl,
+                    shaping,
                 );
 
                 start_run = start_egc;
@@ -608,7 +997,7 @@ impl ShapeWord {
             }
         }
         if start_run < word_range.end {
-            shaping.run(
+            shaping_backend.shape(
                 scratch,
                 &mut glyphs,
                 font_system,
@@ -617,6 +1006,7 @@ impl ShapeWord {
                 start_run,
                 word_range.end,
                 span_rtl,
+                shaping,
             );
         }
 
@@ -636,11 +1026,14 @@ impl ShapeWord {
 /// A shaped span (for bidirectional processing)
 #[derive(Clone, Debug)]
 pub struct ShapeSpan {
+    /// Bidirectional embedding level of the span
     pub level: unicode_bidi::Level,
+    /// Words of the span, in visual order from the start of the line
     pub words: Vec<ShapeWord>,
 }
 
 impl ShapeSpan {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         font_system: &mut FontSystem,
         line: &str,
@@ -649,6 +1042,7 @@ impl ShapeSpan {
         line_rtl: bool,
         level: unicode_bidi::Level,
         shaping: Shaping,
+        break_overrides: &BreakOverrides,
     ) -> Self {
         Self::new_in_buffer(
             &mut ShapeBuffer::default(),
@@ -659,10 +1053,13 @@ impl ShapeSpan {
             line_rtl,
             level,
             shaping,
+            break_overrides,
+            &RustybuzzShapingBackend,
         )
     }
 
     /// Shape a span into a set of words, using a scratch buffer.
+    #[allow(clippy::too_many_arguments)]
     pub fn new_in_buffer(
         scratch: &mut ShapeBuffer,
         font_system: &mut FontSystem,
@@ -672,6 +1069,8 @@ impl ShapeSpan {
         line_rtl: bool,
         level: unicode_bidi::Level,
         shaping: Shaping,
+        break_overrides: &BreakOverrides,
+        shaping_backend: &dyn ShapingBackend,
     ) -> Self {
         let span = &line[span_range.start..span_range.end];
 
@@ -684,7 +1083,7 @@ impl ShapeSpan {
         let mut words = Vec::new();
 
         let mut start_word = 0;
-        for (end_lb, _) in unicode_linebreak::linebreaks(span) {
+        for end_lb in break_overrides.linebreaks(span, span_range.start) {
             let mut start_lb = end_lb;
             for (i, c) in span[start_word..end_lb].char_indices().rev() {
                 // TODO: Not all whitespace characters are linebreakable, e.g. 00A0 (No-break
@@ -707,6 +1106,7 @@ impl ShapeSpan {
                     level,
                     false,
                     shaping,
+                    shaping_backend,
                 ));
             }
             if start_lb < end_lb {
@@ -722,6 +1122,7 @@ impl ShapeSpan {
                         level,
                         true,
                         shaping,
+                        shaping_backend,
                     ));
                 }
             }
@@ -744,360 +1145,224 @@ impl ShapeSpan {
     }
 }
 
-/// A shaped line (or paragraph)
-#[derive(Clone, Debug)]
-pub struct ShapeLine {
-    pub rtl: bool,
-    pub spans: Vec<ShapeSpan>,
-    pub metrics_opt: Option<Metrics>,
-}
+/// Add a range of words to a visual line, unless it is empty
+fn add_to_visual_line(
+    vl: &mut VisualLine,
+    span_index: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+    width: f32,
+    number_of_blanks: u32,
+) {
+    if end == start {
+        return;
+    }
 
-// Visual Line Ranges: (span_index, (first_word_index, first_glyph_index), (last_word_index, last_glyph_index))
-type VlRange = (usize, (usize, usize), (usize, usize));
+    vl.ranges.push((span_index, start, end));
+    vl.w += width;
+    vl.spaces += number_of_blanks;
+}
 
-#[derive(Default)]
-struct VisualLine {
-    ranges: Vec<VlRange>,
-    spaces: u32,
-    w: f32,
+/// Breaks a [`ShapeLine`] into visual lines, see [`ShapeLine::layout_to_buffer_with_breaker`]
+///
+/// Implement this to replace the default [`GreedyLineBreaker`], for example with publisher
+/// rules for CJK text or fixed columns for a terminal.
+pub trait LineBreaker: fmt::Debug + Send + Sync {
+    /// Push the visual lines of `line` to `visual_lines`, in order, fitting them in `width_opt`
+    /// pixels at `font_size` where possible using the `wrap` mode
+    fn break_lines(
+        &self,
+        line: &ShapeLine,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        visual_lines: &mut Vec<VisualLine>,
+    );
 }
 
-impl ShapeLine {
-    /// # Panics
-    ///
-    /// Will panic if `line` contains more than one paragraph.
-    pub fn new(
-        font_system: &mut FontSystem,
-        line: &str,
-        attrs_list: &AttrsList,
-        shaping: Shaping,
-        tab_width: u16,
-    ) -> Self {
-        Self::new_in_buffer(
-            &mut ShapeBuffer::default(),
-            font_system,
-            line,
-            attrs_list,
-            shaping,
-            tab_width,
-        )
-    }
+/// The default [`LineBreaker`], which fills each line with as many words as fit before breaking
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyLineBreaker;
 
-    /// Shape a line into a set of spans, using a scratch buffer. If [`unicode_bidi::BidiInfo`]
-    /// detects multiple paragraphs, they will be joined.
-    ///
-    /// # Panics
-    ///
-    /// Will panic if `line` contains multiple paragraphs that do not have matching direction
-    pub fn new_in_buffer(
-        scratch: &mut ShapeBuffer,
-        font_system: &mut FontSystem,
-        line: &str,
-        attrs_list: &AttrsList,
-        shaping: Shaping,
-        tab_width: u16,
-    ) -> Self {
-        let mut spans = Vec::new();
+impl LineBreaker for GreedyLineBreaker {
+    fn break_lines(
+        &self,
+        line: &ShapeLine,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        visual_lines: &mut Vec<VisualLine>,
+    ) {
+        // This would keep the maximum number of spans that would fit on a visual line
+        // If one span is too large, this variable will hold the range of words inside that span
+        // that fits on a line.
+        // let mut current_visual_line: Vec<VlRange> = Vec::with_capacity(1);
+        let mut current_visual_line = VisualLine::default();
 
-        let bidi = unicode_bidi::BidiInfo::new(line, None);
-        let rtl = if bidi.paragraphs.is_empty() {
-            false
+        if wrap == Wrap::None {
+            for (span_index, span) in line.spans.iter().enumerate() {
+                let mut word_range_width = 0.;
+                let mut number_of_blanks: u32 = 0;
+                for word in span.words.iter() {
+                    let word_width = word.width(font_size);
+                    word_range_width += word_width;
+                    if word.blank {
+                        number_of_blanks += 1;
+                    }
+                }
+                add_to_visual_line(
+                    &mut current_visual_line,
+                    span_index,
+                    (0, 0),
+                    (span.words.len(), 0),
+                    word_range_width,
+                    number_of_blanks,
+                );
+            }
         } else {
-            bidi.paragraphs[0].level.is_rtl()
-        };
+            for (span_index, span) in line.spans.iter().enumerate() {
+                let mut word_range_width = 0.;
+                let mut width_before_last_blank = 0.;
+                let mut number_of_blanks: u32 = 0;
 
-        log::trace!("Line {}: '{}'", if rtl { "RTL" } else { "LTR" }, line);
+                // Create the word ranges that fits in a visual line
+                if line.rtl != span.level.is_rtl() {
+                    // incongruent directions
+                    let mut fitting_start = (span.words.len(), 0);
+                    for (i, word) in span.words.iter().enumerate().rev() {
+                        let word_width = word.width(font_size);
 
-        for para_info in bidi.paragraphs.iter() {
-            let line_rtl = para_info.level.is_rtl();
-            assert_eq!(line_rtl, rtl);
+                        // Addition in the same order used to compute the final width, so that
+                        // relayouts with that width as the `line_width` will produce the same
+                        // wrapping results.
+                        if current_visual_line.w + (word_range_width + word_width)
+                            <= width_opt.unwrap_or(f32::INFINITY)
+                            // Include one blank word over the width limit since it won't be
+                            // counted in the final width
+                            || (word.blank
+                                && (current_visual_line.w + word_range_width) <= width_opt.unwrap_or(f32::INFINITY))
+                        {
+                            // fits
+                            if word.blank {
+                                number_of_blanks += 1;
+                                width_before_last_blank = word_range_width;
+                            }
+                            word_range_width += word_width;
+                            continue;
+                        } else if wrap == Wrap::Glyph
+                            // Make sure that the word is able to fit on it's own line, if not, fall back to Glyph wrapping.
+                            || (wrap == Wrap::WordOrGlyph && word_width > width_opt.unwrap_or(f32::INFINITY))
+                        {
+                            // Commit the current line so that the word starts on the next line.
+                            if word_range_width > 0.
+                                && wrap == Wrap::WordOrGlyph
+                                && word_width > width_opt.unwrap_or(f32::INFINITY)
+                            {
+                                add_to_visual_line(
+                                    &mut current_visual_line,
+                                    span_index,
+                                    (i + 1, 0),
+                                    fitting_start,
+                                    word_range_width,
+                                    number_of_blanks,
+                                );
 
-            let line_range = para_info.range.clone();
-            let levels = Self::adjust_levels(&unicode_bidi::Paragraph::new(&bidi, para_info));
+                                visual_lines.push(current_visual_line);
+                                current_visual_line = VisualLine::default();
 
-            // Find consecutive level runs. We use this to create Spans.
-            // Each span is a set of characters with equal levels.
-            let mut start = line_range.start;
-            let mut run_level = levels[start];
-            spans.reserve(line_range.end - start + 1);
-
-            for (i, &new_level) in levels
-                .iter()
-                .enumerate()
-                .take(line_range.end)
-                .skip(start + 1)
-            {
-                if new_level != run_level {
-                    // End of the previous run, start of a new one.
-                    spans.push(ShapeSpan::new_in_buffer(
-                        scratch,
-                        font_system,
-                        line,
-                        attrs_list,
-                        start..i,
-                        line_rtl,
-                        run_level,
-                        shaping,
-                    ));
-                    start = i;
-                    run_level = new_level;
-                }
-            }
-            spans.push(ShapeSpan::new_in_buffer(
-                scratch,
-                font_system,
-                line,
-                attrs_list,
-                start..line_range.end,
-                line_rtl,
-                run_level,
-                shaping,
-            ));
-        }
-
-        // Adjust for tabs
-        let mut x = 0.0;
-        for span in spans.iter_mut() {
-            for word in span.words.iter_mut() {
-                for glyph in word.glyphs.iter_mut() {
-                    if line.get(glyph.start..glyph.end) == Some("\t") {
-                        // Tabs are shaped as spaces, so they will always have the x_advance of a space.
-                        let tab_x_advance = (tab_width as f32) * glyph.x_advance;
-                        let tab_stop = (math::floorf(x / tab_x_advance) + 1.0) * tab_x_advance;
-                        glyph.x_advance = tab_stop - x;
-                    }
-                    x += glyph.x_advance;
-                }
-            }
-        }
-
-        Self {
-            rtl,
-            spans,
-            metrics_opt: attrs_list.defaults().metrics_opt.map(|x| x.into()),
-        }
-    }
-
-    // A modified version of first part of unicode_bidi::bidi_info::visual_run
-    fn adjust_levels(para: &unicode_bidi::Paragraph) -> Vec<unicode_bidi::Level> {
-        use unicode_bidi::BidiClass::*;
-        let text = para.info.text;
-        let levels = &para.info.levels;
-        let original_classes = &para.info.original_classes;
-
-        let mut levels = levels.clone();
-        let line_classes = &original_classes[..];
-        let line_levels = &mut levels[..];
-
-        // Reset some whitespace chars to paragraph level.
-        // <http://www.unicode.org/reports/tr9/#L1>
-        let mut reset_from: Option<usize> = Some(0);
-        let mut reset_to: Option<usize> = None;
-        for (i, c) in text.char_indices() {
-            match line_classes[i] {
-                // Ignored by X9
-                RLE | LRE | RLO | LRO | PDF | BN => {}
-                // Segment separator, Paragraph separator
-                B | S => {
-                    assert_eq!(reset_to, None);
-                    reset_to = Some(i + c.len_utf8());
-                    if reset_from.is_none() {
-                        reset_from = Some(i);
-                    }
-                }
-                // Whitespace, isolate formatting
-                WS | FSI | LRI | RLI | PDI => {
-                    if reset_from.is_none() {
-                        reset_from = Some(i);
-                    }
-                }
-                _ => {
-                    reset_from = None;
-                }
-            }
-            if let (Some(from), Some(to)) = (reset_from, reset_to) {
-                for level in &mut line_levels[from..to] {
-                    *level = para.para.level;
-                }
-                reset_from = None;
-                reset_to = None;
-            }
-        }
-        if let Some(from) = reset_from {
-            for level in &mut line_levels[from..] {
-                *level = para.para.level;
-            }
-        }
-        levels
-    }
-
-    // A modified version of second part of unicode_bidi::bidi_info::visual run
-    fn reorder(&self, line_range: &[VlRange]) -> Vec<Range<usize>> {
-        let line: Vec<unicode_bidi::Level> = line_range
-            .iter()
-            .map(|(span_index, _, _)| self.spans[*span_index].level)
-            .collect();
-        // Find consecutive level runs.
-        let mut runs = Vec::new();
-        let mut start = 0;
-        let mut run_level = line[start];
-        let mut min_level = run_level;
-        let mut max_level = run_level;
-
-        for (i, &new_level) in line.iter().enumerate().skip(start + 1) {
-            if new_level != run_level {
-                // End of the previous run, start of a new one.
-                runs.push(start..i);
-                start = i;
-                run_level = new_level;
-                min_level = min(run_level, min_level);
-                max_level = max(run_level, max_level);
-            }
-        }
-        runs.push(start..line.len());
-
-        let run_count = runs.len();
-
-        // Re-order the odd runs.
-        // <http://www.unicode.org/reports/tr9/#L2>
-
-        // Stop at the lowest *odd* level.
-        min_level = min_level.new_lowest_ge_rtl().expect("Level error");
-
-        while max_level >= min_level {
-            // Look for the start of a sequence of consecutive runs of max_level or higher.
-            let mut seq_start = 0;
-            while seq_start < run_count {
-                if line[runs[seq_start].start] < max_level {
-                    seq_start += 1;
-                    continue;
-                }
-
-                // Found the start of a sequence. Now find the end.
-                let mut seq_end = seq_start + 1;
-                while seq_end < run_count {
-                    if line[runs[seq_end].start] < max_level {
-                        break;
-                    }
-                    seq_end += 1;
-                }
-
-                // Reverse the runs within this sequence.
-                runs[seq_start..seq_end].reverse();
-
-                seq_start = seq_end;
-            }
-            max_level
-                .lower(1)
-                .expect("Lowering embedding level below zero");
-        }
-
-        runs
-    }
+                                number_of_blanks = 0;
+                                word_range_width = 0.;
 
-    pub fn layout(
-        &self,
-        font_size: f32,
-        width_opt: Option<f32>,
-        wrap: Wrap,
-        align: Option<Align>,
-        match_mono_width: Option<f32>,
-    ) -> Vec<LayoutLine> {
-        let mut lines = Vec::with_capacity(1);
-        self.layout_to_buffer(
-            &mut ShapeBuffer::default(),
-            font_size,
-            width_opt,
-            wrap,
-            align,
-            &mut lines,
-            match_mono_width,
-        );
-        lines
-    }
+                                fitting_start = (i, 0);
+                            }
 
-    pub fn layout_to_buffer(
-        &self,
-        scratch: &mut ShapeBuffer,
-        font_size: f32,
-        width_opt: Option<f32>,
-        wrap: Wrap,
-        align: Option<Align>,
-        layout_lines: &mut Vec<LayoutLine>,
-        match_mono_width: Option<f32>,
-    ) {
-        // For each visual line a list of  (span index,  and range of words in that span)
-        // Note that a BiDi visual line could have multiple spans or parts of them
-        // let mut vl_range_of_spans = Vec::with_capacity(1);
-        let mut visual_lines: Vec<VisualLine> = {
-            let mut visual_lines = mem::take(&mut scratch.visual_lines);
-            visual_lines.clear();
-            visual_lines
-        };
+                            for (glyph_i, glyph) in word.glyphs.iter().enumerate().rev() {
+                                let glyph_width = glyph.width(font_size);
+                                if current_visual_line.w + (word_range_width + glyph_width)
+                                    <= width_opt.unwrap_or(f32::INFINITY)
+                                {
+                                    word_range_width += glyph_width;
+                                    continue;
+                                } else {
+                                    add_to_visual_line(
+                                        &mut current_visual_line,
+                                        span_index,
+                                        (i, glyph_i + 1),
+                                        fitting_start,
+                                        word_range_width,
+                                        number_of_blanks,
+                                    );
+                                    visual_lines.push(current_visual_line);
+                                    current_visual_line = VisualLine::default();
 
-        fn add_to_visual_line(
-            vl: &mut VisualLine,
-            span_index: usize,
-            start: (usize, usize),
-            end: (usize, usize),
-            width: f32,
-            number_of_blanks: u32,
-        ) {
-            if end == start {
-                return;
-            }
+                                    number_of_blanks = 0;
+                                    word_range_width = glyph_width;
+                                    fitting_start = (i, glyph_i + 1);
+                                }
+                            }
+                        } else {
+                            // Wrap::Word, Wrap::WordOrGlyph
 
-            vl.ranges.push((span_index, start, end));
-            vl.w += width;
-            vl.spaces += number_of_blanks;
-        }
+                            // If we had a previous range, commit that line before the next word.
+                            if word_range_width > 0. {
+                                // Current word causing a wrap is not whitespace, so we ignore the
+                                // previous word if it's a whitespace
+                                let trailing_blank = span
+                                    .words
+                                    .get(i + 1)
+                                    .map_or(false, |previous_word| previous_word.blank);
+                                if trailing_blank {
+                                    number_of_blanks = number_of_blanks.saturating_sub(1);
+                                    add_to_visual_line(
+                                        &mut current_visual_line,
+                                        span_index,
+                                        (i + 2, 0),
+                                        fitting_start,
+                                        width_before_last_blank,
+                                        number_of_blanks,
+                                    );
+                                } else {
+                                    add_to_visual_line(
+                                        &mut current_visual_line,
+                                        span_index,
+                                        (i + 1, 0),
+                                        fitting_start,
+                                        word_range_width,
+                                        number_of_blanks,
+                                    );
+                                }
 
-        // This would keep the maximum number of spans that would fit on a visual line
-        // If one span is too large, this variable will hold the range of words inside that span
-        // that fits on a line.
-        // let mut current_visual_line: Vec<VlRange> = Vec::with_capacity(1);
-        let mut current_visual_line = VisualLine::default();
+                                visual_lines.push(current_visual_line);
+                                current_visual_line = VisualLine::default();
+                                number_of_blanks = 0;
+                            }
 
-        if wrap == Wrap::None {
-            for (span_index, span) in self.spans.iter().enumerate() {
-                let mut word_range_width = 0.;
-                let mut number_of_blanks: u32 = 0;
-                for word in span.words.iter() {
-                    let word_width = word.width(font_size);
-                    word_range_width += word_width;
-                    if word.blank {
-                        number_of_blanks += 1;
+                            if word.blank {
+                                word_range_width = 0.;
+                                fitting_start = (i, 0);
+                            } else {
+                                word_range_width = word_width;
+                                fitting_start = (i + 1, 0);
+                            }
+                        }
                     }
-                }
-                add_to_visual_line(
-                    &mut current_visual_line,
-                    span_index,
-                    (0, 0),
-                    (span.words.len(), 0),
-                    word_range_width,
-                    number_of_blanks,
-                );
-            }
-        } else {
-            for (span_index, span) in self.spans.iter().enumerate() {
-                let mut word_range_width = 0.;
-                let mut width_before_last_blank = 0.;
-                let mut number_of_blanks: u32 = 0;
-
-                // Create the word ranges that fits in a visual line
-                if self.rtl != span.level.is_rtl() {
-                    // incongruent directions
-                    let mut fitting_start = (span.words.len(), 0);
-                    for (i, word) in span.words.iter().enumerate().rev() {
+                    add_to_visual_line(
+                        &mut current_visual_line,
+                        span_index,
+                        (0, 0),
+                        fitting_start,
+                        word_range_width,
+                        number_of_blanks,
+                    );
+                } else {
+                    // congruent direction
+                    let mut fitting_start = (0, 0);
+                    for (i, word) in span.words.iter().enumerate() {
                         let word_width = word.width(font_size);
-
-                        // Addition in the same order used to compute the final width, so that
-                        // relayouts with that width as the `line_width` will produce the same
-                        // wrapping results.
                         if current_visual_line.w + (word_range_width + word_width)
                             <= width_opt.unwrap_or(f32::INFINITY)
                             // Include one blank word over the width limit since it won't be
-                            // counted in the final width
+                            // counted in the final width.
                             || (word.blank
                                 && (current_visual_line.w + word_range_width) <= width_opt.unwrap_or(f32::INFINITY))
                         {
@@ -1120,8 +1385,8 @@ impl ShapeLine {
                                 add_to_visual_line(
                                     &mut current_visual_line,
                                     span_index,
-                                    (i + 1, 0),
                                     fitting_start,
+                                    (i, 0),
                                     word_range_width,
                                     number_of_blanks,
                                 );
@@ -1135,7 +1400,7 @@ impl ShapeLine {
                                 fitting_start = (i, 0);
                             }
 
-                            for (glyph_i, glyph) in word.glyphs.iter().enumerate().rev() {
+                            for (glyph_i, glyph) in word.glyphs.iter().enumerate() {
                                 let glyph_width = glyph.width(font_size);
                                 if current_visual_line.w + (word_range_width + glyph_width)
                                     <= width_opt.unwrap_or(f32::INFINITY)
@@ -1146,8 +1411,8 @@ impl ShapeLine {
                                     add_to_visual_line(
                                         &mut current_visual_line,
                                         span_index,
-                                        (i, glyph_i + 1),
                                         fitting_start,
+                                        (i, glyph_i),
                                         word_range_width,
                                         number_of_blanks,
                                     );
@@ -1156,7 +1421,7 @@ impl ShapeLine {
 
                                     number_of_blanks = 0;
                                     word_range_width = glyph_width;
-                                    fitting_start = (i, glyph_i + 1);
+                                    fitting_start = (i, glyph_i);
                                 }
                             }
                         } else {
@@ -1165,18 +1430,16 @@ impl ShapeLine {
                             // If we had a previous range, commit that line before the next word.
                             if word_range_width > 0. {
                                 // Current word causing a wrap is not whitespace, so we ignore the
-                                // previous word if it's a whitespace
-                                let trailing_blank = span
-                                    .words
-                                    .get(i + 1)
-                                    .map_or(false, |previous_word| previous_word.blank);
+                                // previous word if it's a whitespace.
+                                let trailing_blank = i > 0 && span.words[i - 1].blank;
+
                                 if trailing_blank {
                                     number_of_blanks = number_of_blanks.saturating_sub(1);
                                     add_to_visual_line(
                                         &mut current_visual_line,
                                         span_index,
-                                        (i + 2, 0),
                                         fitting_start,
+                                        (i - 1, 0),
                                         width_before_last_blank,
                                         number_of_blanks,
                                     );
@@ -1184,8 +1447,8 @@ impl ShapeLine {
                                     add_to_visual_line(
                                         &mut current_visual_line,
                                         span_index,
-                                        (i + 1, 0),
                                         fitting_start,
+                                        (i, 0),
                                         word_range_width,
                                         number_of_blanks,
                                     );
@@ -1198,150 +1461,552 @@ impl ShapeLine {
 
                             if word.blank {
                                 word_range_width = 0.;
-                                fitting_start = (i, 0);
+                                fitting_start = (i + 1, 0);
                             } else {
                                 word_range_width = word_width;
-                                fitting_start = (i + 1, 0);
+                                fitting_start = (i, 0);
                             }
                         }
                     }
                     add_to_visual_line(
                         &mut current_visual_line,
                         span_index,
-                        (0, 0),
                         fitting_start,
+                        (span.words.len(), 0),
                         word_range_width,
                         number_of_blanks,
                     );
-                } else {
-                    // congruent direction
-                    let mut fitting_start = (0, 0);
-                    for (i, word) in span.words.iter().enumerate() {
-                        let word_width = word.width(font_size);
-                        if current_visual_line.w + (word_range_width + word_width)
-                            <= width_opt.unwrap_or(f32::INFINITY)
-                            // Include one blank word over the width limit since it won't be
-                            // counted in the final width.
-                            || (word.blank
-                                && (current_visual_line.w + word_range_width) <= width_opt.unwrap_or(f32::INFINITY))
-                        {
-                            // fits
-                            if word.blank {
-                                number_of_blanks += 1;
-                                width_before_last_blank = word_range_width;
-                            }
-                            word_range_width += word_width;
-                            continue;
-                        } else if wrap == Wrap::Glyph
-                            // Make sure that the word is able to fit on it's own line, if not, fall back to Glyph wrapping.
-                            || (wrap == Wrap::WordOrGlyph && word_width > width_opt.unwrap_or(f32::INFINITY))
-                        {
-                            // Commit the current line so that the word starts on the next line.
-                            if word_range_width > 0.
-                                && wrap == Wrap::WordOrGlyph
-                                && word_width > width_opt.unwrap_or(f32::INFINITY)
-                            {
-                                add_to_visual_line(
-                                    &mut current_visual_line,
-                                    span_index,
-                                    fitting_start,
-                                    (i, 0),
-                                    word_range_width,
-                                    number_of_blanks,
-                                );
+                }
+            }
+        }
+
+        if !current_visual_line.ranges.is_empty() {
+            visual_lines.push(current_visual_line);
+        }
+    }
+}
+
+/// A shaped line (or paragraph)
+///
+/// This is the input of [`ShapeLine::layout`], and can also be used to build custom line
+/// breaking. Spans are in logical order, and each span has its words and glyphs in visual order
+/// from the start of the line, so they are reordered from right to left in RTL lines.
+///
+/// ```
+/// use cosmic_text::{Attrs, AttrsList, FontSystem, ShapeLine, Shaping};
+///
+/// let mut font_system = FontSystem::new();
+/// let attrs_list = AttrsList::new(Attrs::new());
+/// let line = ShapeLine::new(&mut font_system, "Hello world", &attrs_list, Shaping::Advanced, 8);
+///
+/// // Greedily break into lines of at most 50 pixels at a font size of 14
+/// let mut lines = vec![Vec::new()];
+/// let mut width = 0.0;
+/// for (_, word) in line.words() {
+///     let word_width = word.width(14.0);
+///     if width + word_width > 50.0 && !word.blank && width > 0.0 {
+///         lines.push(Vec::new());
+///         width = 0.0;
+///     }
+///     width += word_width;
+///     lines.last_mut().unwrap().push(word);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShapeLine {
+    /// True if the paragraph direction is right to left
+    pub rtl: bool,
+    /// Spans of text with the same bidirectional level, in logical order
+    pub spans: Vec<ShapeSpan>,
+    /// Metrics override from the default attributes
+    pub metrics_opt: Option<Metrics>,
+}
+
+/// A range of a [`ShapeLine`] in a [`VisualLine`]: the span index, and the start and end
+/// positions as `(word index, glyph index)` in that span, with the end excluded
+pub type VlRange = (usize, (usize, usize), (usize, usize));
+
+/// A visual line of a [`ShapeLine`], produced by a [`LineBreaker`]
+#[derive(Clone, Debug, Default)]
+pub struct VisualLine {
+    /// Ranges of the line, in logical order
+    pub ranges: Vec<VlRange>,
+    /// Number of blank words in the ranges, used for justification
+    pub spaces: u32,
+    /// Width of the ranges in pixels, not counting trailing whitespace
+    pub w: f32,
+}
+
+/// Bidirectional analysis of the text of a line, used to split it into spans for shaping
+///
+/// [`BufferLine`](crate::BufferLine) keeps the analysis while its text stays the same, so
+/// changing only its attributes, like colors from syntax highlighting, does not resolve the
+/// bidirectional levels again.
+#[derive(Clone, Debug)]
+pub struct LineBidi {
+    rtl: bool,
+    paragraphs: Vec<Range<usize>>,
+    levels: Vec<unicode_bidi::Level>,
+}
+
+impl LineBidi {
+    /// Analyze `line`. If [`unicode_bidi::BidiInfo`] detects multiple paragraphs, the line has
+    /// the direction of the first one.
+    pub fn new(line: &str) -> Self {
+        let bidi = unicode_bidi::BidiInfo::new(line, None);
+        let rtl = bidi
+            .paragraphs
+            .first()
+            .map_or(false, |para_info| para_info.level.is_rtl());
+        let mut paragraphs = Vec::with_capacity(bidi.paragraphs.len());
+        let mut levels = Vec::with_capacity(line.len());
+        for para_info in bidi.paragraphs.iter() {
+            let para_levels =
+                ShapeLine::adjust_levels(&unicode_bidi::Paragraph::new(&bidi, para_info));
+            levels.extend_from_slice(&para_levels[para_info.range.clone()]);
+            paragraphs.push(para_info.range.clone());
+        }
+        Self {
+            rtl,
+            paragraphs,
+            levels,
+        }
+    }
+
+    /// True if the line is right-to-left
+    pub fn rtl(&self) -> bool {
+        self.rtl
+    }
+
+    /// Get the bidirectional level of each byte of the line
+    pub fn levels(&self) -> &[unicode_bidi::Level] {
+        &self.levels
+    }
+}
+
+impl ShapeLine {
+    /// Shape a line into a set of spans. If [`unicode_bidi::BidiInfo`] detects multiple
+    /// paragraphs, they will be joined using the direction of the first one.
+    pub fn new(
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        tab_width: u16,
+    ) -> Self {
+        Self::new_in_buffer(
+            &mut ShapeBuffer::default(),
+            font_system,
+            line,
+            attrs_list,
+            shaping,
+            tab_width,
+            &BreakOverrides::default(),
+            &RustybuzzShapingBackend,
+        )
+    }
+
+    /// Shape a line like [`ShapeLine::new_in_buffer`], returning [`LayoutError::NoFont`] if no
+    /// font was found for some of the text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::NoFont`] if no font, including fallbacks, could shape the text.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new_in_buffer(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        tab_width: u16,
+        break_overrides: &BreakOverrides,
+        shaping_backend: &dyn ShapingBackend,
+    ) -> Result<Self, LayoutError> {
+        scratch.missing_font = false;
+        let shape = Self::new_in_buffer(
+            scratch,
+            font_system,
+            line,
+            attrs_list,
+            shaping,
+            tab_width,
+            break_overrides,
+            shaping_backend,
+        );
+        if mem::take(&mut scratch.missing_font) {
+            return Err(LayoutError::NoFont);
+        }
+        Ok(shape)
+    }
+
+    /// Shape a line into a set of spans, using a scratch buffer. If [`unicode_bidi::BidiInfo`]
+    /// detects multiple paragraphs, they will be joined using the direction of the first one.
+    ///
+    /// Text that no font was found for has no glyphs, see [`ShapeLine::try_new_in_buffer`].
+    pub fn new_in_buffer(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        tab_width: u16,
+        break_overrides: &BreakOverrides,
+        shaping_backend: &dyn ShapingBackend,
+    ) -> Self {
+        Self::new_with_bidi(
+            scratch,
+            font_system,
+            line,
+            &mut None,
+            attrs_list,
+            shaping,
+            tab_width,
+            break_overrides,
+            shaping_backend,
+        )
+    }
+
+    /// Shape a line like [`ShapeLine::new_in_buffer`], using the bidirectional analysis in
+    /// `bidi_opt` if it is set, or storing the analysis there so it can be used while the text
+    /// stays the same
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_with_bidi(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        bidi_opt: &mut Option<LineBidi>,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        tab_width: u16,
+        break_overrides: &BreakOverrides,
+        shaping_backend: &dyn ShapingBackend,
+    ) -> Self {
+        let mut spans = Vec::new();
+        let shaping = shaping.resolve(line);
+
+        #[cfg(feature = "perf")]
+        {
+            font_system.perf_counters_mut().lines_shaped += 1;
+        }
+
+        let bidi = match bidi_opt {
+            Some(bidi) => bidi,
+            None => {
+                #[cfg(feature = "perf")]
+                {
+                    font_system.perf_counters_mut().bidi_resolved += 1;
+                }
+                bidi_opt.insert(LineBidi::new(line))
+            }
+        };
+        let rtl = bidi.rtl;
+
+        log::trace!("Line {}: '{}'", if rtl { "RTL" } else { "LTR" }, line);
+
+        let levels = &bidi.levels;
+        for para_range in bidi.paragraphs.iter() {
+            // Paragraph separators inside of the line, like U+2029, may start paragraphs with
+            // another direction, which are laid out in the direction of the line
+            let line_rtl = rtl;
+
+            let line_range = para_range.clone();
+
+            // Find consecutive level runs. We use this to create Spans.
+            // Each span is a set of characters with equal levels.
+            let mut start = line_range.start;
+            let mut run_level = levels[start];
+            spans.reserve(line_range.end - start + 1);
+
+            for (i, &new_level) in levels
+                .iter()
+                .enumerate()
+                .take(line_range.end)
+                .skip(start + 1)
+            {
+                if new_level != run_level {
+                    // End of the previous run, start of a new one.
+                    spans.push(ShapeSpan::new_in_buffer(
+                        scratch,
+                        font_system,
+                        line,
+                        attrs_list,
+                        start..i,
+                        line_rtl,
+                        run_level,
+                        shaping,
+                        break_overrides,
+                        shaping_backend,
+                    ));
+                    start = i;
+                    run_level = new_level;
+                }
+            }
+            spans.push(ShapeSpan::new_in_buffer(
+                scratch,
+                font_system,
+                line,
+                attrs_list,
+                start..line_range.end,
+                line_rtl,
+                run_level,
+                shaping,
+                break_overrides,
+                shaping_backend,
+            ));
+        }
+
+        // Adjust for tabs
+        let mut x = 0.0;
+        for span in spans.iter_mut() {
+            for word in span.words.iter_mut() {
+                for glyph in word.glyphs.iter_mut() {
+                    if line.get(glyph.start..glyph.end) == Some("\t") {
+                        // Tabs are shaped as spaces, so they will always have the x_advance of a space.
+                        let tab_x_advance = (tab_width as f32) * glyph.x_advance;
+                        let tab_stop = (math::floorf(x / tab_x_advance) + 1.0) * tab_x_advance;
+                        glyph.x_advance = tab_stop - x;
+                    }
+                    x += glyph.x_advance;
+                }
+            }
+        }
+
+        // Adjust for span padding
+        for (range, attrs) in attrs_list.spans() {
+            if let Some(padding) = attrs.padding_opt {
+                pad_glyphs(&mut spans, range, padding, rtl);
+            }
+        }
+
+        Self {
+            rtl,
+            spans,
+            metrics_opt: attrs_list.defaults().metrics_opt.map(|x| x.into()),
+        }
+    }
+
+    /// Iterate over the words of every span with the bidirectional level of their span, see
+    /// [`ShapeLine`] for the order
+    pub fn words(&self) -> impl Iterator<Item = (unicode_bidi::Level, &ShapeWord)> + '_ {
+        self.spans
+            .iter()
+            .flat_map(|span| span.words.iter().map(move |word| (span.level, word)))
+    }
+
+    /// Iterate over the glyphs of every word with the bidirectional level of their span, see
+    /// [`ShapeLine`] for the order
+    pub fn glyphs(&self) -> impl Iterator<Item = (unicode_bidi::Level, &ShapeGlyph)> + '_ {
+        self.words()
+            .flat_map(|(level, word)| word.glyphs.iter().map(move |glyph| (level, glyph)))
+    }
 
-                                visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
+    // A modified version of first part of unicode_bidi::bidi_info::visual_run
+    fn adjust_levels(para: &unicode_bidi::Paragraph) -> Vec<unicode_bidi::Level> {
+        use unicode_bidi::BidiClass::*;
+        let text = para.info.text;
+        let levels = &para.info.levels;
+        let original_classes = &para.info.original_classes;
 
-                                number_of_blanks = 0;
-                                word_range_width = 0.;
+        let mut levels = levels.clone();
+        let line_classes = &original_classes[..];
+        let line_levels = &mut levels[..];
 
-                                fitting_start = (i, 0);
-                            }
+        // Reset some whitespace chars to paragraph level.
+        // <http://www.unicode.org/reports/tr9/#L1>
+        let mut reset_from: Option<usize> = Some(0);
+        let mut reset_to: Option<usize> = None;
+        for (i, c) in text.char_indices() {
+            match line_classes[i] {
+                // Ignored by X9
+                RLE | LRE | RLO | LRO | PDF | BN => {}
+                // Segment separator, Paragraph separator
+                B | S => {
+                    assert_eq!(reset_to, None);
+                    reset_to = Some(i + c.len_utf8());
+                    if reset_from.is_none() {
+                        reset_from = Some(i);
+                    }
+                }
+                // Whitespace, isolate formatting
+                WS | FSI | LRI | RLI | PDI => {
+                    if reset_from.is_none() {
+                        reset_from = Some(i);
+                    }
+                }
+                _ => {
+                    reset_from = None;
+                }
+            }
+            if let (Some(from), Some(to)) = (reset_from, reset_to) {
+                for level in &mut line_levels[from..to] {
+                    *level = para.para.level;
+                }
+                reset_from = None;
+                reset_to = None;
+            }
+        }
+        if let Some(from) = reset_from {
+            for level in &mut line_levels[from..] {
+                *level = para.para.level;
+            }
+        }
+        levels
+    }
 
-                            for (glyph_i, glyph) in word.glyphs.iter().enumerate() {
-                                let glyph_width = glyph.width(font_size);
-                                if current_visual_line.w + (word_range_width + glyph_width)
-                                    <= width_opt.unwrap_or(f32::INFINITY)
-                                {
-                                    word_range_width += glyph_width;
-                                    continue;
-                                } else {
-                                    add_to_visual_line(
-                                        &mut current_visual_line,
-                                        span_index,
-                                        fitting_start,
-                                        (i, glyph_i),
-                                        word_range_width,
-                                        number_of_blanks,
-                                    );
-                                    visual_lines.push(current_visual_line);
-                                    current_visual_line = VisualLine::default();
+    // A modified version of second part of unicode_bidi::bidi_info::visual run
+    fn reorder(&self, line_range: &[VlRange]) -> Vec<Range<usize>> {
+        let line: Vec<unicode_bidi::Level> = line_range
+            .iter()
+            .map(|(span_index, _, _)| self.spans[*span_index].level)
+            .collect();
+        // Find consecutive level runs.
+        let mut runs = Vec::new();
+        let mut start = 0;
+        let mut run_level = line[start];
+        let mut min_level = run_level;
+        let mut max_level = run_level;
 
-                                    number_of_blanks = 0;
-                                    word_range_width = glyph_width;
-                                    fitting_start = (i, glyph_i);
-                                }
-                            }
-                        } else {
-                            // Wrap::Word, Wrap::WordOrGlyph
+        for (i, &new_level) in line.iter().enumerate().skip(start + 1) {
+            if new_level != run_level {
+                // End of the previous run, start of a new one.
+                runs.push(start..i);
+                start = i;
+                run_level = new_level;
+                min_level = min(run_level, min_level);
+                max_level = max(run_level, max_level);
+            }
+        }
+        runs.push(start..line.len());
 
-                            // If we had a previous range, commit that line before the next word.
-                            if word_range_width > 0. {
-                                // Current word causing a wrap is not whitespace, so we ignore the
-                                // previous word if it's a whitespace.
-                                let trailing_blank = i > 0 && span.words[i - 1].blank;
+        let run_count = runs.len();
 
-                                if trailing_blank {
-                                    number_of_blanks = number_of_blanks.saturating_sub(1);
-                                    add_to_visual_line(
-                                        &mut current_visual_line,
-                                        span_index,
-                                        fitting_start,
-                                        (i - 1, 0),
-                                        width_before_last_blank,
-                                        number_of_blanks,
-                                    );
-                                } else {
-                                    add_to_visual_line(
-                                        &mut current_visual_line,
-                                        span_index,
-                                        fitting_start,
-                                        (i, 0),
-                                        word_range_width,
-                                        number_of_blanks,
-                                    );
-                                }
+        // Re-order the odd runs.
+        // <http://www.unicode.org/reports/tr9/#L2>
 
-                                visual_lines.push(current_visual_line);
-                                current_visual_line = VisualLine::default();
-                                number_of_blanks = 0;
-                            }
+        // Stop at the lowest *odd* level.
+        min_level = min_level.new_lowest_ge_rtl().expect("Level error");
 
-                            if word.blank {
-                                word_range_width = 0.;
-                                fitting_start = (i + 1, 0);
-                            } else {
-                                word_range_width = word_width;
-                                fitting_start = (i, 0);
-                            }
-                        }
+        while max_level >= min_level {
+            // Look for the start of a sequence of consecutive runs of max_level or higher.
+            let mut seq_start = 0;
+            while seq_start < run_count {
+                if line[runs[seq_start].start] < max_level {
+                    seq_start += 1;
+                    continue;
+                }
+
+                // Found the start of a sequence. Now find the end.
+                let mut seq_end = seq_start + 1;
+                while seq_end < run_count {
+                    if line[runs[seq_end].start] < max_level {
+                        break;
                     }
-                    add_to_visual_line(
-                        &mut current_visual_line,
-                        span_index,
-                        fitting_start,
-                        (span.words.len(), 0),
-                        word_range_width,
-                        number_of_blanks,
-                    );
+                    seq_end += 1;
                 }
+
+                // Reverse the runs within this sequence.
+                runs[seq_start..seq_end].reverse();
+
+                seq_start = seq_end;
             }
+            max_level
+                .lower(1)
+                .expect("Lowering embedding level below zero");
         }
 
-        if !current_visual_line.ranges.is_empty() {
-            visual_lines.push(current_visual_line);
-        }
+        runs
+    }
+
+    /// Get the words in a visual line range, along with the glyphs of each word in that range
+    fn visual_line_words<'a>(
+        &'a self,
+        vl_range: &VlRange,
+    ) -> impl Iterator<Item = (&'a ShapeSpan, &'a ShapeWord, &'a [ShapeGlyph])> + 'a {
+        let &(span_index, (starting_word, starting_glyph), (ending_word, ending_glyph)) = vl_range;
+        let span = &self.spans[span_index];
+        // If ending_glyph is not 0 we need to include glyphs from the ending_word
+        (starting_word..ending_word + usize::from(ending_glyph != 0)).map(move |i| {
+            let word = &span.words[i];
+            let included_glyphs = match (i == starting_word, i == ending_word) {
+                (false, false) => &word.glyphs[..],
+                (true, false) => &word.glyphs[starting_glyph..],
+                (false, true) => &word.glyphs[..ending_glyph],
+                (true, true) => &word.glyphs[starting_glyph..ending_glyph],
+            };
+            (span, word, included_glyphs)
+        })
+    }
+
+    pub fn layout(
+        &self,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        align: Option<Align>,
+        match_mono_width: Option<f32>,
+    ) -> Vec<LayoutLine> {
+        let mut lines = Vec::with_capacity(1);
+        self.layout_to_buffer(
+            &mut ShapeBuffer::default(),
+            font_size,
+            width_opt,
+            wrap,
+            align,
+            &mut lines,
+            match_mono_width,
+            Justification::Spaces,
+        );
+        lines
+    }
+
+    pub fn layout_to_buffer(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        align: Option<Align>,
+        layout_lines: &mut Vec<LayoutLine>,
+        match_mono_width: Option<f32>,
+        justification: Justification,
+    ) {
+        self.layout_to_buffer_with_breaker(
+            scratch,
+            font_size,
+            width_opt,
+            wrap,
+            align,
+            layout_lines,
+            match_mono_width,
+            justification,
+            &GreedyLineBreaker,
+        );
+    }
+
+    /// Layout the line like [`ShapeLine::layout_to_buffer`], using `line_breaker` to split it
+    /// into visual lines
+    pub fn layout_to_buffer_with_breaker(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_size: f32,
+        width_opt: Option<f32>,
+        wrap: Wrap,
+        align: Option<Align>,
+        layout_lines: &mut Vec<LayoutLine>,
+        match_mono_width: Option<f32>,
+        justification: Justification,
+        line_breaker: &dyn LineBreaker,
+    ) {
+        // For each visual line a list of  (span index,  and range of words in that span)
+        // Note that a BiDi visual line could have multiple spans or parts of them
+        // let mut vl_range_of_spans = Vec::with_capacity(1);
+        let mut visual_lines: Vec<VisualLine> = {
+            let mut visual_lines = mem::take(&mut scratch.visual_lines);
+            visual_lines.clear();
+            visual_lines
+        };
+        line_breaker.break_lines(self, font_size, width_opt, wrap, &mut visual_lines);
 
         // Create the LayoutLines using the ranges inside visual lines
         let align = align.unwrap_or({
@@ -1406,37 +2071,48 @@ impl ShapeLine {
             // (also some spaces aren't followed by potential linebreaks but they could
             //  still be expanded)
 
+            // Don't justify the last line in a paragraph.
+            let justify = matches!(align, Align::Justified) && index != number_of_visual_lines - 1;
+
+            // Number of glyphs within a line that can be followed by a kashida.
+            let kashida_points = if justify && justification == Justification::Kashida {
+                visual_line
+                    .ranges
+                    .iter()
+                    .flat_map(|vl_range| self.visual_line_words(vl_range))
+                    .filter(|(_, word, _)| !word.blank)
+                    .flat_map(|(_, _, glyphs)| glyphs)
+                    .filter(|glyph| glyph.kashida_opt.is_some())
+                    .count()
+            } else {
+                0
+            };
+
             // Amount of extra width added to each blank space within a line.
-            let justification_expansion = if matches!(align, Align::Justified)
-                && visual_line.spaces > 0
-                // Don't justify the last line in a paragraph.
-                && index != number_of_visual_lines - 1
-            {
-                (line_width - visual_line.w) / visual_line.spaces as f32
+            let justification_expansion =
+                if justify && kashida_points == 0 && visual_line.spaces > 0 {
+                    (line_width - visual_line.w) / visual_line.spaces as f32
+                } else {
+                    0.
+                };
+
+            // Amount of extra width added after each kashida insertion point within a line.
+            let kashida_expansion = if kashida_points > 0 {
+                (line_width - visual_line.w) / kashida_points as f32
             } else {
                 0.
             };
 
             let mut process_range = |range: Range<usize>| {
-                for &(span_index, (starting_word, starting_glyph), (ending_word, ending_glyph)) in
-                    visual_line.ranges[range.clone()].iter()
-                {
-                    let span = &self.spans[span_index];
-                    // If ending_glyph is not 0 we need to include glyphs from the ending_word
-                    for i in starting_word..ending_word + usize::from(ending_glyph != 0) {
-                        let word = &span.words[i];
-                        let included_glyphs = match (i == starting_word, i == ending_word) {
-                            (false, false) => &word.glyphs[..],
-                            (true, false) => &word.glyphs[starting_glyph..],
-                            (false, true) => &word.glyphs[..ending_glyph],
-                            (true, true) => &word.glyphs[starting_glyph..ending_glyph],
-                        };
-
+                for vl_range in visual_line.ranges[range.clone()].iter() {
+                    for (span, word, included_glyphs) in self.visual_line_words(vl_range) {
                         for glyph in included_glyphs {
                             // Use overridden font size
                             let font_size = glyph.metrics_opt.map_or(font_size, |x| x.font_size);
 
-                            let match_mono_em_width = match_mono_width.map(|w| w / font_size);
+                            let match_mono_em_width = match_mono_width
+                                .filter(|_| glyph.match_mono_width)
+                                .map(|w| w / font_size);
 
                             let glyph_font_size = match (
                                 match_mono_em_width,
@@ -1466,14 +2142,44 @@ impl ShapeLine {
                                 x -= x_advance;
                             }
                             let y_advance = glyph_font_size * glyph.y_advance;
-                            glyphs.push(glyph.layout(
+                            let kashida_opt = glyph
+                                .kashida_opt
+                                .filter(|_| kashida_expansion > 0. && !word.blank);
+                            let mut layout_glyph = glyph.layout(
                                 glyph_font_size,
                                 glyph.metrics_opt.map(|x| x.line_height),
                                 x,
                                 y,
                                 x_advance,
                                 span.level,
-                            ));
+                            );
+                            // Kashidas are placed to the left of the glyph they follow
+                            match kashida_opt {
+                                Some(kashida) if !self.rtl => {
+                                    push_kashida(
+                                        &mut glyphs,
+                                        &mut x,
+                                        false,
+                                        &layout_glyph,
+                                        kashida,
+                                        kashida_expansion,
+                                    );
+                                    layout_glyph.x = x;
+                                    glyphs.push(layout_glyph);
+                                }
+                                Some(kashida) => {
+                                    glyphs.push(layout_glyph.clone());
+                                    push_kashida(
+                                        &mut glyphs,
+                                        &mut x,
+                                        true,
+                                        &layout_glyph,
+                                        kashida,
+                                        kashida_expansion,
+                                    );
+                                }
+                                None => glyphs.push(layout_glyph),
+                            }
                             if !self.rtl {
                                 x += x_advance;
                             }
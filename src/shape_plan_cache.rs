@@ -3,7 +3,10 @@ use hashbrown::hash_map::Entry;
 #[cfg(feature = "std")]
 use std::collections::hash_map::Entry;
 
-use crate::{Font, HashMap};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{Attrs, Font, HashMap, Ligatures};
 
 /// Key for caching shape plans.
 #[derive(Debug, Hash, PartialEq, Eq)]
@@ -12,35 +15,63 @@ struct ShapePlanKey {
     direction: rustybuzz::Direction,
     script: rustybuzz::Script,
     language: Option<rustybuzz::Language>,
+    ligatures: Ligatures,
+    kerning: bool,
 }
 
 /// A helper structure for caching rustybuzz shape plans.
 #[derive(Default)]
-pub struct ShapePlanCache(HashMap<ShapePlanKey, rustybuzz::ShapePlan>);
+pub struct ShapePlanCache {
+    plans: HashMap<ShapePlanKey, rustybuzz::ShapePlan>,
+    #[cfg(feature = "perf")]
+    pub(crate) hits: u64,
+    #[cfg(feature = "perf")]
+    pub(crate) misses: u64,
+}
 
 impl ShapePlanCache {
-    pub fn get(&mut self, font: &Font, buffer: &rustybuzz::UnicodeBuffer) -> &rustybuzz::ShapePlan {
+    pub fn get(
+        &mut self,
+        font: &Font,
+        buffer: &rustybuzz::UnicodeBuffer,
+        attrs: &Attrs,
+    ) -> &rustybuzz::ShapePlan {
         let key = ShapePlanKey {
             font_id: font.id(),
             direction: buffer.direction(),
             script: buffer.script(),
             language: buffer.language(),
+            ligatures: attrs.ligatures,
+            kerning: attrs.kerning,
         };
-        match self.0.entry(key) {
-            Entry::Occupied(occ) => occ.into_mut(),
+        match self.plans.entry(key) {
+            Entry::Occupied(occ) => {
+                #[cfg(feature = "perf")]
+                {
+                    self.hits += 1;
+                }
+                occ.into_mut()
+            }
             Entry::Vacant(vac) => {
+                #[cfg(feature = "perf")]
+                {
+                    self.misses += 1;
+                }
                 let ShapePlanKey {
                     direction,
                     script,
                     language,
+                    ligatures,
+                    kerning,
                     ..
                 } = vac.key();
+                let features = features(*ligatures, *kerning);
                 let plan = rustybuzz::ShapePlan::new(
                     font.rustybuzz(),
                     *direction,
                     Some(*script),
                     language.as_ref(),
-                    &[],
+                    &features,
                 );
                 vac.insert(plan)
             }
@@ -48,6 +79,34 @@ impl ShapePlanCache {
     }
 }
 
+/// Get the font features to enable or disable for the attributes in a [`ShapePlanKey`]
+fn features(ligatures: Ligatures, kerning: bool) -> Vec<rustybuzz::Feature> {
+    let mut features = Vec::new();
+    if !kerning {
+        features.push(rustybuzz::Feature::new(
+            rustybuzz::ttf_parser::Tag::from_bytes(b"kern"),
+            0,
+            ..,
+        ));
+    }
+    let ligature_tags: &[&[u8; 4]] = &[b"liga", b"clig", b"dlig"];
+    let value = match ligatures {
+        Ligatures::Normal => None,
+        Ligatures::None => Some(0),
+        Ligatures::All => Some(1),
+    };
+    if let Some(value) = value {
+        for tag in ligature_tags {
+            features.push(rustybuzz::Feature::new(
+                rustybuzz::ttf_parser::Tag::from_bytes(tag),
+                value,
+                ..,
+            ));
+        }
+    }
+    features
+}
+
 impl core::fmt::Debug for ShapePlanCache {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("ShapePlanCache").finish()
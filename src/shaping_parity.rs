@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    AttrsList, BreakOverrides, FontSystem, RustybuzzShapingBackend, ShapeBuffer, ShapeLine,
+    Shaping, ShapingBackend,
+};
+
+/// Sample text of a script, see [`PARITY_CORPUS`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParitySample {
+    /// Name of the script
+    pub script: &'static str,
+    /// Text written in the script
+    pub text: &'static str,
+}
+
+/// Sample texts of common scripts for [`compare_shaping`]
+pub const PARITY_CORPUS: &[ParitySample] = &[
+    ParitySample {
+        script: "Latin",
+        text: "The quick brown fox jumps over the lazy dog",
+    },
+    ParitySample {
+        script: "Greek",
+        text: "Ξεσκεπάζω την ψυχοφθόρα βδελυγμία",
+    },
+    ParitySample {
+        script: "Cyrillic",
+        text: "Съешь же ещё этих мягких французских булок",
+    },
+    ParitySample {
+        script: "Arabic",
+        text: "نص حكيم له سر قاطع وذو شأن عظيم",
+    },
+    ParitySample {
+        script: "Hebrew",
+        text: "דג סקרן שט בים מאוכזב ולפתע מצא חברה",
+    },
+    ParitySample {
+        script: "Devanagari",
+        text: "ऋषियों को सताने वाले दुष्ट राक्षसों के राजा रावण का सर्वनाश करने वाले",
+    },
+    ParitySample {
+        script: "Thai",
+        text: "เป็นมนุษย์สุดประเสริฐเลิศคุณค่า",
+    },
+    ParitySample {
+        script: "Han",
+        text: "天地玄黄宇宙洪荒日月盈昃辰宿列张",
+    },
+    ParitySample {
+        script: "Japanese",
+        text: "いろはにほへと ちりぬるを カタカナ",
+    },
+    ParitySample {
+        script: "Hangul",
+        text: "다람쥐 헌 쳇바퀴에 타고파",
+    },
+];
+
+/// Text whose advance differs between cosmic-text and a reference shaper, see
+/// [`compare_shaping`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShapingMismatch {
+    /// Text that was shaped
+    pub text: String,
+    /// Byte range of the differing clusters in the text
+    pub range: Range<usize>,
+    /// Advance of the range shaped by cosmic-text, in em units
+    pub advance: f32,
+    /// Advance of the range shaped by the reference, in em units
+    pub reference_advance: f32,
+}
+
+/// Shape each of `texts` with [`RustybuzzShapingBackend`] and with `reference`, returning the
+/// ranges whose advances differ by more than `tolerance` em
+///
+/// Use this to check shaping on a set of fonts, by implementing [`ShapingBackend`] for the
+/// native shaper of a platform. Ranges are compared between the cluster boundaries both shapers
+/// agree on, so clusters grouped differently are compared together instead of reporting every
+/// following cluster.
+pub fn compare_shaping<'a>(
+    font_system: &mut FontSystem,
+    texts: impl IntoIterator<Item = &'a str>,
+    attrs_list: &AttrsList,
+    reference: &dyn ShapingBackend,
+    tolerance: f32,
+) -> Vec<ShapingMismatch> {
+    let mut scratch = ShapeBuffer::default();
+    let mut mismatches = Vec::new();
+    for text in texts {
+        let mut shape = |shaping_backend: &dyn ShapingBackend| {
+            ShapeLine::new_in_buffer(
+                &mut scratch,
+                font_system,
+                text,
+                attrs_list,
+                Shaping::Advanced,
+                0,
+                &BreakOverrides::default(),
+                shaping_backend,
+            )
+        };
+        let advances = cluster_advances(&shape(&RustybuzzShapingBackend));
+        let reference_advances = cluster_advances(&shape(reference));
+
+        // Compare the advances between cluster starts found by both shapers
+        let mut start = 0;
+        let (mut advance, mut reference_advance) = (0.0f32, 0.0f32);
+        let (mut i, mut j) = (0, 0);
+        while i < advances.len() || j < reference_advances.len() {
+            let index = advances.get(i).map_or(usize::MAX, |&(index, _)| index);
+            let reference_index = reference_advances
+                .get(j)
+                .map_or(usize::MAX, |&(index, _)| index);
+            let boundary = index.min(reference_index);
+            if index == reference_index && boundary > start {
+                if (advance - reference_advance).abs() > tolerance {
+                    mismatches.push(ShapingMismatch {
+                        text: text.into(),
+                        range: start..boundary,
+                        advance,
+                        reference_advance,
+                    });
+                }
+                start = boundary;
+                advance = 0.0;
+                reference_advance = 0.0;
+            }
+            if index == boundary {
+                advance += advances[i].1;
+                i += 1;
+            }
+            if reference_index == boundary {
+                reference_advance += reference_advances[j].1;
+                j += 1;
+            }
+        }
+        if (advance - reference_advance).abs() > tolerance {
+            mismatches.push(ShapingMismatch {
+                text: text.into(),
+                range: start..text.len(),
+                advance,
+                reference_advance,
+            });
+        }
+    }
+    mismatches
+}
+
+/// Get the advance of each cluster of a shaped line by its start, in logical order
+fn cluster_advances(shape: &ShapeLine) -> Vec<(usize, f32)> {
+    let mut advances: Vec<(usize, f32)> = shape
+        .glyphs()
+        .map(|(_, glyph)| (glyph.start, glyph.x_advance))
+        .collect();
+    advances.sort_by_key(|&(start, _)| start);
+    advances.dedup_by(|(start, advance), (prev_start, prev_advance)| {
+        if start == prev_start {
+            *prev_advance += *advance;
+            true
+        } else {
+            false
+        }
+    });
+    advances
+}
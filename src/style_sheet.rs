@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::{Attrs, AttrsList, AttrsOwned};
+
+/// Identifier of a style in a [`StyleSheet`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct StyleId(usize);
+
+/// A registry of named styles, such as `"h1"`, `"code"` or `"quote"`
+///
+/// Attributes created with [`StyleSheet::attrs`] remember their [`StyleId`]. When styles are
+/// changed, for example when switching themes, [`Buffer::set_style_sheet`] updates all text using
+/// them at once.
+///
+/// [`Buffer::set_style_sheet`]: crate::Buffer::set_style_sheet
+#[derive(Clone, Debug, Default)]
+pub struct StyleSheet {
+    styles: Vec<(String, AttrsOwned)>,
+}
+
+impl StyleSheet {
+    /// Create an empty style sheet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attributes, including metrics, of a named style, returning its [`StyleId`]
+    ///
+    /// If the style already exists, its attributes are replaced and its [`StyleId`] is kept.
+    pub fn insert(&mut self, name: &str, attrs: Attrs) -> StyleId {
+        match self.id(name) {
+            Some(id) => {
+                self.styles[id.0].1 = AttrsOwned::new(attrs.style_id(id));
+                id
+            }
+            None => {
+                let id = StyleId(self.styles.len());
+                self.styles
+                    .push((name.into(), AttrsOwned::new(attrs.style_id(id))));
+                id
+            }
+        }
+    }
+
+    /// Get the [`StyleId`] of a named style
+    pub fn id(&self, name: &str) -> Option<StyleId> {
+        self.styles
+            .iter()
+            .position(|(style_name, _)| style_name == name)
+            .map(StyleId)
+    }
+
+    /// Get the name of a style
+    pub fn name(&self, id: StyleId) -> Option<&str> {
+        self.styles.get(id.0).map(|(name, _)| name.as_str())
+    }
+
+    /// Get the attributes of a style, referencing its [`StyleId`]
+    pub fn attrs(&self, id: StyleId) -> Option<Attrs<'_>> {
+        self.styles.get(id.0).map(|(_, attrs)| attrs.as_attrs())
+    }
+
+    /// Get the attributes of a named style, referencing its [`StyleId`]
+    pub fn attrs_by_name(&self, name: &str) -> Option<Attrs<'_>> {
+        self.attrs(self.id(name)?)
+    }
+
+    /// Resolve the styled defaults and spans of an attributes list again, returning None if
+    /// nothing changed
+    pub fn restyle(&self, attrs_list: &AttrsList) -> Option<AttrsList> {
+        let restyle = |attrs: Attrs<'_>| match attrs.style_id_opt.and_then(|id| self.attrs(id)) {
+            Some(styled) if styled != attrs => Some(styled),
+            _ => None,
+        };
+
+        let defaults = attrs_list.defaults();
+        let spans = attrs_list.spans();
+        let restyled_defaults = restyle(defaults);
+        let restyled_spans: Vec<_> = spans
+            .iter()
+            .map(|(_, attrs)| restyle(attrs.as_attrs()))
+            .collect();
+        if restyled_defaults.is_none() && restyled_spans.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let mut new = AttrsList::new(restyled_defaults.unwrap_or(defaults));
+        for ((range, attrs), restyled) in spans.iter().zip(restyled_spans) {
+            new.add_span((*range).clone(), restyled.unwrap_or(attrs.as_attrs()));
+        }
+        Some(new)
+    }
+}
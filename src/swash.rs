@@ -1,15 +1,18 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use core::fmt;
-use swash::scale::{image::Content, ScaleContext};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+use swash::scale::image::Content;
 use swash::scale::{Render, Source, StrikeWith};
 use swash::zeno::{Format, Vector};
 
 use crate::{CacheKey, CacheKeyFlags, Color, FontSystem, HashMap};
 
 pub use swash::scale::image::{Content as SwashContent, Image as SwashImage};
+pub use swash::scale::ScaleContext;
 pub use swash::zeno::{Angle, Command, Placement, Transform};
 
 fn swash_image(
@@ -94,11 +97,80 @@ fn swash_outline_commands(
     Some(path.commands().collect())
 }
 
+/// Renders glyph images for a [`SwashCache`], see [`SwashCache::set_rasterizer`]
+///
+/// Implement this to render with another rasterizer, such as a platform rasterizer to match
+/// native text. Images are cached by their [`CacheKey`] whichever rasterizer renders them. The
+/// default is [`SwashRasterizer`].
+pub trait Rasterizer: fmt::Debug + Send + Sync {
+    /// Render the glyph of `cache_key`, or return None if it has no image
+    ///
+    /// The placement of the image is relative to the glyph origin, with `top` going up.
+    /// [`SwashContent::Mask`] images contain coverage that is tinted with the glyph color when
+    /// drawing. `context` is the scale context of the cache, which swash based rasterizers can
+    /// reuse.
+    fn rasterize(
+        &self,
+        context: &mut ScaleContext,
+        font_system: &mut FontSystem,
+        cache_key: CacheKey,
+    ) -> Option<SwashImage>;
+}
+
+/// The default [`Rasterizer`], which renders color outlines, color bitmaps, and outlines with
+/// the swash scaler
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SwashRasterizer;
+
+impl Rasterizer for SwashRasterizer {
+    fn rasterize(
+        &self,
+        context: &mut ScaleContext,
+        font_system: &mut FontSystem,
+        cache_key: CacheKey,
+    ) -> Option<SwashImage> {
+        swash_image(font_system, context, cache_key)
+    }
+}
+
+/// Render the glyph of `cache_key` with `rasterizer`, counting it in the performance counters
+fn rasterize(
+    rasterizer: &dyn Rasterizer,
+    context: &mut ScaleContext,
+    font_system: &mut FontSystem,
+    cache_key: CacheKey,
+) -> Option<SwashImage> {
+    #[cfg(feature = "perf")]
+    {
+        font_system.perf_counters_mut().glyphs_rasterized += 1;
+    }
+
+    rasterizer.rasterize(context, font_system, cache_key)
+}
+
+type EvictionObserver = Box<dyn FnMut(CacheKey) + Send + Sync>;
+
+/// Statistics of the image cache of a [`SwashCache`], see [`SwashCache::stats`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SwashCacheStats {
+    /// Number of images found in the cache
+    pub hits: u64,
+    /// Number of images rendered and added to the cache
+    pub misses: u64,
+    /// Number of images removed from the cache by [`SwashCache::trim`]
+    pub evictions: u64,
+}
+
 /// Cache for rasterizing with the swash scaler
 pub struct SwashCache {
     context: ScaleContext,
+    rasterizer: Arc<dyn Rasterizer>,
     pub image_cache: HashMap<CacheKey, Option<SwashImage>>,
     pub outline_command_cache: HashMap<CacheKey, Option<Vec<swash::zeno::Command>>>,
+    age: u64,
+    image_ages: HashMap<CacheKey, u64>,
+    stats: SwashCacheStats,
+    eviction_observers: Vec<EvictionObserver>,
 }
 
 impl fmt::Debug for SwashCache {
@@ -112,18 +184,94 @@ impl SwashCache {
     pub fn new() -> Self {
         Self {
             context: ScaleContext::new(),
+            rasterizer: Arc::new(SwashRasterizer),
             image_cache: HashMap::default(),
             outline_command_cache: HashMap::default(),
+            age: 0,
+            image_ages: HashMap::default(),
+            stats: SwashCacheStats::default(),
+            eviction_observers: Vec::new(),
+        }
+    }
+
+    /// Get the current [`Rasterizer`]
+    pub fn rasterizer(&self) -> &Arc<dyn Rasterizer> {
+        &self.rasterizer
+    }
+
+    /// Set the [`Rasterizer`] used to render images, the default is [`SwashRasterizer`]
+    ///
+    /// Images rendered by the previous rasterizer are removed from the cache, without reporting
+    /// them to [`SwashCache::on_evict`] callbacks.
+    pub fn set_rasterizer(&mut self, rasterizer: Arc<dyn Rasterizer>) {
+        if !Arc::ptr_eq(&rasterizer, &self.rasterizer) {
+            self.rasterizer = rasterizer;
+            self.image_cache.clear();
+            self.image_ages.clear();
         }
     }
 
+    /// Get the statistics of the image cache since it was created or
+    /// [`SwashCache::reset_stats`] was called
+    pub fn stats(&self) -> SwashCacheStats {
+        self.stats
+    }
+
+    /// Reset the statistics of the image cache to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = SwashCacheStats::default();
+    }
+
+    /// Call `f` with the key of every image removed from the cache by [`SwashCache::trim`], for
+    /// example to free its space in a texture atlas
+    pub fn on_evict<F: FnMut(CacheKey) + Send + Sync + 'static>(&mut self, f: F) {
+        self.eviction_observers.push(Box::new(f));
+    }
+
+    /// Remove all callbacks registered with [`SwashCache::on_evict`]
+    pub fn clear_eviction_observers(&mut self) {
+        self.eviction_observers.clear();
+    }
+
+    /// Remove images that were not used in the last `keep_ages` calls to this function, like
+    /// [`ShapeRunCache::trim`], reporting them to [`SwashCache::on_evict`] callbacks
+    ///
+    /// Call this once per frame to keep only the images that are drawn.
+    ///
+    /// [`ShapeRunCache::trim`]: crate::ShapeRunCache::trim
+    pub fn trim(&mut self, keep_ages: u64) {
+        let Self {
+            image_cache,
+            age,
+            image_ages,
+            stats,
+            eviction_observers,
+            ..
+        } = self;
+        image_cache.retain(|cache_key, _image| {
+            // Images added to the cache directly are kept until they are not used for a while
+            let image_age = *image_ages.entry(*cache_key).or_insert(*age);
+            let keep = image_age + keep_ages >= *age;
+            if !keep {
+                stats.evictions += 1;
+                for f in eviction_observers.iter_mut() {
+                    f(*cache_key);
+                }
+            }
+            keep
+        });
+        image_ages.retain(|cache_key, _age| image_cache.contains_key(cache_key));
+        // Increase age
+        *age += 1;
+    }
+
     /// Create a swash Image from a cache key, without caching results
     pub fn get_image_uncached(
         &mut self,
         font_system: &mut FontSystem,
         cache_key: CacheKey,
     ) -> Option<SwashImage> {
-        swash_image(font_system, &mut self.context, cache_key)
+        rasterize(&*self.rasterizer, &mut self.context, font_system, cache_key)
     }
 
     /// Create a swash Image from a cache key, caching results
@@ -132,9 +280,28 @@ impl SwashCache {
         font_system: &mut FontSystem,
         cache_key: CacheKey,
     ) -> &Option<SwashImage> {
+        self.image_ages.insert(cache_key, self.age);
+        if self.image_cache.contains_key(&cache_key) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        self.image_cache.entry(cache_key).or_insert_with(|| {
+            rasterize(&*self.rasterizer, &mut self.context, font_system, cache_key)
+        })
+    }
+
+    /// Get the content of the cached image for `cache_key`, or None if it was not rendered yet
+    /// or the glyph has no image
+    ///
+    /// Images are cached without a color. [`SwashContent::Mask`] images only contain coverage,
+    /// so renderers can upload them once and tint them with the color of each glyph when
+    /// drawing, while [`SwashContent::Color`] images, like emoji, are drawn as they are.
+    pub fn image_content(&self, cache_key: CacheKey) -> Option<SwashContent> {
         self.image_cache
-            .entry(cache_key)
-            .or_insert_with(|| swash_image(font_system, &mut self.context, cache_key))
+            .get(&cache_key)?
+            .as_ref()
+            .map(|image| image.content)
     }
 
     pub fn get_outline_commands(
@@ -165,12 +332,7 @@ impl SwashCache {
                     let mut i = 0;
                     for off_y in 0..image.placement.height as i32 {
                         for off_x in 0..image.placement.width as i32 {
-                            //TODO: blend base alpha?
-                            f(
-                                x + off_x,
-                                y + off_y,
-                                Color(((image.data[i] as u32) << 24) | base.0 & 0xFF_FF_FF),
-                            );
+                            f(x + off_x, y + off_y, base.mul_alpha(image.data[i]));
                             i += 1;
                         }
                     }
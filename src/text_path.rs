@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::{math, LayoutGlyph, LayoutLine};
+
+/// A glyph placed along a [`TextPath`]
+#[derive(Clone, Debug)]
+pub struct PathGlyph {
+    /// The laid out glyph, with position relative to its line
+    pub glyph: LayoutGlyph,
+    /// X position of the glyph origin on the path
+    pub x: f32,
+    /// Y position of the glyph origin on the path
+    pub y: f32,
+    /// Rotation of the glyph around its origin, in radians clockwise
+    pub angle: f32,
+}
+
+/// A path made of straight segments that a line of text can be laid out along
+///
+/// Curves are flattened into segments, see [`TextPath::quad_to`] and [`TextPath::cubic_to`].
+/// Text is shaped and laid out as usual, then [`TextPath::layout_line`] places each glyph at
+/// the point of the path matching its position in the line, which keeps clusters and bidi
+/// ordering intact.
+#[derive(Clone, Debug, Default)]
+pub struct TextPath {
+    points: Vec<(f32, f32)>,
+    /// Distance along the path at each point
+    distances: Vec<f32>,
+}
+
+impl TextPath {
+    /// Create a new path starting at `(x, y)`
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            points: vec![(x, y)],
+            distances: vec![0.0],
+        }
+    }
+
+    /// Create a new path through a list of points
+    ///
+    /// Returns None if there are no points.
+    pub fn from_points<I: IntoIterator<Item = (f32, f32)>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let (x, y) = points.next()?;
+        let mut path = Self::new(x, y);
+        for (x, y) in points {
+            path.line_to(x, y);
+        }
+        Some(path)
+    }
+
+    /// Add a straight segment to `(x, y)`
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        let (last_x, last_y) = self.last_point();
+        let length = math::sqrtf((x - last_x) * (x - last_x) + (y - last_y) * (y - last_y));
+        self.points.push((x, y));
+        self.distances.push(self.length() + length);
+        self
+    }
+
+    /// Add a quadratic Bézier curve to `(x, y)`, flattened into `segments` straight segments
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32, segments: usize) -> &mut Self {
+        let (x0, y0) = self.last_point();
+        let segments = segments.max(1);
+        for i in 1..=segments {
+            let t = i as f32 / segments as f32;
+            let mt = 1.0 - t;
+            self.line_to(
+                mt * mt * x0 + 2.0 * mt * t * cx + t * t * x,
+                mt * mt * y0 + 2.0 * mt * t * cy + t * t * y,
+            );
+        }
+        self
+    }
+
+    /// Add a cubic Bézier curve to `(x, y)`, flattened into `segments` straight segments
+    #[allow(clippy::too_many_arguments)]
+    pub fn cubic_to(
+        &mut self,
+        c1x: f32,
+        c1y: f32,
+        c2x: f32,
+        c2y: f32,
+        x: f32,
+        y: f32,
+        segments: usize,
+    ) -> &mut Self {
+        let (x0, y0) = self.last_point();
+        let segments = segments.max(1);
+        for i in 1..=segments {
+            let t = i as f32 / segments as f32;
+            let mt = 1.0 - t;
+            let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            self.line_to(
+                a * x0 + b * c1x + c * c2x + d * x,
+                a * y0 + b * c1y + c * c2y + d * y,
+            );
+        }
+        self
+    }
+
+    /// Get the points of the path
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Get the total length of the path
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Get the point and direction angle, in radians clockwise, at a distance along the path
+    ///
+    /// Returns None if the distance is outside of the path or the path has no length.
+    pub fn sample(&self, distance: f32) -> Option<(f32, f32, f32)> {
+        if distance < 0.0 || distance > self.length() {
+            return None;
+        }
+        // Find the first segment ending at or after the distance, skipping empty segments
+        let i = (1..self.points.len()).find(|&i| {
+            self.distances[i] >= distance && self.distances[i] > self.distances[i - 1]
+        })?;
+        let (x0, y0) = self.points[i - 1];
+        let (x1, y1) = self.points[i];
+        let t = (distance - self.distances[i - 1]) / (self.distances[i] - self.distances[i - 1]);
+        Some((
+            x0 + (x1 - x0) * t,
+            y0 + (y1 - y0) * t,
+            math::atan2f(y1 - y0, x1 - x0),
+        ))
+    }
+
+    /// Place the glyphs of a [`LayoutLine`] along the path, starting at `offset` along the path
+    ///
+    /// Each glyph is positioned so that its center lies on the path and it is rotated to follow
+    /// the direction of the path at that point. Glyphs that do not fit on the path are skipped.
+    pub fn layout_line(&self, line: &LayoutLine, offset: f32) -> Vec<PathGlyph> {
+        let mut glyphs = Vec::with_capacity(line.glyphs.len());
+        for glyph in line.glyphs.iter() {
+            let half_w = glyph.w / 2.0;
+            if let Some((x, y, angle)) = self.sample(offset + glyph.x + half_w) {
+                let (sin, cos) = (math::sinf(angle), math::cosf(angle));
+                // Move back to the glyph origin, then down to the glyph baseline offset
+                glyphs.push(PathGlyph {
+                    glyph: glyph.clone(),
+                    x: x - cos * half_w - sin * glyph.y,
+                    y: y - sin * half_w + cos * glyph.y,
+                    angle,
+                });
+            }
+        }
+        glyphs
+    }
+
+    fn last_point(&self) -> (f32, f32) {
+        self.points.last().copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{Attrs, BufferLine};
+
+/// A run of text with the same attributes, in logical order, see [`Buffer::text_runs`]
+///
+/// Unlike [`LayoutRun`], text runs do not depend on shaping, layout or scrolling, which makes
+/// them suited to search indexing, text-to-speech and screen readers.
+///
+/// [`Buffer::text_runs`]: crate::Buffer::text_runs
+/// [`LayoutRun`]: crate::LayoutRun
+#[derive(Debug)]
+pub struct TextRun<'a> {
+    /// The index of the original text line
+    pub line_i: usize,
+    /// The byte range of the run in the original text line
+    pub range: Range<usize>,
+    /// The text of the run
+    pub text: &'a str,
+    /// The attributes of the run
+    pub attrs: Attrs<'a>,
+}
+
+impl<'a> TextRun<'a> {
+    /// Get the BCP 47 language tag of the run, if set with [`Attrs::lang`]
+    pub fn language_opt(&self) -> Option<&'a str> {
+        self.attrs.language_opt
+    }
+}
+
+/// Split a line into text runs, filling gaps between attribute spans with the default attributes
+pub(crate) fn line_text_runs<'a>(line_i: usize, line: &'a BufferLine) -> Vec<TextRun<'a>> {
+    let text = line.text();
+    let attrs_list = line.attrs_list();
+    let mut runs = Vec::new();
+    let mut push = |range: Range<usize>, attrs: Attrs<'a>| {
+        let range = range.start.min(text.len())..range.end.min(text.len());
+        if !range.is_empty() {
+            runs.push(TextRun {
+                line_i,
+                text: &text[range.clone()],
+                range,
+                attrs,
+            });
+        }
+    };
+
+    let mut end = 0;
+    for (range, attrs) in attrs_list.spans() {
+        if range.start > end {
+            push(end..range.start, attrs_list.defaults());
+        }
+        push(range.clone(), attrs.as_attrs());
+        end = range.end;
+    }
+    push(end..text.len(), attrs_list.defaults());
+    runs
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::{fmt, ops::Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::BufferLine;
+
+/// Rules for finding words, used by word motions such as [`Motion::NextWord`] and by word
+/// selection such as [`Selection::Word`]
+///
+/// [`Motion::NextWord`]: crate::Motion::NextWord
+/// [`Selection::Word`]: crate::Selection::Word
+#[derive(Clone, Debug, Default)]
+pub enum WordBoundaries {
+    /// Words as defined by Unicode Standard Annex #29
+    ///
+    /// With the `dictionary-segmentation` feature, Thai, Lao, Khmer, and Burmese text is split
+    /// into words using a dictionary.
+    #[default]
+    Unicode,
+    /// Words as defined by Unicode Standard Annex #29, extended with these characters, such as
+    /// `"-$"` for identifiers in many programming languages
+    ///
+    /// Adjacent words and runs of these characters are joined into one word.
+    Extra(String),
+    /// Words are runs of characters that the function returns true for
+    Classifier(fn(char) -> bool),
+}
+
+impl WordBoundaries {
+    /// Get the byte ranges of the words in `text`
+    pub fn words(&self, text: &str) -> Vec<Range<usize>> {
+        let mut words: Vec<Range<usize>> = Vec::new();
+        match self {
+            Self::Unicode => {
+                for (i, word) in text.unicode_word_indices() {
+                    words.push(i..i + word.len());
+                }
+            }
+            Self::Extra(extra) => {
+                for (i, segment) in text.split_word_bound_indices() {
+                    let is_word = segment.chars().any(char::is_alphanumeric)
+                        || segment.chars().all(|c| extra.contains(c));
+                    if !is_word {
+                        continue;
+                    }
+                    match words.last_mut() {
+                        Some(last) if last.end == i => last.end = i + segment.len(),
+                        _ => words.push(i..i + segment.len()),
+                    }
+                }
+            }
+            Self::Classifier(is_word_char) => {
+                for (i, c) in text.char_indices() {
+                    if !is_word_char(c) {
+                        continue;
+                    }
+                    match words.last_mut() {
+                        Some(last) if last.end == i => last.end = i + c.len_utf8(),
+                        _ => words.push(i..i + c.len_utf8()),
+                    }
+                }
+                return words;
+            }
+        }
+
+        // Replace words in scripts segmented by dictionary
+        #[cfg(feature = "dictionary-segmentation")]
+        {
+            let (dictionary_words, runs) = crate::dictionary::dictionary_words(text);
+            if !runs.is_empty() {
+                words.retain(|word| {
+                    !runs
+                        .iter()
+                        .any(|run| word.start < run.end && run.start < word.end)
+                });
+                words.extend(dictionary_words);
+                words.sort_by_key(|word| word.start);
+            }
+        }
+
+        words
+    }
+
+    /// Get the start of the last word starting before `index`, or 0
+    pub fn previous_word_start(&self, text: &str, index: usize) -> usize {
+        previous_word_start(&self.words(text), index)
+    }
+
+    /// Get the end of the first word ending after `index`, or the length of `text`
+    pub fn next_word_end(&self, text: &str, index: usize) -> usize {
+        next_word_end(&self.words(text), index, text.len())
+    }
+}
+
+/// Get the start of the last of `words` starting before `index`, or 0
+pub(crate) fn previous_word_start(words: &[Range<usize>], index: usize) -> usize {
+    words
+        .iter()
+        .rev()
+        .map(|word| word.start)
+        .find(|&start| start < index)
+        .unwrap_or(0)
+}
+
+/// Get the end of the first of `words` ending after `index`, or `len`
+pub(crate) fn next_word_end(words: &[Range<usize>], index: usize, len: usize) -> usize {
+    words
+        .iter()
+        .map(|word| word.end)
+        .find(|&end| end > index)
+        .unwrap_or(len)
+}
+
+/// Adjusts the words of a line found with [`WordBoundaries`], see [`Buffer::set_word_hook`]
+///
+/// Implement this to make word motions and word selection follow the syntax of a language, for
+/// example to stop at each part of `foo::bar`, or to keep kebab-case identifiers together.
+///
+/// [`Buffer::set_word_hook`]: crate::Buffer::set_word_hook
+pub trait WordHook: fmt::Debug + Send + Sync {
+    /// Adjust the byte ranges of the words in `line`, which is at index `line_i` in the buffer
+    ///
+    /// Words may be added, removed, or changed, and are sorted by their start afterwards.
+    fn adjust_words(&self, line_i: usize, line: &BufferLine, words: &mut Vec<Range<usize>>);
+}
+
+#[test]
+fn test_word_boundaries() {
+    let text = "let $foo-bar = a_b;";
+    let words = |boundaries: WordBoundaries| -> Vec<&str> {
+        boundaries
+            .words(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    };
+    assert_eq!(words(WordBoundaries::Unicode), ["let", "foo", "bar", "a_b"]);
+    assert_eq!(
+        words(WordBoundaries::Extra("$-".into())),
+        ["let", "$foo-bar", "a_b"]
+    );
+    assert_eq!(
+        words(WordBoundaries::Classifier(|c| !c.is_whitespace())),
+        ["let", "$foo-bar", "=", "a_b;"]
+    );
+
+    let boundaries = WordBoundaries::Extra("$-".into());
+    assert_eq!(boundaries.previous_word_start(text, 10), 4);
+    assert_eq!(boundaries.next_word_end(text, 4), 12);
+    assert_eq!(boundaries.next_word_end(text, 18), text.len());
+}
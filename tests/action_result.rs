@@ -0,0 +1,38 @@
+use cosmic_text::{
+    Action, ActionResult, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion,
+    Selection, Shaping,
+};
+
+#[test]
+fn action_result() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(40.0));
+    let text = (0..20).map(|i| format!("line {i}\n")).collect::<String>();
+    buffer.set_text(&mut font_system, &text, Attrs::new(), Shaping::Advanced);
+    let mut editor = Editor::new(&mut buffer);
+
+    // Moving left at the start of the text does nothing
+    let result = editor.action(&mut font_system, Action::Motion(Motion::Left));
+    assert!(result.is_empty());
+
+    let result = editor.action(&mut font_system, Action::Motion(Motion::Right));
+    assert_eq!(result, ActionResult::CURSOR);
+
+    let result = editor.action(&mut font_system, Action::Insert('x'));
+    assert_eq!(result, ActionResult::TEXT | ActionResult::CURSOR);
+
+    // Deleting a selection changes text, cursor and selection
+    editor.set_selection(Selection::Normal(Cursor::new(0, 0)));
+    let result = editor.action(&mut font_system, Action::Backspace);
+    assert_eq!(
+        result,
+        ActionResult::TEXT | ActionResult::CURSOR | ActionResult::SELECTION
+    );
+
+    let result = editor.action(&mut font_system, Action::Escape);
+    assert!(result.is_empty());
+
+    let result = editor.action(&mut font_system, Action::Scroll { lines: 2 });
+    assert_eq!(result, ActionResult::SCROLL);
+}
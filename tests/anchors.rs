@@ -0,0 +1,61 @@
+use cosmic_text::{AnchorBias, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping};
+
+fn editor(font_system: &mut FontSystem, text: &str) -> Editor<'static> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    Editor::new(buffer)
+}
+
+#[test]
+fn anchors_follow_inserts() {
+    let mut font_system = FontSystem::new();
+    let mut editor = editor(&mut font_system, "hello world\nsecond line");
+
+    let (before, after, later, next_line) = editor.with_buffer_mut(|buffer| {
+        (
+            buffer.add_anchor(Cursor::new(0, 6), AnchorBias::Before),
+            buffer.add_anchor(Cursor::new(0, 6), AnchorBias::After),
+            buffer.add_anchor(Cursor::new(0, 8), AnchorBias::Before),
+            buffer.add_anchor(Cursor::new(1, 2), AnchorBias::Before),
+        )
+    });
+
+    editor.insert_at(Cursor::new(0, 6), "big\nnew ", None);
+
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines[1].text(), "new world");
+        assert_eq!(buffer.anchor(before), Some(Cursor::new(0, 6)));
+        assert_eq!(buffer.anchor(after), Some(Cursor::new(1, 4)));
+        assert_eq!(buffer.anchor(later), Some(Cursor::new(1, 6)));
+        assert_eq!(buffer.anchor(next_line), Some(Cursor::new(2, 2)));
+    });
+}
+
+#[test]
+fn anchors_follow_deletes() {
+    let mut font_system = FontSystem::new();
+    let mut editor = editor(&mut font_system, "first line\nsecond line\nthird line");
+
+    let (start, inside, after, next_line) = editor.with_buffer_mut(|buffer| {
+        (
+            buffer.add_anchor(Cursor::new(0, 2), AnchorBias::Before),
+            buffer.add_anchor(Cursor::new(1, 3), AnchorBias::Before),
+            buffer.add_anchor(Cursor::new(1, 8), AnchorBias::Before),
+            buffer.add_anchor(Cursor::new(2, 5), AnchorBias::Before),
+        )
+    });
+
+    editor.delete_range(Cursor::new(0, 5), Cursor::new(1, 6));
+
+    editor.with_buffer_mut(|buffer| {
+        assert_eq!(buffer.lines[0].text(), "first line");
+        assert_eq!(buffer.anchor(start), Some(Cursor::new(0, 2)));
+        assert_eq!(buffer.anchor(inside), Some(Cursor::new(0, 5)));
+        assert_eq!(buffer.anchor(after), Some(Cursor::new(0, 7)));
+        assert_eq!(buffer.anchor(next_line), Some(Cursor::new(1, 5)));
+
+        assert_eq!(buffer.remove_anchor(inside), Some(Cursor::new(0, 5)));
+        assert_eq!(buffer.anchor(inside), None);
+        assert_eq!(buffer.anchors().count(), 3);
+    });
+}
@@ -0,0 +1,40 @@
+use cosmic_text::{Attrs, AttrsList, Buffer, Color, FontSystem, Metrics, Shaping, Weight};
+
+#[test]
+fn drawing_attrs_do_not_reshape() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "fn main() {}",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    let red = Color::rgb(255, 0, 0);
+
+    // Highlighting with colors keeps the shape and layout, and updates their glyphs
+    let mut attrs_list = AttrsList::new(Attrs::new());
+    attrs_list.add_span(0..2, Attrs::new().color(red).metadata(1));
+    assert!(buffer.lines[0].set_attrs_list(attrs_list.clone()));
+    assert!(buffer.lines[0].pending_reshape_reasons().is_empty());
+    assert!(buffer.lines[0].shape_opt().is_some());
+    let layout = buffer.lines[0].layout_opt().as_ref().unwrap();
+    for glyph in layout[0].glyphs.iter() {
+        let highlighted = glyph.start < 2;
+        assert_eq!(glyph.color_opt, highlighted.then_some(red));
+        assert_eq!(glyph.metadata, usize::from(highlighted));
+    }
+    let shape = buffer.lines[0].shape_opt().as_ref().unwrap();
+    for (_, glyph) in shape.glyphs() {
+        assert_eq!(glyph.color_opt, (glyph.start < 2).then_some(red));
+    }
+    assert!(!buffer.lines[0].set_attrs_list(attrs_list));
+
+    // Changing the weight shapes the line again
+    let mut attrs_list = AttrsList::new(Attrs::new());
+    attrs_list.add_span(0..2, Attrs::new().color(red).weight(Weight::BOLD));
+    assert!(buffer.lines[0].set_attrs_list(attrs_list));
+    assert!(buffer.lines[0].shape_opt().is_none());
+    assert!(!buffer.lines[0].pending_reshape_reasons().is_empty());
+}
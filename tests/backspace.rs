@@ -0,0 +1,53 @@
+use cosmic_text::{
+    Action, Attrs, BackspaceMode, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping,
+};
+
+fn backspace(font_system: &mut FontSystem, mode: BackspaceMode, text: &str) -> String {
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    let mut editor = Editor::new(buffer);
+    editor.set_backspace_mode(mode);
+    editor.set_cursor(Cursor::new(0, text.len()));
+    editor.action(font_system, Action::Backspace);
+    editor.with_buffer(|buffer| buffer.lines[0].text().to_string())
+}
+
+#[test]
+fn backspace_emoji() {
+    let mut font_system = FontSystem::new();
+    for mode in [BackspaceMode::Grapheme, BackspaceMode::Codepoint] {
+        // Family ZWJ sequence
+        let family = "a\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        assert_eq!(backspace(&mut font_system, mode, family), "a");
+        // Flags, one after another
+        assert_eq!(
+            backspace(
+                &mut font_system,
+                mode,
+                "a\u{1F1E9}\u{1F1EA}\u{1F1EB}\u{1F1F7}"
+            ),
+            "a\u{1F1E9}\u{1F1EA}"
+        );
+        // Skin tone and subdivision flag
+        assert_eq!(
+            backspace(&mut font_system, mode, "a\u{1F44B}\u{1F3FD}"),
+            "a"
+        );
+        let scotland = "\u{1F3F4}\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}";
+        assert_eq!(backspace(&mut font_system, mode, scotland), "");
+    }
+}
+
+#[test]
+fn backspace_mode() {
+    let mut font_system = FontSystem::new();
+    let text = "ae\u{301}";
+    assert_eq!(
+        backspace(&mut font_system, BackspaceMode::Grapheme, text),
+        "a"
+    );
+    assert_eq!(
+        backspace(&mut font_system, BackspaceMode::Codepoint, text),
+        "ae"
+    );
+}
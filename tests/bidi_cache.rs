@@ -0,0 +1,47 @@
+#![cfg(feature = "perf")]
+
+use cosmic_text::{Attrs, AttrsList, Buffer, FontSystem, LineBidi, Metrics, Shaping, Weight};
+
+#[test]
+fn bidi_analysis_is_kept_for_attribute_changes() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let text = "שלום abc עולם";
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let shaped = font_system.perf_counters();
+    let glyphs: Vec<_> = buffer.layout_runs().next().unwrap().glyphs.to_vec();
+
+    // Making a word bold reshapes the line, but does not analyze its text again
+    let mut attrs_list = AttrsList::new(Attrs::new());
+    attrs_list.add_span(
+        0..text.find(' ').unwrap(),
+        Attrs::new().weight(Weight::BOLD),
+    );
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let emboldened = font_system.perf_counters().since(&shaped);
+    assert_eq!(emboldened.lines_shaped, 1);
+    assert_eq!(emboldened.bidi_resolved, 0);
+    let run = buffer.layout_runs().next().unwrap();
+    assert!(run.rtl);
+    assert_eq!(run.glyphs.len(), glyphs.len());
+    for (glyph, old) in run.glyphs.iter().zip(glyphs.iter()) {
+        assert_eq!(
+            (glyph.start, glyph.end, glyph.level),
+            (old.start, old.end, old.level)
+        );
+    }
+
+    // Changing the text analyzes it again
+    let before = font_system.perf_counters();
+    buffer.lines[0].set_text("abc", Default::default(), AttrsList::new(Attrs::new()));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(font_system.perf_counters().since(&before).bidi_resolved, 1);
+    assert!(!buffer.layout_runs().next().unwrap().rtl);
+
+    let bidi = LineBidi::new(text);
+    assert!(bidi.rtl());
+    assert_eq!(bidi.levels().len(), text.len());
+    assert!(bidi.levels()[text.find('a').unwrap()].is_ltr());
+}
@@ -0,0 +1,38 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn bidi_runs_and_logical_order() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let text = "ab שלום cd";
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let run = buffer.layout_runs().next().unwrap();
+
+    // Runs are left to right, and the Hebrew word is right-to-left
+    let runs = run.bidi_runs();
+    let levels: Vec<_> = runs.iter().map(|run| run.level.is_rtl()).collect();
+    assert_eq!(levels, [false, true, false]);
+    assert_eq!(&text[runs[1].range.clone()], "שלום");
+    for pair in runs.windows(2) {
+        assert!((pair[0].x + pair[0].w - pair[1].x).abs() < 0.01);
+    }
+    assert_eq!(
+        runs.iter().map(|run| run.glyphs.len()).sum::<usize>(),
+        run.glyphs.len()
+    );
+
+    // Glyphs of the Hebrew word are reversed on screen
+    let hebrew = &runs[1].glyphs;
+    let first = &run.glyphs[hebrew[0]];
+    let last = &run.glyphs[*hebrew.last().unwrap()];
+    assert!(first.start > last.start);
+
+    // Logical order visits the text from the start
+    let order = run.logical_order();
+    let starts: Vec<_> = order.iter().map(|&i| run.glyphs[i].start).collect();
+    let mut sorted = starts.clone();
+    sorted.sort();
+    assert_eq!(starts, sorted);
+    assert_eq!(order.len(), run.glyphs.len());
+}
@@ -0,0 +1,69 @@
+use cosmic_text::{
+    Attrs, AttrsList, Buffer, BufferLine, FontSystem, LineEnding, Metrics, Scroll, Shaping,
+};
+
+fn lines(count: usize) -> String {
+    (0..count)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn push_line(buffer: &mut Buffer) {
+    let text = format!("line {}", buffer.lines.len());
+    buffer.lines.push(BufferLine::new(
+        text,
+        LineEnding::default(),
+        AttrsList::new(Attrs::new()),
+        Shaping::Advanced,
+    ));
+}
+
+fn visible(buffer: &Buffer) -> Vec<(String, f32)> {
+    buffer
+        .layout_runs()
+        .map(|run| (run.text.to_string(), run.line_top))
+        .collect()
+}
+
+#[test]
+fn bottom_up_grows_upward() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(400.0), Some(100.0));
+    buffer.set_bottom_up(true);
+    assert!(buffer.bottom_up());
+
+    // Short content is aligned to the bottom
+    buffer.set_text(&lines(2), Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    assert_eq!(
+        visible(&buffer),
+        [("line 0".to_string(), 60.0), ("line 1".to_string(), 80.0)]
+    );
+
+    // New lines push earlier ones up
+    for _ in 2..8 {
+        push_line(&mut buffer);
+    }
+    buffer.shape_until_scroll(false);
+    assert_eq!(buffer.scroll(), Scroll::new(3, 0.0, 0.0));
+    assert_eq!(
+        visible(&buffer).last().unwrap(),
+        &("line 7".to_string(), 80.0)
+    );
+
+    // Scrolling away from the end keeps the scroll position
+    buffer.set_scroll(Scroll::new(1, 0.0, 0.0));
+    push_line(&mut buffer);
+    buffer.shape_until_scroll(false);
+    assert_eq!(buffer.scroll(), Scroll::new(1, 0.0, 0.0));
+
+    // Scrolling back to the end sticks to it again
+    buffer.set_scroll(Scroll::new(4, 0.0, 0.0));
+    buffer.shape_until_scroll(false);
+    push_line(&mut buffer);
+    buffer.shape_until_scroll(false);
+    assert_eq!(buffer.scroll(), Scroll::new(5, 0.0, 0.0));
+}
@@ -0,0 +1,77 @@
+use cosmic_text::{Attrs, BreakOverrides, Buffer, FontSystem, Metrics, Shaping, Wrap};
+
+fn wrapped(
+    font_system: &mut FontSystem,
+    text: &str,
+    break_overrides: BreakOverrides,
+) -> Vec<String> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_size(Some(50.0), None);
+    buffer.set_text(text, Attrs::new(), Shaping::Advanced);
+    buffer.lines[0].set_break_overrides(break_overrides);
+    buffer.shape_until_scroll(false);
+    buffer
+        .layout_runs()
+        .map(|run| {
+            let start = run
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.start)
+                .min()
+                .unwrap_or(0);
+            let end = run.glyphs.iter().map(|glyph| glyph.end).max().unwrap_or(0);
+            run.text[start..end].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn break_overrides() {
+    let mut font_system = FontSystem::new();
+
+    // Without a break opportunity the text overflows
+    let text = "abcdefghij";
+    assert_eq!(
+        wrapped(&mut font_system, text, BreakOverrides::new()).len(),
+        1
+    );
+
+    // An allowed break, like one from a dictionary segmenter, wraps the text there
+    assert_eq!(
+        wrapped(&mut font_system, text, BreakOverrides::new().allow(5)),
+        ["abcde", "fghij"]
+    );
+
+    // A forbidden break keeps words together
+    let text = "abcde fghij";
+    assert_eq!(
+        wrapped(&mut font_system, text, BreakOverrides::new()).len(),
+        2
+    );
+    assert_eq!(
+        wrapped(&mut font_system, text, BreakOverrides::new().forbid(6)).len(),
+        1
+    );
+}
+
+#[test]
+fn break_overrides_split() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "abcdef", Attrs::new(), Shaping::Advanced);
+    let line = &mut buffer.lines[0];
+    assert!(line.set_break_overrides(BreakOverrides::new().allow(2).allow(4)));
+    assert!(!line.set_break_overrides(BreakOverrides::new().allow(2).allow(4)));
+
+    // Overrides follow the text when a line is split and joined
+    let second = line.split_off(3);
+    assert_eq!(line.break_overrides(), &BreakOverrides::new().allow(2));
+    assert_eq!(second.break_overrides(), &BreakOverrides::new().allow(1));
+    line.append(second);
+    assert_eq!(
+        line.break_overrides(),
+        &BreakOverrides::new().allow(2).allow(4)
+    );
+}
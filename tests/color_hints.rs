@@ -0,0 +1,66 @@
+use cosmic_text::{
+    Attrs, Buffer, Color, Cursor, Edit, Editor, FontSystem, Metrics, Selection, Shaping, SwashCache,
+};
+
+#[test]
+fn span_color_hints() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), None);
+    let code_selection = Color::rgb(0x40, 0x40, 0x80);
+    let code_cursor = Color::rgb(0xFF, 0xFF, 0xFF);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("text ", Attrs::new()),
+            (
+                "code",
+                Attrs::new()
+                    .selection_color(code_selection)
+                    .cursor_color(code_cursor),
+            ),
+        ],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.shape_as_needed(false);
+
+    let text_color = Color::rgb(0, 0, 0);
+    let cursor_color = Color::rgb(0, 0, 0xFF);
+    let selection_color = Color::rgb(0xC0, 0xC0, 0xFF);
+    let mut rects = |editor: &mut cosmic_text::BorrowedWithFontSystem<Editor>| {
+        let mut rects = Vec::new();
+        editor.draw(
+            &mut swash_cache,
+            text_color,
+            cursor_color,
+            selection_color,
+            text_color,
+            |x, _, w, h, color| {
+                // Skip glyph pixels
+                if h > 1 {
+                    rects.push((x, w, color));
+                }
+            },
+        );
+        rects
+    };
+
+    // A selection over both spans is drawn in two colors
+    editor.set_selection(Selection::Normal(Cursor::new(0, 0)));
+    editor.set_cursor(Cursor::new(0, 9));
+    let drawn = rects(&mut editor);
+    let colors: Vec<_> = drawn.iter().map(|rect| rect.2).collect();
+    assert_eq!(colors, [selection_color, code_selection, code_cursor]);
+    assert_eq!(drawn[0].0 + drawn[0].1 as i32, drawn[1].0);
+
+    // The cursor uses the hint of the text before it
+    editor.set_selection(Selection::None);
+    editor.set_cursor(Cursor::new(0, 5));
+    assert_eq!(rects(&mut editor).last().unwrap().2, cursor_color);
+    editor.set_cursor(Cursor::new(0, 6));
+    assert_eq!(rects(&mut editor).last().unwrap().2, code_cursor);
+}
@@ -0,0 +1,37 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn content_size_and_scroll_fraction() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(None, Some(100.0));
+    let text = (0..50)
+        .map(|i| format!("line {}", "x".repeat(i)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    buffer.set_text(&text, Attrs::new(), Shaping::Advanced);
+
+    // The height includes lines below the visible area
+    let (width, height) = buffer.content_size();
+    assert!(width > 0.0);
+    assert_eq!(height, 50.0 * 20.0);
+    assert_eq!(buffer.scroll_fraction(), 0.0);
+
+    // The width grows as wider lines are laid out
+    for line_i in 0..buffer.lines.len() {
+        buffer.line_layout(line_i);
+    }
+    assert!(buffer.content_size().0 > width);
+
+    // Scrolling to a fraction is reported back
+    buffer.set_scroll_fraction(0.5);
+    let scroll = buffer.scroll();
+    assert_eq!((scroll.line, scroll.vertical), (22, 10.0));
+    assert_eq!(buffer.scroll_fraction(), 0.5);
+
+    buffer.set_scroll_fraction(1.0);
+    assert_eq!(buffer.scroll().line, 45);
+    assert_eq!(buffer.scroll_fraction(), 1.0);
+    assert_eq!(buffer.layout_runs().next_back().unwrap().line_i, 49);
+}
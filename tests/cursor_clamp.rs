@@ -0,0 +1,38 @@
+use cosmic_text::{Attrs, Buffer, Cursor, FontSystem, Metrics, Selection, Shaping};
+
+#[test]
+fn cursor_clamp_to() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    // "e" with a combining acute accent is one grapheme of three bytes
+    buffer.set_text(
+        &mut font_system,
+        "cafe\u{301}!\nend",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+
+    // Valid cursors are unchanged
+    for cursor in [Cursor::new(0, 0), Cursor::new(0, 3), Cursor::new(0, 6)] {
+        assert_eq!(cursor.clamp_to(&buffer), cursor);
+    }
+
+    // Inside of a grapheme or a code point
+    assert_eq!(Cursor::new(0, 4).clamp_to(&buffer), Cursor::new(0, 3));
+    assert_eq!(Cursor::new(0, 5).clamp_to(&buffer), Cursor::new(0, 3));
+
+    // Past the end of a line or the text
+    assert_eq!(Cursor::new(0, 100).clamp_to(&buffer), Cursor::new(0, 7));
+    assert_eq!(Cursor::new(5, 1).clamp_to(&buffer), Cursor::new(1, 3));
+
+    // Selections clamp their anchor
+    assert_eq!(
+        Selection::Word(Cursor::new(0, 5)).normalized(&buffer),
+        Selection::Word(Cursor::new(0, 3))
+    );
+    assert_eq!(Selection::None.normalized(&buffer), Selection::None);
+
+    // An empty buffer only has one position
+    let buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    assert_eq!(Cursor::new(2, 4).clamp_to(&buffer), Cursor::new(0, 0));
+}
@@ -0,0 +1,48 @@
+use cosmic_text::{Attrs, Buffer, Cursor, CursorShape, Edit, Editor, FontSystem, Metrics, Shaping};
+
+#[test]
+fn cursor_shape() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), None);
+    buffer.set_text(&mut font_system, "iWb", Attrs::new(), Shaping::Advanced);
+    let mut editor = Editor::new(&mut buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // A one pixel bar by default
+    assert_eq!(editor.cursor_shape(), CursorShape::Bar(1.0));
+    assert_eq!(editor.cursor_rect(), Some((0, 0, 1, 20)));
+
+    editor.set_cursor_shape(CursorShape::Bar(2.0));
+    assert_eq!(editor.cursor_rect(), Some((0, 0, 2, 20)));
+
+    // A block covers the whole glyph at the cursor, however wide
+    let (narrow, wide) = editor.with_buffer(|buffer| {
+        let run = buffer.layout_runs().next().unwrap();
+        (run.glyphs[0].clone(), run.glyphs[1].clone())
+    });
+    assert!(wide.w > narrow.w);
+    editor.set_cursor_shape(CursorShape::Block);
+    editor.set_cursor(Cursor::new(0, 1));
+    assert_eq!(
+        editor.cursor_rect(),
+        Some((wide.x as i32, 0, wide.w as u32, 20))
+    );
+
+    // An underline at the bottom of the line, half of the font size wide at the end of the line
+    editor.set_cursor_shape(CursorShape::Underline(2.0));
+    editor.set_cursor(Cursor::new(0, "iWb".len()));
+    let (x, _) = editor.cursor_position().unwrap();
+    assert_eq!(editor.cursor_rect(), Some((x, 18, 7, 2)));
+
+    // Overtype always uses a block
+    editor.set_overtype(true);
+    assert_eq!(editor.cursor_rect(), Some((x, 0, 7, 20)));
+
+    // Line widths follow the display scale
+    editor.set_overtype(false);
+    editor.with_buffer_mut(|buffer| buffer.set_scale(&mut font_system, 2.0));
+    editor.shape_as_needed(&mut font_system, false);
+    let (x, _) = editor.cursor_position().unwrap();
+    assert_eq!(editor.cursor_rect(), Some((x, 36, 14, 4)));
+}
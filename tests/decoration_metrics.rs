@@ -0,0 +1,35 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn decoration_metrics_scale_with_size() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_text("Text", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    let font_id = buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+
+    let small = font_system.decoration_metrics(font_id, 14.0).unwrap();
+    let large = font_system.decoration_metrics(font_id, 28.0).unwrap();
+
+    // Underlines are below the baseline and strikethroughs above it
+    assert!(small.underline_offset > 0.0);
+    assert!(small.strikethrough_offset < 0.0);
+    assert!(small.underline_thickness > 0.0);
+    assert!(small.strikethrough_thickness > 0.0);
+
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+    assert!(close(large.underline_offset, small.underline_offset * 2.0));
+    assert!(close(
+        large.underline_thickness,
+        small.underline_thickness * 2.0
+    ));
+    assert!(close(
+        large.strikethrough_offset,
+        small.strikethrough_offset * 2.0
+    ));
+    assert!(close(
+        large.strikethrough_thickness,
+        small.strikethrough_thickness * 2.0
+    ));
+}
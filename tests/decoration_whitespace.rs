@@ -0,0 +1,30 @@
+use cosmic_text::{Attrs, Buffer, DecorationWhitespace, FontSystem, Metrics, Shaping};
+
+#[test]
+fn decoration_whitespace_policy() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_text("ab cd  ", Attrs::new().decoration(1), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph_end = |i: usize| run.glyphs[i].x + run.glyphs[i].w;
+
+    let all = run.decoration_spans_with(DecorationWhitespace::All);
+    assert_eq!(all, run.decoration_spans());
+    assert_eq!(all, [(1, 0.0, glyph_end(6))]);
+
+    // Trailing spaces are not decorated
+    let inner = run.decoration_spans_with(DecorationWhitespace::Inner);
+    assert_eq!(inner, [(1, 0.0, glyph_end(4))]);
+
+    // Decorations are split at spaces
+    let none = run.decoration_spans_with(DecorationWhitespace::None);
+    assert_eq!(
+        none,
+        [
+            (1, 0.0, glyph_end(1)),
+            (1, run.glyphs[3].x, glyph_end(4) - run.glyphs[3].x)
+        ]
+    );
+}
@@ -0,0 +1,51 @@
+use cosmic_text::{Attrs, Buffer, Color, Edit, Editor, FontSystem, Metrics, Shaping, SwashCache};
+
+#[test]
+fn decorations() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), None);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("hi ", Attrs::new()),
+            ("@alice", Attrs::new().decoration(1)),
+            (" and ", Attrs::new()),
+            ("@bob", Attrs::new().decoration(2)),
+        ],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.shape_as_needed(false);
+
+    // One rectangle per decorated span, in visual order
+    let mut rects = Vec::new();
+    let color = Color::rgb(0xFF, 0xFF, 0xFF);
+    editor.draw_with_decorations(
+        &mut swash_cache,
+        color,
+        color,
+        color,
+        color,
+        None,
+        &[],
+        |id, x, y, w, h| rects.push((id, x, y, w, h)),
+        |_, _, _, _, _| {},
+    );
+    assert_eq!(rects.len(), 2);
+    let (alice, bob) = (rects[0], rects[1]);
+    assert_eq!((alice.0, bob.0), (1, 2));
+    assert!(alice.1 > 0 && alice.3 > 0);
+    assert!(bob.1 > alice.1 + alice.3 as i32);
+    assert_eq!((alice.2, alice.4), (0, 20));
+
+    // The spans match the glyphs they cover
+    editor.with_buffer(|buffer| {
+        let run = buffer.layout_runs().next().unwrap();
+        let start = run.glyphs.iter().find(|glyph| glyph.start == 3).unwrap();
+        assert_eq!(run.decoration_spans()[0].1, start.x);
+    });
+}
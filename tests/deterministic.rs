@@ -0,0 +1,45 @@
+use cosmic_text::{fontdb, Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn deterministic_font_system() {
+    let font_system = FontSystem::new_deterministic(core::iter::empty());
+    assert!(font_system.is_deterministic());
+    assert_eq!(font_system.locale(), "en-US");
+    assert_eq!(font_system.db().len(), 0);
+
+    // Only the provided fonts are used
+    let system_fonts = FontSystem::new();
+    assert!(!system_fonts.is_deterministic());
+    let source = system_fonts
+        .db()
+        .faces()
+        .find(|face| {
+            face.style == fontdb::Style::Normal
+                && face.weight == fontdb::Weight::NORMAL
+                && face.stretch == fontdb::Stretch::Normal
+        })
+        .map(|face| face.source.clone())
+        .expect("no regular font found");
+    let layout = || {
+        let mut font_system = FontSystem::new_deterministic([source.clone()]);
+        let font_ids: Vec<_> = font_system.db().faces().map(|face| face.id).collect();
+        let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+        buffer.set_text(
+            &mut font_system,
+            "Hello 世界 مرحبا",
+            Attrs::new(),
+            Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(&mut font_system, false);
+        let run = buffer.layout_runs().next().unwrap();
+        assert!(run
+            .glyphs
+            .iter()
+            .all(|glyph| font_ids.contains(&glyph.font_id)));
+        run.glyphs
+            .iter()
+            .map(|glyph| (glyph.start, glyph.end, glyph.glyph_id, glyph.x, glyph.w))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(layout(), layout());
+}
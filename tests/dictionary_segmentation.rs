@@ -0,0 +1,65 @@
+#![cfg(feature = "dictionary-segmentation")]
+
+use cosmic_text::{
+    set_dictionary, Attrs, Buffer, DictionaryScript, FontSystem, Metrics, Shaping, WordBoundaries,
+    Wrap,
+};
+
+#[test]
+fn dictionary_word_boundaries() {
+    let text = "ขอบคุณมากครับ hello";
+    let words: Vec<&str> = WordBoundaries::Unicode
+        .words(text)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(words, ["ขอบคุณ", "มาก", "ครับ", "hello"]);
+
+    // Word motions stop between dictionary words
+    let boundaries = WordBoundaries::Unicode;
+    assert_eq!(boundaries.next_word_end(text, 0), "ขอบคุณ".len());
+    assert_eq!(
+        boundaries.previous_word_start(text, "ขอบคุณมาก".len()),
+        "ขอบคุณ".len()
+    );
+}
+
+#[test]
+fn dictionary_line_breaks() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_size(Some(40.0), None);
+    let text = "ขอบคุณมากครับ";
+    buffer.set_text(text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+
+    // Lines wrap between words instead of overflowing
+    let starts: Vec<usize> = buffer
+        .layout_runs()
+        .map(|run| run.glyphs.iter().map(|glyph| glyph.start).min().unwrap())
+        .collect();
+    assert!(starts.len() > 1);
+    let word_starts = [0, "ขอบคุณ".len(), "ขอบคุณมาก".len()];
+    assert!(starts.iter().all(|start| word_starts.contains(start)));
+}
+
+#[test]
+fn custom_dictionary() {
+    // Only this test segments Khmer, so its dictionary has not been loaded yet
+    let words = ["កខ", "គឃ"];
+    assert!(set_dictionary(
+        DictionaryScript::Khmer,
+        words.iter().rev().map(|word| word.to_string())
+    ));
+    assert!(!set_dictionary(DictionaryScript::Khmer, Vec::new()));
+
+    let text = "កខគឃ";
+    let segments: Vec<&str> = WordBoundaries::Unicode
+        .words(text)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(segments, words);
+}
@@ -0,0 +1,56 @@
+use cosmic_text::{Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping};
+
+#[test]
+fn scale_applies_to_layout() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "hello\tworld\nsecond",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    let widths: Vec<f32> = buffer.layout_runs().map(|run| run.line_w).collect();
+
+    buffer.set_scale(&mut font_system, 2.0);
+    assert_eq!(buffer.scale(), 2.0);
+    assert_eq!(buffer.metrics(), Metrics::new(14.0, 20.0));
+    assert_eq!(buffer.scaled_metrics(), Metrics::new(28.0, 40.0));
+
+    // Lines, including tab stops, are twice as large
+    let runs: Vec<_> = buffer
+        .layout_runs()
+        .map(|run| (run.line_top, run.line_height, run.line_w))
+        .collect();
+    assert_eq!(runs.len(), 2);
+    assert_eq!((runs[1].0, runs[1].1), (40.0, 40.0));
+    for ((_, _, w), old_w) in runs.iter().zip(widths) {
+        assert!((w - old_w * 2.0).abs() < 1.0, "{} != 2 * {}", w, old_w);
+    }
+
+    // Hit testing in logical pixels reverses the scale
+    assert_eq!(buffer.hit_logical(1.0, 25.0), buffer.hit(2.0, 50.0));
+    let cursor = buffer.hit_logical(1.0, 25.0).unwrap();
+    assert_eq!((cursor.line, cursor.index), (1, 0));
+
+    // The caret is as wide as the scale
+    let mut editor = Editor::new(&mut buffer);
+    editor.set_cursor(Cursor::new(1, 0));
+    let (_, y, w, h) = editor.cursor_rect().unwrap();
+    assert_eq!((y, w, h), (40, 2, 40));
+}
+
+#[test]
+fn fit_text_uses_scaled_line_height() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(10.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(None, Some(200.0));
+    buffer.set_text("1\n2\n3\n4\n5\n6\n7\n8", Attrs::new(), Shaping::Advanced);
+
+    // Eight lines at twice the line height fit in 200 physical pixels up to a font size of 6.25
+    buffer.set_scale(2.0);
+    assert_eq!(buffer.fit_text(1.0, 100.0, 1.0, None), 6.0);
+    assert_eq!(buffer.metrics(), Metrics::new(6.0, 12.0));
+}
@@ -0,0 +1,36 @@
+use cosmic_text::{Attrs, Buffer, Color, FontSystem, Metrics, Shaping, SwashCache};
+
+#[test]
+fn draw_clip() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_text(
+        "first\nsecond\nthird\nfourth",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    let color = Color::rgb(0xFF, 0xFF, 0xFF);
+    let mut rows = |clip_opt| {
+        let (mut min_y, mut max_y) = (i32::MAX, i32::MIN);
+        buffer.draw_clipped(&mut swash_cache, color, clip_opt, &[], |_, y, _, _, _| {
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+        });
+        (min_y, max_y)
+    };
+
+    // Without a clip every line is drawn
+    let (min_y, max_y) = rows(None);
+    assert!(min_y < 20 && max_y >= 60);
+
+    // Only the lines intersecting the clip are drawn
+    let (min_y, max_y) = rows(Some((0, 25, 100, 30)));
+    assert!(min_y >= 20 && max_y < 60);
+
+    // Nothing is drawn outside of the buffer
+    assert_eq!(rows(Some((0, 200, 100, 20))), (i32::MAX, i32::MIN));
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "swash")]
+
+use cosmic_text::{draw_text, Attrs, Buffer, Color, FontSystem, Metrics, Shaping, SwashCache};
+
+#[test]
+fn draw_text_matches_buffer() {
+    let mut font_system = FontSystem::new();
+    let mut cache = SwashCache::new();
+    let metrics = Metrics::new(14.0, 20.0);
+    let color = Color::rgb(0xFF, 0xFF, 0xFF);
+    let text = "FPS: 60";
+
+    let mut buffer = Buffer::new(&mut font_system, metrics);
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let mut expected = Vec::new();
+    buffer.draw(&mut font_system, &mut cache, color, |x, y, _, _, c| {
+        expected.push((x + 10, y + 30, c));
+    });
+    assert!(!expected.is_empty());
+
+    let mut pixels = Vec::new();
+    let (w, h) = draw_text(
+        &mut font_system,
+        &mut cache,
+        10.0,
+        30.0,
+        text,
+        Attrs::new(),
+        metrics,
+        color,
+        |x, y, _, _, c| pixels.push((x, y, c)),
+    );
+    assert_eq!(pixels, expected);
+    assert_eq!(w, buffer.layout_runs().next().unwrap().line_w);
+    assert_eq!(h, 20.0);
+}
@@ -0,0 +1,59 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, EditHooks, Editor, FontSystem, HookedEditor, Metrics,
+    Shaping,
+};
+
+/// Drops digits, turns tabs into indents, and records performed actions
+#[derive(Debug, Default)]
+struct NoDigits {
+    performed: Vec<Action>,
+}
+
+impl<'buffer> EditHooks<Editor<'buffer>> for NoDigits {
+    fn pre_action(
+        &mut self,
+        _editor: &mut Editor<'buffer>,
+        _font_system: &mut FontSystem,
+        action: Action,
+    ) -> Option<Action> {
+        match action {
+            Action::Insert(c) if c.is_ascii_digit() => None,
+            Action::Insert('\t') => Some(Action::Indent),
+            _ => Some(action),
+        }
+    }
+
+    fn post_action(
+        &mut self,
+        _editor: &mut Editor<'buffer>,
+        _font_system: &mut FontSystem,
+        action: Action,
+    ) {
+        self.performed.push(action);
+    }
+}
+
+#[test]
+fn hooks_intercept_actions() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "", Attrs::new(), Shaping::Advanced);
+    let mut editor = HookedEditor::new(Editor::new(&mut buffer), NoDigits::default());
+
+    {
+        let mut editor = editor.borrow_with(&mut font_system);
+        for c in "a1b\t2".chars() {
+            editor.action(Action::Insert(c));
+        }
+    }
+
+    assert_eq!(editor.cursor(), Cursor::new(0, 10));
+    assert_eq!(
+        editor.with_buffer(|buffer| buffer.lines[0].text().to_string()),
+        "        ab"
+    );
+    assert_eq!(
+        editor.hooks().performed,
+        [Action::Insert('a'), Action::Insert('b'), Action::Indent]
+    );
+}
@@ -0,0 +1,44 @@
+use cosmic_text::{Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping};
+
+#[test]
+fn batch_defers_redraw() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(200.0));
+    buffer.set_text(
+        &mut font_system,
+        "fn main() {",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.shape_as_needed(false);
+    editor.with_buffer_mut(|buffer| buffer.set_redraw(false));
+
+    let lines = editor.batch(|editor| {
+        assert!(editor.is_batching());
+        editor.set_cursor(Cursor::new(0, 11));
+        editor.action(Action::Enter);
+        editor.insert_string("body();", None);
+        editor.action(Action::Indent);
+        editor.action(Action::Enter);
+
+        // Nested batches do not end the outer batch
+        editor.batch(|editor| editor.action(Action::Backspace));
+        assert!(editor.is_batching());
+
+        assert!(!editor.with_buffer(|buffer| buffer.redraw()));
+        editor.with_buffer(|buffer| buffer.lines.len())
+    });
+
+    assert!(!editor.is_batching());
+    assert_eq!(lines, 2);
+    assert!(editor.with_buffer(|buffer| buffer.redraw()));
+    assert_eq!(editor.cursor(), Cursor::new(1, 15));
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines[1].text(), "        body();");
+        // The batch lays out the edited lines when it ends
+        assert!(buffer.lines.iter().all(|line| line.layout_opt().is_some()));
+    });
+}
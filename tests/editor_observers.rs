@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion, Scroll, Shaping,
+};
+
+#[test]
+fn observers_report_events() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(40.0));
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree\nfour",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let cursors = Arc::new(Mutex::new(Vec::new()));
+    let scrolls = Arc::new(Mutex::new(Vec::<Scroll>::new()));
+    {
+        let changes = changes.clone();
+        editor.on_change(move |change| {
+            for item in change.items.iter() {
+                changes
+                    .lock()
+                    .unwrap()
+                    .push((item.insert, item.text.clone()));
+            }
+        });
+    }
+    {
+        let cursors = cursors.clone();
+        editor.on_cursor_move(move |cursor| cursors.lock().unwrap().push(cursor));
+    }
+    {
+        let scrolls = scrolls.clone();
+        editor.on_scroll(move |scroll| scrolls.lock().unwrap().push(scroll));
+    }
+
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.shape_as_needed(false);
+    assert!(cursors.lock().unwrap().is_empty());
+    assert!(scrolls.lock().unwrap().is_empty());
+
+    editor.action(Action::Insert('x'));
+    editor.action(Action::Backspace);
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [(true, "x".to_string()), (false, "x".to_string())]
+    );
+    assert_eq!(
+        *cursors.lock().unwrap(),
+        [Cursor::new(0, 1), Cursor::new(0, 0)]
+    );
+
+    // Moving to the last line scrolls it into view
+    editor.action(Action::Motion(Motion::BufferEnd));
+    editor.shape_as_needed(false);
+    assert_eq!(cursors.lock().unwrap().last(), Some(&Cursor::new(3, 4)));
+    assert_eq!(scrolls.lock().unwrap().len(), 1);
+    assert!(scrolls.lock().unwrap()[0].line > 0);
+
+    // Setting the same cursor again is not reported
+    editor.set_cursor(Cursor::new(3, 4));
+    assert_eq!(cursors.lock().unwrap().len(), 3);
+
+    editor.clear_observers();
+    editor.action(Action::Insert('y'));
+    assert_eq!(changes.lock().unwrap().len(), 2);
+}
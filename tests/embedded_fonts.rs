@@ -0,0 +1,50 @@
+use cosmic_text::{FontSystem, FontSystemBuilder};
+
+fn dejavu_sans_mono() -> &'static [u8] {
+    let data = std::fs::read("/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf")
+        .expect("DejaVu Sans Mono is not installed");
+    Box::leak(data.into_boxed_slice())
+}
+
+fn families(font_system: &FontSystem) -> Vec<String> {
+    font_system
+        .db()
+        .faces()
+        .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+        .collect()
+}
+
+#[test]
+fn embedded_fonts_without_system_fonts() {
+    let font = dejavu_sans_mono();
+
+    let font_system = FontSystem::builder()
+        .locale("de-DE")
+        .system_fonts(false)
+        .embedded_fonts(&[font])
+        .build();
+    assert_eq!(font_system.locale(), "de-DE");
+    assert_eq!(families(&font_system), ["DejaVu Sans Mono"]);
+
+    let font_system = FontSystem::builder()
+        .system_fonts(false)
+        .embedded_fonts(&[font])
+        .add_embedded_fonts(&[font])
+        .build();
+    assert_eq!(font_system.db().len(), 2);
+}
+
+#[test]
+fn embedded_fonts_can_be_removed() {
+    let font_system = FontSystem::builder()
+        .system_fonts(false)
+        .embedded_fonts(&[])
+        .build();
+    assert_eq!(font_system.db().len(), 0);
+
+    let font_system = FontSystemBuilder::default().system_fonts(false).build();
+    assert_eq!(
+        font_system.db().len(),
+        FontSystemBuilder::DEFAULT_EMBEDDED_FONTS.len()
+    );
+}
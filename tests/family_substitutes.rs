@@ -0,0 +1,52 @@
+use cosmic_text::{Attrs, Buffer, Family, FamilyOwned, FontSystem, Metrics, Shaping};
+
+fn first_family(font_system: &mut FontSystem, family: &str) -> String {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        font_system,
+        "text",
+        Attrs::new().family(Family::Name(family)),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(font_system, false);
+    let font_id = buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+    font_system.db().face(font_id).unwrap().families[0]
+        .0
+        .clone()
+}
+
+#[test]
+fn unavailable_families_are_substituted() {
+    let mut font_system = FontSystem::builder()
+        .family_substitutes(
+            "Helvetica",
+            vec![
+                FamilyOwned::Name("Missing Sans".to_string()),
+                FamilyOwned::Name("DejaVu Serif".to_string()),
+            ],
+        )
+        .family_substitutes("ui-monospace", vec![FamilyOwned::Monospace])
+        .build();
+    assert_eq!(font_system.generation(), 0);
+    assert_eq!(first_family(&mut font_system, "Helvetica"), "DejaVu Serif");
+    assert_eq!(
+        first_family(&mut font_system, "ui-monospace"),
+        "DejaVu Sans Mono"
+    );
+
+    // Substitutes are only used when no font has the family
+    font_system.set_family_substitutes(
+        "DejaVu Sans",
+        vec![FamilyOwned::Name("DejaVu Serif".to_string())],
+    );
+    assert_eq!(font_system.generation(), 1);
+    assert_eq!(first_family(&mut font_system, "DejaVu Sans"), "DejaVu Sans");
+
+    // An empty list removes the substitution
+    font_system.set_family_substitutes("Helvetica", Vec::new());
+    assert!(font_system.family_substitutes("Helvetica").is_empty());
+    assert_eq!(
+        font_system.family_substitutes("ui-monospace"),
+        [FamilyOwned::Monospace]
+    );
+}
@@ -0,0 +1,51 @@
+use cosmic_text::{fontdb, Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Wrap};
+
+// Measure layout lines and height of all lines, including those clipped by the buffer height
+fn measure(buffer: &Buffer) -> (usize, f32) {
+    let mut lines = 0;
+    let mut height = 0.0;
+    for line in buffer.lines.iter() {
+        for layout_line in line.layout_opt().as_ref().expect("line not laid out") {
+            lines += 1;
+            height += layout_line
+                .line_height_opt
+                .unwrap_or(buffer.metrics().line_height);
+        }
+    }
+    (lines, height)
+}
+
+#[test]
+fn fit_text() {
+    // Only use the fonts in this repository, so results do not depend on the installed fonts
+    let mut font_db = fontdb::Database::new();
+    font_db.load_fonts_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts"));
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), font_db);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(10.0, 12.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_size(Some(200.0), Some(60.0));
+    buffer.set_text(
+        "The quick brown fox jumps over the lazy dog",
+        Attrs::new().family(Family::Name("Noto Sans")),
+        Shaping::Advanced,
+    );
+
+    let font_size = buffer.fit_text(4.0, 100.0, 1.0, None);
+    assert!(font_size > 4.0 && font_size < 100.0);
+    assert_eq!(buffer.metrics(), Metrics::relative(font_size, 1.2));
+    assert!(measure(&buffer).1 <= 60.0);
+
+    // The next step does not fit
+    buffer.set_metrics(Metrics::relative(font_size + 1.0, 1.2));
+    assert!(measure(&buffer).1 > 60.0);
+
+    // Limiting to one line requires a smaller font size
+    let one_line_size = buffer.fit_text(4.0, 100.0, 1.0, Some(1));
+    assert!(one_line_size < font_size);
+    assert_eq!(measure(&buffer).0, 1);
+
+    // Falls back to the minimum size if nothing fits
+    assert_eq!(buffer.fit_text(80.0, 100.0, 1.0, Some(1)), 80.0);
+}
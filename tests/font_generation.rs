@@ -0,0 +1,33 @@
+use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, ReshapeReasons, Shaping};
+
+#[test]
+fn font_changes_reshape_buffers() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "Hello\nworld",
+        Attrs::new().family(Family::Serif),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    buffer.set_redraw(false);
+    let font_id = |buffer: &Buffer| buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+    let serif = font_id(&buffer);
+
+    // Changing the serif family takes effect without resetting lines by hand
+    let generation = font_system.generation();
+    font_system.db_mut().set_serif_family("DejaVu Sans Mono");
+    assert_eq!(font_system.generation(), generation + 1);
+
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert!(buffer.redraw());
+    for line in buffer.lines.iter() {
+        assert_eq!(line.reshape_reasons(), ReshapeReasons::FONTS);
+    }
+    assert_ne!(font_id(&buffer), serif);
+
+    // Nothing is reshaped while the generation is unchanged
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert!(buffer.lines[0].pending_reshape_reasons().is_empty());
+}
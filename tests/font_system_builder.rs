@@ -0,0 +1,57 @@
+use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
+
+const DEJAVU_DIR: &str = "/usr/share/fonts/truetype/dejavu";
+
+fn families(font_system: &FontSystem) -> Vec<String> {
+    let mut families: Vec<String> = font_system
+        .db()
+        .faces()
+        .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+        .collect();
+    families.sort();
+    families.dedup();
+    families
+}
+
+#[test]
+fn font_dirs_without_system_fonts() {
+    let font_system = FontSystem::builder()
+        .locale("fr-FR")
+        .system_fonts(false)
+        .font_dir(DEJAVU_DIR)
+        .deterministic(true)
+        .font_matches_cache_size(1)
+        .build();
+    assert_eq!(font_system.locale(), "fr-FR");
+    assert!(font_system.is_deterministic());
+    assert!(!font_system.db().is_empty());
+    assert!(families(&font_system)
+        .iter()
+        .all(|family| family.starts_with("DejaVu")));
+}
+
+#[test]
+fn monospace_fallback_families() {
+    let mut font_system = FontSystem::builder()
+        .system_fonts(false)
+        .font_dir(DEJAVU_DIR)
+        .monospace_fallback_families(["Missing Mono", "DejaVu Sans Mono"])
+        .font_matches_cache_size(1)
+        .build();
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    // Shape with two different attrs so the one entry font matches cache is cleared
+    for family in [Family::Monospace, Family::SansSerif, Family::Monospace] {
+        buffer.set_text(
+            &mut font_system,
+            "mono",
+            Attrs::new().family(family),
+            Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(&mut font_system, false);
+    }
+
+    let font_id = buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+    let face = font_system.db().face(font_id).unwrap();
+    assert_eq!(face.families[0].0, "DejaVu Sans Mono");
+}
@@ -0,0 +1,85 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, FootnoteMarkers, HookedEditor,
+    Metrics, Motion, Shaping,
+};
+
+fn text(buffer: &Buffer) -> Vec<String> {
+    buffer
+        .lines
+        .iter()
+        .map(|line| line.text().to_string())
+        .collect()
+}
+
+#[test]
+fn footnotes_are_numbered_in_order() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut markers = FootnoteMarkers::new();
+
+    let b = markers.insert(&mut buffer, Cursor::new(1, 3), Attrs::new());
+    let c = markers.insert(&mut buffer, Cursor::new(2, 5), Attrs::new());
+    assert_eq!(text(&buffer), ["one", "two¹", "three²"]);
+
+    // Shape everything, then insert a marker before the others
+    buffer.shape_until_scroll(&mut font_system, false);
+    let a = markers.insert(&mut buffer, Cursor::new(0, 3), Attrs::new());
+    assert_eq!(text(&buffer), ["one¹", "two²", "three³"]);
+    assert_eq!(markers.number(a), Some(1));
+    assert_eq!(markers.number(c), Some(3));
+    assert_eq!(
+        markers.range(&buffer, b),
+        Some((Cursor::new(1, 3), Cursor::new(1, 5)))
+    );
+
+    // Resolving again changes nothing and keeps shaping
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert!(!markers.resolve(&mut buffer));
+    assert!(buffer.lines.iter().all(|line| line.shape_opt().is_some()));
+
+    // Removing a marker only renumbers the markers after it
+    assert!(markers.remove(&mut buffer, b));
+    assert_eq!(text(&buffer), ["one¹", "two", "three²"]);
+    assert!(buffer.lines[0].shape_opt().is_some());
+    assert!(buffer.lines[2].shape_opt().is_none());
+    assert_eq!(markers.iter().collect::<Vec<_>>(), [(a, 1), (c, 2)]);
+}
+
+#[test]
+fn footnotes_are_edited_atomically() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "ab", Attrs::new(), Shaping::Advanced);
+    let mut markers = FootnoteMarkers::new();
+    let a = markers.insert(&mut buffer, Cursor::new(0, 1), Attrs::new());
+    let b = markers.insert(&mut buffer, Cursor::new(0, 4), Attrs::new());
+    assert_eq!(text(&buffer), ["a¹b²"]);
+
+    let mut hooked = HookedEditor::new(Editor::new(&mut buffer), markers);
+    let mut editor = hooked.borrow_with(&mut font_system);
+
+    // Moving right skips over the whole marker
+    editor.set_cursor(Cursor::new(0, 1));
+    editor.action(Action::Motion(Motion::Right));
+    assert_eq!(editor.cursor().index, 3);
+
+    // Typing next to a marker does not extend it
+    editor.action(Action::Insert('x'));
+    assert_eq!(editor.with_buffer(text), ["a¹xb²"]);
+
+    // Backspace at the end of a marker deletes all of it, renumbering the next marker
+    editor.set_cursor(Cursor::new(0, 3));
+    editor.action(Action::Backspace);
+    assert_eq!(editor.with_buffer(text), ["axb¹"]);
+    assert_eq!(editor.cursor().index, 1);
+
+    let markers = hooked.hooks();
+    assert_eq!(markers.number(a), None);
+    assert_eq!(markers.number(b), Some(1));
+}
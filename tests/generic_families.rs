@@ -0,0 +1,44 @@
+use cosmic_text::{Attrs, Buffer, FamilyOwned, FontSystem, GenericFamily, Metrics, Shaping};
+
+fn first_family(font_system: &mut FontSystem, attrs: Attrs) -> String {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(font_system, "text", attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_system, false);
+    let font_id = buffer.layout_runs().next().unwrap().glyphs[0].font_id;
+    font_system.db().face(font_id).unwrap().families[0]
+        .0
+        .clone()
+}
+
+#[test]
+fn generic_family_names() {
+    for generic in GenericFamily::ALL {
+        assert_eq!(GenericFamily::from_name(generic.name()), Some(generic));
+    }
+    assert_eq!(
+        GenericFamily::from_name("ui-monospace"),
+        Some(GenericFamily::UiMonospace)
+    );
+    assert_eq!(GenericFamily::from_name("monospace"), None);
+}
+
+#[test]
+fn generic_families_use_configurable_defaults() {
+    let mut font_system = FontSystem::new();
+    assert_eq!(
+        font_system.family_substitutes("ui-monospace").last(),
+        Some(&FamilyOwned::Monospace)
+    );
+    let attrs = Attrs::new().family(GenericFamily::UiMonospace.family());
+    assert_eq!(first_family(&mut font_system, attrs), "DejaVu Sans Mono");
+
+    font_system.set_family_substitutes(
+        GenericFamily::UiMonospace.name(),
+        vec![FamilyOwned::Name("DejaVu Serif".into())],
+    );
+    assert_eq!(first_family(&mut font_system, attrs), "DejaVu Serif");
+
+    // Deterministic font systems do not use platform defaults
+    let font_system = FontSystem::new_deterministic(core::iter::empty());
+    assert!(font_system.family_substitutes("system-ui").is_empty());
+}
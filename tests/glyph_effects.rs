@@ -0,0 +1,50 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, GlyphEffect, Metrics, Shaping};
+
+#[test]
+fn glyph_effects_apply_without_relayout() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "abc", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    buffer.set_redraw(false);
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph = run.glyphs[1].clone();
+    let first_x = run.glyphs[0].physical((0., 0.), 1.0).x;
+    let plain = glyph.physical((0., 0.), 1.0);
+    assert_eq!(
+        glyph
+            .physical_with_effect((0., 0.), 1.0, GlyphEffect::default())
+            .x,
+        plain.x
+    );
+
+    let effect = GlyphEffect {
+        x: 3.0,
+        y: -4.0,
+        scale: 2.0,
+    };
+    buffer.set_glyph_effect(0, glyph.start, Some(effect));
+    assert!(buffer.redraw());
+    assert_eq!(buffer.glyph_effect(0, glyph.start), Some(effect));
+    assert!(buffer.lines[0].layout_opt().is_some());
+
+    let run = buffer.layout_runs().next().unwrap();
+    let moved = buffer.physical_glyph(&run, &run.glyphs[1], (0., 0.), 1.0);
+    assert_eq!(moved.x, plain.x + 3);
+    assert_eq!(moved.y, plain.y - 4);
+    assert_eq!(
+        moved.cache_key.font_size_bits,
+        (glyph.font_size * 2.0).to_bits()
+    );
+    // Other glyphs are unchanged
+    let other = buffer.physical_glyph(&run, &run.glyphs[0], (0., 0.), 1.0);
+    assert_eq!(other.x, first_x);
+
+    buffer.set_redraw(false);
+    buffer.set_glyph_effect(0, glyph.start, Some(effect));
+    assert!(!buffer.redraw());
+    buffer.clear_glyph_effects();
+    assert!(buffer.redraw());
+    assert_eq!(buffer.glyph_effect(0, glyph.start), None);
+}
@@ -0,0 +1,61 @@
+use cosmic_text::{cell_count, Attrs, Buffer, Cursor, Family, FontSystem, Metrics, Shaping, Wrap};
+
+#[test]
+fn grid_cell_count() {
+    assert_eq!(cell_count("abc"), 3);
+    assert_eq!(cell_count("日本"), 4);
+    assert_eq!(cell_count("e\u{301}"), 1);
+    assert_eq!(cell_count("한a"), 3);
+    assert_eq!(cell_count("\u{1F600}"), 2);
+    assert_eq!(cell_count(""), 0);
+}
+
+#[test]
+fn grid_layout() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_cell_width(Some(10.0));
+    buffer.set_wrap(Wrap::Glyph);
+    buffer.set_size(Some(55.0), None);
+    buffer.set_text(
+        "ab日本cd",
+        Attrs::new().family(Family::Monospace),
+        Shaping::Advanced,
+    );
+
+    // Glyphs are snapped to cells, wide characters take two cells and lines wrap at 5 cells
+    let layout = buffer.line_layout(0).unwrap();
+    let rows: Vec<Vec<(usize, f32, f32)>> = layout
+        .iter()
+        .map(|line| {
+            line.glyphs
+                .iter()
+                .map(|glyph| (glyph.start, glyph.x, glyph.w))
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![(0, 0.0, 10.0), (1, 10.0, 10.0), (2, 20.0, 20.0)],
+            vec![(5, 0.0, 20.0), (8, 20.0, 10.0), (9, 30.0, 10.0)],
+        ]
+    );
+    assert_eq!(layout[0].w, 40.0);
+
+    // Cells map to cursors and back, counting columns across wrapped lines
+    assert_eq!(buffer.grid_position(Cursor::new(0, 1)), Some((0, 1)));
+    assert_eq!(buffer.grid_position(Cursor::new(0, 5)), Some((0, 4)));
+    assert_eq!(buffer.grid_position(Cursor::new(0, 9)), Some((0, 7)));
+    assert_eq!(buffer.grid_position(Cursor::new(0, 10)), Some((0, 8)));
+    assert_eq!(buffer.grid_cursor(0, 3), Some(Cursor::new(0, 2)));
+    assert_eq!(buffer.grid_cursor(0, 6), Some(Cursor::new(0, 8)));
+    assert_eq!(buffer.grid_cursor(0, 20), Some(Cursor::new(0, 10)));
+    assert_eq!(buffer.grid_cursor(1, 0), None);
+
+    // Disabling the grid restores the proportional layout
+    buffer.set_cell_width(None);
+    assert_eq!(buffer.grid_position(Cursor::new(0, 1)), None);
+    assert_eq!(buffer.line_layout(0).unwrap().len(), 1);
+}
@@ -0,0 +1,85 @@
+use cosmic_text::{Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Scroll, Shaping};
+
+#[test]
+fn height_index() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(120.0), Some(40.0));
+    let text = (0..100)
+        .map(|i| {
+            if i % 10 == 0 {
+                "a line long enough to be wrapped more than once"
+            } else {
+                "short"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    buffer.set_text(&text, Attrs::new(), Shaping::Advanced);
+
+    // Lines that were not laid out are estimated to be one line high
+    let estimate = buffer.total_height();
+    assert!(estimate > 100.0 * 20.0 && estimate < 100.0 * 20.0 + 100.0);
+    assert_eq!(buffer.line_for_y(0.0), Some(0));
+
+    // Laying out refines the estimate
+    for line_i in 0..buffer.lines.len() {
+        buffer.line_layout(line_i);
+    }
+    let wrapped = buffer.line_layout(0).unwrap().len() as f32;
+    assert!(wrapped > 2.0);
+    assert_eq!(buffer.total_height(), 90.0 * 20.0 + 10.0 * wrapped * 20.0);
+    assert_eq!(buffer.y_for_line(11), 9.0 * 20.0 + 2.0 * wrapped * 20.0);
+    assert_eq!(buffer.line_for_y(wrapped * 20.0 - 1.0), Some(0));
+    assert_eq!(buffer.line_for_y(wrapped * 20.0), Some(1));
+    assert_eq!(buffer.line_for_y(buffer.total_height()), None);
+
+    // Runs are found relative to the scroll position
+    buffer.set_scroll(Scroll::new(11, 0.0, 0.0));
+    let run = buffer.run_for_line(10).unwrap();
+    assert_eq!(run.line_top, -wrapped * 20.0);
+    let run = buffer.run_at_y(-1.0).unwrap();
+    assert_eq!((run.line_i, run.layout_i), (10, wrapped as usize - 1));
+}
+
+#[test]
+fn height_index_after_edits() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(120.0), Some(40.0));
+    let text = (0..20)
+        .map(|i| {
+            if i % 10 == 0 {
+                "a line long enough to be wrapped more than once"
+            } else {
+                "short"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    buffer.set_text(&mut font_system, &text, Attrs::new(), Shaping::Advanced);
+    for line_i in 0..buffer.lines.len() {
+        buffer.line_layout(&mut font_system, line_i);
+    }
+    let wrapped = buffer.lines[0].layout_opt().as_ref().unwrap().len() as f32;
+    assert!(wrapped > 2.0);
+
+    // Remove the first wrapped line and add a line before the second one, keeping the number of
+    // lines, so the second wrapped line stays at line 10 while the lines before it change
+    let mut editor = Editor::new(&mut buffer);
+    editor.delete_range(Cursor::new(0, 0), Cursor::new(1, 0));
+    editor.insert_at(Cursor::new(5, 0), "new\n", None);
+
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines.len(), 20);
+        assert_eq!(buffer.total_height(), 19.0 * 20.0 + wrapped * 20.0);
+        assert_eq!(buffer.y_for_line(10), 10.0 * 20.0);
+        assert_eq!(buffer.line_for_y(10.0 * 20.0 - 1.0), Some(9));
+        assert_eq!(
+            buffer.line_for_y(10.0 * 20.0 + wrapped * 20.0 - 1.0),
+            Some(10)
+        );
+        assert_eq!(buffer.line_for_y(10.0 * 20.0 + wrapped * 20.0), Some(11));
+    });
+}
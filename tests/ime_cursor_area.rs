@@ -0,0 +1,38 @@
+use cosmic_text::{
+    Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Scroll, Selection, Shaping,
+};
+
+#[test]
+fn ime_cursor_area() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(40.0));
+    buffer.set_text(
+        &mut font_system,
+        "first\nsecond line\nthird\nfourth",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // Without a selection the area is the caret
+    editor.set_cursor(Cursor::new(1, 7));
+    assert_eq!(editor.ime_cursor_area(), editor.cursor_rect());
+    let (caret_x, caret_y, _, h) = editor.ime_cursor_area().unwrap();
+    assert_eq!((caret_y, h), (20, 20));
+
+    // A preedit string selected while composing starts the area, covering it
+    editor.set_selection(Selection::Normal(Cursor::new(1, 0)));
+    let (x, y, w, _) = editor.ime_cursor_area().unwrap();
+    assert_eq!((x, y), (0, 20));
+    assert_eq!(w as i32, caret_x);
+
+    // The area follows scrolling
+    editor.with_buffer_mut(|buffer| buffer.set_scroll(Scroll::new(1, 0.0, 0.0)));
+    editor.shape_as_needed(&mut font_system, false);
+    assert_eq!(editor.ime_cursor_area().map(|area| area.1), Some(0));
+    editor.with_buffer_mut(|buffer| buffer.set_scroll(Scroll::new(2, 0.0, 0.0)));
+    editor.shape_as_needed(&mut font_system, false);
+    assert_eq!(editor.ime_cursor_area(), None);
+}
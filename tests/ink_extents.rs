@@ -0,0 +1,45 @@
+use cosmic_text::{fontdb, Attrs, Buffer, CacheKeyFlags, Family, FontSystem, Metrics, Shaping};
+
+fn line_ink(
+    font_system: &mut FontSystem,
+    text: &str,
+    attrs: Attrs,
+) -> Option<(f32, f32, f32, f32, f32)> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(32.0, 40.0));
+    buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_system, false);
+    let line = &buffer.lines[0]
+        .layout_opt()
+        .as_ref()
+        .expect("line not laid out")[0];
+    line.ink(font_system)
+        .map(|ink| (ink.x_min, ink.y_min, ink.x_max, ink.y_max, line.w))
+}
+
+#[test]
+fn ink_extents() {
+    // Only use the fonts in this repository, so results do not depend on the installed fonts
+    let mut font_db = fontdb::Database::new();
+    font_db.load_fonts_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts"));
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), font_db);
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+
+    // Ascenders are above the baseline and descenders below
+    let (x_min, y_min, x_max, y_max, w) = line_ink(&mut font_system, "Ag", attrs).expect("no ink");
+    assert!(x_min >= -1.0 && x_min < w / 2.0);
+    assert!(x_max > w / 2.0 && x_max <= w + 1.0);
+    assert!(y_min < -16.0);
+    assert!(y_max > 4.0);
+
+    // Fake italic leans the ink to the right
+    let (_, _, italic_x_max, _, _) = line_ink(
+        &mut font_system,
+        "Ag",
+        attrs.cache_key_flags(CacheKeyFlags::FAKE_ITALIC),
+    )
+    .expect("no ink");
+    assert!(italic_x_max > x_max);
+
+    // Spaces have no ink
+    assert_eq!(line_ink(&mut font_system, "   ", attrs), None);
+}
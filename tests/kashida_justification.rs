@@ -0,0 +1,64 @@
+use cosmic_text::{
+    fontdb, Align, Attrs, Buffer, Family, FontSystem, Justification, LayoutRun, Metrics, Shaping,
+    Wrap,
+};
+
+fn justified_runs(justification: Justification) -> Vec<(f32, usize, f32)> {
+    // Only use the fonts in this repository, so results do not depend on the installed fonts
+    let mut font_db = fontdb::Database::new();
+    font_db.load_fonts_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts"));
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), font_db);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(16.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+
+    buffer.set_wrap(Wrap::Word);
+    buffer.set_size(Some(200.0), None);
+    buffer.set_text(
+        "السلام عليكم ورحمة الله وبركاته، كيف حالكم اليوم يا أصدقائي الأعزاء",
+        Attrs::new().family(Family::Name("Noto Sans Arabic")),
+        Shaping::Advanced,
+    );
+    for line in buffer.lines.iter_mut() {
+        line.set_align(Some(Align::Justified));
+        line.set_justification(justification);
+    }
+    buffer.shape_until_scroll(false);
+
+    buffer
+        .layout_runs()
+        .map(|run: LayoutRun| {
+            let blank_w = run
+                .glyphs
+                .iter()
+                .filter(|glyph| run.text[glyph.start..glyph.end] == *" ")
+                .map(|glyph| glyph.w)
+                .fold(0.0, f32::max);
+            (run.line_w, run.glyphs.len(), blank_w)
+        })
+        .collect()
+}
+
+// Kashida justification fills lines by elongating letters instead of stretching spaces
+#[test]
+fn kashida_justification() {
+    let spaces = justified_runs(Justification::Spaces);
+    let kashida = justified_runs(Justification::Kashida);
+
+    assert!(spaces.len() > 1, "text should wrap");
+    assert_eq!(spaces.len(), kashida.len());
+
+    let last = spaces.len() - 1;
+    for (i, (spaces_run, kashida_run)) in spaces.iter().zip(kashida.iter()).enumerate() {
+        if i == last {
+            // The last line of a paragraph is not justified
+            assert_eq!(spaces_run, kashida_run);
+            continue;
+        }
+
+        assert!((spaces_run.0 - 200.0).abs() < 0.01);
+        assert!((kashida_run.0 - 200.0).abs() < 0.01);
+        // Kashida glyphs are inserted and spaces keep their natural width
+        assert!(kashida_run.1 > spaces_run.1);
+        assert!(kashida_run.2 < spaces_run.2);
+    }
+}
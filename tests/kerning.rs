@@ -0,0 +1,30 @@
+use cosmic_text::{fontdb, Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
+
+fn advances(font_system: &mut FontSystem, text: &str, attrs: Attrs) -> Vec<f32> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_text(text, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter().map(|glyph| glyph.w))
+        .collect()
+}
+
+#[test]
+fn kerning_attribute() {
+    // Only use the fonts in this repository, so results do not depend on the installed fonts
+    let mut font_db = fontdb::Database::new();
+    font_db.load_fonts_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts"));
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), font_db);
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    let a = advances(&mut font_system, "A", attrs)[0];
+    let v = advances(&mut font_system, "V", attrs)[0];
+
+    // Noto Sans kerns this pair by default
+    let kerned = advances(&mut font_system, "AV", attrs);
+    assert!(kerned[0] + kerned[1] < a + v);
+
+    let unkerned = advances(&mut font_system, "AV", attrs.kerning(false));
+    assert_eq!(unkerned, [a, v]);
+}
@@ -0,0 +1,42 @@
+use cosmic_text::{layout_label, Attrs, Buffer, FontSystem, Metrics, Shaping, Wrap};
+
+#[test]
+fn label_matches_buffer() {
+    let mut font_system = FontSystem::new();
+    let metrics = Metrics::new(14.0, 20.0);
+
+    for (text, max_width) in [
+        ("OK", None),
+        ("A longer label that has to wrap", Some(80.0)),
+        ("שלום עולם", None),
+    ] {
+        let mut buffer = Buffer::new(&mut font_system, metrics);
+        let mut buffer = buffer.borrow_with(&mut font_system);
+        buffer.set_wrap(Wrap::WordOrGlyph);
+        buffer.set_size(max_width, None);
+        buffer.set_text(text, Attrs::new(), Shaping::Advanced);
+        buffer.shape_until_scroll(false);
+        let expected: Vec<_> = buffer
+            .layout_runs()
+            .map(|run| {
+                let glyphs: Vec<_> = run.glyphs.iter().map(|glyph| (glyph.x, glyph.w)).collect();
+                (run.rtl, run.line_w, run.line_top, run.line_y, glyphs)
+            })
+            .collect();
+
+        let label = layout_label(&mut font_system, text, Attrs::new(), metrics, max_width);
+        let runs: Vec<_> = label
+            .lines_with_position()
+            .map(|(line, line_top, line_y)| {
+                let glyphs: Vec<_> = line.glyphs.iter().map(|glyph| (glyph.x, glyph.w)).collect();
+                (label.rtl, line.w, line_top, line_y, glyphs)
+            })
+            .collect();
+
+        assert_eq!(runs, expected, "{text}");
+        assert_eq!(label.lines.len() > 1, max_width.is_some());
+        assert_eq!(label.h, expected.len() as f32 * metrics.line_height);
+        let w = expected.iter().map(|run| run.1).fold(0.0, f32::max);
+        assert_eq!(label.w, w);
+    }
+}
@@ -0,0 +1,76 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Scroll, Shaping};
+
+fn runs(buffer: &Buffer) -> Vec<(usize, usize, f32)> {
+    buffer
+        .layout_runs()
+        .map(|run| (run.line_i, run.layout_i, run.line_top))
+        .collect()
+}
+
+#[test]
+fn layout_runs_reverse() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(80.0), Some(70.0));
+    let text = (0..20)
+        .map(|i| format!("line {i} with some wrapped text"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    buffer.set_text(&text, Attrs::new(), Shaping::Advanced);
+    buffer.set_scroll(Scroll::new(2, 10.0, 0.0));
+    buffer.shape_until_scroll(false);
+
+    let forward = runs(&buffer);
+    assert!(forward.len() > 2);
+    let mut backward: Vec<_> = buffer
+        .layout_runs()
+        .rev()
+        .map(|run| (run.line_i, run.layout_i, run.line_top))
+        .collect();
+    backward.reverse();
+    assert_eq!(forward, backward);
+
+    // Iterating from both ends meets in the middle
+    let mut iter = buffer.layout_runs();
+    let first = iter.next().unwrap();
+    let last = iter.next_back().unwrap();
+    assert_eq!((first.line_i, first.layout_i), (forward[0].0, forward[0].1));
+    let end = forward[forward.len() - 1];
+    assert_eq!((last.line_i, last.layout_i), (end.0, end.1));
+    assert_eq!(iter.count(), forward.len() - 2);
+}
+
+#[test]
+fn layout_runs_random_access() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(200.0), Some(40.0));
+    buffer.set_text(
+        "zero\none\ntwo\nthree\nfour",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+    for line_i in 0..5 {
+        buffer.line_layout(line_i);
+    }
+    buffer.set_scroll(Scroll::new(1, 0.0, 0.0));
+
+    // Lines before and after the visible area are reachable
+    let run = buffer.run_for_line(0).unwrap();
+    assert_eq!((run.line_i, run.line_top), (0, -20.0));
+    let run = buffer.run_for_line(4).unwrap();
+    assert_eq!((run.line_i, run.line_top), (4, 60.0));
+    assert!(buffer.run_for_line(5).is_none());
+
+    let run = buffer.run_at_y(-5.0).unwrap();
+    assert_eq!(run.line_i, 0);
+    let run = buffer.run_at_y(0.0).unwrap();
+    assert_eq!(run.line_i, 1);
+    let run = buffer.run_at_y(75.0).unwrap();
+    assert_eq!(run.line_i, 4);
+    assert!(buffer.run_at_y(-25.0).is_none());
+    assert!(buffer.run_at_y(80.0).is_none());
+}
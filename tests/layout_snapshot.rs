@@ -0,0 +1,50 @@
+use std::{sync::Arc, thread};
+
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn layout_snapshot_outlives_edits() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_text("one line\nsecond line", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+
+    let snapshot: Vec<_> = buffer
+        .lines
+        .iter()
+        .map(|line| line.layout_arc().cloned().expect("line not laid out"))
+        .collect();
+    let glyph_counts: Vec<_> = snapshot
+        .iter()
+        .map(|layout| layout[0].glyphs.len())
+        .collect();
+    assert_eq!(glyph_counts, [8, 11]);
+
+    // Cloning a buffer shares the snapshots that were already taken
+    let cloned = buffer.clone();
+    assert!(Arc::ptr_eq(
+        cloned.lines[0].layout_arc().unwrap(),
+        &snapshot[0]
+    ));
+
+    let render = thread::spawn(move || {
+        snapshot
+            .iter()
+            .map(|layout| layout[0].glyphs.len())
+            .collect::<Vec<_>>()
+    });
+
+    let attrs_list = buffer.lines[1].attrs_list().clone();
+    let ending = buffer.lines[1].ending();
+    buffer.lines[1].set_text("2nd", ending, attrs_list);
+    buffer.shape_until_scroll(false);
+    assert_eq!(
+        buffer.lines[1].layout_opt().as_ref().unwrap()[0]
+            .glyphs
+            .len(),
+        3
+    );
+
+    assert_eq!(render.join().unwrap(), glyph_counts);
+}
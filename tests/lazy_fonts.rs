@@ -0,0 +1,49 @@
+use cosmic_text::{fontdb, FontSystem};
+
+fn font_system() -> FontSystem {
+    // Only use the fonts in this repository, so results do not depend on the installed fonts
+    let mut font_db = fontdb::Database::new();
+    font_db.load_fonts_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts"));
+    FontSystem::new_with_locale_and_db("en-US".into(), font_db)
+}
+
+fn mono_id(font_system: &FontSystem) -> fontdb::ID {
+    font_system
+        .db()
+        .faces()
+        .find(|face| face.monospaced)
+        .map(|face| face.id)
+        .expect("no monospace font")
+}
+
+#[test]
+fn monospace_scripts_are_known_before_loading() {
+    let font_system = font_system();
+    let id = mono_id(&font_system);
+    assert!(font_system.is_monospace(id));
+    assert!(font_system
+        .get_monospace_ids_for_scripts([*b"latn"].into_iter())
+        .contains(&id));
+}
+
+#[test]
+fn codepoints_match_cmap_lookups() {
+    let mut font_system = font_system();
+    let id = mono_id(&font_system);
+    let font = font_system.get_font(id).expect("failed to load font");
+
+    assert!(font.has_codepoint(u32::from('A')));
+    assert!(!font.has_codepoint(0x10FFFF));
+    // Surrogates are not characters
+    assert!(!font.has_codepoint(0xD800));
+
+    let codepoints = font.unicode_codepoints();
+    assert!(codepoints.contains(&u32::from('A')));
+    assert!(codepoints
+        .iter()
+        .all(|&codepoint| font.has_codepoint(codepoint)));
+    assert_eq!(
+        font_system.get_font_supported_codepoints_in_word(id, "A\u{10FFFF}B"),
+        Some(2)
+    );
+}
@@ -0,0 +1,55 @@
+use cosmic_text::{Attrs, Buffer, Cursor, Family, FontSystem, Metrics, Shaping};
+
+// Carets can be placed between the graphemes of a ligature
+#[test]
+fn ligature_carets() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(32.0, 40.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+
+    buffer.set_text(
+        "fi",
+        Attrs::new().family(Family::Name("DejaVu Sans")),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    let run = buffer.layout_runs().next().expect("no layout run");
+    let glyph = run
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.end - glyph.start > 1)
+        .expect("no ligature glyph")
+        .clone();
+    let parts: Vec<_> = glyph.sub_clusters(run.text).collect();
+    assert_eq!(parts.len(), 2);
+    assert_eq!((parts[0].start, parts[0].end), (0, 1));
+    assert_eq!((parts[1].start, parts[1].end), (1, 2));
+
+    let y = run.line_top + run.line_height / 2.0;
+    // Hitting the right side of the first grapheme places the caret inside the ligature
+    let x = parts[0].x + parts[0].w * 0.75;
+    assert_eq!(buffer.hit(x, y).map(|cursor| cursor.index), Some(1));
+    // Hitting the left side of the second grapheme does as well
+    let x = parts[1].x + parts[1].w * 0.25;
+    assert_eq!(buffer.hit(x, y).map(|cursor| cursor.index), Some(1));
+    // Hitting the edges places the caret around the ligature
+    assert_eq!(
+        buffer.hit(glyph.x + 1.0, y).map(|cursor| cursor.index),
+        Some(0)
+    );
+    assert_eq!(
+        buffer
+            .hit(glyph.x + glyph.w - 1.0, y)
+            .map(|cursor| cursor.index),
+        Some(2)
+    );
+
+    // Selecting the first grapheme highlights only its part of the ligature
+    let run = buffer.layout_runs().next().expect("no layout run");
+    let (x, w) = run
+        .highlight(Cursor::new(0, 0), Cursor::new(0, 1))
+        .expect("no highlight");
+    assert!((x - parts[0].x).abs() < 0.01);
+    assert!((w - parts[0].w).abs() < 0.01);
+}
@@ -0,0 +1,29 @@
+use cosmic_text::{Attrs, AttrsList, Buffer, FontSystem, Ligatures, Metrics, Shaping};
+
+fn glyph_count(font_system: &mut FontSystem, attrs_list: AttrsList) -> usize {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_text("fi fi", Attrs::new(), Shaping::Advanced);
+    buffer.lines[0].set_attrs_list(attrs_list);
+    buffer.shape_until_scroll(false);
+    buffer.layout_runs().map(|run| run.glyphs.len()).sum()
+}
+
+#[test]
+fn ligatures_attribute() {
+    let mut font_system = FontSystem::new();
+
+    let normal = AttrsList::new(Attrs::new());
+    assert_eq!(glyph_count(&mut font_system, normal), 3);
+
+    let none = AttrsList::new(Attrs::new().ligatures(Ligatures::None));
+    assert_eq!(glyph_count(&mut font_system, none), 5);
+
+    let all = AttrsList::new(Attrs::new().ligatures(Ligatures::All));
+    assert_eq!(glyph_count(&mut font_system, all), 3);
+
+    // Ligatures can be disabled for a single span
+    let mut mixed = AttrsList::new(Attrs::new());
+    mixed.add_span(3..5, Attrs::new().ligatures(Ligatures::None));
+    assert_eq!(glyph_count(&mut font_system, mixed), 4);
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use cosmic_text::{
+    Attrs, Buffer, FontSystem, GreedyLineBreaker, LineBreaker, Metrics, ShapeLine, Shaping,
+    VisualLine, Wrap,
+};
+
+/// Puts every word that is not blank on its own line
+#[derive(Debug)]
+struct WordPerLine;
+
+impl LineBreaker for WordPerLine {
+    fn break_lines(
+        &self,
+        line: &ShapeLine,
+        font_size: f32,
+        _width_opt: Option<f32>,
+        _wrap: Wrap,
+        visual_lines: &mut Vec<VisualLine>,
+    ) {
+        for (span_index, span) in line.spans.iter().enumerate() {
+            for (word_index, word) in span.words.iter().enumerate() {
+                if word.blank {
+                    continue;
+                }
+                visual_lines.push(VisualLine {
+                    ranges: vec![(span_index, (word_index, 0), (word_index + 1, 0))],
+                    spaces: 0,
+                    w: word.width(font_size),
+                });
+            }
+        }
+    }
+}
+
+fn run_texts(buffer: &Buffer) -> Vec<String> {
+    buffer
+        .layout_runs()
+        .map(|run| {
+            let start = run
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.start)
+                .min()
+                .unwrap_or(0);
+            let end = run.glyphs.iter().map(|glyph| glyph.end).max().unwrap_or(0);
+            run.text[start..end].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn custom_line_breaker() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(1000.0), None);
+    buffer.set_text("one two three", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    assert_eq!(run_texts(&buffer), ["one two three"]);
+
+    buffer.set_line_breaker(Arc::new(WordPerLine));
+    assert_eq!(run_texts(&buffer), ["one", "two", "three"]);
+
+    // Going back to the default restores greedy breaking
+    buffer.set_line_breaker(Arc::new(GreedyLineBreaker));
+    assert_eq!(run_texts(&buffer), ["one two three"]);
+}
@@ -0,0 +1,42 @@
+use cosmic_text::{Attrs, AttrsList, BufferLine, LineEnding, Shaping};
+
+#[derive(Debug, PartialEq)]
+struct Bookmark(&'static str);
+
+#[derive(Debug, PartialEq)]
+struct SyntaxState(u32);
+
+#[test]
+fn line_data_persistence() {
+    let mut line = BufferLine::new(
+        "fn main() {}",
+        LineEnding::default(),
+        AttrsList::new(Attrs::new()),
+        Shaping::Advanced,
+    );
+    assert_eq!(line.data::<Bookmark>(), None);
+
+    line.set_persistent_data(Bookmark("entry"));
+    line.set_data(SyntaxState(1));
+    line.set_data(SyntaxState(2));
+    assert_eq!(line.data::<Bookmark>(), Some(&Bookmark("entry")));
+    assert_eq!(line.data::<SyntaxState>(), Some(&SyntaxState(2)));
+
+    // Editing the text resets the line, only persistent data is kept
+    assert!(line.set_text(
+        "fn main() { }",
+        LineEnding::default(),
+        AttrsList::new(Attrs::new())
+    ));
+    assert_eq!(line.data::<Bookmark>(), Some(&Bookmark("entry")));
+    assert_eq!(line.data::<SyntaxState>(), None);
+
+    // Split data stays with the first line
+    let other = line.split_off(3);
+    assert_eq!(line.data::<Bookmark>(), Some(&Bookmark("entry")));
+    assert_eq!(other.data::<Bookmark>(), None);
+
+    assert!(line.remove_data::<Bookmark>());
+    assert!(!line.remove_data::<Bookmark>());
+    assert_eq!(line.data::<Bookmark>(), None);
+}
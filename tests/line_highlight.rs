@@ -0,0 +1,52 @@
+use cosmic_text::{
+    Attrs, Buffer, Color, Cursor, Edit, Editor, FontSystem, Metrics, Shaping, SwashCache,
+};
+
+#[test]
+fn line_highlight_follows_visual_line() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(100.0), None);
+    buffer.set_text(
+        &mut font_system,
+        "a line long enough to wrap\nsecond",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    let highlight = Color::rgba(0xFF, 0xFF, 0xFF, 0x20);
+    editor.set_line_highlight(Some(highlight));
+    assert_eq!(editor.line_highlight(), Some(highlight));
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.shape_as_needed(false);
+
+    let color = Color::rgb(0, 0, 0);
+    let mut highlights = |editor: &mut cosmic_text::BorrowedWithFontSystem<Editor>| {
+        let mut rects = Vec::new();
+        editor.draw(
+            &mut swash_cache,
+            color,
+            color,
+            color,
+            color,
+            |x, y, w, h, color| {
+                if color == highlight {
+                    rects.push((x, y, w, h));
+                }
+            },
+        );
+        rects
+    };
+
+    assert_eq!(highlights(&mut editor), [(0, 0, 100, 20)]);
+
+    // Only the wrapped visual line containing the cursor is highlighted
+    editor.set_cursor(Cursor::new(0, 25));
+    let rects = highlights(&mut editor);
+    assert_eq!(rects.len(), 1);
+    assert!(rects[0].1 > 0);
+
+    editor.set_line_highlight(None);
+    assert!(highlights(&mut editor).is_empty());
+}
@@ -0,0 +1,43 @@
+use cosmic_text::{Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping};
+
+#[test]
+fn line_ids_follow_lines() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    let ids: Vec<_> = buffer.lines.iter().map(|line| line.id()).collect();
+    assert_ne!(ids[0], ids[1]);
+    assert_ne!(ids[1], ids[2]);
+    let run_ids: Vec<_> = buffer.layout_runs().map(|run| run.line_id).collect();
+    assert_eq!(run_ids, ids);
+
+    let mut editor = Editor::new(&mut buffer);
+
+    // Inserting lines above moves the identified lines down
+    editor.insert_at(Cursor::new(0, 0), "zero\n", None);
+    // Splitting a line keeps the identifier on the first part
+    editor.insert_at(Cursor::new(2, 1), "\n", None);
+    editor.shape_as_needed(&mut font_system, false);
+    editor.with_buffer(|buffer| {
+        let texts: Vec<_> = buffer.lines.iter().map(|line| line.text()).collect();
+        assert_eq!(texts, ["zero", "one", "t", "wo", "three"]);
+        assert_eq!(buffer.line_index(ids[0]), Some(1));
+        assert_eq!(buffer.line_index(ids[1]), Some(2));
+        assert_eq!(buffer.line_index(ids[2]), Some(4));
+    });
+
+    // Merging lines keeps the identifier of the first line
+    editor.delete_range(Cursor::new(1, 3), Cursor::new(2, 0));
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines[1].text(), "onet");
+        assert_eq!(buffer.line_index(ids[0]), Some(1));
+        assert_eq!(buffer.line_index(ids[1]), None);
+        assert_eq!(buffer.line_index(ids[2]), Some(3));
+    });
+}
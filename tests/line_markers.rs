@@ -0,0 +1,39 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, LineMarkers, Metrics, Shaping};
+
+#[test]
+fn line_markers_in_layout_runs() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(80.0), None);
+    buffer.set_text(
+        "unchanged\nthis added line is long enough to wrap\nbreak here",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let custom = LineMarkers::from_bits_retain(1 << 16);
+    buffer.lines[1].set_markers(LineMarkers::ADDED);
+    buffer.lines[2].set_markers(LineMarkers::BREAKPOINT | custom);
+    // Markers survive line resets
+    buffer.lines[2].reset();
+    buffer.shape_until_scroll(false);
+
+    let runs: Vec<_> = buffer
+        .layout_runs()
+        .map(|run| (run.line_i, run.layout_i, run.markers))
+        .collect();
+    assert!(runs.len() > 3);
+    for (line_i, layout_i, markers) in runs {
+        match line_i {
+            0 => assert_eq!(markers, LineMarkers::empty()),
+            1 => assert_eq!(markers, LineMarkers::ADDED),
+            _ => {
+                assert_eq!(layout_i, 0);
+                assert!(markers.contains(LineMarkers::BREAKPOINT | custom));
+            }
+        }
+    }
+    assert!(buffer
+        .layout_runs()
+        .any(|run| run.line_i == 1 && run.layout_i > 0));
+}
@@ -0,0 +1,37 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, Wrap};
+
+#[test]
+fn wrap_per_line() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(60.0), None);
+    buffer.set_text(
+        &mut font_system,
+        "one two three four\none two three four",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    let layout_lines = |buffer: &Buffer, line_i: usize| {
+        buffer
+            .layout_runs()
+            .filter(|run| run.line_i == line_i)
+            .count()
+    };
+    assert!(layout_lines(&buffer, 0) > 1);
+    assert!(layout_lines(&buffer, 1) > 1);
+
+    // A line that does not wrap in a buffer that does
+    assert!(buffer.lines[1].set_wrap(Some(Wrap::None)));
+    assert!(!buffer.lines[1].set_wrap(Some(Wrap::None)));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert!(layout_lines(&buffer, 0) > 1);
+    assert_eq!(layout_lines(&buffer, 1), 1);
+
+    // The override is kept when the buffer wrap mode changes, and when the line is split
+    buffer.set_wrap(&mut font_system, Wrap::Glyph);
+    assert_eq!(layout_lines(&buffer, 1), 1);
+    let after = buffer.lines[1].split_off(4);
+    assert_eq!(after.wrap(), Some(Wrap::None));
+    assert_eq!(buffer.lines[0].wrap(), None);
+}
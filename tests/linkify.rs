@@ -0,0 +1,72 @@
+#![cfg(feature = "linkify")]
+
+use cosmic_text::{
+    find_links, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, LinkKind, Metrics, Shaping,
+};
+
+fn links(text: &str) -> Vec<(LinkKind, &str)> {
+    find_links(text)
+        .into_iter()
+        .map(|link| (link.kind, &text[link.range]))
+        .collect()
+}
+
+#[test]
+fn links_are_found() {
+    assert_eq!(
+        links("see https://example.com/a_(b), or www.example.org."),
+        [
+            (LinkKind::Url, "https://example.com/a_(b)"),
+            (LinkKind::Url, "www.example.org"),
+        ]
+    );
+    assert_eq!(
+        links("(mail user.name+tag@mail.example.com!)"),
+        [(LinkKind::Email, "user.name+tag@mail.example.com")]
+    );
+    assert_eq!(
+        links("<ftp://host/file>\tuser@host"),
+        [(LinkKind::Url, "ftp://host/file")]
+    );
+    assert!(links("awww.example nothttps: a@b. @example.com https://").is_empty());
+
+    let text = "www.example.org me@example.com";
+    let found = find_links(text);
+    assert_eq!(found[0].target(text), "https://www.example.org");
+    assert_eq!(found[1].target(text), "mailto:me@example.com");
+}
+
+#[test]
+fn links_follow_edits() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "go to https://example.com now",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(buffer.lines[0].links()[0].range, 6..25);
+
+    let visible = buffer.visible_links();
+    assert_eq!(visible.len(), 1);
+    let (x, y, w, h) = visible[0].rects[0];
+    assert_eq!(
+        (visible[0].line_i, visible[0].link.kind, y, h),
+        (0, LinkKind::Url, 0.0, 20.0)
+    );
+    let hit = buffer.link_at(x + w / 2.0, 10.0).unwrap();
+    assert_eq!(hit, visible[0]);
+    assert_eq!(hit.link.range, 6..25);
+    assert_eq!(buffer.link_at(x - 10.0, 10.0), None);
+
+    // Inserting a line above moves the link with its line, inserting text moves its range
+    let mut editor = Editor::new(&mut buffer);
+    editor.insert_at(Cursor::new(0, 0), "first\nsee ", None);
+    editor.shape_as_needed(&mut font_system, false);
+    editor.with_buffer(|buffer| {
+        assert!(buffer.lines[0].links().is_empty());
+        assert_eq!(buffer.lines[1].links()[0].range, 10..29);
+    });
+}
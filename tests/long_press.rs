@@ -0,0 +1,68 @@
+use cosmic_text::{Action, Attrs, Buffer, Edit, Editor, FontSystem, Metrics, Shaping};
+
+fn glyph_x(editor: &Editor, index: usize) -> i32 {
+    editor.with_buffer(|buffer| {
+        let run = buffer.layout_runs().next().unwrap();
+        let glyph = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.start == index)
+            .unwrap();
+        (glyph.x + glyph.w / 2.0) as i32
+    })
+}
+
+#[test]
+fn long_press() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), None);
+    buffer.set_text(
+        &mut font_system,
+        "hello brave world",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+    assert_eq!(editor.context_menu_anchor(), None);
+
+    // The word under the touch point is selected and the anchor covers it
+    let x = glyph_x(&editor, 8);
+    editor.action(&mut font_system, Action::LongPress { x, y: 10 });
+    assert_eq!(editor.copy_selection().as_deref(), Some("brave"));
+    let (x, y, w, h) = editor.context_menu_anchor().unwrap();
+    assert!(x <= glyph_x(&editor, 6) && x + w as i32 >= glyph_x(&editor, 10));
+    assert!(x > glyph_x(&editor, 4) && x + (w as i32) < glyph_x(&editor, 12));
+    assert_eq!((y, h), (0, 20));
+}
+
+#[test]
+fn long_press_rtl() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(60.0), None);
+    buffer.set_text(
+        &mut font_system,
+        "שלום עולם",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // Right-to-left words start at the right edge of the line, where the anchor is
+    let x = glyph_x(&editor, "ש".len());
+    editor.action(&mut font_system, Action::LongPress { x, y: 10 });
+    assert_eq!(editor.copy_selection().as_deref(), Some("שלום"));
+    let (x, y, w, _) = editor.context_menu_anchor().unwrap();
+    assert_eq!(y, 0);
+    let right = editor.with_buffer(|buffer| {
+        let run = buffer.layout_runs().next().unwrap();
+        run.glyphs
+            .iter()
+            .map(|glyph| glyph.x + glyph.w)
+            .fold(0.0, f32::max) as i32
+    });
+    assert!((x + w as i32 - right).abs() <= 1);
+}
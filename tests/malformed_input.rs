@@ -0,0 +1,165 @@
+use cosmic_text::{
+    Action, Attrs, AttrsList, Buffer, BufferLine, Cursor, Edit, Editor, FontSystem, LayoutError,
+    LineEnding, Metrics, Motion, Selection, Shaping, Wrap,
+};
+
+#[test]
+fn paragraph_separators() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    for text in [
+        "שלום\u{2029}hello",
+        "hello\u{2029}שלום",
+        "مرحبا\u{2029}abc\u{85}שלום",
+    ] {
+        buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+        buffer.shape_until_scroll(&mut font_system, false);
+        assert_eq!(buffer.lines.len(), 1);
+        assert!(buffer.layout_runs().next().is_some());
+    }
+}
+
+#[test]
+fn invalid_metrics() {
+    let mut font_system = FontSystem::new();
+    assert_eq!(
+        Buffer::try_new(&mut font_system, Metrics::new(0.0, 20.0)).err(),
+        Some(LayoutError::InvalidFontSize)
+    );
+    assert_eq!(
+        Metrics::new(14.0, f32::NAN).validate(),
+        Err(LayoutError::InvalidLineHeight)
+    );
+
+    let mut buffer = Buffer::try_new(&mut font_system, Metrics::new(14.0, 20.0)).unwrap();
+    assert_eq!(
+        buffer.try_set_metrics_and_size(&mut font_system, Metrics::new(-3.0, -4.0), None, None),
+        Err(LayoutError::InvalidFontSize)
+    );
+    assert_eq!(buffer.metrics(), Metrics::new(14.0, 20.0));
+
+    // Negative line heights do not prevent scrolling from finishing
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(-3.0, -4.0));
+    buffer.set_size(&mut font_system, Some(f32::NAN), Some(100.0));
+    buffer.set_text(&mut font_system, "a\nb", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let mut line = BufferLine::new(
+        "a",
+        LineEnding::None,
+        AttrsList::new(Attrs::new()),
+        Shaping::Advanced,
+    );
+    assert_eq!(
+        line.try_layout(&mut font_system, 0.0, None, Wrap::Word, None, 8)
+            .err(),
+        Some(LayoutError::InvalidFontSize)
+    );
+}
+
+#[test]
+fn zero_and_sub_pixel_metrics() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(0.5, 0.5));
+    buffer.set_size(&mut font_system, Some(100.0), Some(100.0));
+    buffer.set_text(&mut font_system, "a\nb\nc", Attrs::new(), Shaping::Advanced);
+
+    // Zero and invalid sizes are ignored
+    buffer.set_scale(&mut font_system, 0.0);
+    assert_eq!(buffer.scale(), 1.0);
+    assert_eq!(buffer.fit_text(&mut font_system, 0.0, 20.0, 1.0, None), 0.5);
+    assert_eq!(buffer.metrics(), Metrics::new(0.5, 0.5));
+
+    // Line heights below one pixel move by one line per pixel
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.action(Action::Motion(Motion::Vertical(2)));
+    assert_eq!(editor.cursor().line, 2);
+    editor.action(Action::Motion(Motion::PageUp));
+    assert_eq!(editor.cursor().line, 0);
+}
+
+#[test]
+fn invalid_cursors() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "ab\nשלום",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+
+    editor.set_cursor(Cursor::new(5, 3));
+    assert_eq!(editor.cursor(), Cursor::new(1, 8));
+    // Inside of a multi-byte character
+    editor.set_cursor(Cursor::new(1, 3));
+    assert_eq!(editor.cursor(), Cursor::new(1, 2));
+    editor.set_cursor(Cursor::new(0, 1000));
+    assert_eq!(editor.cursor(), Cursor::new(0, 2));
+    // Buffers do not clamp cursors, but ignore ones outside of the text
+    editor.with_buffer_mut(|buffer| buffer.shape_until_cursor(Cursor::new(9, 0), false));
+
+    editor.set_selection(Selection::Normal(Cursor::new(7, 7)));
+    assert_eq!(editor.selection(), Selection::Normal(Cursor::new(1, 8)));
+    editor.delete_selection();
+    editor.action(Action::Backspace);
+    editor.action(Action::Motion(Motion::Down));
+    assert_eq!(
+        editor.with_buffer(|buffer| buffer.lines[0].text().to_string()),
+        "a"
+    );
+}
+
+#[test]
+fn missing_font() {
+    let mut font_system = FontSystem::new_deterministic(core::iter::empty());
+    let mut line = BufferLine::new(
+        "hello",
+        LineEnding::None,
+        AttrsList::new(Attrs::new()),
+        Shaping::Advanced,
+    );
+    assert_eq!(
+        line.try_shape(&mut font_system, 8).err(),
+        Some(LayoutError::NoFont)
+    );
+    assert!(line.shape_opt().is_none());
+    assert_eq!(
+        line.try_layout(&mut font_system, 14.0, None, Wrap::Word, None, 8)
+            .err(),
+        Some(LayoutError::NoFont)
+    );
+    assert!(line.layout_opt().is_none());
+
+    // Shaping without a font leaves the text without glyphs
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "hello", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert!(buffer.layout_runs().all(|run| run.glyphs.is_empty()));
+}
+
+#[test]
+fn unindent_cursor_inside_indent() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "        x",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+
+    // The cursor is moved to the start of the removed indent
+    editor.set_cursor(Cursor::new(0, 3));
+    editor.action(Action::Unindent);
+    assert_eq!(editor.cursor(), Cursor::new(0, 0));
+    assert_eq!(
+        editor.with_buffer(|buffer| buffer.lines[0].text().to_string()),
+        "x"
+    );
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "swash")]
+
+use cosmic_text::{Attrs, Buffer, Color, FontSystem, Metrics, Shaping, SwashCache, SwashContent};
+
+#[test]
+fn mask_images_are_cached_without_color() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "a", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let glyph = buffer.layout_runs().next().unwrap().glyphs[0].physical((0.0, 0.0), 1.0);
+    assert_eq!(swash_cache.image_content(glyph.cache_key), None);
+
+    // Drawing in different colors renders the image once
+    for color in [Color::rgb(0, 0, 0), Color::rgb(255, 0, 0)] {
+        let mut drawn = 0;
+        swash_cache.with_pixels(&mut font_system, glyph.cache_key, color, |_, _, pixel| {
+            if pixel.a() > 0 {
+                assert_eq!(
+                    (pixel.r(), pixel.g(), pixel.b()),
+                    (color.r(), color.g(), color.b())
+                );
+                drawn += 1;
+            }
+        });
+        assert!(drawn > 0);
+    }
+    assert_eq!(swash_cache.image_cache.len(), 1);
+    assert_eq!(
+        swash_cache.image_content(glyph.cache_key),
+        Some(SwashContent::Mask)
+    );
+}
@@ -0,0 +1,48 @@
+use cosmic_text::{fontdb, FontSystem};
+
+fn font_id(font_system: &FontSystem, family: &str) -> fontdb::ID {
+    font_system
+        .db()
+        .query(&fontdb::Query {
+            families: &[fontdb::Family::Name(family)],
+            ..Default::default()
+        })
+        .expect("font is not installed")
+}
+
+#[test]
+fn math_table_constants_and_variants() {
+    let mut font_system = FontSystem::new();
+    let id = font_id(&font_system, "DejaVu Math TeX Gyre");
+
+    let constants = font_system.math_constants(id, 20.0).unwrap();
+    assert!(constants.script_scale_down > 0.0 && constants.script_scale_down < 1.0);
+    assert!(constants.script_script_scale_down <= constants.script_scale_down);
+    assert!(constants.axis_height > 0.0);
+    assert!(constants.fraction_rule_thickness > 0.0);
+
+    // Constants scale with the font size
+    let double = font_system.math_constants(id, 40.0).unwrap();
+    assert_eq!(double.axis_height, constants.axis_height * 2.0);
+    assert_eq!(double.script_scale_down, constants.script_scale_down);
+
+    // Parentheses grow vertically
+    let font = font_system.get_font(id).unwrap();
+    let paren = font.rustybuzz().glyph_index('(').unwrap().0;
+    let variants = font.math_variants(paren, true);
+    assert!(variants.len() > 1);
+    assert!(variants
+        .windows(2)
+        .all(|pair| pair[0].advance <= pair[1].advance));
+    assert!(font.math_variants(paren, false).is_empty());
+}
+
+#[test]
+fn fonts_without_math_table() {
+    let mut font_system = FontSystem::new();
+    let id = font_id(&font_system, "DejaVu Sans Mono");
+    assert_eq!(font_system.math_constants(id, 20.0), None);
+    let font = font_system.get_font(id).unwrap();
+    let paren = font.rustybuzz().glyph_index('(').unwrap().0;
+    assert!(font.math_variants(paren, true).is_empty());
+}
@@ -0,0 +1,36 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn measure_text_matches_buffer() {
+    let mut font_system = FontSystem::new();
+    let metrics = Metrics::new(14.0, 20.0);
+    let text = "Hello, world!";
+
+    let mut buffer = Buffer::new(&mut font_system, metrics);
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let run = buffer.layout_runs().next().unwrap();
+
+    let (width, height, ascent, descent) =
+        font_system.measure_text(text, Attrs::new(), metrics, Shaping::Advanced);
+    assert_eq!(width, run.line_w);
+    assert_eq!(height, run.line_height);
+    assert!(ascent > 0.0);
+    assert!(descent > 0.0);
+
+    // Larger text is wider and taller
+    let (large_width, _, large_ascent, _) = font_system.measure_text(
+        text,
+        Attrs::new().metrics(Metrics::new(28.0, 40.0)),
+        metrics,
+        Shaping::Advanced,
+    );
+    assert!(large_width > width);
+    assert!(large_ascent > ascent);
+
+    // Empty text still has a line height
+    let (width, height, _, _) =
+        font_system.measure_text("", Attrs::new(), metrics, Shaping::Advanced);
+    assert_eq!(width, 0.0);
+    assert_eq!(height, 20.0);
+}
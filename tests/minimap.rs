@@ -0,0 +1,51 @@
+use cosmic_text::{
+    Attrs, AttrsList, Buffer, BufferLine, Color, FontSystem, LineEnding, Metrics, Shaping,
+};
+
+#[test]
+fn minimap_blocks_and_lines() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "fn main() {\n    let x = 1;\n}",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    // A line that was never shaped uses estimated widths
+    let red = Color::rgb(0xFF, 0, 0);
+    buffer.lines.push(BufferLine::new(
+        "\tone two",
+        LineEnding::default(),
+        AttrsList::new(Attrs::new().color(red)),
+        Shaping::Advanced,
+    ));
+
+    let minimap = buffer.minimap(Metrics::new(2.0, 3.0));
+    assert_eq!(minimap.height(), 12.0);
+
+    let blocks = minimap.line_blocks(1).expect("line 1");
+    assert_eq!(blocks.len(), 4);
+    assert!(blocks[0].x > 0.0);
+    assert!(blocks.windows(2).all(|w| w[0].x + w[0].w <= w[1].x));
+
+    let blocks = minimap.line_blocks(3).expect("line 3");
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].color_opt, Some(red));
+    assert!(blocks[0].x > blocks[0].w);
+
+    assert_eq!(minimap.line_at(-1.0), Some(0));
+    assert_eq!(minimap.line_at(4.0), Some(1));
+    assert_eq!(minimap.line_at(100.0), Some(3));
+    assert_eq!(minimap.line_top(2), 6.0);
+
+    let mut rects = 0;
+    minimap.draw(Color::rgb(0, 0, 0), |_, y, w, h, _| {
+        assert!(y >= 0 && w > 0 && h > 0);
+        rects += 1;
+    });
+    let blocks: usize = (0..4)
+        .map(|line_i| minimap.line_blocks(line_i).expect("line").len())
+        .sum();
+    assert_eq!(rects, blocks);
+}
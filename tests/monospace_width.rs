@@ -0,0 +1,41 @@
+use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
+
+fn glyph_widths(buffer: &Buffer) -> Vec<f32> {
+    buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter().map(|glyph| glyph.w))
+        .collect()
+}
+
+#[test]
+fn monospace_width() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    let mono = Attrs::new().family(Family::Monospace);
+    buffer.set_rich_text(
+        [("code", mono), ("text", mono.match_mono_width(false))],
+        mono,
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    let natural = glyph_widths(&buffer);
+    assert_eq!(natural.len(), 8);
+    let cell = natural[0];
+    assert!(natural.iter().all(|w| (w - cell).abs() < 0.01));
+
+    // Monospace glyphs are resized to the monospace width, unless their span opts out
+    buffer.set_monospace_width(Some(cell * 1.5));
+    let matched = glyph_widths(&buffer);
+    assert_eq!(buffer.monospace_width(), Some(cell * 1.5));
+    for w in &matched[..4] {
+        assert!((w - cell * 1.5).abs() < 0.01, "{w} != {}", cell * 1.5);
+    }
+    for w in &matched[4..] {
+        assert!((w - cell).abs() < 0.01, "{w} != {cell}");
+    }
+
+    buffer.set_monospace_width(None);
+    assert_eq!(glyph_widths(&buffer), natural);
+}
@@ -0,0 +1,63 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, Family, FontSystem, Metrics, Selection, Shaping,
+};
+
+fn text(editor: &Editor) -> String {
+    editor.with_buffer(|buffer| buffer.lines[0].text().to_string())
+}
+
+#[test]
+fn overtype() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "abe\u{301}c",
+        Attrs::new().family(Family::Monospace),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // The cursor is a line until overtype is enabled
+    let (_, _, w, h) = editor.cursor_rect().unwrap();
+    assert_eq!((w, h), (1, 20));
+    editor.action(&mut font_system, Action::ToggleOvertype);
+    assert!(editor.overtype());
+    let (x, _, w, _) = editor.cursor_rect().unwrap();
+    assert_eq!(x, 0);
+    assert!(w > 1);
+
+    // Graphemes after the cursor are replaced, including combining marks
+    editor.start_change();
+    editor.action(&mut font_system, Action::Insert('x'));
+    assert_eq!(text(&editor), "xbe\u{301}c");
+    editor.set_cursor(Cursor::new(0, 2));
+    editor.action(&mut font_system, Action::Insert('y'));
+    assert_eq!(text(&editor), "xbyc");
+    assert_eq!(editor.cursor(), Cursor::new(0, 3));
+
+    // At the end of the line text is appended
+    editor.set_cursor(Cursor::new(0, 4));
+    editor.action(&mut font_system, Action::Insert('z'));
+    editor.action(&mut font_system, Action::Insert('!'));
+    assert_eq!(text(&editor), "xbycz!");
+
+    // A selection is replaced as a whole
+    editor.set_cursor(Cursor::new(0, 0));
+    editor.set_selection(Selection::Normal(Cursor::new(0, 2)));
+    editor.action(&mut font_system, Action::Insert('w'));
+    assert_eq!(text(&editor), "wycz!");
+
+    // Undoing restores the original text
+    let mut change = editor.finish_change().unwrap();
+    change.reverse();
+    editor.apply_change(&change);
+    assert_eq!(text(&editor), "abe\u{301}c");
+
+    editor.action(&mut font_system, Action::ToggleOvertype);
+    assert!(!editor.overtype());
+    editor.set_cursor(Cursor::new(0, 0));
+    editor.action(&mut font_system, Action::Insert('v'));
+    assert_eq!(text(&editor), "vabe\u{301}c");
+}
@@ -0,0 +1,69 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Page, PageBreaks, Shaping, Wrap};
+
+fn buffer(font_system: &mut FontSystem, text: &str) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(10.0, 10.0));
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer
+}
+
+fn page_lines(buffer: &Buffer, pages: &[Page]) -> Vec<Vec<usize>> {
+    pages
+        .iter()
+        .map(|page| buffer.page_runs(page).map(|run| run.line_i).collect())
+        .collect()
+}
+
+#[test]
+fn paginate_single_lines() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = buffer(&mut font_system, "a\nb\nc\nd\ne");
+
+    let pages = buffer.paginate(&mut font_system, PageBreaks::new(20.0));
+    assert_eq!(
+        page_lines(&buffer, &pages),
+        vec![vec![0, 1], vec![2, 3], vec![4]]
+    );
+
+    // Runs are relative to the top of their page
+    let tops: Vec<f32> = buffer
+        .page_runs(&pages[1])
+        .map(|run| run.line_top)
+        .collect();
+    assert_eq!(tops, vec![0.0, 10.0]);
+
+    // Keep a heading with the following line
+    buffer.lines[1].set_keep_with_next(true);
+    let pages = buffer.paginate(&mut font_system, PageBreaks::new(20.0));
+    assert_eq!(
+        page_lines(&buffer, &pages),
+        vec![vec![0], vec![1, 2], vec![3, 4]]
+    );
+}
+
+#[test]
+fn paginate_widows_and_orphans() {
+    let mut font_system = FontSystem::new();
+    // Lay out the second paragraph as 4 lines
+    let mut buffer = buffer(&mut font_system, "a\n1 2 3 4\nb");
+    buffer.set_wrap(&mut font_system, Wrap::Word);
+    buffer.set_size(&mut font_system, Some(1.0), None);
+    let pages = buffer.paginate(&mut font_system, PageBreaks::new(40.0));
+    let layout_lines = |line_i: usize| {
+        buffer.lines[line_i]
+            .layout_opt()
+            .as_ref()
+            .expect("layout")
+            .len()
+    };
+    assert_eq!(layout_lines(1), 4);
+    // Greedy would leave 1 widow, so 2 lines move to the next page
+    assert_eq!(pages[0].layout_lines, 3);
+    assert_eq!(pages[1].start_line, 1);
+    assert_eq!(pages[1].start_layout, 2);
+
+    // With 3 orphans required, the whole paragraph moves
+    let pages = buffer.paginate(&mut font_system, PageBreaks::new(40.0).orphans(3));
+    assert_eq!(pages[0].layout_lines, 1);
+    assert_eq!(pages[1].start_line, 1);
+    assert_eq!(pages[1].start_layout, 0);
+}
@@ -0,0 +1,49 @@
+use cosmic_text::{Attrs, Buffer, Color, FontSystem, Metrics, Shaping, SwashCache};
+
+#[test]
+fn palette_colors() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    let fallback = Color::rgb(0x10, 0x20, 0x30);
+    buffer.set_rich_text(
+        [
+            ("keyword ", Attrs::new().palette_color(1).color(fallback)),
+            ("plain", Attrs::new()),
+        ],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    let glyphs: Vec<_> = buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter().cloned())
+        .collect();
+    let keyword = &glyphs[0];
+    let plain = glyphs.last().unwrap();
+    assert_eq!(keyword.palette_index_opt, Some(1));
+    assert_eq!(plain.palette_index_opt, None);
+
+    // Without a matching palette entry the glyph color is used
+    assert_eq!(keyword.color(&[]), Some(fallback));
+    assert_eq!(keyword.color(&[Color::rgb(0, 0, 0)]), Some(fallback));
+
+    // Switching palettes changes the resolved color without reshaping
+    let light = [Color::rgb(0, 0, 0), Color::rgb(0xC0, 0, 0)];
+    let dark = [Color::rgb(0xFF, 0xFF, 0xFF), Color::rgb(0, 0xC0, 0)];
+    assert_eq!(keyword.color(&light), Some(light[1]));
+    assert_eq!(keyword.color(&dark), Some(dark[1]));
+    assert_eq!(plain.color(&dark), None);
+
+    let text_color = Color::rgb(0xFF, 0xFF, 0xFF);
+    let mut keyword_pixels = 0;
+    buffer.draw_with_palette(&mut swash_cache, text_color, &dark, |_, _, _, _, color| {
+        assert!(color.r() == color.b());
+        if color.a() > 0 && color.r() == 0 {
+            keyword_pixels += 1;
+        }
+    });
+    assert!(keyword_pixels > 0);
+}
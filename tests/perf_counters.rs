@@ -0,0 +1,41 @@
+#![cfg(feature = "perf")]
+
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, PerfCounters, Shaping, SwashCache};
+
+#[test]
+fn perf_counters() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    font_system.reset_perf_counters();
+    assert_eq!(font_system.perf_counters(), PerfCounters::default());
+
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    let shaped = font_system.perf_counters();
+    assert_eq!(shaped.lines_shaped, 3);
+    assert_eq!(shaped.lines_laid_out, 3);
+    assert!(shaped.shape_plan_cache_misses >= 1);
+    assert!(shaped.shape_plan_cache_hits >= 2);
+
+    // Resizing lays out the shaped lines again without shaping them
+    buffer.set_size(&mut font_system, Some(100.0), None);
+    let resized = font_system.perf_counters().since(&shaped);
+    assert_eq!(resized.lines_shaped, 0);
+    assert_eq!(resized.lines_laid_out, 3);
+    assert_eq!(resized.relayouts, 1);
+
+    // Glyph images are rendered once and then cached
+    let glyph = buffer.layout_runs().next().unwrap().glyphs[0].physical((0.0, 0.0), 1.0);
+    swash_cache.get_image(&mut font_system, glyph.cache_key);
+    swash_cache.get_image(&mut font_system, glyph.cache_key);
+    assert_eq!(font_system.perf_counters().glyphs_rasterized, 1);
+
+    font_system.reset_perf_counters();
+    assert_eq!(font_system.perf_counters(), PerfCounters::default());
+}
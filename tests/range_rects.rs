@@ -0,0 +1,37 @@
+use cosmic_text::{Attrs, Buffer, Cursor, FontSystem, Metrics, Shaping};
+
+#[test]
+fn rects_for_range() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(60.0), None);
+    buffer.set_text(
+        "read aloud\nshalom שלום עולם",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+
+    // A single word gives a single rectangle, on the line it was wrapped to
+    let rects = buffer.rects_for_range(Cursor::new(0, 5), Cursor::new(0, 10));
+    assert_eq!(rects.len(), 1);
+    let (x, y, w, h) = rects[0];
+    assert!(x >= 0.0 && w > 0.0);
+    assert_eq!((y, h), (20.0, 20.0));
+
+    // Wrapped text gives one rectangle per line
+    let rects = buffer.rects_for_range(Cursor::new(0, 0), Cursor::new(0, 10));
+    assert_eq!(rects.len(), 2);
+    assert!(rects[1].1 > rects[0].1);
+
+    // Hebrew words are found on their own visual line
+    let start = "shalom ".len();
+    let end = start + "שלום".len();
+    let rects = buffer.rects_for_range(Cursor::new(1, start), Cursor::new(1, end));
+    assert_eq!(rects.len(), 1);
+    assert!(rects[0].2 > 0.0);
+
+    assert!(buffer
+        .rects_for_range(Cursor::new(0, 3), Cursor::new(0, 3))
+        .is_empty());
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "swash")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cosmic_text::{
+    Attrs, Buffer, CacheKey, Color, FontSystem, Metrics, Placement, Rasterizer, ScaleContext,
+    Shaping, SwashCache, SwashContent, SwashImage, SwashRasterizer,
+};
+
+/// Renders every glyph as a filled square
+#[derive(Debug, Default)]
+struct SquareRasterizer {
+    calls: AtomicUsize,
+}
+
+impl Rasterizer for SquareRasterizer {
+    fn rasterize(
+        &self,
+        _context: &mut ScaleContext,
+        _font_system: &mut FontSystem,
+        _cache_key: CacheKey,
+    ) -> Option<SwashImage> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let mut image = SwashImage::new();
+        image.content = SwashContent::Mask;
+        image.placement = Placement {
+            left: 0,
+            top: 2,
+            width: 2,
+            height: 2,
+        };
+        image.data = vec![255; 4];
+        Some(image)
+    }
+}
+
+#[test]
+fn custom_rasterizer() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "a", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let cache_key = buffer.layout_runs().next().unwrap().glyphs[0]
+        .physical((0.0, 0.0), 1.0)
+        .cache_key;
+    swash_cache.get_image(&mut font_system, cache_key);
+    assert_eq!(swash_cache.image_cache.len(), 1);
+
+    // Images of the previous rasterizer are dropped
+    let rasterizer = Arc::new(SquareRasterizer::default());
+    swash_cache.set_rasterizer(rasterizer.clone());
+    assert!(swash_cache.image_cache.is_empty());
+
+    let mut pixels = Vec::new();
+    let color = Color::rgb(0, 0, 255);
+    for _ in 0..2 {
+        swash_cache.with_pixels(&mut font_system, cache_key, color, |x, y, color| {
+            pixels.push((x, y, color));
+        });
+    }
+    assert_eq!(rasterizer.calls.load(Ordering::Relaxed), 1);
+    assert_eq!(pixels.len(), 8);
+    assert_eq!(
+        pixels[..4],
+        [
+            (0, -2, color),
+            (1, -2, color),
+            (0, -1, color),
+            (1, -1, color)
+        ]
+    );
+
+    swash_cache.set_rasterizer(Arc::new(SwashRasterizer));
+    let image = swash_cache.get_image(&mut font_system, cache_key).as_ref();
+    assert_ne!(image.unwrap().placement.width, 2);
+}
@@ -0,0 +1,42 @@
+use cosmic_text::{Attrs, AttrsList, Buffer, FontSystem, Metrics, ReshapeReasons, Shaping};
+
+#[test]
+fn reshape_reasons_explain_cache_misses() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "a\tb\nplain",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+    for line in buffer.lines.iter() {
+        assert_eq!(line.reshape_reasons(), ReshapeReasons::NEW);
+        assert!(line.pending_reshape_reasons().is_empty());
+    }
+
+    // Setting an equal attributes list keeps the shape
+    let attrs_list = AttrsList::new(Attrs::new());
+    assert!(!buffer.lines[1].set_attrs_list(attrs_list));
+    assert!(buffer.lines[1].pending_reshape_reasons().is_empty());
+
+    // Changes are collected until the line is shaped again
+    let ending = buffer.lines[1].ending();
+    let bold = AttrsList::new(Attrs::new().weight(cosmic_text::Weight::BOLD));
+    buffer.lines[1].set_text("changed", ending, bold);
+    buffer.lines[1].reset_shaping();
+    assert_eq!(
+        buffer.lines[1].pending_reshape_reasons(),
+        ReshapeReasons::TEXT | ReshapeReasons::ATTRS | ReshapeReasons::RESET
+    );
+
+    // Only lines with tabs are reshaped for a new tab width
+    buffer.set_tab_width(&mut font_system, 4);
+    assert_eq!(buffer.lines[0].reshape_reasons(), ReshapeReasons::TAB_WIDTH);
+    assert_eq!(
+        buffer.lines[1].reshape_reasons(),
+        ReshapeReasons::TEXT | ReshapeReasons::ATTRS | ReshapeReasons::RESET
+    );
+    assert!(buffer.lines[1].pending_reshape_reasons().is_empty());
+}
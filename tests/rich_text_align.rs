@@ -0,0 +1,41 @@
+use cosmic_text::{Align, Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+#[test]
+fn rich_text_paragraph_alignment() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), None);
+    let attrs = Attrs::new();
+    buffer.set_rich_text_with_align(
+        &mut font_system,
+        [("left\ncen", attrs), ("ter\nright\nlast", attrs)],
+        attrs,
+        Shaping::Advanced,
+        [None, Some(Align::Center), Some(Align::Right)],
+    );
+
+    let aligns: Vec<_> = buffer.lines.iter().map(|line| line.align()).collect();
+    assert_eq!(
+        aligns,
+        [None, Some(Align::Center), Some(Align::Right), None]
+    );
+
+    // Lines are laid out with their alignment
+    let starts: Vec<_> = buffer
+        .layout_runs()
+        .map(|run| (run.line_i, run.glyphs[0].x, run.line_w))
+        .collect();
+    assert_eq!(starts[0].1, 0.0);
+    assert!((starts[1].1 - (200.0 - starts[1].2) / 2.0).abs() < 1.0);
+    assert!((starts[2].1 - (200.0 - starts[2].2)).abs() < 1.0);
+    assert_eq!(starts[3].1, 0.0);
+
+    // Setting rich text again without alignments resets them
+    buffer.set_rich_text(
+        &mut font_system,
+        [("one\ntwo", attrs)],
+        attrs,
+        Shaping::Advanced,
+    );
+    assert!(buffer.lines.iter().all(|line| line.align().is_none()));
+}
@@ -0,0 +1,87 @@
+use cosmic_text::{
+    Align, Attrs, Buffer, Color, FontSystem, Metrics, ReshapeReasons, Shaping, Weight, Wrap,
+};
+
+#[test]
+fn set_rich_text_keeps_unchanged_lines() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let attrs = Attrs::new();
+    let bold = attrs.weight(Weight::BOLD);
+    let spans = |count: &'static str| {
+        [
+            ("Title\n", bold),
+            ("body ", attrs),
+            ("text\n", bold),
+            (count, attrs),
+        ]
+    };
+
+    buffer.set_rich_text(&mut font_system, spans("1 item"), attrs, Shaping::Advanced);
+    let ids = buffer
+        .lines
+        .iter()
+        .map(|line| line.id())
+        .collect::<Vec<_>>();
+
+    // The same spans, like on the next frame, keep all lines
+    buffer.set_rich_text(&mut font_system, spans("1 item"), attrs, Shaping::Advanced);
+    for (line, id) in buffer.lines.iter().zip(ids.iter()) {
+        assert_eq!(line.id(), *id);
+        assert_eq!(line.reshape_reasons(), ReshapeReasons::NEW);
+    }
+
+    // Only the changed line is replaced, without properties of the old line
+    buffer.lines[1].set_wrap(Some(Wrap::None));
+    buffer.lines[2].set_wrap(Some(Wrap::None));
+    buffer.set_rich_text(&mut font_system, spans("2 items"), attrs, Shaping::Advanced);
+    assert_eq!(buffer.lines.len(), 3);
+    assert_eq!(buffer.lines[0].reshape_reasons(), ReshapeReasons::NEW);
+    assert_eq!(buffer.lines[1].reshape_reasons(), ReshapeReasons::NEW);
+    assert_eq!(buffer.lines[1].wrap(), Some(Wrap::None));
+    assert!(!ids.contains(&buffer.lines[2].id()));
+    assert_eq!(buffer.lines[2].text(), "2 items");
+    assert_eq!(buffer.lines[2].wrap(), None);
+
+    // Changing a color repaints the line
+    let red = Color::rgb(0xFF, 0, 0);
+    buffer.set_rich_text(
+        &mut font_system,
+        [
+            ("Title\n", bold.color(red)),
+            ("body ", attrs),
+            ("text\n", bold),
+            ("2 items", attrs),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.lines[0].reshape_reasons(), ReshapeReasons::NEW);
+    let glyph = &buffer.layout_runs().next().unwrap().glyphs[0];
+    assert_eq!(glyph.color_opt, Some(red));
+
+    // Changing the weight shapes the line again
+    buffer.set_rich_text(&mut font_system, spans("2 items"), bold, Shaping::Advanced);
+    assert_eq!(buffer.lines[1].reshape_reasons(), ReshapeReasons::ATTRS);
+
+    // Alignments not given are reset
+    buffer.set_rich_text_with_align(
+        &mut font_system,
+        spans("2 items"),
+        bold,
+        Shaping::Advanced,
+        [Some(Align::Center), Some(Align::Right)],
+    );
+    assert_eq!(buffer.lines[1].align(), Some(Align::Right));
+    buffer.set_rich_text_with_align(
+        &mut font_system,
+        spans("2 items"),
+        bold,
+        Shaping::Advanced,
+        [Some(Align::Center)],
+    );
+    assert_eq!(buffer.lines[0].align(), Some(Align::Center));
+    assert_eq!(buffer.lines[1].align(), None);
+    buffer.set_rich_text(&mut font_system, spans("2 items"), bold, Shaping::Advanced);
+    assert_eq!(buffer.lines[0].align(), None);
+}
@@ -0,0 +1,55 @@
+use cosmic_text::{
+    Attrs, AttrsList, Buffer, BufferLine, Cursor, Edit, Editor, FontSystem, LineEnding, Metrics,
+    Scroll, Shaping,
+};
+
+fn first_visible(buffer: &Buffer) -> String {
+    buffer.layout_runs().next().unwrap().text.to_string()
+}
+
+#[test]
+fn scroll_anchoring_keeps_visible_text() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), Some(60.0));
+    let text: Vec<_> = (0..20).map(|i| format!("line {i}")).collect();
+    buffer.set_text(
+        &mut font_system,
+        &text.join("\n"),
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.set_scroll_anchoring(true);
+    buffer.set_scroll(Scroll::new(10, 5.0, 0.0));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(first_visible(&buffer), "line 10");
+
+    // Lines prepended directly are reported as an insertion
+    let history = (0..3).map(|i| {
+        BufferLine::new(
+            format!("old {i}"),
+            LineEnding::default(),
+            AttrsList::new(Attrs::new()),
+            Shaping::Advanced,
+        )
+    });
+    buffer.lines.splice(0..0, history);
+    buffer.shift_anchors_insert(Cursor::new(0, 0), Cursor::new(3, 0));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(buffer.scroll(), Scroll::new(13, 5.0, 0.0));
+    assert_eq!(first_visible(&buffer), "line 10");
+
+    // Edits above the scroll position move it as well
+    let mut editor = Editor::new(&mut buffer);
+    let mut editor = editor.borrow_with(&mut font_system);
+    editor.insert_at(Cursor::new(0, 0), "a\nb\n", None);
+    editor.shape_as_needed(false);
+    editor.with_buffer(|buffer| assert_eq!(first_visible(buffer), "line 10"));
+
+    editor.delete_range(Cursor::new(0, 0), Cursor::new(1, 0));
+    editor.shape_as_needed(false);
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.scroll().line, 14);
+        assert_eq!(first_visible(buffer), "line 10");
+    });
+}
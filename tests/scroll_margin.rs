@@ -0,0 +1,46 @@
+use cosmic_text::{Attrs, Buffer, Cursor, FontSystem, Metrics, Shaping};
+
+fn visible_lines(buffer: &Buffer) -> (usize, usize) {
+    let mut runs = buffer.layout_runs();
+    let first = runs.next().unwrap().line_i;
+    let last = runs.next_back().map_or(first, |run| run.line_i);
+    (first, last)
+}
+
+#[test]
+fn scroll_margin() {
+    let mut font_system = FontSystem::new();
+    // Five lines are visible
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(100.0));
+    let text = (0..20).map(|i| format!("line {i}")).collect::<Vec<_>>();
+    buffer.set_text(
+        &mut font_system,
+        &text.join("\n"),
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.set_scroll_margin(1);
+    assert_eq!(buffer.scroll_margin(), 1);
+
+    // One line below the cursor is kept visible
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(4, 0), false);
+    assert_eq!(visible_lines(&buffer), (1, 5));
+
+    // Moving within the margin does not scroll
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(2, 0), false);
+    assert_eq!(visible_lines(&buffer), (1, 5));
+
+    // One line above the cursor is kept visible
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(1, 0), false);
+    assert_eq!(visible_lines(&buffer), (0, 4));
+
+    // No margin is kept past the end of the text
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(19, 0), false);
+    assert_eq!(visible_lines(&buffer), (15, 19));
+
+    // The margin is limited to half of the visible lines
+    buffer.set_scroll_margin(10);
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(10, 0), false);
+    assert_eq!(visible_lines(&buffer), (8, 12));
+}
@@ -0,0 +1,35 @@
+use cosmic_text::{
+    Attrs, AttrsList, BreakOverrides, Buffer, FontSystem, Metrics, Segmentation, Shaping,
+};
+
+#[test]
+fn segmentation_is_cached_per_line() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let text = "e\u{301}tude, ok";
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let segmentation = buffer.lines[0].segmentation();
+    assert_eq!(segmentation.graphemes(), [0, 3, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(segmentation.words(), [0, 7, 8, 9]);
+    assert_eq!(segmentation.line_breaks(), [9, 11]);
+    assert_eq!(segmentation.previous_grapheme(3), 0);
+    assert_eq!(segmentation.next_grapheme(0), 3);
+    assert_eq!(segmentation.next_grapheme(10), 11);
+    assert_eq!(segmentation.next_grapheme(11), 11);
+    assert_eq!(
+        *segmentation,
+        Segmentation::new(text, &BreakOverrides::default())
+    );
+
+    // Line break overrides are applied when the line is shaped again
+    buffer.lines[0].set_break_overrides(BreakOverrides::new().allow(4));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(buffer.lines[0].segmentation().line_breaks(), [4, 9, 11]);
+
+    // Changing the text segments it again
+    buffer.lines[0].set_text("ab", Default::default(), AttrsList::new(Attrs::new()));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(buffer.lines[0].segmentation().graphemes(), [0, 1]);
+}
@@ -0,0 +1,92 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Selection, SelectionHandle,
+    Shaping,
+};
+
+#[test]
+fn selection_handles() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), Some(100.0));
+    buffer.set_text(
+        &mut font_system,
+        "hello brave new world\nsecond line",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // No handles without a selection
+    assert_eq!(editor.selection_handles(), (None, None));
+
+    // Handles sit at the edges of the selection
+    editor.set_cursor(Cursor::new(0, 7));
+    editor.set_selection(Selection::Word(editor.cursor()));
+    assert_eq!(editor.copy_selection().as_deref(), Some("brave"));
+    let (start, end) = editor.selection_handles();
+    let (start, end) = (start.unwrap(), end.unwrap());
+    assert!(start.x < end.x);
+    assert_eq!((start.y, start.height), (0, 20));
+    assert_eq!((end.y, end.height), (0, 20));
+
+    // Dragging the end handle into a word snaps to its end
+    let glyph_x = |index: usize| {
+        editor.with_buffer(|buffer| {
+            let run = buffer.layout_runs().next().unwrap();
+            let glyph = run
+                .glyphs
+                .iter()
+                .find(|glyph| glyph.start == index)
+                .unwrap();
+            glyph.x as i32 + 1
+        })
+    };
+    let (hello_x, world_x) = (glyph_x(2), glyph_x(17));
+    editor.action(
+        &mut font_system,
+        Action::DragHandle {
+            which: SelectionHandle::End,
+            x: world_x,
+            y: 10,
+        },
+    );
+    assert_eq!(editor.copy_selection().as_deref(), Some("brave new world"));
+
+    // Dragging the start handle into a word snaps to its start
+    editor.action(
+        &mut font_system,
+        Action::DragHandle {
+            which: SelectionHandle::Start,
+            x: hello_x,
+            y: 10,
+        },
+    );
+    assert_eq!(
+        editor.copy_selection().as_deref(),
+        Some("hello brave new world")
+    );
+
+    // Handles can move across lines, and past the other handle
+    editor.action(
+        &mut font_system,
+        Action::DragHandle {
+            which: SelectionHandle::End,
+            x: 5,
+            y: 30,
+        },
+    );
+    assert_eq!(
+        editor.copy_selection().as_deref(),
+        Some("hello brave new world\nsecond")
+    );
+    editor.action(
+        &mut font_system,
+        Action::DragHandle {
+            which: SelectionHandle::Start,
+            x: 400,
+            y: 30,
+        },
+    );
+    assert_eq!(editor.copy_selection().as_deref(), Some(" line"));
+}
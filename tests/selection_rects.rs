@@ -0,0 +1,39 @@
+use cosmic_text::{Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Selection, Shaping};
+
+#[test]
+fn selection_rects_split_bidi_text() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(400.0), None);
+    let text = "abc שלום def\nghi";
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    // From inside of the Latin text to inside of the Hebrew word, which is reordered on screen
+    let hebrew = text.find('ש').unwrap();
+    let middle = hebrew + "של".len();
+    let rects = buffer.selection_rects(Cursor::new(0, 1), Cursor::new(0, middle));
+    assert_eq!(rects.len(), 2);
+    let (x0, _, w0, _) = rects[0];
+    let (x1, _, _, _) = rects[1];
+    assert!(x0 + w0 < x1 - 0.5);
+
+    // Text that is contiguous on screen is a single rectangle
+    let rects = buffer.selection_rects(Cursor::new(0, 0), Cursor::new(0, 3));
+    assert_eq!(rects.len(), 1);
+
+    // Selections continuing on the next line extend to the edge of the buffer
+    let rects =
+        buffer.selection_rects(Cursor::new(0, text.find("def").unwrap()), Cursor::new(1, 1));
+    assert_eq!(rects.len(), 2);
+    let (x, y, w, h) = rects[0];
+    assert!((x + w - 400.0).abs() < 0.01);
+    assert_eq!((y, h), (0.0, 20.0));
+    assert_eq!(rects[1].1, 20.0);
+
+    let mut editor = Editor::new(&mut buffer);
+    assert!(editor.selection_rects().is_empty());
+    editor.set_selection(Selection::Normal(Cursor::new(0, 0)));
+    editor.set_cursor(Cursor::new(0, 3));
+    assert_eq!(editor.selection_rects().len(), 1);
+}
@@ -0,0 +1,70 @@
+use cosmic_text::{Attrs, Buffer, Color, FontSystem, Metrics, ReshapeReasons, Shaping, Wrap};
+
+#[test]
+fn set_text_diffed_keeps_unchanged_lines() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let text = "# Title\n\nfirst\nsecond\nthird";
+    buffer.set_text(&mut font_system, text, Attrs::new(), Shaping::Advanced);
+    let ids = buffer
+        .lines
+        .iter()
+        .map(|line| line.id())
+        .collect::<Vec<_>>();
+
+    // Setting the same text changes nothing
+    assert!(!buffer.set_text_diffed(&mut font_system, text, Attrs::new(), Shaping::Advanced));
+    for line in buffer.lines.iter() {
+        assert_eq!(line.reshape_reasons(), ReshapeReasons::NEW);
+    }
+
+    // Changing and inserting a line in the middle
+    buffer.lines[2].set_wrap(Some(Wrap::None));
+    assert!(buffer.set_text_diffed(
+        &mut font_system,
+        "# Title\n\nfirst!\ninserted\nsecond\nthird",
+        Attrs::new(),
+        Shaping::Advanced,
+    ));
+    let texts = buffer
+        .lines
+        .iter()
+        .map(|line| line.text())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        ["# Title", "", "first!", "inserted", "second", "third"]
+    );
+    // Unchanged lines keep their identifiers and shapes
+    for (line_i, old_i) in [(0, 0), (1, 1), (4, 3), (5, 4)] {
+        assert_eq!(buffer.lines[line_i].id(), ids[old_i]);
+        assert_eq!(buffer.lines[line_i].reshape_reasons(), ReshapeReasons::NEW);
+    }
+    // The changed and inserted lines are new, without properties of the old lines
+    assert!(!ids.contains(&buffer.lines[2].id()));
+    assert_eq!(buffer.lines[2].wrap(), None);
+    assert!(!ids.contains(&buffer.lines[3].id()));
+
+    // Removing lines
+    assert!(buffer.set_text_diffed(
+        &mut font_system,
+        "# Title\nthird",
+        Attrs::new(),
+        Shaping::Advanced,
+    ));
+    assert_eq!(buffer.lines.len(), 2);
+    assert_eq!(buffer.lines[0].id(), ids[0]);
+    assert_eq!(buffer.lines[1].id(), ids[4]);
+    assert_eq!(buffer.lines[1].reshape_reasons(), ReshapeReasons::NEW);
+
+    // Changing only the color repaints the lines
+    let red = Attrs::new().color(Color::rgb(0xFF, 0, 0));
+    assert!(buffer.set_text_diffed(&mut font_system, "# Title\nthird", red, Shaping::Advanced));
+    assert_eq!(buffer.lines[1].reshape_reasons(), ReshapeReasons::NEW);
+    assert_eq!(buffer.lines[1].attrs_list().defaults(), red);
+
+    // Changing the shaping reshapes the lines
+    assert!(buffer.set_text_diffed(&mut font_system, "# Title\nthird", red, Shaping::Basic));
+    assert_eq!(buffer.lines[1].shaping(), Shaping::Basic);
+    assert_eq!(buffer.lines[1].reshape_reasons(), ReshapeReasons::SHAPING);
+}
@@ -0,0 +1,45 @@
+use cosmic_text::{Attrs, AttrsList, FontSystem, ShapeLine, Shaping};
+
+#[test]
+fn shape_access() {
+    let mut font_system = FontSystem::new();
+    let attrs_list = AttrsList::new(Attrs::new());
+    let text = "hi שלום";
+    let line = ShapeLine::new(&mut font_system, text, &attrs_list, Shaping::Advanced, 8);
+    assert!(!line.rtl);
+
+    // Words come with the level of their span
+    let words: Vec<_> = line
+        .words()
+        .map(|(level, word)| {
+            let start = word.glyphs.iter().map(|glyph| glyph.start).min().unwrap();
+            let end = word.glyphs.iter().map(|glyph| glyph.end).max().unwrap();
+            (level.is_rtl(), word.blank, &text[start..end])
+        })
+        .collect();
+    assert_eq!(
+        words,
+        [
+            (false, false, "hi"),
+            (false, true, " "),
+            (true, false, "שלום")
+        ]
+    );
+
+    // Glyph clusters cover the text, and advances add up to the word widths
+    let mut clusters: Vec<_> = line.glyphs().map(|(_, glyph)| glyph.cluster()).collect();
+    clusters.sort_by_key(|cluster| cluster.start);
+    clusters.dedup();
+    assert_eq!(clusters.first().unwrap().start, 0);
+    assert_eq!(clusters.last().unwrap().end, text.len());
+    let glyphs_width: f32 = line.glyphs().map(|(_, glyph)| glyph.width(14.0)).sum();
+    let words_width: f32 = line.words().map(|(_, word)| word.width(14.0)).sum();
+    assert!((glyphs_width - words_width).abs() < 0.01);
+
+    // Glyphs of the RTL word are in visual order from the start of the line
+    let (_, rtl_word) = line.words().last().unwrap();
+    assert!(rtl_word
+        .glyphs
+        .windows(2)
+        .all(|pair| pair[0].start > pair[1].start));
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "swash")]
+
+use cosmic_text::{Attrs, AttrsList, FontSystem, ShapeLine, Shaping};
+
+fn clusters(font_system: &mut FontSystem, text: &str, shaping: Shaping) -> Vec<(usize, usize)> {
+    let line = ShapeLine::new(font_system, text, &AttrsList::new(Attrs::new()), shaping, 8);
+    line.glyphs()
+        .map(|(_, glyph)| (glyph.start, glyph.end))
+        .collect()
+}
+
+#[test]
+fn shaping_auto_resolve() {
+    assert_eq!(Shaping::Auto.resolve("tail -f /var/log"), Shaping::Basic);
+    assert_eq!(Shaping::Auto.resolve("café ½"), Shaping::Basic);
+    assert_eq!(Shaping::Auto.resolve("cafe\u{301}"), Shaping::Advanced);
+    assert_eq!(Shaping::Auto.resolve("שלום"), Shaping::Advanced);
+    assert_eq!(Shaping::Advanced.resolve("abc"), Shaping::Advanced);
+    assert_eq!(Shaping::Basic.resolve("שלום"), Shaping::Basic);
+}
+
+#[test]
+fn shaping_basic_clusters() {
+    let mut font_system = FontSystem::new();
+
+    // One glyph per character, covering its bytes, in every word of the line
+    assert_eq!(
+        clusters(&mut font_system, "ab é", Shaping::Auto),
+        [(0, 1), (1, 2), (2, 3), (3, 5)]
+    );
+    assert_eq!(
+        clusters(&mut font_system, "ab é", Shaping::Auto),
+        clusters(&mut font_system, "ab é", Shaping::Basic)
+    );
+
+    // Complex text is shaped like advanced shaping
+    let text = "مرحبا بالعالم";
+    assert_eq!(
+        clusters(&mut font_system, text, Shaping::Auto),
+        clusters(&mut font_system, text, Shaping::Advanced)
+    );
+}
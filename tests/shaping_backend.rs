@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use cosmic_text::{
+    Attrs, AttrsList, Buffer, FontSystem, Metrics, RustybuzzShapingBackend, ShapeBuffer,
+    ShapeGlyph, Shaping, ShapingBackend,
+};
+
+/// Shapes with the default backend, then gives every glyph the same advance
+#[derive(Debug)]
+struct FixedAdvance(f32);
+
+impl ShapingBackend for FixedAdvance {
+    fn shape(
+        &self,
+        scratch: &mut ShapeBuffer,
+        glyphs: &mut Vec<ShapeGlyph>,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        start_run: usize,
+        end_run: usize,
+        span_rtl: bool,
+        shaping: Shaping,
+    ) {
+        let start = glyphs.len();
+        RustybuzzShapingBackend.shape(
+            scratch,
+            glyphs,
+            font_system,
+            line,
+            attrs_list,
+            start_run,
+            end_run,
+            span_rtl,
+            shaping,
+        );
+        for glyph in glyphs[start..].iter_mut() {
+            glyph.x_advance = self.0;
+        }
+    }
+}
+
+#[test]
+fn custom_shaping_backend() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(10.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_text("Hello world", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(false);
+    let default_w = buffer.layout_runs().next().unwrap().line_w;
+
+    buffer.set_shaping_backend(Arc::new(FixedAdvance(0.5)));
+    let run = buffer.layout_runs().next().unwrap();
+    assert_eq!(run.glyphs.len(), 11);
+    assert_eq!(run.line_w, 11.0 * 5.0);
+    assert!(run.glyphs.iter().all(|glyph| glyph.w == 5.0));
+
+    buffer.set_shaping_backend(Arc::new(RustybuzzShapingBackend));
+    assert_eq!(buffer.layout_runs().next().unwrap().line_w, default_w);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "shaping-parity")]
+
+use cosmic_text::{
+    compare_shaping, Attrs, AttrsList, FontSystem, RustybuzzShapingBackend, ShapeBuffer,
+    ShapeGlyph, Shaping, ShapingBackend, PARITY_CORPUS,
+};
+
+/// Shapes with the default backend, then widens the glyphs of the letter `x`
+#[derive(Debug)]
+struct WideX;
+
+impl ShapingBackend for WideX {
+    fn shape(
+        &self,
+        scratch: &mut ShapeBuffer,
+        glyphs: &mut Vec<ShapeGlyph>,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        start_run: usize,
+        end_run: usize,
+        span_rtl: bool,
+        shaping: Shaping,
+    ) {
+        let start = glyphs.len();
+        RustybuzzShapingBackend.shape(
+            scratch,
+            glyphs,
+            font_system,
+            line,
+            attrs_list,
+            start_run,
+            end_run,
+            span_rtl,
+            shaping,
+        );
+        for glyph in glyphs[start..].iter_mut() {
+            if &line[glyph.start..glyph.end] == "x" {
+                glyph.x_advance += 1.0;
+            }
+        }
+    }
+}
+
+#[test]
+fn shaping_parity() {
+    let mut font_system = FontSystem::new();
+    let attrs_list = AttrsList::new(Attrs::new());
+    let texts = PARITY_CORPUS.iter().map(|sample| sample.text);
+
+    // The default shaper matches itself on every script
+    let mismatches = compare_shaping(
+        &mut font_system,
+        texts.clone(),
+        &attrs_list,
+        &RustybuzzShapingBackend,
+        0.001,
+    );
+    assert_eq!(mismatches, []);
+
+    let mismatches = compare_shaping(&mut font_system, texts, &attrs_list, &WideX, 0.001);
+    assert_eq!(mismatches.len(), 1);
+    let mismatch = &mismatches[0];
+    assert_eq!(mismatch.text, "The quick brown fox jumps over the lazy dog");
+    assert_eq!(&mismatch.text[mismatch.range.clone()], "x");
+    assert!((mismatch.reference_advance - mismatch.advance - 1.0).abs() < 0.001);
+}
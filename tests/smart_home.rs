@@ -0,0 +1,54 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion, Selection, Shaping,
+};
+
+#[test]
+fn smart_home_toggles() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "    indented",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+    editor.set_cursor(Cursor::new(0, 8));
+
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 4);
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 0);
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 4);
+}
+
+#[test]
+fn select_extends_selection() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, None, Some(100.0));
+    buffer.set_text(
+        &mut font_system,
+        "  first line\nsecond line",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(&mut buffer);
+    editor.set_cursor(Cursor::new(0, 7));
+
+    // Selecting starts at the cursor
+    editor.action(&mut font_system, Action::Select(Motion::End));
+    assert_eq!(editor.selection(), Selection::Normal(Cursor::new(0, 7)));
+    assert_eq!(editor.copy_selection().as_deref(), Some(" line"));
+
+    // And keeps its anchor when extended further
+    editor.action(&mut font_system, Action::Select(Motion::SmartHome));
+    assert_eq!(editor.copy_selection().as_deref(), Some("first"));
+    editor.action(&mut font_system, Action::Select(Motion::SmartHome));
+    assert_eq!(editor.copy_selection().as_deref(), Some("  first"));
+
+    editor.action(&mut font_system, Action::Select(Motion::PageDown));
+    assert_eq!(editor.selection(), Selection::Normal(Cursor::new(0, 7)));
+    assert_eq!(editor.cursor().line, 1);
+}
@@ -0,0 +1,61 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, LayoutGlyph, Metrics, Shaping};
+
+fn glyphs(font_system: &mut FontSystem, chip: Attrs) -> Vec<LayoutGlyph> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(font_system);
+    buffer.set_rich_text(
+        [("a ", Attrs::new()), ("@bo", chip), (" c", Attrs::new())],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+    let glyphs = buffer.layout_runs().next().unwrap().glyphs.to_vec();
+    glyphs
+}
+
+#[test]
+fn span_padding() {
+    let mut font_system = FontSystem::new();
+    let plain = glyphs(&mut font_system, Attrs::new());
+    let padded = glyphs(&mut font_system, Attrs::new().padding(0.5, 0.0));
+
+    // The first and last glyphs of the span grow by 10 pixels, moving the text after it
+    assert_eq!(padded[2].x, plain[2].x);
+    assert!((padded[2].w - plain[2].w - 10.0).abs() < 0.01);
+    assert!((padded[4].w - plain[4].w - 10.0).abs() < 0.01);
+    assert!((padded[5].x - plain[5].x - 20.0).abs() < 0.01);
+    assert!((padded[3].x - plain[3].x - 10.0).abs() < 0.01);
+
+    // The first glyph is drawn after the padding
+    assert!((padded[2].x_offset - 0.5).abs() < 0.01);
+
+    // A minimum width grows both sides evenly
+    let min_width = glyphs(&mut font_system, Attrs::new().padding(0.0, 10.0));
+    let chip_w = min_width[5].x - min_width[2].x;
+    assert!((chip_w - 200.0).abs() < 0.01);
+}
+
+#[test]
+fn span_padding_hit() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(20.0, 24.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_rich_text(
+        [
+            ("a ", Attrs::new()),
+            ("@bo", Attrs::new().padding(2.0, 0.0)),
+            (" c", Attrs::new()),
+        ],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    // Clicks in the padding land on the chip instead of the text around it
+    let (start_x, end_x) = {
+        let run = buffer.layout_runs().next().unwrap();
+        (run.glyphs[2].x, run.glyphs[4].x + run.glyphs[4].w)
+    };
+    assert_eq!(buffer.hit(start_x + 5.0, 10.0).unwrap().index, 2);
+    assert_eq!(buffer.hit(end_x - 5.0, 10.0).unwrap().index, 5);
+}
@@ -0,0 +1,49 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+
+fn buffer(font_system: &mut FontSystem, is_static: bool) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_static(is_static);
+    buffer.set_size(font_system, Some(100.0), None);
+    buffer.set_text(
+        font_system,
+        "Hello world, this is a label\nשלום עולם",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(font_system, false);
+    buffer
+}
+
+fn runs(buffer: &Buffer) -> Vec<(usize, bool, f32, usize)> {
+    buffer
+        .layout_runs()
+        .map(|run| (run.line_i, run.rtl, run.line_w, run.glyphs.len()))
+        .collect()
+}
+
+#[test]
+fn static_buffer_compacts_lines() {
+    let mut font_system = FontSystem::new();
+    let mut expected = buffer(&mut font_system, false);
+    let mut buffer = buffer(&mut font_system, true);
+    assert!(buffer.is_static());
+
+    assert_eq!(runs(&buffer), runs(&expected));
+    assert!(buffer.layout_runs().any(|run| run.rtl));
+    for line in buffer.lines.iter() {
+        assert!(line.is_compact());
+        assert!(line.shape_opt().as_ref().unwrap().spans.is_empty());
+    }
+    assert!(expected.lines.iter().all(|line| !line.is_compact()));
+
+    // Resizing shapes the lines again, and compacts them after layout
+    buffer.set_size(&mut font_system, Some(1000.0), None);
+    expected.set_size(&mut font_system, Some(1000.0), None);
+    assert_eq!(runs(&buffer), runs(&expected));
+    assert!(buffer.lines.iter().all(|line| line.is_compact()));
+
+    // Requesting the shape of a compacted line shapes it again
+    let shape = buffer.line_shape(&mut font_system, 0).unwrap();
+    assert!(!shape.spans.is_empty());
+    assert!(!buffer.lines[0].is_compact());
+}
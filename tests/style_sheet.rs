@@ -0,0 +1,57 @@
+use cosmic_text::{Attrs, Buffer, Color, Family, FontSystem, Metrics, Shaping, StyleSheet, Weight};
+
+#[test]
+fn style_sheet_restyles_buffer() {
+    let mut font_system = FontSystem::new();
+    let mut style_sheet = StyleSheet::new();
+    let body = style_sheet.insert("body", Attrs::new().color(Color::rgb(0, 0, 0)));
+    let code = style_sheet.insert(
+        "code",
+        Attrs::new()
+            .family(Family::Monospace)
+            .color(Color::rgb(0x80, 0, 0)),
+    );
+    assert_eq!(style_sheet.id("code"), Some(code));
+    assert_eq!(style_sheet.name(body), Some("body"));
+    assert_eq!(style_sheet.id("quote"), None);
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    let body_attrs = style_sheet.attrs(body).expect("body");
+    let code_attrs = style_sheet.attrs(code).expect("code");
+    buffer.set_rich_text(
+        [("run ", body_attrs), ("cargo test", code_attrs)],
+        body_attrs,
+        Shaping::Advanced,
+    );
+    // Spans remember the style they were created from
+    buffer.lines[0]
+        .attrs_list()
+        .spans()
+        .iter()
+        .for_each(|(_, attrs)| assert!(attrs.style_id_opt.is_some()));
+
+    // Switching theme updates every styled span, reshaping the buffer
+    style_sheet.insert(
+        "code",
+        Attrs::new()
+            .family(Family::Monospace)
+            .weight(Weight::BOLD)
+            .color(Color::rgb(0xFF, 0x80, 0x80)),
+    );
+    assert_eq!(style_sheet.id("code"), Some(code));
+    buffer.set_style_sheet(&style_sheet);
+
+    let span = buffer.lines[0].attrs_list().get_span(5);
+    assert_eq!(span.color_opt, Some(Color::rgb(0xFF, 0x80, 0x80)));
+    assert_eq!(span.weight, Weight::BOLD);
+    assert_eq!(span.style_id_opt, Some(code));
+    assert_eq!(
+        buffer.lines[0].attrs_list().get_span(0),
+        style_sheet.attrs(body).expect("body")
+    );
+    assert!(buffer.lines[0].shape_opt().is_some());
+
+    // Nothing changes when applying the same style sheet again
+    assert!(style_sheet.restyle(buffer.lines[0].attrs_list()).is_none());
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "swash")]
+
+use std::sync::{Arc, Mutex};
+
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, SwashCache, SwashCacheStats};
+
+#[test]
+fn swash_cache_stats_and_evictions() {
+    let mut font_system = FontSystem::new();
+    let mut swash_cache = SwashCache::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "ab", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system, false);
+    let keys: Vec<_> = buffer.layout_runs().next().unwrap().glyphs[..2]
+        .iter()
+        .map(|glyph| glyph.physical((0.0, 0.0), 1.0).cache_key)
+        .collect();
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    swash_cache.on_evict({
+        let evicted = evicted.clone();
+        move |cache_key| evicted.lock().unwrap().push(cache_key)
+    });
+
+    swash_cache.get_image(&mut font_system, keys[0]);
+    swash_cache.get_image(&mut font_system, keys[0]);
+    swash_cache.get_image(&mut font_system, keys[1]);
+    assert_eq!(
+        swash_cache.stats(),
+        SwashCacheStats {
+            hits: 1,
+            misses: 2,
+            evictions: 0
+        }
+    );
+
+    // Only the image that is still used is kept
+    swash_cache.trim(0);
+    swash_cache.get_image(&mut font_system, keys[0]);
+    swash_cache.trim(0);
+    assert_eq!(*evicted.lock().unwrap(), [keys[1]]);
+    assert_eq!(swash_cache.image_cache.len(), 1);
+    assert_eq!(swash_cache.stats().evictions, 1);
+    assert_eq!(swash_cache.stats().hits, 2);
+
+    swash_cache.reset_stats();
+    assert_eq!(swash_cache.stats(), SwashCacheStats::default());
+}
@@ -0,0 +1,49 @@
+use cosmic_text::{Attrs, AttrsDelta, Buffer, FontSystem, Metrics, Shaping, TextAnimation};
+
+#[test]
+fn text_animation() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_rich_text(
+        [
+            ("alert ", Attrs::new().animation(TextAnimation::Blink)),
+            ("plain", Attrs::new()),
+        ],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(false);
+
+    // The animation is carried to the glyphs for the renderer
+    let glyphs: Vec<_> = buffer
+        .layout_runs()
+        .flat_map(|run| run.glyphs.iter().cloned())
+        .collect();
+    assert_eq!(glyphs[0].animation_opt, Some(TextAnimation::Blink));
+    assert_eq!(glyphs.last().unwrap().animation_opt, None);
+
+    // Deltas inherit, set, and unset the animation
+    let attrs = Attrs::new().animation(TextAnimation::Pulse);
+    assert_eq!(
+        attrs.apply(&AttrsDelta::new()).animation_opt,
+        Some(TextAnimation::Pulse)
+    );
+    assert_eq!(
+        attrs
+            .apply(&AttrsDelta::new().animation(TextAnimation::Marquee))
+            .animation_opt,
+        Some(TextAnimation::Marquee)
+    );
+    assert_eq!(
+        attrs
+            .apply(&AttrsDelta::new().unset_animation())
+            .animation_opt,
+        None
+    );
+
+    // Marquee is only animated when the user opts in
+    assert!(TextAnimation::Blink.enabled_by_default());
+    assert!(TextAnimation::Pulse.enabled_by_default());
+    assert!(!TextAnimation::Marquee.enabled_by_default());
+}
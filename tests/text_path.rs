@@ -0,0 +1,59 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, TextPath};
+
+fn line_glyphs(font_system: &mut FontSystem, text: &str) -> cosmic_text::LayoutLine {
+    let mut buffer = Buffer::new(font_system, Metrics::new(20.0, 24.0));
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(font_system, false);
+    buffer.lines[0].layout_opt().as_ref().expect("layout")[0].clone()
+}
+
+#[test]
+fn test_text_path_sample() {
+    let path = TextPath::from_points([(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]).expect("path");
+    assert_eq!(path.length(), 20.0);
+
+    let (x, y, angle) = path.sample(5.0).expect("sample");
+    assert_eq!((x, y, angle), (5.0, 0.0, 0.0));
+
+    let (x, y, angle) = path.sample(15.0).expect("sample");
+    assert_eq!((x, y), (10.0, 5.0));
+    assert!((angle - core::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+    assert!(path.sample(-1.0).is_none());
+    assert!(path.sample(21.0).is_none());
+}
+
+#[test]
+fn test_text_path_straight_line_matches_layout() {
+    let mut font_system = FontSystem::new();
+    let line = line_glyphs(&mut font_system, "Hello path");
+
+    let path = TextPath::from_points([(0.0, 0.0), (1000.0, 0.0)]).expect("path");
+    let placed = path.layout_line(&line, 0.0);
+    assert_eq!(placed.len(), line.glyphs.len());
+    for (placed, glyph) in placed.iter().zip(line.glyphs.iter()) {
+        assert!((placed.x - glyph.x).abs() < 1e-3);
+        assert!((placed.y - glyph.y).abs() < 1e-3);
+        assert_eq!(placed.angle, 0.0);
+    }
+}
+
+#[test]
+fn test_text_path_curve_and_overflow() {
+    let mut font_system = FontSystem::new();
+    let line = line_glyphs(&mut font_system, "Around the badge");
+
+    let mut path = TextPath::new(0.0, 100.0);
+    path.cubic_to(0.0, 0.0, 200.0, 0.0, 200.0, 100.0, 32);
+    assert!(path.length() > 200.0);
+
+    let placed = path.layout_line(&line, 0.0);
+    assert_eq!(placed.len(), line.glyphs.len());
+    // Glyphs follow the curve upwards, then downwards
+    assert!(placed.first().expect("first").angle < 0.0);
+    assert!(placed.last().expect("last").angle > 0.0);
+
+    // A short path drops glyphs that do not fit
+    let short = TextPath::from_points([(0.0, 0.0), (30.0, 0.0)]).expect("path");
+    assert!(short.layout_line(&line, 0.0).len() < line.glyphs.len());
+}
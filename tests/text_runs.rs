@@ -0,0 +1,37 @@
+use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Weight};
+
+#[test]
+fn text_runs_cover_whole_buffer() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    // Only one line is visible, text runs still cover every line
+    buffer.set_size(Some(200.0), Some(20.0));
+
+    let attrs = Attrs::new().family(Family::Serif);
+    buffer.set_rich_text(
+        [
+            ("Hello ", attrs),
+            ("bold", attrs.weight(Weight::BOLD)),
+            ("\nBonjour", attrs.lang("fr")),
+        ],
+        attrs,
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.layout_runs().count(), 1);
+
+    let runs: Vec<_> = buffer
+        .text_runs()
+        .map(|run| (run.line_i, run.range.clone(), run.text, run.language_opt()))
+        .collect();
+    assert_eq!(
+        runs,
+        vec![
+            (0, 0..6, "Hello ", None),
+            (0, 6..10, "bold", None),
+            (1, 0..7, "Bonjour", Some("fr")),
+        ]
+    );
+    let bold = buffer.text_runs().nth(1).expect("bold run");
+    assert_eq!(bold.attrs.weight, Weight::BOLD);
+}
@@ -0,0 +1,90 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion, Selection,
+    SourceEditor, TextSource,
+};
+
+#[test]
+fn source_follows_edits() {
+    let mut font_system = FontSystem::new();
+    let buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    let source: Vec<String> = vec!["hello".into(), "world".into()];
+    let mut editor = SourceEditor::new(Editor::new(buffer), source);
+    editor.reload(&mut font_system, Attrs::new());
+    editor.with_buffer(|buffer| {
+        let texts: Vec<_> = buffer.lines.iter().map(|line| line.text()).collect();
+        assert_eq!(texts, ["hello", "world"]);
+    });
+
+    // Actions, direct edits, and undoing with changes are applied to the source
+    editor.set_cursor(Cursor::new(0, 5));
+    editor.action(&mut font_system, Action::Insert('!'));
+    editor.action(&mut font_system, Action::Enter);
+    editor.start_change();
+    editor.insert_at(Cursor::new(2, 0), "big\nwide ", None);
+    let mut change = editor.finish_change().unwrap();
+    assert_eq!(
+        editor.source(),
+        &["hello!", "", "big", "wide world"].map(String::from)
+    );
+
+    change.reverse();
+    assert!(editor.apply_change(&change));
+    assert_eq!(editor.source(), &["hello!", "", "world"].map(String::from));
+    editor.set_cursor(Cursor::new(2, 1));
+    editor.set_selection(Selection::Normal(Cursor::new(0, 2)));
+    editor.action(&mut font_system, Action::Delete);
+    editor.action(&mut font_system, Action::Enter);
+    assert_eq!(editor.source(), &["he", "orld"].map(String::from));
+    editor.with_buffer(|buffer| {
+        let texts: Vec<_> = buffer.lines.iter().map(|line| line.text()).collect();
+        assert_eq!(texts, ["he", "orld"]);
+    });
+
+    // Changes made by someone else are loaded from the source
+    editor.source_mut()[1].insert_str(0, "new ");
+    assert!(editor.reload_line(1));
+    assert!(!editor.reload_line(2));
+    editor.with_buffer(|buffer| assert_eq!(buffer.lines[1].text(), "new orld"));
+    assert_eq!(editor.source().line_count(), 2);
+}
+
+#[test]
+fn lines_load_lazily() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), Some(100.0));
+    let source: Vec<String> = (0..1000).map(|i| format!("line {i}")).collect();
+    let text = source.join("\n");
+    let mut editor = SourceEditor::new(Editor::new(buffer), source);
+    editor.reload(&mut font_system, Attrs::new());
+
+    // Only the lines near the view hold their text
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines.len(), 1000);
+        assert_eq!(buffer.lines[5].text(), "line 5");
+        assert_eq!(buffer.lines[500].text(), "");
+    });
+    assert!((0..1000).filter(|&i| editor.is_line_loaded(i)).count() < 20);
+
+    // Moving to the end loads the lines there, and pruning unloads the lines at the start
+    editor.action(&mut font_system, Action::Motion(Motion::BufferEnd));
+    editor.shape_as_needed(&mut font_system, true);
+    assert_eq!(editor.cursor(), Cursor::new(999, 8));
+    assert!(editor.is_line_loaded(995));
+    assert!(!editor.is_line_loaded(0));
+    assert!((0..1000).filter(|&i| editor.is_line_loaded(i)).count() < 40);
+
+    // Selections over placeholders use the text of the source
+    editor.set_selection(Selection::Normal(Cursor::new(0, 0)));
+    assert_eq!(editor.copy_selection().as_deref(), Some(text.as_str()));
+    editor.start_change();
+    editor.action(&mut font_system, Action::Delete);
+    let mut change = editor.finish_change().unwrap();
+    assert_eq!(editor.source(), &[String::new()]);
+    editor.with_buffer(|buffer| assert_eq!(buffer.lines.len(), 1));
+
+    // Undoing restores the text that was loaded to delete it
+    change.reverse();
+    assert!(editor.apply_change(&change));
+    assert_eq!(editor.source().join("\n"), text);
+}
@@ -0,0 +1,53 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, LineEnding, Metrics, Shaping};
+
+fn lines(buffer: &Buffer) -> Vec<(&str, LineEnding)> {
+    buffer
+        .lines
+        .iter()
+        .map(|line| (line.text(), line.ending()))
+        .collect()
+}
+
+#[test]
+fn trailing_empty_line() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, Some(200.0), None);
+
+    // Label semantics by default
+    assert!(!buffer.trailing_empty_line());
+    buffer.set_text(&mut font_system, "a\n", Attrs::new(), Shaping::Advanced);
+    assert_eq!(lines(&buffer), [("a", LineEnding::Lf)]);
+    assert_eq!(buffer.layout_runs().count(), 1);
+
+    // Editor semantics
+    buffer.set_trailing_empty_line(true);
+    buffer.set_text(&mut font_system, "a\n", Attrs::new(), Shaping::Advanced);
+    assert_eq!(
+        lines(&buffer),
+        [("a", LineEnding::Lf), ("", LineEnding::None)]
+    );
+    assert_eq!(buffer.layout_runs().count(), 2);
+
+    buffer.set_text(&mut font_system, "a\r\n", Attrs::new(), Shaping::Advanced);
+    assert_eq!(
+        lines(&buffer),
+        [("a", LineEnding::CrLf), ("", LineEnding::None)]
+    );
+
+    // Text without a trailing newline is unchanged
+    buffer.set_text(&mut font_system, "a", Attrs::new(), Shaping::Advanced);
+    assert_eq!(lines(&buffer), [("a", LineEnding::None)]);
+    buffer.set_text(&mut font_system, "", Attrs::new(), Shaping::Advanced);
+    assert_eq!(buffer.lines.len(), 1);
+
+    buffer.set_rich_text(
+        &mut font_system,
+        [("a", Attrs::new()), ("b\n", Attrs::new())],
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    assert_eq!(buffer.lines.len(), 2);
+    assert_eq!(buffer.lines[0].text(), "ab");
+    assert_eq!(buffer.lines[1].text(), "");
+}
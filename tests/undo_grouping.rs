@@ -0,0 +1,111 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Selection, Shaping,
+    UndoGrouping,
+};
+
+fn text(editor: &Editor) -> String {
+    editor.with_buffer(|buffer| {
+        buffer
+            .lines
+            .iter()
+            .map(|line| line.text())
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+#[test]
+fn undo_grouping() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "", Attrs::new(), Shaping::Advanced);
+    let mut editor = Editor::new(&mut buffer);
+    editor.set_undo_grouping(UndoGrouping {
+        max_gap: None,
+        ..UndoGrouping::default()
+    });
+    assert!(!editor.can_undo());
+
+    // Typing is undone word by word
+    for c in "hello world".chars() {
+        editor.action(&mut font_system, Action::Insert(c));
+    }
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "hello");
+    assert!(editor.redo());
+    assert_eq!(text(&editor), "hello world");
+    assert!(!editor.can_redo());
+
+    // Consecutive deletions are one step
+    for _ in 0..3 {
+        editor.action(&mut font_system, Action::Backspace);
+    }
+    assert_eq!(text(&editor), "hello wo");
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "hello world");
+    assert_eq!(editor.cursor().index, 11);
+
+    // Replacing a selection is one step
+    editor.set_selection(Selection::Normal(Cursor::new(0, 0)));
+    editor.set_cursor(Cursor::new(0, 5));
+    editor.insert_string("goodbye", None);
+    assert_eq!(text(&editor), "goodbye world");
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "hello world");
+
+    // Explicit groups are one step, and are not merged with typing around them
+    editor.set_cursor(Cursor::new(0, 11));
+    editor.action(&mut font_system, Action::Insert('!'));
+    editor.begin_undo_group();
+    editor.insert_at(Cursor::new(0, 0), "> ", None);
+    editor.set_cursor(Cursor::new(0, 14));
+    editor.begin_undo_group();
+    editor.action(&mut font_system, Action::Insert('?'));
+    editor.end_undo_group();
+    editor.end_undo_group();
+    editor.action(&mut font_system, Action::Insert('.'));
+    assert_eq!(text(&editor), "> hello world!?.");
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "> hello world!?");
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "hello world!");
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "hello world");
+
+    // Editing after undoing drops the steps to redo
+    editor.action(&mut font_system, Action::Insert('x'));
+    assert!(!editor.can_redo());
+    editor.clear_undo_history();
+    assert!(!editor.undo());
+}
+
+#[test]
+fn undo_limit_and_disable() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut editor = Editor::new(&mut buffer);
+    assert_eq!(editor.undo_limit(), Some(1000));
+
+    // Only the last steps are kept
+    editor.set_undo_limit(Some(2));
+    for c in "a b c".chars() {
+        editor.action(&mut font_system, Action::Insert(c));
+    }
+    assert!(editor.undo());
+    assert!(editor.undo());
+    assert!(!editor.undo());
+    assert_eq!(text(&editor), "a");
+
+    // Disabling forgets the history and stops recording
+    assert!(editor.can_redo());
+    editor.set_undo_enabled(false);
+    assert!(!editor.undo_enabled());
+    assert!(!editor.can_redo());
+    editor.action(&mut font_system, Action::Insert('d'));
+    assert!(!editor.can_undo());
+
+    editor.set_undo_enabled(true);
+    editor.action(&mut font_system, Action::Insert('e'));
+    assert!(editor.undo());
+    assert_eq!(text(&editor), "ad");
+}
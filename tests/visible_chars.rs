@@ -0,0 +1,33 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Scroll, Shaping};
+
+#[test]
+fn visible_chars_with_positions() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, None, Some(40.0));
+    buffer.set_text(
+        &mut font_system,
+        "ab\ncd\nef",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let chars: Vec<_> = buffer.visible_chars().collect();
+    let texts: Vec<_> = chars.iter().map(|c| (c.line_i, c.text)).collect();
+    assert_eq!(texts, [(0, "a"), (0, "b"), (1, "c"), (1, "d")]);
+    assert_eq!(chars[1].range, 1..2);
+    let (x, y, w, h) = chars[1].rect;
+    assert_eq!((y, h), (0.0, 20.0));
+    assert!(w > 0.0);
+    assert_eq!(x, chars[0].rect.0 + chars[0].rect.2);
+    assert_eq!(chars[2].rect.1, 20.0);
+    assert_eq!(buffer.visible_text(), "ab\ncd");
+
+    // Scrolling changes what is visible without reshaping
+    buffer.set_scroll(Scroll::new(1, 0.0, 0.0));
+    buffer.shape_until_scroll(&mut font_system, false);
+    assert_eq!(buffer.visible_text(), "cd\nef");
+    let first = buffer.visible_chars().next().unwrap();
+    assert_eq!((first.line_i, first.text, first.rect.1), (1, "c", 0.0));
+}
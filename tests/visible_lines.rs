@@ -0,0 +1,36 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Scroll, Shaping};
+
+fn messages(range: core::ops::Range<usize>) -> String {
+    range
+        .map(|i| format!("message {i}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn anchor_survives_prepend() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(Some(400.0), Some(70.0));
+    buffer.set_text(&messages(10..30), Attrs::new(), Shaping::Advanced);
+    buffer.set_scroll(Scroll::new(5, 10.0, 0.0));
+    buffer.shape_until_scroll(false);
+
+    // Lines 5 to 8 are partly visible in 70 pixels scrolled by 10
+    assert_eq!(buffer.visible_line_range(), 5..9);
+
+    // Keep the second visible message at the same position while earlier ones are loaded
+    let line_i = buffer.visible_line_range().start + 1;
+    let offset = buffer.run_for_line(line_i).unwrap().line_top;
+    assert_eq!(offset, 10.0);
+
+    buffer.set_text(&messages(0..30), Attrs::new(), Shaping::Advanced);
+    buffer.anchor_scroll_to(line_i + 10, offset);
+    buffer.shape_until_scroll(false);
+
+    let run = buffer.run_for_line(line_i + 10).unwrap();
+    assert_eq!(run.text, "message 16");
+    assert_eq!(run.line_top, offset);
+    assert_eq!(buffer.visible_line_range(), 15..19);
+}
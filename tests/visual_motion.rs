@@ -0,0 +1,77 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, Family, FontSystem, Metrics, Motion, Shaping,
+};
+
+fn editor(font_system: &mut FontSystem) -> Editor<'static> {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    let mut borrowed = buffer.borrow_with(font_system);
+    borrowed.set_size(Some(100.0), None);
+    borrowed.set_text(
+        "first\nthis paragraph is long enough to wrap several times\nlast",
+        Attrs::new().family(Family::Monospace),
+        Shaping::Advanced,
+    );
+    Editor::new(buffer)
+}
+
+#[test]
+fn visual_and_logical_motion() {
+    let mut font_system = FontSystem::new();
+    let mut editor = editor(&mut font_system);
+    let wrapped =
+        editor.with_buffer(|buffer| buffer.lines[1].layout_opt().as_ref().expect("layout").len());
+    assert!(wrapped > 2);
+
+    editor.set_cursor(Cursor::new(0, 2));
+    assert_eq!(
+        editor.cursor_visual_position().map(|(line, _)| line),
+        Some(0)
+    );
+
+    // Visual motion stops on each wrapped line
+    editor.action(&mut font_system, Action::Motion(Motion::Down));
+    editor.action(&mut font_system, Action::Motion(Motion::Down));
+    assert_eq!(editor.cursor().line, 1);
+    let (visual_line, x) = editor.cursor_visual_position().expect("visual position");
+    assert_eq!(visual_line, 2);
+    assert!(x > 0);
+
+    // Logical motion skips the rest of the paragraph
+    editor.set_cursor(Cursor::new(0, 2));
+    editor.action(&mut font_system, Action::Motion(Motion::DownParagraph));
+    assert_eq!((editor.cursor().line, editor.cursor().index), (1, 2));
+    editor.action(&mut font_system, Action::Motion(Motion::DownParagraph));
+    assert_eq!(editor.cursor().line, 2);
+    assert_eq!(editor.cursor().index, 2);
+    assert_eq!(
+        editor.cursor_visual_position().map(|(line, _)| line),
+        Some(wrapped + 1)
+    );
+
+    editor.action(&mut font_system, Action::Motion(Motion::UpParagraph));
+    assert_eq!((editor.cursor().line, editor.cursor().index), (1, 2));
+}
+
+#[test]
+fn preferred_column_survives_zoom() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "0123456789\nab\n0123456789",
+        Attrs::new().family(Family::Monospace),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+
+    editor.set_cursor(Cursor::new(0, 4));
+    editor.action(&mut font_system, Action::Motion(Motion::Down));
+    assert_eq!(editor.cursor().line, 1);
+    assert_eq!(editor.cursor().index, 2);
+
+    // Zoom in, the preferred column is kept
+    editor.with_buffer_mut(|buffer| buffer.set_metrics(&mut font_system, Metrics::new(28.0, 40.0)));
+    editor.action(&mut font_system, Action::Motion(Motion::Down));
+    assert_eq!(editor.cursor().line, 2);
+    assert_eq!(editor.cursor().index, 4);
+}
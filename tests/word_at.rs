@@ -0,0 +1,49 @@
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, WordBoundaries};
+
+#[test]
+fn word_at_position() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "hello wide-world\nsecond",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system, false);
+
+    let glyph_x = |line_i: usize, index: usize| {
+        let run = buffer
+            .layout_runs()
+            .find(|run| run.line_i == line_i)
+            .unwrap();
+        let glyph = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.start == index)
+            .unwrap();
+        glyph.x + glyph.w / 2.0
+    };
+
+    let hit = buffer.word_at(glyph_x(0, 1), 10.0).unwrap();
+    assert_eq!(hit.line_i, 0);
+    assert_eq!(hit.range, 0..5);
+    assert_eq!(hit.text, "hello");
+    assert_eq!(hit.rects.len(), 1);
+    let (x, y, w, h) = hit.rects[0];
+    assert_eq!((x, y, h), (0.0, 0.0, 20.0));
+    assert!(w > 0.0 && w < glyph_x(0, 6));
+
+    assert_eq!(buffer.word_at(glyph_x(0, 12), 10.0).unwrap().text, "world");
+    assert_eq!(buffer.word_at(glyph_x(1, 2), 30.0).unwrap().text, "second");
+
+    // Whitespace and positions past the text are not words
+    assert_eq!(buffer.word_at(glyph_x(0, 5), 10.0), None);
+    assert_eq!(buffer.word_at(1000.0, 10.0), None);
+    assert_eq!(buffer.word_at(10.0, 1000.0), None);
+
+    // The word boundaries of the buffer are used
+    let world_x = glyph_x(0, 12);
+    buffer.set_word_boundaries(WordBoundaries::Extra("-".into()));
+    assert_eq!(buffer.word_at(world_x, 10.0).unwrap().text, "wide-world");
+}
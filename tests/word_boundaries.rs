@@ -0,0 +1,57 @@
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Motion, Selection, Shaping,
+    WordBoundaries,
+};
+
+fn selected(editor: &Editor) -> Option<String> {
+    editor.copy_selection()
+}
+
+#[test]
+fn word_boundaries() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "let $foo-bar = 1;",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // Unicode rules stop at punctuation
+    editor.set_cursor(Cursor::new(0, 6));
+    editor.set_selection(Selection::Word(editor.cursor()));
+    assert_eq!(selected(&editor).as_deref(), Some("foo"));
+
+    editor.with_buffer_mut(|buffer| {
+        buffer.set_word_boundaries(WordBoundaries::Extra("$-".into()));
+    });
+
+    // Word selection joins extra characters
+    editor.set_selection(Selection::Word(editor.cursor()));
+    assert_eq!(selected(&editor).as_deref(), Some("$foo-bar"));
+
+    // Word motions use the same rules
+    editor.set_selection(Selection::None);
+    editor.set_cursor(Cursor::new(0, 4));
+    editor.action(&mut font_system, Action::Motion(Motion::NextWord));
+    assert_eq!(editor.cursor().index, 12);
+    editor.action(&mut font_system, Action::Motion(Motion::PreviousWord));
+    assert_eq!(editor.cursor().index, 4);
+
+    // Word deletion built from a word motion
+    editor.set_selection(Selection::Normal(editor.cursor()));
+    editor.action(&mut font_system, Action::Motion(Motion::NextWord));
+    editor.delete_selection();
+    editor.with_buffer(|buffer| assert_eq!(buffer.lines[0].text(), "let  = 1;"));
+
+    // A custom classifier
+    editor.with_buffer_mut(|buffer| {
+        buffer.set_word_boundaries(WordBoundaries::Classifier(|c| !c.is_whitespace()));
+    });
+    editor.set_cursor(Cursor::new(0, 8));
+    editor.set_selection(Selection::Word(editor.cursor()));
+    assert_eq!(selected(&editor).as_deref(), Some("1;"));
+}
@@ -0,0 +1,67 @@
+use std::{ops::Range, sync::Arc};
+
+use cosmic_text::{
+    Action, Attrs, Buffer, BufferLine, Cursor, Edit, Editor, FontSystem, Metrics, Motion,
+    Selection, Shaping, WordHook,
+};
+
+/// Joins words separated by a single `-` on lines after the first, like a language where only
+/// code lines have kebab-case identifiers
+#[derive(Debug)]
+struct KebabCase;
+
+impl WordHook for KebabCase {
+    fn adjust_words(&self, line_i: usize, line: &BufferLine, words: &mut Vec<Range<usize>>) {
+        if line_i == 0 {
+            return;
+        }
+        let text = line.text();
+        let mut joined: Vec<Range<usize>> = Vec::new();
+        for word in words.drain(..) {
+            match joined.last_mut() {
+                Some(last) if &text[last.end..word.start] == "-" => last.end = word.end,
+                _ => joined.push(word),
+            }
+        }
+        *words = joined;
+    }
+}
+
+#[test]
+fn word_hook() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "some-text\nlet kebab-case-name = 1;",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.set_word_hook(Some(Arc::new(KebabCase)));
+    assert_eq!(buffer.line_words(0), [0..4, 5..9]);
+    assert_eq!(buffer.line_words(1), [0..3, 4..19, 22..23]);
+    assert!(buffer.line_words(2).is_empty());
+
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system, false);
+
+    // Word selection and motions use the adjusted words
+    editor.set_cursor(Cursor::new(1, 12));
+    editor.set_selection(Selection::Word(editor.cursor()));
+    assert_eq!(editor.copy_selection().as_deref(), Some("kebab-case-name"));
+
+    editor.set_selection(Selection::None);
+    editor.set_cursor(Cursor::new(1, 4));
+    editor.action(&mut font_system, Action::Motion(Motion::NextWord));
+    assert_eq!(editor.cursor().index, 19);
+    editor.action(&mut font_system, Action::Motion(Motion::PreviousWord));
+    assert_eq!(editor.cursor().index, 4);
+
+    // Lines the hook leaves alone
+    editor.set_cursor(Cursor::new(0, 0));
+    editor.action(&mut font_system, Action::Motion(Motion::NextWord));
+    assert_eq!(editor.cursor().index, 4);
+
+    editor.with_buffer_mut(|buffer| buffer.set_word_hook(None));
+    assert_eq!(editor.with_buffer(|buffer| buffer.line_words(1)).len(), 5);
+}
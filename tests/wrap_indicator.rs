@@ -0,0 +1,87 @@
+use cosmic_text::{
+    Attrs, Buffer, Cursor, FontSystem, Metrics, Shaping, WrapIndicator, WrapIndicatorPosition,
+};
+
+fn buffer(font_system: &mut FontSystem) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(font_system, Some(100.0), None);
+    buffer.set_text(
+        font_system,
+        "one two three four five six seven\nshort",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer
+}
+
+#[test]
+fn wrap_indicator_at_end() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = buffer(&mut font_system);
+    assert!(buffer
+        .layout_runs()
+        .all(|run| run.wrap_indicator_opt.is_none()));
+
+    buffer.set_wrap_indicator(
+        &mut font_system,
+        Some(WrapIndicator::new('>', WrapIndicatorPosition::End)),
+    );
+    assert_eq!(buffer.wrap_indicator().unwrap().character, '>');
+
+    let runs = buffer.layout_runs().collect::<Vec<_>>();
+    let first_line_len = runs.iter().filter(|run| run.line_i == 0).count();
+    assert!(first_line_len > 1);
+    for run in runs.iter() {
+        let last_visual_line = run.line_i == 1 || run.layout_i + 1 == first_line_len;
+        match run.wrap_indicator_opt {
+            Some(ref glyph) => {
+                assert!(!last_visual_line);
+                // After the text, with the font size of the line
+                let text_end = run.glyphs.iter().map(|g| g.x + g.w).fold(0.0, f32::max);
+                assert_eq!(glyph.x, text_end);
+                assert_eq!(glyph.font_size, 14.0);
+                assert!(glyph.w > 0.0);
+            }
+            None => assert!(last_visual_line),
+        }
+    }
+
+    // The indicator is not hit
+    let run = &runs[0];
+    let glyph = run.wrap_indicator_opt.as_ref().unwrap();
+    let cursor = buffer
+        .hit(glyph.x + glyph.w / 2.0, run.line_top + 1.0)
+        .unwrap();
+    assert_eq!(cursor.line, 0);
+    assert!(cursor.index <= run.glyphs.last().unwrap().end);
+
+    buffer.set_wrap_indicator(&mut font_system, None);
+    assert!(buffer
+        .layout_runs()
+        .all(|run| run.wrap_indicator_opt.is_none()));
+}
+
+#[test]
+fn wrap_indicator_at_start() {
+    let mut font_system = FontSystem::new();
+    let mut buffer = buffer(&mut font_system);
+    buffer.set_wrap_indicator(
+        &mut font_system,
+        Some(WrapIndicator::new('>', WrapIndicatorPosition::Start)),
+    );
+
+    for run in buffer.layout_runs() {
+        match run.wrap_indicator_opt {
+            Some(glyph) => {
+                assert!(run.layout_i > 0);
+                // Before the text, at the wrap point
+                assert_eq!(glyph.x, -glyph.w);
+                assert_eq!(
+                    Cursor::new(run.line_i, glyph.start),
+                    Cursor::new(0, run.glyphs[0].start)
+                );
+            }
+            None => assert_eq!(run.layout_i, 0),
+        }
+    }
+}